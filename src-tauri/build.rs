@@ -1,9 +1,42 @@
 /*****************************************************************************
  *  build.rs - Rust 构建脚本
  *
- *  @brief  Tauri 构建配置
+ *  @brief  Tauri 构建配置，并嵌入 git 提交和构建时间（见 app_info.rs）
  *****************************************************************************/
 
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 fn main() {
+    // 不在 git 仓库中构建（如源码包）时不设置，运行时为 None
+    if let Some(commit) = git_commit() {
+        println!("cargo:rustc-env=YOUTUDOWN_GIT_COMMIT={}", commit);
+    }
+    println!("cargo:rustc-env=YOUTUDOWN_BUILT_AT={}", build_time_millis());
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
     tauri_build::build()
 }
+
+/// 当前提交的短哈希
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !commit.is_empty()).then_some(commit)
+}
+
+/// 构建时间（Unix 毫秒）；设置了 SOURCE_DATE_EPOCH 时使用该时间，便于可复现构建
+fn build_time_millis() -> u128 {
+    if let Some(epoch) = std::env::var("SOURCE_DATE_EPOCH").ok().and_then(|s| s.parse::<u128>().ok()) {
+        return epoch * 1000;
+    }
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,135 @@
+/****************************************************************************
+ *  app_info.rs - 应用版本信息与更新检查
+ *
+ *  @brief  汇总应用版本、构建信息和运行平台，并查询 GitHub 上的最新发布
+ *  @note   git 提交和构建时间由 build.rs 在编译时嵌入，不在 git 仓库中构建时为空。
+ *          更新检查只比较应用本身的版本，与 yt-dlp 的更新无关；
+ *          网络不可用或接口出错时返回 Unknown，不作为错误
+ *****************************************************************************/
+
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::{debug, warn};
+use ts_rs::TS;
+
+use crate::network::http_client;
+
+/// 最新发布查询接口（不含草稿和预发布版本）
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/Alcu1n/youtudown/releases/latest";
+
+/// 更新检查的超时时间
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AppInfo {
+    pub version: String,            // 应用版本（tauri.conf.json）
+    pub git_commit: Option<String>, // 构建时的 git 提交（短哈希）
+    #[ts(type = "number | null")]
+    pub built_at: Option<u64>,      // 构建时间（Unix 毫秒）
+    pub os: String,                 // 操作系统（如 "macos"、"windows"）
+    pub arch: String,               // 架构（如 "aarch64"、"x86_64"）
+}
+
+impl AppInfo {
+    pub fn new(version: String) -> Self {
+        Self {
+            version,
+            git_commit: option_env!("YOUTUDOWN_GIT_COMMIT").map(String::from),
+            built_at: option_env!("YOUTUDOWN_BUILT_AT").and_then(|t| t.parse().ok()),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateStatus {
+    UpToDate,
+    Available,
+    Unknown,                        // 无法获取最新版本（网络错误、接口限流等）
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AppUpdate {
+    pub status: UpdateStatus,
+    pub current_version: String,
+    pub latest_version: Option<String>,
+    pub release_url: Option<String>, // 发布说明页面
+    pub detail: Option<String>,     // 状态为 Unknown 时的原因
+}
+
+/***************************************************************************
+ * 检查是否有新版本
+ *
+ * @param current_version - 当前应用版本
+ * @return AppUpdate - 检查结果（失败时状态为 Unknown）
+ ***************************************************************************/
+
+pub async fn check_update(current_version: &str) -> AppUpdate {
+    let unknown = |detail: String| {
+        warn!("检查应用更新失败: {}", detail);
+        AppUpdate {
+            status: UpdateStatus::Unknown,
+            current_version: current_version.to_string(),
+            latest_version: None,
+            release_url: None,
+            detail: Some(detail),
+        }
+    };
+
+    let release = match fetch_latest_release().await {
+        Ok(release) => release,
+        Err(e) => return unknown(e),
+    };
+    let Some(tag) = release["tag_name"].as_str() else {
+        return unknown("发布信息中没有版本号".to_string());
+    };
+    let latest = tag.trim_start_matches('v');
+    let status = match (parse_version(latest), parse_version(current_version)) {
+        (Some(latest), Some(current)) if latest > current => UpdateStatus::Available,
+        (Some(_), Some(_)) => UpdateStatus::UpToDate,
+        _ => return unknown(format!("无法比较版本: {} / {}", latest, current_version)),
+    };
+    debug!("应用更新检查: 当前 {}，最新 {}", current_version, latest);
+
+    AppUpdate {
+        status,
+        current_version: current_version.to_string(),
+        latest_version: Some(latest.to_string()),
+        release_url: release["html_url"].as_str().map(String::from),
+        detail: None,
+    }
+}
+
+/// 请求最新发布的信息（GitHub 接口要求带 User-Agent）
+async fn fetch_latest_release() -> Result<Value, String> {
+    let client = http_client(UPDATE_CHECK_TIMEOUT).map_err(|e| format!("无法创建 HTTP 客户端: {}", e))?;
+    let response = client
+        .get(LATEST_RELEASE_URL)
+        .header("User-Agent", concat!("YouTuDown/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("请求失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("接口返回 {}", response.status()));
+    }
+    let body = response.text().await.map_err(|e| format!("读取响应失败: {}", e))?;
+    serde_json::from_str(&body).map_err(|e| format!("解析响应失败: {}", e))
+}
+
+/// 解析 "主.次.修订" 版本号（忽略 "-beta.1" 等后缀）
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((major, minor, patch))
+}
@@ -0,0 +1,55 @@
+/****************************************************************************
+ *  archive.rs - 下载存档
+ *
+ *  @brief  读取 yt-dlp --download-archive 记录的已完成视频
+ *  @note   存档每行为 "提取器 视频ID"（如 "youtube dQw4w9WgXcQ"），由 yt-dlp
+ *          在视频下载完成后追加。扁平解析的播放列表条目不带提取器名，
+ *          因此只按视频ID匹配
+ *****************************************************************************/
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/***************************************************************************
+ * 校验存档文件路径
+ *
+ * 文件不存在时由 yt-dlp 创建，但所在目录必须已存在
+ *
+ * @param path - 存档文件路径
+ ***************************************************************************/
+
+pub fn validate_archive_file(path: &str) -> Result<(), String> {
+    let path = Path::new(path);
+    if path.is_dir() {
+        return Err(format!("下载存档应为文件而不是目录: {}", path.display()));
+    }
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() && !dir.is_dir() => {
+            Err(format!("下载存档所在目录不存在: {}", dir.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/***************************************************************************
+ * 读取存档中已完成的视频ID
+ *
+ * @param path - 存档文件路径
+ * @return HashSet<String> - 视频ID（文件不存在时为空，即首次下载）
+ ***************************************************************************/
+
+pub fn read_archive_ids(path: &Path) -> Result<HashSet<String>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashSet::new()),
+        Err(e) => return Err(format!("无法读取下载存档: {}", e)),
+    };
+
+    Ok(content
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(String::from)
+        .collect())
+}
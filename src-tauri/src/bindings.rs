@@ -0,0 +1,183 @@
+/****************************************************************************
+ *  bindings.rs - 前端 TypeScript 类型生成
+ *
+ *  @brief  把命令参数/返回值和事件内容的类型导出到 src/bindings/
+ *  @note   由 cargo test 重新生成（见文末的测试），生成的文件随代码提交，
+ *          CI 检查提交的文件与生成结果一致；
+ *          修改 Rust 类型后前端按新类型做类型检查，字段不一致时 tsc 报错。
+ *          每个类型一个文件，另有 events.ts（事件名常量及事件内容类型）和
+ *          汇总导出的 index.ts
+ *****************************************************************************/
+
+use std::fs;
+use std::path::Path;
+use ts_rs::TS;
+
+use crate::app_info::{AppInfo, AppUpdate};
+use crate::channel::{ChannelVideo, PlaylistEntryParsed, PlaylistInfo};
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumProgress};
+use crate::cleanup::{CleanupReport, OrphanedFile};
+use crate::commands::{
+    ChannelNewVideos, CommandPreview, DeleteHistoryResult, DestinationCheck, DownloadComplete, DownloadRetry,
+    DownloadThrottled, DownloadWarning, ImpersonationDiagnosis, InfoExtractionProgress, MoveFailed, PlaylistEnqueued,
+    RelocateResult, StoryboardResult, UrlSupport, VideoInfo, YtdlpCandidate,
+};
+use crate::cookies::CookieCheck;
+use crate::diagnostics::DiagnosticsReport;
+use crate::disk::DiskSpace;
+use crate::downloads::{DownloadState, QueueEta, QueueProgress, SpeedHistory};
+use crate::duplicates::DuplicateStatus;
+use crate::errors::AppError;
+use crate::events;
+use crate::files::FileOperationResult;
+use crate::history::HistoryEntry;
+use crate::impersonation::ImpersonationSupport;
+use crate::manifest::ManifestFormat;
+use crate::options::DownloadOptions;
+use crate::presets::Preset;
+use crate::process::PriorityMode;
+use crate::progress::{CommentsProgress, PostProcessingProgress, ProgressInfo};
+use crate::queue::{
+    BatchCompleted, BatchResult, DestinationRestored, DestinationUnavailable, DownloadFailed, NetworkRestored,
+    PriorityChange, QueueOverview, QueuePauseChanged,
+};
+use crate::search::SearchResult;
+use crate::settings::Settings;
+use crate::site_presets::SitePreset;
+use crate::subscriptions::{NewVideosFound, Subscription};
+use crate::verify::{MediaVerification, Verification};
+use crate::warnings::YtdlpWarning;
+
+/// 生成文件的说明头
+const GENERATED_HEADER: &str = "// 由 src-tauri/src/bindings.rs 生成，请勿手动修改\n";
+
+/// 事件：(常量名, 事件名, 事件内容类型)
+fn event_table() -> Vec<(&'static str, &'static str, String)> {
+    macro_rules! event {
+        ($name:ident, $payload:ty) => {
+            (stringify!($name), events::$name, <$payload as TS>::name())
+        };
+    }
+    vec![
+        event!(INFO_EXTRACTION_PROGRESS, InfoExtractionProgress),
+        event!(PLAYLIST_ENTRY_PARSED, PlaylistEntryParsed),
+        event!(DOWNLOAD_PROGRESS, ProgressInfo),
+        event!(DOWNLOAD_POSTPROCESSING, PostProcessingProgress),
+        event!(DOWNLOAD_COMMENTS, CommentsProgress),
+        event!(DOWNLOAD_THROTTLED, DownloadThrottled),
+        event!(DOWNLOAD_RETRY, DownloadRetry),
+        event!(DOWNLOAD_MOVE_FAILED, MoveFailed),
+        event!(DOWNLOAD_WARNING, DownloadWarning),
+        event!(YTDLP_WARNING, YtdlpWarning),
+        event!(DOWNLOAD_COMPLETE, DownloadComplete),
+        event!(DOWNLOAD_FAILED, DownloadFailed),
+        event!(CHECKSUM_PROGRESS, ChecksumProgress),
+        event!(QUEUE_PROGRESS, QueueProgress),
+        event!(BATCH_COMPLETED, BatchCompleted),
+        event!(NETWORK_RESTORED, NetworkRestored),
+        event!(DESTINATION_UNAVAILABLE, DestinationUnavailable),
+        event!(DESTINATION_RESTORED, DestinationRestored),
+        event!(NEW_VIDEOS_FOUND, NewVideosFound),
+        event!(STARTUP_HEALTH, DiagnosticsReport),
+        event!(QUEUE_PAUSED, QueuePauseChanged),
+        event!(QUEUE_RESUMED, QueuePauseChanged),
+    ]
+}
+
+/***************************************************************************
+ * 导出全部类型到指定目录
+ *
+ * 依赖的类型（如 VideoInfo 中的 VideoFormat）随之导出
+ *
+ * @param dir - 输出目录（不存在时创建）
+ ***************************************************************************/
+
+pub fn export_bindings(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建类型目录: {}", e))?;
+
+    // 先清空旧文件，已删除或改名的类型不会残留
+    for entry in fs::read_dir(dir).map_err(|e| format!("无法读取类型目录: {}", e))?.flatten() {
+        if entry.path().extension().is_some_and(|ext| ext == "ts") {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+
+    macro_rules! export {
+        ($($ty:ty),* $(,)?) => {
+            $(<$ty as TS>::export_all_to(dir).map_err(|e| format!("导出 {} 失败: {}", stringify!($ty), e))?;)*
+        };
+    }
+    export!(
+        // 命令参数和返回值
+        VideoInfo, DownloadOptions, Settings, Preset, SitePreset, PriorityMode, ManifestFormat,
+        DownloadState, QueueOverview, QueueProgress, QueueEta, SpeedHistory, HistoryEntry, DuplicateStatus,
+        ImpersonationSupport, ImpersonationDiagnosis, YtdlpCandidate, UrlSupport, DiagnosticsReport,
+        DiskSpace, OrphanedFile, CleanupReport, DeleteHistoryResult, FileOperationResult, RelocateResult,
+        Checksum, ChecksumAlgorithm, SearchResult, ChannelVideo, Subscription, StoryboardResult,
+        PlaylistEnqueued, ChannelNewVideos, BatchResult, Verification, MediaVerification, AppInfo, AppUpdate,
+        AppError, PlaylistInfo, CookieCheck, CommandPreview, DestinationCheck, PriorityChange,
+        // 事件内容
+        InfoExtractionProgress, PlaylistEntryParsed, ProgressInfo, PostProcessingProgress, CommentsProgress,
+        DownloadThrottled,
+        DownloadRetry, MoveFailed, DownloadWarning, YtdlpWarning, DownloadComplete, DownloadFailed, ChecksumProgress,
+        BatchCompleted, NetworkRestored, DestinationUnavailable, DestinationRestored, NewVideosFound,
+        QueuePauseChanged,
+    );
+
+    let events = event_table();
+    fs::write(dir.join("events.ts"), events_ts(&events)).map_err(|e| format!("写入 events.ts 失败: {}", e))?;
+    fs::write(dir.join("index.ts"), index_ts(dir)?).map_err(|e| format!("写入 index.ts 失败: {}", e))?;
+    Ok(())
+}
+
+/// events.ts：事件名常量，以及事件名 → 事件内容类型的映射
+fn events_ts(events: &[(&str, &str, String)]) -> String {
+    let mut payloads: Vec<&str> = events.iter().map(|(_, _, payload)| payload.as_str()).collect();
+    payloads.sort();
+    payloads.dedup();
+
+    let mut out = String::from(GENERATED_HEADER);
+    for payload in payloads {
+        out.push_str(&format!("import type {{ {0} }} from \"./{0}\";\n", payload));
+    }
+    out.push('\n');
+    for (name, event, _) in events {
+        out.push_str(&format!("export const {} = \"{}\" as const;\n", name, event));
+    }
+    out.push_str("\nexport type EventPayloads = {\n");
+    for (name, _, payload) in events {
+        out.push_str(&format!("  [{}]: {};\n", name, payload));
+    }
+    out.push_str("};\n\nexport type EventName = keyof EventPayloads;\n");
+    out
+}
+
+/// index.ts：汇总导出目录中的全部类型文件
+fn index_ts(dir: &Path) -> Result<String, String> {
+    let mut modules: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| format!("无法读取类型目录: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter_map(|name| name.strip_suffix(".ts").map(str::to_string))
+        .filter(|name| name != "index")
+        .collect();
+    modules.sort();
+
+    let mut out = String::from(GENERATED_HEADER);
+    for module in modules {
+        out.push_str(&format!("export * from \"./{}\";\n", module));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 重新生成 src/bindings（CI 随后用 git diff 检查是否与提交的文件一致）
+    #[test]
+    fn export_frontend_bindings() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/bindings");
+        export_bindings(&dir).unwrap();
+    }
+}
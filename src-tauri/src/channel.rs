@@ -0,0 +1,165 @@
+/****************************************************************************
+ *  channel.rs - 频道视频列表
+ *
+ *  @brief  列出频道/用户主页最近上传的视频（可按上传日期过滤）及播放列表条目
+ *  @note   上万个视频的频道一次性解析会很慢，命令逐行读取 yt-dlp 输出，
+ *          达到数量上限后立即结束进程
+ *****************************************************************************/
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use ts_rs::TS;
+
+/// 单次最多列出的视频数
+pub const MAX_CHANNEL_VIDEOS: usize = 500;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ChannelVideo {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub duration: Option<f64>,      // 时长（秒）
+    pub upload_date: Option<String>, // 上传日期（YYYYMMDD，扁平解析时部分站点不提供）
+}
+
+/// get_playlist_info 的返回值
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PlaylistInfo {
+    pub fetch_id: String,           // 用于取消和关联 playlist-entry-parsed 事件
+    pub title: Option<String>,      // 播放列表标题
+    pub entries: Vec<ChannelVideo>,
+    pub cancelled: bool,            // 获取中途被取消，entries 只包含取消前解析出的条目
+}
+
+/// 获取播放列表信息时每解析出一个条目发送的事件
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PlaylistEntryParsed {
+    pub fetch_id: String,
+    pub index: usize,               // 条目在列表中的序号（从 0 开始）
+    pub entry: ChannelVideo,        // 解析出的条目（与最终结果中的条目相同）
+}
+
+/***************************************************************************
+ * 进行中的播放列表信息获取（Tauri 托管状态）
+ ***************************************************************************/
+
+#[derive(Default)]
+pub struct PlaylistFetches {
+    cancelled: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl PlaylistFetches {
+    /// 登记一次获取，返回其取消标志
+    pub fn start(&self, fetch_id: &str) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Ok(mut fetches) = self.cancelled.lock() {
+            fetches.insert(fetch_id.to_string(), flag.clone());
+        }
+        flag
+    }
+
+    pub fn finish(&self, fetch_id: &str) {
+        if let Ok(mut fetches) = self.cancelled.lock() {
+            fetches.remove(fetch_id);
+        }
+    }
+
+    /// 取消获取（不存在或已结束时返回 false）
+    pub fn cancel(&self, fetch_id: &str) -> bool {
+        let flag = self.cancelled.lock().ok().and_then(|fetches| fetches.get(fetch_id).cloned());
+        match flag {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/***************************************************************************
+ * 校验日期参数
+ *
+ * @param date - YYYYMMDD 格式的日期
+ * @return String - 校验通过的日期
+ ***************************************************************************/
+
+pub fn validate_date(date: &str) -> Result<String, String> {
+    let date = date.trim();
+    let valid = date.len() == 8
+        && date.chars().all(|c| c.is_ascii_digit())
+        && matches!(date[4..6].parse::<u32>(), Ok(1..=12))
+        && matches!(date[6..8].parse::<u32>(), Ok(1..=31));
+    if !valid {
+        return Err(format!("日期格式无效（应为 YYYYMMDD）: {}", date));
+    }
+    Ok(date.to_string())
+}
+
+/***************************************************************************
+ * Unix 毫秒时间对应的日期（UTC）
+ *
+ * @return String - YYYYMMDD 格式，可直接用于 --dateafter
+ ***************************************************************************/
+
+pub fn date_of(unix_millis: u64) -> String {
+    // 按公历推算（Howard Hinnant 的 civil_from_days）
+    let days = (unix_millis / 86_400_000) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}{:02}{:02}", year, month, day)
+}
+
+/***************************************************************************
+ * 解析 --flat-playlist --dump-json 输出中的一行
+ *
+ * 不是视频条目（如嵌套的播放列表标签页）时返回 None
+ ***************************************************************************/
+
+pub fn parse_channel_entry(line: &str) -> Option<ChannelVideo> {
+    let json: Value = serde_json::from_str(line).ok()?;
+    channel_video(&json)
+}
+
+/// 由已解析的 JSON 条目生成 ChannelVideo（不是视频条目时返回 None）
+pub fn channel_video(json: &Value) -> Option<ChannelVideo> {
+    if json["_type"].as_str() == Some("playlist") {
+        return None;
+    }
+
+    let id = json["id"].as_str()?.to_string();
+    let url = json["url"]
+        .as_str()
+        .or_else(|| json["webpage_url"].as_str())
+        .map(String::from)
+        .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", id));
+
+    Some(ChannelVideo {
+        title: json["title"].as_str().unwrap_or("未知标题").to_string(),
+        url,
+        duration: json["duration"].as_f64(),
+        upload_date: json["upload_date"].as_str().map(String::from),
+        id,
+    })
+}
+
+/// 已知上传日期早于 after_date 的条目不返回（日期未知时保留，交给下载时再过滤）
+pub fn is_after(video: &ChannelVideo, after_date: Option<&str>) -> bool {
+    match (video.upload_date.as_deref(), after_date) {
+        (Some(date), Some(after)) => date >= after,
+        _ => true,
+    }
+}
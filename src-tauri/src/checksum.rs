@@ -0,0 +1,105 @@
+/****************************************************************************
+ *  checksum.rs - 文件校验和
+ *
+ *  @brief  流式计算下载文件的 SHA-256 / MD5，供归档时核对文件完整性
+ *  @note   计算在阻塞线程中进行（spawn_blocking），大文件按块读取并回调进度，
+ *          不会把整个文件读入内存，也不会阻塞异步运行时
+ *****************************************************************************/
+
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// 每次读取的块大小
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// 超过该大小的文件才发送进度事件
+pub const PROGRESS_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// 进度回调的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Md5,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub hex: String,                // 小写十六进制摘要
+    #[ts(type = "number")]
+    pub computed_at: u64,           // 计算时间（Unix 毫秒）
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ChecksumProgress {
+    pub path: String,
+    #[ts(type = "number")]
+    pub hashed_bytes: u64,
+    #[ts(type = "number")]
+    pub total_bytes: u64,
+    pub percent: f64,
+}
+
+/***************************************************************************
+ * 流式计算文件摘要
+ *
+ * @param path - 文件路径
+ * @param algorithm - 摘要算法
+ * @param on_progress - 进度回调 (已处理字节, 总字节)，最多每 500ms 调用一次，结束时必定调用
+ * @return String - 小写十六进制摘要
+ ***************************************************************************/
+
+pub fn hash_file(
+    path: &Path,
+    algorithm: ChecksumAlgorithm,
+    mut on_progress: impl FnMut(u64, u64),
+) -> io::Result<String> {
+    match algorithm {
+        ChecksumAlgorithm::Sha256 => hash_with::<Sha256>(path, &mut on_progress),
+        ChecksumAlgorithm::Md5 => hash_with::<Md5>(path, &mut on_progress),
+    }
+}
+
+fn hash_with<D: Digest>(path: &Path, on_progress: &mut impl FnMut(u64, u64)) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let total = file.metadata()?.len();
+
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut hashed = 0u64;
+    let mut last_report = Instant::now();
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        hashed += read as u64;
+
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            on_progress(hashed, total);
+            last_report = Instant::now();
+        }
+    }
+    on_progress(hashed, total);
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
@@ -0,0 +1,214 @@
+/****************************************************************************
+ *  cleanup.rs - 残留临时文件清理
+ *
+ *  @brief  扫描崩溃或取消后遗留的 yt-dlp 临时文件（.part、.ytdl、未合并的 .fNNN 分流）
+ *  @note   只匹配 yt-dlp 已知的临时文件命名规则，不会触及完成的媒体文件；
+ *          正在进行或暂停的下载所使用的文件、等待重新合并的音视频流由调用方通过 exclude 排除
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::{debug, warn};
+use ts_rs::TS;
+
+/// 递归扫描的最大目录深度（按站点/上传者分目录时需要进入子目录）
+const MAX_SCAN_DEPTH: usize = 4;
+
+/// 分流文件（name.f137.mp4）可能使用的扩展名
+const FRAGMENT_EXTENSIONS: &[&str] = &[
+    "mp4", "m4a", "webm", "mkv", "mp3", "opus", "ogg", "flv", "3gp", "mov", "ts", "aac",
+];
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+pub enum OrphanKind {
+    Partial,                        // 未下载完的 .part / .part-FragN
+    Metadata,                       // 断点续传信息 .ytdl
+    Fragment,                       // 未合并的分流文件 name.fNNN.ext
+    Temp,                           // 后处理中间文件 name.temp.ext
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct OrphanedFile {
+    pub path: String,
+    pub kind: OrphanKind,
+    #[ts(type = "number")]
+    pub size: u64,                  // 文件大小（字节）
+    #[ts(type = "number | null")]
+    pub modified: Option<u64>,      // 修改时间（Unix 毫秒）
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CleanupFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct CleanupReport {
+    pub deleted: usize,             // 删除的文件数
+    #[ts(type = "number")]
+    pub reclaimed_bytes: u64,       // 释放的空间（字节）
+    pub failed: Vec<CleanupFailure>,
+}
+
+/***************************************************************************
+ * 按文件名判断是否为 yt-dlp 临时文件
+ ***************************************************************************/
+
+pub fn classify(file_name: &str) -> Option<OrphanKind> {
+    if file_name.ends_with(".ytdl") {
+        return Some(OrphanKind::Metadata);
+    }
+    if file_name.ends_with(".part") || is_part_fragment(file_name) {
+        return Some(OrphanKind::Partial);
+    }
+
+    // name.<marker>.<ext>，marker 为 temp 或 f + 格式ID
+    let (rest, ext) = file_name.rsplit_once('.')?;
+    if !FRAGMENT_EXTENSIONS.contains(&ext.to_lowercase().as_str()) {
+        return None;
+    }
+    let (_, marker) = rest.rsplit_once('.')?;
+    if marker == "temp" {
+        return Some(OrphanKind::Temp);
+    }
+    is_format_marker(marker).then_some(OrphanKind::Fragment)
+}
+
+/// name.part-Frag12 形式的分片文件
+fn is_part_fragment(file_name: &str) -> bool {
+    file_name
+        .rsplit_once(".part-Frag")
+        .is_some_and(|(_, index)| !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// f + 数字开头的格式ID（如 f137、f140-drc、f303-1），避免误判 "final" 等普通单词
+fn is_format_marker(marker: &str) -> bool {
+    let Some(id) = marker.strip_prefix('f') else {
+        return false;
+    };
+    id.starts_with(|c: char| c.is_ascii_digit())
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 文件是否属于正在使用的下载（临时文件名以下载目标路径开头）
+fn is_excluded(path: &Path, exclude: &[String]) -> bool {
+    let path = path.to_string_lossy();
+    exclude.iter().any(|prefix| path.starts_with(prefix.as_str()))
+}
+
+/***************************************************************************
+ * 扫描目录中的残留临时文件
+ *
+ * @param dir - 要扫描的目录
+ * @param exclude - 正在进行或暂停的下载的目标文件及等待重新合并的流的路径
+ * @return Vec<OrphanedFile> - 按修改时间从旧到新排列
+ ***************************************************************************/
+
+pub fn scan_orphaned_files(dir: &Path, exclude: &[String]) -> Result<Vec<OrphanedFile>, String> {
+    if !dir.is_dir() {
+        return Err(format!("目录不存在: {}", dir.display()));
+    }
+
+    let mut files = Vec::new();
+    scan_dir(dir, exclude, 0, &mut files);
+    files.sort_by_key(|file| file.modified.unwrap_or(0));
+
+    debug!("在 {} 中发现 {} 个残留文件", dir.display(), files.len());
+    Ok(files)
+}
+
+fn scan_dir(dir: &Path, exclude: &[String], depth: usize, files: &mut Vec<OrphanedFile>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("读取目录失败: {} ({})", dir.display(), e);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        if metadata.is_dir() {
+            if depth + 1 < MAX_SCAN_DEPTH {
+                scan_dir(&path, exclude, depth + 1, files);
+            }
+            continue;
+        }
+
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let Some(kind) = classify(&file_name) else {
+            continue;
+        };
+        if is_excluded(&path, exclude) {
+            debug!("跳过正在使用的文件: {}", path.display());
+            continue;
+        }
+
+        files.push(OrphanedFile {
+            path: path.to_string_lossy().into_owned(),
+            kind,
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64),
+        });
+    }
+}
+
+/***************************************************************************
+ * 删除用户确认的残留文件
+ *
+ * 删除前重新校验文件名规则和使用状态，不符合的路径记为失败而不是删除
+ *
+ * @param paths - 待删除的文件（来自 scan_orphaned_files 的结果）
+ * @param exclude - 正在进行或暂停的下载的目标文件及等待重新合并的流的路径
+ ***************************************************************************/
+
+pub fn clean_orphaned_files(paths: &[String], exclude: &[String]) -> CleanupReport {
+    let mut report = CleanupReport::default();
+
+    for path in paths {
+        let path_buf = PathBuf::from(path);
+        let fail = |error: String| CleanupFailure {
+            path: path.clone(),
+            error,
+        };
+
+        let is_temp_file = path_buf
+            .file_name()
+            .and_then(|name| classify(&name.to_string_lossy()))
+            .is_some();
+        if !is_temp_file {
+            report.failed.push(fail("不是 yt-dlp 临时文件".to_string()));
+            continue;
+        }
+        if is_excluded(&path_buf, exclude) {
+            report.failed.push(fail("文件正在被下载任务使用".to_string()));
+            continue;
+        }
+
+        let size = fs::metadata(&path_buf).map(|m| m.len()).unwrap_or(0);
+        match fs::remove_file(&path_buf) {
+            Ok(()) => {
+                report.deleted += 1;
+                report.reclaimed_bytes += size;
+            }
+            Err(e) => report.failed.push(fail(e.to_string())),
+        }
+    }
+
+    report
+}
@@ -6,43 +6,376 @@
  *****************************************************************************/
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
-use tauri::{command, AppHandle, Emitter};
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tokio::io::BufReader;
+use tracing::{debug, info, info_span, warn, Instrument};
+use ts_rs::TS;
+
+use crate::app_info::{check_update, AppInfo, AppUpdate};
+use crate::archive::read_archive_ids;
+use crate::channel::{
+    channel_video, date_of, is_after, parse_channel_entry, validate_date, ChannelVideo, PlaylistEntryParsed,
+    PlaylistFetches, PlaylistInfo, MAX_CHANNEL_VIDEOS,
+};
+use crate::checksum::{
+    hash_file, Checksum, ChecksumAlgorithm, ChecksumProgress,
+    PROGRESS_THRESHOLD as CHECKSUM_PROGRESS_THRESHOLD,
+};
+use crate::cleanup::{self, CleanupReport, OrphanedFile};
+use crate::cookies::{self, CookieCheck, CookieSource};
+use crate::diagnostics::{ffmpeg_install_command, ytdlp_install_command, DiagnosticCheck, DiagnosticsReport};
+use crate::disk::{disk_space, DiskSpace};
+use crate::downloads::{
+    compute_queue_eta, compute_queue_progress, unix_millis, DownloadManager, DownloadState, DownloadStatus, QueueEta,
+    QueueProgress, SpeedHistory,
+};
+use crate::duplicates::{find_duplicate, DuplicateStatus};
+use crate::errors::{
+    geo_available_region, geo_bypass_country, is_bot_detection_error, is_geo_blocked_error, is_postprocess_error,
+    is_upcoming_error, ytdlp_error,
+};
+use crate::events;
+use crate::extra_args::{overridden_options, redact_secrets, validate_extra_args};
+use crate::extractors::{filter_extractors, ExtractorState, GENERIC_EXTRACTOR};
+use crate::ffmpeg::{find_ffmpeg, find_ffprobe, merge_streams, probe_media, MediaProbe};
+use crate::files::{plan_move, plan_rename, relocate, trash_entry_files, FileLocks, FileOperationResult};
+use crate::history::{HistoryEntry, HistoryStore, OutputFiles, PendingMerge};
+use crate::i18n::{self, Locale};
+use crate::impersonation::{
+    filter_impersonate_args, is_impersonation_error, ImpersonationState, ImpersonationSupport,
+};
+use crate::process::{self, ytdlp_command, PriorityMode};
+use crate::presets::{all_presets, is_builtin_name, merge_options, Preset};
+use crate::progress::{
+    CommentsProgress, DownloadedFormat, FormatReportFile, OutputTracker, PostProcessingStage, ThrottleDetector,
+};
+use crate::network::{is_online, offline_error};
+use crate::output_lines::{lossy_lines, resolve_lossy_path};
+use crate::options::{
+    archive_best_args, build_download_args, duration_in_range, filename_args, incompatible_codecs, network_args,
+    validate_output_template, validate_user_agent, DownloadOptions, FormatGoal, FormatSelector, DEFAULT_USER_AGENT,
+};
+use crate::queue::{
+    parse_batch_lines, BatchEnqueued, BatchLineError, BatchResult, DownloadQueue, PriorityChange, QueueOverview,
+    MAX_GROUP_CONCURRENCY,
+};
+use crate::search::{parse_search_results, search_target, SearchResult};
+use crate::settings::{validate_writable_dir, Settings, SettingsState};
+use crate::site_presets::{find_site_preset, validate_site_presets, SitePreset};
+use crate::staging::{
+    move_staged_files, remove_staging_if_empty, resolve_moved_path, staging_dir, staging_path, staging_root,
+};
+use crate::storyboard::{extract_mhtml_images, is_storyboard_format};
+use crate::subscriptions::{Subscription, SubscriptionStore};
+use crate::urls::{is_single_video_url, is_watch_with_list, normalize_url, validate_url};
+use crate::playlist_items::PartialSuccess;
+use crate::thumbnails::{best_thumbnail, parse_thumbnails, Thumbnail};
+use crate::verify::{verify_media, verify_output, MediaVerification, Verification};
+use crate::warnings::{parse_warning_line, WarningKind, YtdlpWarning};
+use crate::ytdlp::{
+    first_video_entry, get_ytdlp_path, pin_ytdlp_path, prefer_bundled_ytdlp, ytdlp_candidates, ytdlp_path_source,
+    DownloadEvent, DownloadOutcome, MediaBackend, YtDlp, YtdlpError,
+};
+
+/// 高于该帧率的格式单独列为高帧率选项（如 "1080p60"）
+const HIGH_FRAME_RATE: f64 = 30.0;
+
+/// 检查链接是否受支持的超时时间
+const URL_PROBE_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// 后处理阶段发送 download-postprocessing 事件的间隔
+const POSTPROCESSING_TICK: Duration = Duration::from_secs(1);
+
+/// 只下载频道新视频时默认检查的最新视频数
+const CHANNEL_NEW_LIMIT: usize = 50;
+
+/// 首映或直播开始时间到了仍不可下载时，重新检查的间隔（秒，--wait-for-video）
+const RELEASE_RECHECK_INTERVAL: u32 = 60;
 
 /***************************************************************************
  * 数据结构定义
  ***************************************************************************/
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, TS)]
 pub struct VideoInfo {
     pub id: String,
     pub title: String,
     pub duration: f64,              // 视频时长（秒）
-    pub thumbnail: String,          // 缩略图URL
+    pub thumbnail: String,          // 缩略图URL（yt-dlp 选出的一张，可能分辨率较低）
+    pub thumbnails: Vec<Thumbnail>, // 全部缩略图（偏好从低到高）
+    pub description: Option<String>, // 视频简介
     pub formats: Vec<VideoFormat>,
     pub available_resolutions: Vec<ResolutionOption>,  // 可用分辨率选项
+    pub audio_languages: Vec<String>, // 可选的音轨语言（多语言配音视频）
+    pub extractor: Option<String>,  // 提取器名称（如 "youtube"、"BiliBili"）
+    pub extractor_key: Option<String>, // 提取器标识（如 "Youtube"，用于显示来源和站点默认选项）
+    pub webpage_url: Option<String>, // 视频页面地址
+    pub drm_only: bool,             // 所有视频格式都受 DRM 保护，无法下载
+    pub cookies_refreshed: bool,    // 因机器人验证失败，重新读取浏览器 Cookie 后重试成功
+    pub playlist: Option<PlaylistSummary>, // 链接同时指向播放列表且未指定按哪种处理时，播放列表的概要
+    pub upcoming: Option<UpcomingRelease>, // 尚未开始的首映或直播（暂时没有可下载的格式）
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 尚未开始的首映或直播
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct UpcomingRelease {
+    #[ts(type = "number | null")]
+    pub release_at: Option<u64>,    // 计划开始时间（Unix 毫秒，yt-dlp 未提供时为 None）
+}
+
+/// 带 list 参数的视频链接所指向的播放列表（供界面询问用户按视频还是播放列表处理）
+#[derive(Debug, Serialize, Deserialize, TS)]
+pub struct PlaylistSummary {
+    pub title: Option<String>,      // 播放列表标题
+    pub count: Option<usize>,       // 条目数（yt-dlp 未报告时为 None）
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
 pub struct ResolutionOption {
+    #[ts(type = "number")]
     pub height: i64,                // 分辨率高度
     pub label: String,              // 显示标签（如 "1080p"）
-    pub format_id: String,          // 推荐的格式ID
+    pub format_id: String,          // 推荐的格式ID（即 candidates 中 recommended 的一项）
+    pub requires_merge: bool,       // 推荐格式是纯视频，需要与音频合并（需要 ffmpeg）
+    pub is_progressive: bool,       // 推荐格式本身包含音频，无需合并
+    pub fps: Option<f64>,           // 高帧率（超过 30fps）时的帧率，其余为 None
+    pub is_hdr: bool,               // HDR 选项（与同分辨率的 SDR 选项分开列出）
+    pub above_max_resolution: bool, // 超过设置中的分辨率上限
+    pub candidates: Vec<FormatCandidate>, // 该选项下的全部格式（如同为 1080p 的 avc1 和 vp9），按 yt-dlp 的顺序
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 同一分辨率选项下可选的一个格式
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct FormatCandidate {
+    pub format_id: String,
+    pub vcodec: Option<String>,     // 视频编码（如 "avc1.640028"、"vp9"）
+    pub ext: String,                // 文件扩展名
+    #[ts(type = "number | null")]
+    pub filesize: Option<i64>,      // 文件大小（字节，可能为估算值）
+    pub requires_merge: bool,       // 纯视频，需要与音频合并
+    pub recommended: bool,          // 默认选择的格式（与 ResolutionOption.format_id 相同）
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub enum FormatKind {
+    Video,                          // 纯视频
+    Audio,                          // 纯音频
+    Muxed,                          // 音视频合一（编码未知时也归为此类）
+}
+
+impl FormatKind {
+    /// 按编码判断格式种类，音视频编码都为 "none"（如故事板）时返回 None
+    fn classify(vcodec: Option<&str>, acodec: Option<&str>) -> Option<Self> {
+        let has_video = vcodec.map(|c| c != "none");
+        let has_audio = acodec.map(|c| c != "none");
+        match (has_video, has_audio) {
+            (Some(false), Some(false)) => None,
+            (Some(false), _) => Some(FormatKind::Audio),
+            (_, Some(false)) => Some(FormatKind::Video),
+            _ => Some(FormatKind::Muxed),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, TS)]
 pub struct VideoFormat {
     pub format_id: String,
+    pub kind: Option<FormatKind>,   // 格式种类（没有音视频流时为 None）
+    #[ts(type = "number | null")]
     pub height: Option<i64>,        // 分辨率高度
+    #[ts(type = "number | null")]
     pub width: Option<i64>,         // 分辨率宽度
     pub ext: String,                // 文件扩展名
-    pub filesize: Option<i64>,      // 文件大小（字节）
+    #[ts(type = "number | null")]
+    pub filesize: Option<i64>,      // 文件大小（字节，没有精确值时为 yt-dlp 的估算值）
     pub vcodec: Option<String>,     // 视频编码
     pub acodec: Option<String>,     // 音频编码
+    pub language: Option<String>,   // 音轨语言（如 "en"、"ja"）
+    #[serde(default)]
+    pub has_drm: bool,              // 受 DRM 保护（yt-dlp 无法下载）
+    #[serde(default)]
+    pub format_note: Option<String>, // 格式说明（如 "1080p60 HDR"、"English original"）
+    #[serde(default)]
+    pub dynamic_range: Option<String>, // 动态范围（SDR/HDR10/HLG 等）
+    #[serde(default)]
+    pub fps: Option<f64>,           // 帧率
+}
+
+impl VideoFormat {
+    /// 纯视频格式，下载后需要与音频合并
+    fn requires_merge(&self) -> bool {
+        self.acodec.as_deref() == Some("none")
+    }
+
+    /// 高动态范围格式（动态范围未知或为 SDR 时为 false）
+    fn is_hdr(&self) -> bool {
+        self.dynamic_range
+            .as_deref()
+            .is_some_and(|range| !range.eq_ignore_ascii_case("SDR"))
+    }
+
+    /// 高帧率格式的帧率（取整，30fps 及以下或未知时为 None）
+    fn high_frame_rate(&self) -> Option<i64> {
+        self.fps.filter(|fps| *fps > HIGH_FRAME_RATE).map(|fps| fps.round() as i64)
+    }
+
+    /// 音视频合一的格式（编码均已知且都不为 "none"）
+    fn is_progressive(&self) -> bool {
+        let has = |codec: &Option<String>| codec.as_deref().is_some_and(|c| c != "none");
+        has(&self.vcodec) && has(&self.acodec)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct InfoExtractionProgress {
+    pub url: String,
+    pub extractor: Option<String>,  // 提取器（如 "youtube"），非提取器输出时为 None
+    pub message: String,            // 当前步骤（如 "Downloading player JSON"）
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ImpersonationDiagnosis {
+    pub caused_by_impersonation: bool, // 错误由伪装目标不可用（缺少 curl_cffi）引起
+    pub support: ImpersonationSupport,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct StoryboardResult {
+    pub format_id: String,          // 使用的故事板格式（分辨率最高的一个）
+    #[ts(type = "number | null")]
+    pub width: Option<i64>,         // 单帧宽度
+    #[ts(type = "number | null")]
+    pub height: Option<i64>,        // 单帧高度
+    pub images: Vec<String>,        // 保存的拼图图片
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PlaylistEnqueued {
+    pub playlist_id: String,        // 分组ID（用于查询整体进度）
+    pub entries: Vec<ChannelVideo>,
+    pub download_ids: Vec<String>,  // 与 entries 顺序一致
+    pub concurrency: usize,
+    pub skipped: Vec<ChannelVideo>, // 已在下载存档中、没有加入队列的条目
+    pub filtered: Vec<ChannelVideo>, // 时长不在 min_duration/max_duration 范围内、没有加入队列的条目
+}
+
+/// download_channel_new 的返回值
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ChannelNewVideos {
+    #[ts(type = "number | null")]
+    pub since: Option<u64>,         // 上次运行时间（Unix 毫秒，首次运行时为 None）
+    pub new_count: usize,           // 加入队列的新视频数
+    pub enqueued: PlaylistEnqueued, // 分组结束时 batch-completed 的 downloaded 为实际新下载的视频数
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DownloadComplete {
+    pub download_id: String,
+    pub output_dir: Option<String>,   // 下载目录（任务指定的目录或默认下载目录）
+    pub output_path: Option<String>,  // 最终输出文件
+    pub kept_files: Vec<String>,      // 保留下来的中间文件（开启 keep_fragments 时）
+    pub downloaded_format: Option<DownloadedFormat>, // 实际下载的格式（分辨率可能已回退）
+    pub audio_language_fallback: bool, // 请求的音轨语言不存在，已回退到默认音轨
+    pub thumbnail_path: Option<String>, // 缩略图文件（转换格式后为转换后的文件）
+    pub stream_files: Vec<String>,    // 分别下载音视频时的视频文件和音频文件
+    pub verified: bool,               // 输出文件校验是否通过
+    pub verification: Verification,   // 校验详情（大小、发现的问题）
+    pub archive_tracks: Option<MediaProbe>, // 归档模式下输出文件包含的轨道（未找到 ffprobe 时为 None）
+    pub warnings: u32,                // 下载过程中 yt-dlp 输出的警告数（内容见 ytdlp-warning 事件）
+    pub partial: Option<PartialSuccess>, // 播放列表部分条目失败时各条目的结果（全部成功时为 None）
+    #[ts(type = "number | null")]
+    pub comment_count: Option<u64>,   // 写入 .info.json 的评论数（未开启 write_comments 或读取失败时为 None）
+}
+
+/// 下载速度持续低于限速阈值，或 yt-dlp 报告被限速
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DownloadThrottled {
+    pub download_id: String,
+    pub bytes_per_sec: Option<f64>, // 当前速度（yt-dlp 报告时可能未知）
+    #[ts(type = "number | null")]
+    pub threshold: Option<u64>,     // 限速阈值（字节/秒）
+    pub restarting: bool,           // 已开启 --throttled-rate，yt-dlp 会重新提取并重启分片
+}
+
+/// 下载出错，yt-dlp 等待后重试
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DownloadRetry {
+    pub download_id: String,
+    pub attempt: u32,               // 第几次重试（从 1 开始）
+    pub retries: u32,               // 最多重试次数
+    pub wait_seconds: Option<f64>,  // 重试前的等待（按 retry_sleep 计算，未设置时为 None）
+    pub reason: String,             // 出错原因（如 "HTTP Error 429: Too Many Requests"）
+}
+
+/// 下载完成但输出文件可疑（截断、空文件、容器损坏）
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DownloadWarning {
+    pub download_id: String,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DeleteHistoryResult {
+    pub entry_removed: bool,          // 记录是否已删除（有文件处理失败时保留）
+    pub files: Vec<FileOperationResult>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct RelocateResult {
+    pub entry: HistoryEntry,          // 更新后的历史记录
+    pub files: Vec<FileOperationResult>,
+}
+
+/// 暂存模式下，下载成功但移动到下载目录失败（文件仍保留在暂存目录中）
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct MoveFailed {
+    pub download_id: String,
+    pub staging_dir: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct UrlSupport {
+    pub supported: bool,            // yt-dlp 能否解析该链接
+    pub extractor: Option<String>,  // 匹配的提取器（如 "Youtube"）
+    pub generic: bool,              // 只被通用提取器匹配（页面中找到了媒体，但站点没有专门支持）
+    pub timed_out: bool,            // 探测超时，无法确定
+    pub error: Option<String>,      // 无法解析时 yt-dlp 的错误信息
+}
+
+/// validate_download_dir 的返回值
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DestinationCheck {
+    pub path: String,               // 检查的目录
+    pub available: bool,            // 目录存在且可写
+    pub error: Option<String>,      // 不可用的原因
+}
+
+/// 下载命令预览（与实际下载使用相同的参数）
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CommandPreview {
+    pub program: String,            // yt-dlp 路径
+    pub args: Vec<String>,          // 完整参数（含配置文件参数，最后一项为 URL）
+    pub extra_args_start: usize,    // 自定义参数在 args 中的起始位置
+    pub extra_args_len: usize,      // 自定义参数个数（没有时为 0）
+    pub overridden: Vec<String>,    // 被自定义参数覆盖的生成选项
+    pub redacted: Vec<String>,      // 值已隐藏的选项（如 --cookies），只显示选项名
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct YtdlpCandidate {
+    pub path: String,
+    pub version: Option<String>,    // yt-dlp --version 的输出（无法运行时为 None）
+    pub pinned: bool,               // 设置中固定的路径
+    pub active: bool,               // 当前实际使用的路径
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -52,213 +385,494 @@ pub struct DownloadConfig {
 }
 
 /***************************************************************************
- * 公共函数 - 获取 yt-dlp 可执行文件路径
+ * 格式化 yt-dlp 错误信息
+ *
+ * @param stderr - yt-dlp 标准错误输出
+ * @param ytdlp_path - 出错的 yt-dlp（用于给出对应安装环境的修复命令）
+ * @return String - 当前语言的错误信息，包含解决建议
  ***************************************************************************/
 
-fn get_ytdlp_path() -> Result<PathBuf, String> {
-    let ytdlp_names = if cfg!(target_os = "windows") {
-        vec!["yt-dlp.exe", "yt-dlp_x86.exe", "yt-dlp.exe_x86.exe"]
-    } else {
-        vec!["yt-dlp", "yt-dlp_linux", "yt-dlp_macos"]
-    };
+fn format_ytdlp_error(stderr: &str, ytdlp_path: &Path) -> String {
+    ytdlp_error(stderr, ytdlp_path).to_string()
+}
 
-    // 1. 尝试从 PATH 环境变量查找
-    if let Ok(path_var) = std::env::var("PATH") {
-        for dir in std::env::split_paths(&path_var) {
-            for name in &ytdlp_names {
-                let path = dir.join(name);
-                if path.exists() && path.is_file() {
-                    return Ok(path);
-                }
-            }
-        }
-    }
+/***************************************************************************
+ * Tauri 命令 - 获取视频信息
+ *
+ * 先区分单个视频和播放列表：链接本身能确定是单个视频时直接完整解析；
+ * 否则用 --flat-playlist 做一次快速探测，结果已带格式列表（单个视频）时直接使用，
+ * 不带时（播放列表条目，或部分站点的扁平结果）再完整解析，保证格式来自完整的视频信息。
+ * 尚未开始的首映或直播不作为错误，返回的 upcoming 中带计划开始时间。
+ * 带 list 参数的视频链接（watch?v=ID&list=...）默认附加 --no-playlist 只解析该视频；
+ * treat_as_playlist 为 Some(true) 时按播放列表处理，未指定时另外获取播放列表概要
+ * 填入 playlist，由界面询问用户想要哪一种
+ *
+ * @param url - 视频URL（支持YouTube、Bilibili等yt-dlp支持的网站）
+ * @param user_agent - 覆盖默认 User-Agent（None 使用 DEFAULT_USER_AGENT）
+ * @param treat_as_playlist - 带 list 参数的视频链接是否按播放列表处理（None 为未指定）
+ * @return VideoInfo - 包含标题、时长、缩略图、可用格式等信息
+ ***************************************************************************/
 
-    // 2. 尝试 common 安装路径
-    #[cfg(target_os = "macos")]
-    {
-        let homebrew_paths = vec![
-            "/opt/homebrew/bin/yt-dlp",
-            "/usr/local/bin/yt-dlp",
-            "/opt/homebrew/bin/yt-dlp",
-        ];
-        for path in homebrew_paths {
-            let path = PathBuf::from(path);
-            if path.exists() {
-                return Ok(path);
-            }
-        }
+#[command]
+pub async fn get_video_info(
+    app: AppHandle,
+    impersonation: State<'_, ImpersonationState>,
+    settings: State<'_, SettingsState>,
+    url: String,
+    user_agent: Option<String>,
+    treat_as_playlist: Option<bool>,
+) -> Result<VideoInfo, String> {
+    info!("开始获取视频信息: {}", url);
+    let user_agent = user_agent.as_deref();
+    validate_user_agent(user_agent)?;
+
+    // 规范化链接（展开短链接/跳转链接）
+    let url = normalize_url(&url).await?;
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    debug!("使用 yt-dlp 路径: {:?}", ytdlp_path);
+
+    // 伪装依赖 curl_cffi，不可用时不附加 --impersonate
+    let settings = settings.get();
+    if settings.network_check && !is_online().await {
+        return Err(offline_error());
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        let linux_paths = vec![
-            "/usr/bin/yt-dlp",
-            "/usr/local/bin/yt-dlp",
-            "/snap/bin/yt-dlp",
-        ];
-        for path in linux_paths {
-            let path = PathBuf::from(path);
-            if path.exists() {
-                return Ok(path);
+    let impersonate = impersonation.get(&ytdlp_path).await.supports("chrome");
+    let watch_with_list = is_watch_with_list(&url);
+    let no_playlist = watch_with_list && treat_as_playlist != Some(true);
+
+    let single_video = no_playlist || is_single_video_url(&url);
+
+    // 未指定按哪种处理时，与视频信息同时获取播放列表概要
+    let video = async {
+        let mut refreshed = false;
+        if !single_video {
+            let (json, probe_refreshed) =
+                fetch_info_json(&app, &ytdlp_path, &url, impersonate, user_agent, no_playlist, true).await?;
+            let mut info = parse_video_info(json, &settings)?;
+            info.cookies_refreshed = probe_refreshed;
+            if !info.formats.is_empty() {
+                return Ok(info);
             }
+            // 扁平结果不带 formats（播放列表条目或部分站点），改为完整解析
+            info!("扁平解析未返回格式，改为完整解析: {}", url);
+            refreshed = probe_refreshed;
         }
-    }
 
-    #[cfg(target_os = "windows")]
-    {
-        let windows_paths = vec![
-            "C:\\ProgramData\\chocolatey\\bin\\yt-dlp.exe",
-            "C:\\Program Files\\yt-dlp\\yt-dlp.exe",
-            "C:\\Program Files (x86)\\yt-dlp\\yt-dlp.exe",
-        ];
-        for path in windows_paths {
-            let path = PathBuf::from(path);
-            if path.exists() {
-                return Ok(path);
+        let (json, refreshed_again) =
+            fetch_info_json(&app, &ytdlp_path, &url, impersonate, user_agent, no_playlist, false).await?;
+        let mut info = parse_video_info(json, &settings)?;
+        info.cookies_refreshed = refreshed || refreshed_again;
+        Ok::<_, String>(info)
+    };
+    let playlist = async {
+        if !watch_with_list || treat_as_playlist.is_some() {
+            return None;
+        }
+        match fetch_playlist_summary(&ytdlp_path, &url, impersonate, user_agent).await {
+            Ok(summary) => Some(summary),
+            Err(e) => {
+                warn!("获取播放列表概要失败: {} ({})", url, e);
+                None
             }
         }
+    };
+    let (info, playlist) = tokio::join!(video, playlist);
+    let mut info = match info {
+        Err(e) if is_upcoming_error(&e) => {
+            info!("视频尚未开始（首映或直播预告）: {}", url);
+            fetch_upcoming_info(&ytdlp_path, &url, impersonate, user_agent, &settings).await?
+        }
+        result => result?,
+    };
+    info.playlist = playlist;
+    Ok(info)
+}
+
+/***************************************************************************
+ * 获取尚未开始的首映或直播的信息
+ *
+ * 附加 --ignore-no-formats-error，yt-dlp 没有可下载的格式时也输出视频信息
+ * （含 live_status 和 release_timestamp）
+ *
+ * @return VideoInfo - upcoming 一定为 Some（yt-dlp 未报告开始时间时 release_at 为 None）
+ ***************************************************************************/
+
+async fn fetch_upcoming_info(
+    ytdlp_path: &Path,
+    url: &str,
+    impersonate: bool,
+    user_agent: Option<&str>,
+    settings: &Settings,
+) -> Result<VideoInfo, String> {
+    let extra_args = ["--ignore-no-formats-error", "--no-playlist"];
+    let json = fetch_video_json(ytdlp_path, url, impersonate, user_agent, false, &extra_args).await?;
+    let mut info = parse_video_info(json, settings)?;
+    info.upcoming.get_or_insert(UpcomingRelease { release_at: None });
+    Ok(info)
+}
+
+/***************************************************************************
+ * 获取带 list 参数的视频链接所指向的播放列表概要
+ *
+ * 使用 --flat-playlist -J 只列出条目，不解析每个视频
+ ***************************************************************************/
+
+async fn fetch_playlist_summary(
+    ytdlp_path: &Path,
+    url: &str,
+    impersonate: bool,
+    user_agent: Option<&str>,
+) -> Result<PlaylistSummary, String> {
+    let output = ytdlp_command(ytdlp_path)
+        .args(["-J", "--no-warnings", "--flat-playlist", "--yes-playlist"])
+        .args(request_args(impersonate, user_agent))
+        .arg(url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 yt-dlp: {}", e))?;
+    if !output.status.success() {
+        return Err(format_ytdlp_error(&String::from_utf8_lossy(&output.stderr), ytdlp_path));
     }
 
-    // 3. 尝试 sidecar 模式（与可执行文件同目录）
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            for name in &ytdlp_names {
-                let path = exe_dir.join(name);
-                if path.exists() {
-                    return Ok(path);
-                }
-                // 尝试 resources 目录
-                let resources_path = exe_dir.join("../").join("Resources").join(name);
-                if resources_path.exists() {
-                    return Ok(resources_path);
+    let json: Value = serde_json::from_slice(&output.stdout).map_err(|e| format!("解析播放列表信息失败: {}", e))?;
+    let count = json["playlist_count"]
+        .as_u64()
+        .map(|count| count as usize)
+        .or_else(|| json["entries"].as_array().map(Vec::len));
+    Ok(PlaylistSummary {
+        title: json["title"].as_str().map(String::from),
+        count,
+    })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取合适尺寸的缩略图
+ *
+ * 只解析视频信息，不下载视频；用于信息卡片显示清晰的缩略图
+ *
+ * @param url - 视频URL
+ * @param min_width - 需要的最小宽度（像素）
+ * @return Option<Thumbnail> - 宽度足够的最小一张，都不够宽时为最大的一张；没有缩略图时为 None
+ ***************************************************************************/
+
+#[command]
+pub async fn get_best_thumbnail(
+    app: AppHandle,
+    impersonation: State<'_, ImpersonationState>,
+    url: String,
+    min_width: u32,
+) -> Result<Option<Thumbnail>, String> {
+    let url = normalize_url(&url).await?;
+    let ytdlp_path = get_ytdlp_path().await?;
+    let impersonate = impersonation.get(&ytdlp_path).await.supports("chrome");
+    let no_playlist = is_watch_with_list(&url);
+    let (json, _) = fetch_info_json(&app, &ytdlp_path, &url, impersonate, None, no_playlist, true).await?;
+
+    let thumbnails = parse_thumbnails(&json);
+    let thumbnail = best_thumbnail(&thumbnails, min_width.into()).cloned();
+    debug!("缩略图: {} -> {:?}", url, thumbnail.as_ref().map(|t| (&t.url, t.width)));
+    Ok(thumbnail)
+}
+
+/***************************************************************************
+ * 获取视频信息JSON，遇到机器人验证或地区限制时重试一次（见 fetch_with_retries）
+ *
+ * @param no_playlist - 附加 --no-playlist（带 list 参数的视频链接只解析该视频）
+ * @return (Value, bool) - 视频信息JSON，以及是否经过 Cookie 刷新重试
+ ***************************************************************************/
+
+async fn fetch_info_json(
+    app: &AppHandle,
+    ytdlp_path: &Path,
+    url: &str,
+    impersonate: bool,
+    user_agent: Option<&str>,
+    no_playlist: bool,
+    flat: bool,
+) -> Result<(Value, bool), String> {
+    let base_args: &[&str] = if no_playlist { &["--no-playlist"] } else { &[] };
+    fetch_with_retries(url, base_args, |args| async move {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        fetch_video_json_streaming(app, ytdlp_path, url, impersonate, user_agent, flat, &args).await
+    })
+    .await
+}
+
+/***************************************************************************
+ * 获取视频信息的重试策略
+ *
+ * 触发机器人验证时附加 --no-cache-dir 重试一次，不使用 yt-dlp 缓存的会话数据，
+ * 并重新从浏览器读取 Cookie。遇到地区限制且 yt-dlp 报告了可观看的
+ * 国家代码时，附加 --geo-bypass-country 重试一次（yt-dlp 默认的地区绕过
+ * 已经失败，不指定国家重试没有意义）
+ *
+ * @param base_args - 每次获取都附加的参数
+ * @param fetch - 以给定参数获取一次视频信息
+ * @return (Value, bool) - 视频信息JSON，以及是否经过 Cookie 刷新重试
+ ***************************************************************************/
+
+async fn fetch_with_retries<F, Fut>(url: &str, base_args: &[&str], fetch: F) -> Result<(Value, bool), String>
+where
+    F: Fn(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    let args_with = |extra: &[&str]| base_args.iter().chain(extra).map(|arg| arg.to_string()).collect();
+    match fetch(args_with(&[])).await {
+        Err(e) if is_bot_detection_error(&e) => {
+            info!("触发机器人验证，刷新浏览器 Cookie 后重试: {}", url);
+            match fetch(args_with(&["--no-cache-dir"])).await {
+                Ok(json) => {
+                    info!("刷新 Cookie 后获取成功: {}", url);
+                    Ok((json, true))
                 }
+                Err(e) => Err(format!("{}\n\n（已刷新浏览器 Cookie 重试一次，仍然失败）", e)),
             }
         }
+        Err(e) if is_geo_blocked_error(&e) => {
+            let Some(country) = geo_available_region(&e).as_deref().and_then(geo_bypass_country) else {
+                return Err(e);
+            };
+            info!("视频受地区限制，使用 --geo-bypass-country {} 重试: {}", country, url);
+            match fetch(args_with(&["--geo-bypass-country", country.as_str()])).await {
+                Ok(json) => Ok((json, false)),
+                Err(e) => Err(format!("{}\n\n（已使用 --geo-bypass-country {} 重试一次，仍然失败）", e, country)),
+            }
+        }
+        result => result.map(|json| (json, false)),
     }
-
-    Err("未找到 yt-dlp 可执行文件。请确保 yt-dlp 已安装并在 PATH 中。".to_string())
 }
 
 /***************************************************************************
- * 格式化 yt-dlp 错误信息
+ * 运行 yt-dlp --dump-json 并取第一个视频条目
  *
- * @param stderr - yt-dlp 标准错误输出
- * @return String - 格式化后的错误信息，包含解决建议
+ * @param flat - 是否使用 --flat-playlist（快速，但部分站点不返回格式列表）；
+ *               完整解析时只取播放列表的第一项，避免逐个解析整个列表
+ * @param extra_args - 附加的参数（如 --no-playlist）
  ***************************************************************************/
 
-fn format_ytdlp_error(stderr: &str) -> String {
-    let base_error = format!("yt-dlp 执行失败: {}", stderr);
-
-    // 检测特定错误类型并提供解决方案
-    if stderr.contains("Sign in to confirm you're not a bot") {
-        format!(
-            "{}\n\n🔧 解决方案:\n\
-            1. 确保您的 Chrome 浏览器已登录 YouTube\n\
-            2. 尝试使用不同的视频链接\n\
-            3. 在高级设置中调整反检测选项\n\
-            4. 如果问题持续，请等待一段时间后重试",
-            base_error
-        )
-    } else if stderr.contains("429") || stderr.contains("Too Many Requests") {
-        format!(
-            "{}\n\n🔧 解决方案:\n\
-            1. 在高级设置中增加请求间隔时间\n\
-            2. 等待几分钟后重试\n\
-            3. 尝试使用代理连接",
-            base_error
-        )
-    } else if stderr.contains("cookies") || stderr.contains("login") {
-        format!(
-            "{}\n\n🔧 解决方案:\n\
-            1. 确保浏览器中已登录相应账号\n\
-            2. 检查浏览器 Cookie 权限\n\
-            3. 尝试手动导出 Cookie 文件",
-            base_error
-        )
-    } else if stderr.contains("Impersonate target") && stderr.contains("not available") {
-        format!(
-            "{}\n\n🔧 解决方案:\n\
-            1. 请运行: /opt/homebrew/bin/python3.10 -m pip install curl_cffi\n\
-            2. 或重新安装: /opt/homebrew/bin/python3.10 -m pip install --upgrade 'yt-dlp[curl-cffi]'\n\
-            3. 详细说明请参考项目文档",
-            base_error
-        )
-    } else if stderr.contains("ERROR: [youtube]") {
-        format!(
-            "{}\n\n🔧 解决方案:\n\
-            1. 检查视频链接是否正确\n\
-            2. 尝试刷新网页获取最新链接\n\
-            3. 视频可能受地区限制或已被删除",
-            base_error
-        )
+async fn fetch_video_json(
+    ytdlp_path: &Path,
+    url: &str,
+    impersonate: bool,
+    user_agent: Option<&str>,
+    flat: bool,
+    extra_args: &[&str],
+) -> Result<Value, String> {
+    // 构建命令: yt-dlp --dump-json <url> (添加反检测参数)
+    let mut args = vec!["--dump-json", "--no-warnings"];
+    args.extend(extra_args);
+    if flat {
+        args.push("--flat-playlist");
     } else {
-        base_error
+        args.extend(["--playlist-items", "1"]);
     }
+
+    args.extend(request_args(impersonate, user_agent));
+
+    YtDlp::new(ytdlp_path)
+        .fetch_info(&args, url)
+        .await
+        .map_err(|e| match e {
+            YtdlpError::Failed { stderr } => format_ytdlp_error(&stderr, ytdlp_path),
+            e => e.to_string(),
+        })
 }
 
 /***************************************************************************
- * Tauri 命令 - 获取视频信息
+ * 逐行读取输出的 fetch_video_json
  *
- * @param url - 视频URL（支持YouTube、Bilibili等yt-dlp支持的网站）
- * @return VideoInfo - 包含标题、时长、缩略图、可用格式等信息
+ * 附加 --no-quiet 让 yt-dlp 输出提取步骤（如 "[youtube] xxx: Downloading webpage"），
+ * 每一步发送 info-extraction-progress 事件；不支持 --no-quiet 的旧版本
+ * 回退到不带进度的 fetch_video_json
+ *
+ * @param extra_args - 附加的参数（如 --no-playlist，重试时另有机器人验证后的 --no-cache-dir）
  ***************************************************************************/
 
-#[command]
-pub async fn get_video_info(url: String) -> Result<VideoInfo, String> {
-    println!("开始获取视频信息: {}", url);
-
-    let ytdlp_path = get_ytdlp_path()?;
-    println!("使用 yt-dlp 路径: {:?}", ytdlp_path);
+async fn fetch_video_json_streaming(
+    app: &AppHandle,
+    ytdlp_path: &Path,
+    url: &str,
+    impersonate: bool,
+    user_agent: Option<&str>,
+    flat: bool,
+    extra_args: &[&str],
+) -> Result<Value, String> {
+    let mut args = vec!["--dump-json", "--no-quiet"];
+    args.extend(extra_args);
+    if flat {
+        args.push("--flat-playlist");
+    } else {
+        args.extend(["--playlist-items", "1"]);
+    }
+    args.extend(request_args(impersonate, user_agent));
+    let site_args = site_preset_args(app, ytdlp_path, url, None).await;
 
-    // 构建命令: yt-dlp --dump-json <url> (添加反检测参数)
-    let output = Command::new(&ytdlp_path)
-        .args(&[
-            "--dump-json",
-            "--no-warnings",
-            "--flat-playlist",
-            "--impersonate",
-            "chrome",
-            "--user-agent",
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-            "--cookies-from-browser",
-            "chrome",
-            &url
-        ])
+    let mut child = ytdlp_command(ytdlp_path)
+        .args(args)
+        .args(&site_args)
+        .arg(url)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .output()
-        .await
+        .kill_on_drop(true)
+        .spawn()
         .map_err(|e| format!("无法执行 yt-dlp: {}", e))?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format_ytdlp_error(&stderr));
-    }
+    let emit_step = {
+        let app = app.clone();
+        let url = url.to_string();
+        move |line: &str| {
+            let Some((extractor, message)) = parse_extraction_line(line) else {
+                return false;
+            };
+            let progress = InfoExtractionProgress {
+                url: url.clone(),
+                extractor,
+                message,
+            };
+            if let Err(e) = app.emit(events::INFO_EXTRACTION_PROGRESS, &progress) {
+                debug!("发送解析进度失败: {}", e);
+            }
+            true
+        }
+    };
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let lines: Vec<&str> = stdout.lines().collect();
+    // stderr 单独读取，避免管道写满阻塞 yt-dlp
+    let stderr = child.stderr.take().ok_or("无法读取 yt-dlp 错误输出")?;
+    let stderr_emit = emit_step.clone();
+    let stderr_app = app.clone();
+    let stderr_task = tokio::spawn(async move {
+        let mut lines = lossy_lines(BufReader::new(stderr));
+        let mut output = String::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            // 警告单独发送，不混入失败时的错误信息
+            if let Some((kind, message)) = parse_warning_line(&line) {
+                emit_ytdlp_warning(&stderr_app, None, kind, message);
+                continue;
+            }
+            stderr_emit(&line);
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output
+    });
 
-    if lines.is_empty() {
-        return Err("无法获取视频信息: 无响应数据".to_string());
+    // 提取步骤可能与 JSON 一起输出到 stdout，只保留 JSON 部分
+    let stdout = child.stdout.take().ok_or("无法读取 yt-dlp 输出")?;
+    let mut lines = lossy_lines(BufReader::new(stdout));
+    let mut json_output = String::new();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if emit_step(&line) {
+            continue;
+        }
+        json_output.push_str(&line);
+        json_output.push('\n');
     }
 
-    // 尝试解析JSON，如果是播放列表，取第一条
-    for line in lines {
-        if let Ok(json) = serde_json::from_str::<Value>(line) {
-            return parse_video_info(json);
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("等待 yt-dlp 失败: {}", e))?;
+    if !status.success() {
+        let stderr = stderr_task.await.unwrap_or_default();
+        if stderr.contains("no such option") && stderr.contains("--no-quiet") {
+            debug!("yt-dlp 不支持 --no-quiet，改为不带进度的解析");
+            return fetch_video_json(ytdlp_path, url, impersonate, user_agent, flat, extra_args).await;
         }
+        return Err(format_ytdlp_error(&stderr, ytdlp_path));
+    }
+
+    first_video_entry(&json_output)
+}
+
+/// 发送 ytdlp-warning 事件
+fn emit_ytdlp_warning(app: &AppHandle, download_id: Option<&str>, kind: WarningKind, message: String) {
+    let warning = YtdlpWarning {
+        download_id: download_id.map(String::from),
+        kind,
+        message,
+    };
+    if let Err(e) = app.emit(events::YTDLP_WARNING, &warning) {
+        debug!("发送 yt-dlp 警告事件失败: {}", e);
     }
+}
+
+/***************************************************************************
+ * 解析提取步骤输出
+ *
+ * 格式示例:
+ * [youtube] dQw4w9WgXcQ: Downloading player JSON
+ * [info] dQw4w9WgXcQ: Downloading 1 format(s): 22
+ *
+ * @return Option<(提取器, 步骤)> - 不是提取步骤的行返回 None
+ ***************************************************************************/
+
+fn parse_extraction_line(line: &str) -> Option<(Option<String>, String)> {
+    let rest = line.strip_prefix('[')?;
+    let (tag, message) = rest.split_once("] ")?;
+    if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == ':' || c == '_') {
+        return None;
+    }
+    let extractor = (tag != "info" && tag != "debug").then(|| tag.to_lowercase());
+    Some((extractor, message.trim().to_string()))
+}
+
+/***************************************************************************
+ * 按链接域名套用站点预设
+ *
+ * 预设中的伪装目标不可用时移除；下载选项中已指定伪装目标或 Cookie 浏览器时
+ * 不覆盖
+ *
+ * @param options - 下载选项（获取信息时为 None）
+ * @return Vec<String> - 追加到其他参数之后的参数（没有匹配的预设时为空）
+ ***************************************************************************/
 
-    Err("无法解析视频信息".to_string())
+async fn site_preset_args(
+    app: &AppHandle,
+    ytdlp_path: &Path,
+    url: &str,
+    options: Option<&DownloadOptions>,
+) -> Vec<String> {
+    let presets = app.state::<SettingsState>().get().site_presets;
+    let Some((site, preset)) = find_site_preset(&presets, url) else {
+        return Vec::new();
+    };
+
+    let keep_impersonate = options.is_some_and(|o| o.impersonate.is_some());
+    let keep_cookies = options.is_some_and(|o| o.cookies_from_browser.is_some());
+    let args = preset.args(keep_impersonate, keep_cookies);
+    debug!("套用站点预设 {}: {:?}", site, args);
+
+    let support = app.state::<ImpersonationState>().get(ytdlp_path).await;
+    filter_impersonate_args(args, &support)
+}
+
+/// 获取信息/搜索时共用的反检测参数（伪装、UA、浏览器 Cookie），user_agent 为 None 时使用默认 UA
+fn request_args(impersonate: bool, user_agent: Option<&str>) -> Vec<&str> {
+    let mut args = Vec::new();
+    if impersonate {
+        args.extend(["--impersonate", "chrome"]);
+    }
+    args.extend([
+        "--user-agent",
+        user_agent.unwrap_or(DEFAULT_USER_AGENT),
+        "--cookies-from-browser",
+        "chrome",
+    ]);
+    args
 }
 
 /***************************************************************************
  * 解析视频信息JSON
+ *
+ * @param settings - 全局设置（合一格式偏好、分辨率上限）
  ***************************************************************************/
 
-fn parse_video_info(json: Value) -> Result<VideoInfo, String> {
-    println!("解析视频信息: {}", json["title"].as_str().unwrap_or("未知"));
+fn parse_video_info(json: Value, settings: &Settings) -> Result<VideoInfo, String> {
+    debug!("解析视频信息: {}", json["title"].as_str().unwrap_or("未知"));
 
     let id = json["id"]
         .as_str()
@@ -277,16 +891,62 @@ fn parse_video_info(json: Value) -> Result<VideoInfo, String> {
         .unwrap_or("")
         .to_string();
 
-    let formats = parse_formats(&json);
-    let available_resolutions = extract_available_resolutions(&formats);
+    let thumbnails = parse_thumbnails(&json);
+
+    let description = json["description"]
+        .as_str()
+        .filter(|d| !d.is_empty())
+        .map(|d| d.to_string());
+
+    // 部分提取器不提供这些字段，扁平解析时也可能缺失
+    let optional_str = |key: &str| {
+        json[key]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+    };
+    let extractor = optional_str("extractor");
+    let extractor_key = optional_str("extractor_key");
+    let webpage_url = optional_str("webpage_url");
+
+    let mut formats = parse_formats(&json);
+
+    // 全部视频格式都受 DRM 保护时保留格式列表并标记；部分受保护时直接去掉受保护的格式
+    let mut video_formats = formats
+        .iter()
+        .filter(|f| matches!(f.kind, Some(FormatKind::Video) | Some(FormatKind::Muxed)))
+        .peekable();
+    let drm_only = video_formats.peek().is_some() && video_formats.all(|f| f.has_drm);
+    if drm_only {
+        warn!("所有视频格式都受 DRM 保护: {}", id);
+    } else {
+        formats.retain(|f| !f.has_drm);
+    }
+
+    let available_resolutions = extract_available_resolutions(&formats, settings);
+    let audio_languages = extract_audio_languages(&formats);
+
+    let upcoming = (json["live_status"].as_str() == Some("is_upcoming")).then(|| UpcomingRelease {
+        release_at: json["release_timestamp"].as_u64().map(|seconds| seconds * 1000),
+    });
 
     Ok(VideoInfo {
         id,
         title,
         duration,
         thumbnail,
+        thumbnails,
+        description,
         formats,
         available_resolutions,
+        audio_languages,
+        extractor,
+        extractor_key,
+        webpage_url,
+        drm_only,
+        cookies_refreshed: false,
+        playlist: None,
+        upcoming,
     })
 }
 
@@ -306,15 +966,28 @@ fn parse_formats(json: &Value) -> Vec<VideoFormat> {
                 .as_str()
                 .unwrap_or("unknown")
                 .to_string();
-            let filesize = format["filesize"].as_i64();
+            let filesize = format["filesize"].as_i64().or_else(|| format["filesize_approx"].as_i64());
             let vcodec = format["vcodec"]
                 .as_str()
                 .map(|s| s.to_string());
             let acodec = format["acodec"]
                 .as_str()
                 .map(|s| s.to_string());
+            let language = format["language"]
+                .as_str()
+                .map(|s| s.to_string());
+            // has_drm 可能为 "maybe"（无法确定），此时仍尝试下载
+            let has_drm = format["has_drm"].as_bool().unwrap_or(false);
+            let format_note = format["format_note"]
+                .as_str()
+                .map(|s| s.to_string());
+            let dynamic_range = format["dynamic_range"]
+                .as_str()
+                .map(|s| s.to_string());
+            let fps = format["fps"].as_f64();
 
             formats.push(VideoFormat {
+                kind: FormatKind::classify(vcodec.as_deref(), acodec.as_deref()),
                 format_id,
                 height,
                 width,
@@ -322,6 +995,11 @@ fn parse_formats(json: &Value) -> Vec<VideoFormat> {
                 filesize,
                 vcodec,
                 acodec,
+                language,
+                has_drm,
+                format_note,
+                dynamic_range,
+                fps,
             });
         }
     } else if let Some(format) = json["format"].as_object() {
@@ -337,26 +1015,59 @@ fn parse_formats(json: &Value) -> Vec<VideoFormat> {
 
         formats.push(VideoFormat {
             format_id,
+            kind: Some(FormatKind::Muxed),
             height: None,
             width: None,
             ext,
             filesize: format["filesize"].as_i64(),
             vcodec: None,
             acodec: None,
+            language: None,
+            has_drm: format.get("has_drm").and_then(Value::as_bool).unwrap_or(false),
+            format_note: None,
+            dynamic_range: None,
+            fps: format.get("fps").and_then(Value::as_f64),
         });
     }
 
     formats
 }
 
+/***************************************************************************
+ * 提取可用的音轨语言
+ *
+ * 只统计包含音频的格式，去重后按字母排序
+ ***************************************************************************/
+
+fn extract_audio_languages(formats: &[VideoFormat]) -> Vec<String> {
+    let mut languages: Vec<String> = formats
+        .iter()
+        .filter(|f| f.acodec.as_deref().is_some_and(|acodec| acodec != "none"))
+        .filter_map(|f| f.language.clone())
+        .collect();
+    languages.sort();
+    languages.dedup();
+    languages
+}
+
 /***************************************************************************
  * 提取可用分辨率选项
  *
+ * 同一分辨率下高帧率（如 "1080p60"）和 HDR（如 "4K HDR"）单独成为选项；
+ * 同一分辨率同时有 HDR 和 SDR 时，SDR 选项标为 "4K SDR"
+ * 每个选项列出其下的全部格式（candidates），供界面在同一分辨率内选择编码，
+ * 按下面的规则选出的推荐格式标记 recommended
+ * 超过分辨率上限的选项标记 above_max_resolution；开启隐藏时去掉这些选项，
+ * 但全部超过上限时仍然保留（否则没有可选的分辨率）
+ *
  * @param formats - 视频格式列表
+ * @param settings - 全局设置（合一格式偏好、分辨率上限）
  * @return Vec<ResolutionOption> - 按分辨率排序的可用选项
  ***************************************************************************/
 
-fn extract_available_resolutions(formats: &Vec<VideoFormat>) -> Vec<ResolutionOption> {
+fn extract_available_resolutions(formats: &Vec<VideoFormat>, settings: &Settings) -> Vec<ResolutionOption> {
+    let prefer_progressive = settings.prefer_progressive;
+    let max_resolution = settings.max_resolution;
     let mut resolutions = std::collections::HashMap::new();
 
     // 常见分辨率映射
@@ -379,199 +1090,3148 @@ fn extract_available_resolutions(formats: &Vec<VideoFormat>) -> Vec<ResolutionOp
             continue;
         }
 
+        // 故事板是预览拼图，不能作为可播放的分辨率
+        if is_storyboard_format(&format.format_id, &format.ext) {
+            continue;
+        }
+
+        // 受 DRM 保护的格式无法下载
+        if format.has_drm {
+            continue;
+        }
+
         // 只处理有高度信息的格式
         if let Some(height) = format.height {
-            // 获取分辨率标签
-            let label = resolution_labels
+            // 获取分辨率标签，高帧率时附加帧率（"1080p60"、"4K60"）
+            let frame_rate = format.high_frame_rate();
+            let mut label = resolution_labels
                 .get(&height)
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| format!("{}p", height));
+            if let Some(fps) = frame_rate {
+                label.push_str(&fps.to_string());
+            }
+            if format.is_hdr() {
+                label.push_str(" HDR");
+            }
 
             // 如果这个分辨率还没有被记录，或者当前格式更好
-            let entry = resolutions.entry(height).or_insert(ResolutionOption {
+            let key = (height, frame_rate, format.is_hdr());
+            let entry = resolutions.entry(key).or_insert(ResolutionOption {
                 height,
                 label,
                 format_id: format.format_id.clone(),
+                requires_merge: format.requires_merge(),
+                is_progressive: format.is_progressive(),
+                fps: frame_rate.map(|fps| fps as f64),
+                is_hdr: format.is_hdr(),
+                above_max_resolution: max_resolution.is_some_and(|max| height > max),
+                candidates: Vec::new(),
+            });
+            entry.candidates.push(FormatCandidate {
+                format_id: format.format_id.clone(),
+                vcodec: format.vcodec.clone(),
+                ext: format.ext.clone(),
+                filesize: format.filesize,
+                requires_merge: format.requires_merge(),
+                recommended: false,
             });
 
+            // 开启"优先合一格式"时，同一分辨率下合一格式优先于纯视频格式
+            if prefer_progressive && format.is_progressive() && !entry.is_progressive {
+                entry.format_id = format.format_id.clone();
+                entry.requires_merge = format.requires_merge();
+                entry.is_progressive = true;
+                continue;
+            }
+            if prefer_progressive && entry.is_progressive && !format.is_progressive() {
+                continue;
+            }
+
             // 优先选择有文件大小的格式
             if format.filesize.is_some() &&
                formats.iter().find(|f| f.format_id == entry.format_id && f.filesize.is_none()).is_some() {
                 entry.format_id = format.format_id.clone();
+                entry.requires_merge = format.requires_merge();
+                entry.is_progressive = format.is_progressive();
             }
         }
     }
 
-    // 转换为向量并按分辨率降序排序
-    let mut result: Vec<ResolutionOption> = resolutions.into_values().collect();
-    result.sort_by(|a, b| b.height.cmp(&a.height));
+    for entry in resolutions.values_mut() {
+        let recommended = entry.format_id.clone();
+        for candidate in &mut entry.candidates {
+            candidate.recommended = candidate.format_id == recommended;
+        }
+    }
+
+    // 同一分辨率和帧率同时有 HDR 选项时，SDR 选项标为 " SDR"
+    let hdr_keys: Vec<(i64, Option<i64>)> = resolutions
+        .keys()
+        .filter(|(_, _, hdr)| *hdr)
+        .map(|(height, fps, _)| (*height, *fps))
+        .collect();
+    for ((height, fps, hdr), entry) in resolutions.iter_mut() {
+        if !hdr && hdr_keys.contains(&(*height, *fps)) {
+            entry.label.push_str(" SDR");
+        }
+    }
+
+    // 转换为向量并按分辨率、帧率降序排序，同一档位 HDR 在前
+    let mut result: Vec<ResolutionOption> = resolutions.into_values().collect();
+    result.sort_by(|a, b| {
+        b.height
+            .cmp(&a.height)
+            .then(b.fps.unwrap_or(0.0).total_cmp(&a.fps.unwrap_or(0.0)))
+            .then(b.is_hdr.cmp(&a.is_hdr))
+    });
+
+    if settings.hide_above_max_resolution && result.iter().any(|option| !option.above_max_resolution) {
+        result.retain(|option| !option.above_max_resolution);
+    }
 
     result
 }
 
+/***************************************************************************
+ * 生成下载任务ID
+ *
+ * 时间戳 + 进程内递增计数，保证同一毫秒内发起的下载也不会重复
+ ***************************************************************************/
+
+pub fn next_download_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let millis = unix_millis();
+    let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
+
+    format!("dl-{}-{}", millis, seq)
+}
+
 /***************************************************************************
  * Tauri 命令 - 下载视频
  *
  * @param url - 视频URL
- * @param args - yt-dlp 命令行参数
+ * @param options - 结构化下载选项（由后端结合设置生成 yt-dlp 参数）
+ * @param download_id - 下载任务ID（可选，未提供时自动生成，用于日志关联）
+ * @param goal - 格式选择目标（可选，覆盖 options.format_goal）
  * @return Result<(), String> - 成功或错误消息
  ***************************************************************************/
 
 #[command]
-pub async fn download_video(app: AppHandle, url: String, args: Vec<String>) -> Result<(), String> {
-    println!("开始下载视频: {}", url);
-    println!("参数: {:?}", args);
+pub async fn download_video(
+    app: AppHandle,
+    url: String,
+    mut options: DownloadOptions,
+    download_id: Option<String>,
+    goal: Option<FormatGoal>,
+) -> Result<(), String> {
+    if let Some(goal) = goal {
+        options.format_goal = goal;
+    }
+    let download_id = download_id.unwrap_or_else(next_download_id);
+    app.state::<DownloadManager>().register(&download_id);
 
-    let ytdlp_path = get_ytdlp_path()?;
-    println!("使用 yt-dlp 路径: {:?}", ytdlp_path);
+    run_download(app, download_id, url, options).await
+}
 
-    // 创建子进程
-    let mut child = Command::new(&ytdlp_path)
-        .args(&args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("无法启动下载进程: {}", e))?;
+/***************************************************************************
+ * 后处理阶段定时发送 download-postprocessing 事件
+ *
+ * 合并、转码时 yt-dlp 没有进度行，进度条会停在 100%；这里每隔 POSTPROCESSING_TICK
+ * 发送当前步骤、已用时间和（合并时）估算的进度，任务离开后处理状态后结束，
+ * 之后再次进入后处理时重新启动
+ ***************************************************************************/
+
+fn spawn_postprocessing_ticker(app: AppHandle, download_id: String, stage: Arc<Mutex<Option<PostProcessingStage>>>) {
+    tauri::async_runtime::spawn(async move {
+        let manager = app.state::<DownloadManager>();
+        while manager.state(&download_id).map(|s| s.status) == Some(DownloadStatus::PostProcessing) {
+            let progress = stage
+                .lock()
+                .ok()
+                .and_then(|stage| stage.as_ref().map(|stage| stage.snapshot(&download_id)));
+            if let Some(progress) = progress {
+                if let Err(e) = app.emit(events::DOWNLOAD_POSTPROCESSING, &progress) {
+                    warn!(download_id = %download_id, "发送后处理进度事件失败: {}", e);
+                }
+            }
+            tokio::time::sleep(POSTPROCESSING_TICK).await;
+        }
+        if let Ok(mut stage) = stage.lock() {
+            *stage = None;
+        }
+    });
+}
 
-    let stdout = child.stdout.take().ok_or("无法捕获标准输出")?;
-    let stderr = child.stderr.take().ok_or("无法捕获标准错误")?;
+/***************************************************************************
+ * 下载的启动超时
+ *
+ * 等待首映或直播开始（wait_for_video）时 yt-dlp 可能很久才开始下载，不限制
+ *
+ * @return Option<Duration> - None 表示不限制
+ ***************************************************************************/
 
-    let reader = BufReader::new(stdout).lines();
-    let mut stderr_reader = BufReader::new(stderr).lines();
+fn download_startup_timeout(settings: &Settings, options: &DownloadOptions) -> Option<Duration> {
+    if options.wait_for_video.is_some() {
+        return None;
+    }
+    settings.startup_timeout.map(Duration::from_secs)
+}
 
-    // 克隆 AppHandle 用于异步任务
-    let app_clone = app.clone();
+/***************************************************************************
+ * 执行一次下载（直接下载和队列调度共用）
+ *
+ * 调用前任务须已在 DownloadManager 中登记
+ *
+ * @param download_id - 下载任务ID
+ * @param url - 视频URL
+ * @param options - 结构化下载选项
+ ***************************************************************************/
 
-    // 异步读取标准输出（yt-dlp 进度信息）
-    tokio::spawn(async move {
-        let mut lines = reader;
-        let mut line_count = 0;
-        while let Ok(Some(line)) = lines.next_line().await {
-            if !line.trim().is_empty() {
-                line_count += 1;
-                println!("[yt-dlp-{}] {}", line_count, line);
-
-                // 解析并发送进度信息
-                if let Some(progress) = parse_progress_line(&line) {
-                    println!("✅ 解析到进度数据: {:?}", progress);
-                    // 发送进度事件到前端
-                    match app_clone.emit("download-progress", &progress) {
-                        Ok(_) => println!("✅ 进度事件发送成功"),
-                        Err(e) => eprintln!("❌ 发送进度事件失败: {}", e),
+pub async fn run_download(
+    app: AppHandle,
+    download_id: String,
+    url: String,
+    options: DownloadOptions,
+) -> Result<(), String> {
+    run_download_with(app, download_id, url, options, YtDlp::new).await
+}
+
+/***************************************************************************
+ * 执行下载（run_download 的实现），由 backend 根据定位到的 yt-dlp 创建媒体后端
+ ***************************************************************************/
+
+pub async fn run_download_with<B, F>(
+    app: AppHandle,
+    download_id: String,
+    url: String,
+    options: DownloadOptions,
+    backend: F,
+) -> Result<(), String>
+where
+    B: MediaBackend + Sync,
+    F: FnOnce(PathBuf) -> B,
+{
+    info!(download_id = %download_id, "开始下载视频: {}", url);
+    let manager = app.state::<DownloadManager>();
+
+    // 未指定下载目录时使用默认下载目录，完成事件和历史记录中的目录与实际一致
+    let options = match download_destination(&app, &options) {
+        Ok(dir) => DownloadOptions {
+            output_dir: Some(dir.to_string_lossy().into_owned()),
+            ..options
+        },
+        Err(_) => options,
+    };
+
+    if options.write_comments {
+        warn!(download_id = %download_id, "已开启评论获取，评论较多时可能需要数分钟");
+    }
+
+    let PreparedDownload {
+        url: canonical_url,
+        ytdlp_path,
+        args,
+        staging_dir,
+        format_report,
+    } = match prepare_download(&app, &download_id, &url, &options).await {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            manager.set_status(&download_id, DownloadStatus::Failed, Some(error.clone()));
+            return Err(error);
+        }
+    };
+
+    let settings = app.state::<SettingsState>().get();
+    let throttle_threshold = settings.throttle_threshold;
+    let restart_throttled = settings.restart_throttled;
+    let startup_timeout = download_startup_timeout(&settings, &options);
+
+    // 下载事件：更新任务状态、记录速度样本并发送到前端
+    let app_clone = app.clone();
+    let event_id = download_id.clone();
+    let mut throttle = throttle_threshold.map(ThrottleDetector::new);
+    let emit_throttled = move |app: &AppHandle, download_id: &str, bytes_per_sec: Option<f64>| {
+        warn!(download_id = %download_id, "下载被限速: {:?} B/s", bytes_per_sec);
+        let throttled = DownloadThrottled {
+            download_id: download_id.to_string(),
+            bytes_per_sec,
+            threshold: throttle_threshold,
+            restarting: restart_throttled,
+        };
+        if let Err(e) = app.emit(events::DOWNLOAD_THROTTLED, &throttled) {
+            warn!(download_id = %download_id, "发送限速事件失败: {}", e);
+        }
+    };
+    let postprocessing: Arc<Mutex<Option<PostProcessingStage>>> = Arc::default();
+    let retry_sleep = options.retry_sleep.clone();
+    let on_event = move |mut event: DownloadEvent| {
+        app_clone.state::<DownloadManager>().apply_event(&event_id, &mut event);
+        match event {
+            DownloadEvent::Started | DownloadEvent::Destination(_) => {}
+            DownloadEvent::Progress {
+                progress,
+                bytes_per_sec,
+                at,
+            } => {
+                if let (Some(detector), Some(speed)) = (throttle.as_mut(), bytes_per_sec) {
+                    if detector.record(at, speed) {
+                        emit_throttled(&app_clone, &event_id, Some(speed));
                     }
-                } else {
-                    // 如果这行包含进度相关信息但解析失败，输出警告
-                    if line.contains("[download]") || line.contains("%") {
-                        println!("⚠️  进度行解析失败: {}", line);
+                }
+
+                if let Err(e) = app_clone.emit(events::DOWNLOAD_PROGRESS, &progress) {
+                    warn!(download_id = %event_id, "发送进度事件失败: {}", e);
+                }
+            }
+            DownloadEvent::Throttled => emit_throttled(&app_clone, &event_id, None),
+            DownloadEvent::FetchingComments {
+                fetched,
+                estimated_total,
+            } => {
+                let progress = CommentsProgress {
+                    download_id: event_id.clone(),
+                    fetched,
+                    estimated_total,
+                };
+                if let Err(e) = app_clone.emit(events::DOWNLOAD_COMMENTS, &progress) {
+                    warn!(download_id = %event_id, "发送评论进度事件失败: {}", e);
+                }
+            }
+            DownloadEvent::PostProcessing { stage, merge } => {
+                let Ok(mut current) = postprocessing.lock() else {
+                    return;
+                };
+                let idle = current.is_none();
+                // 同一步骤的后续输出行不重新计时
+                if merge.is_some() || current.as_ref().map(PostProcessingStage::name) != Some(stage.as_str()) {
+                    *current = Some(PostProcessingStage::new(&stage, merge));
+                }
+                if idle {
+                    spawn_postprocessing_ticker(app_clone.clone(), event_id.clone(), postprocessing.clone());
+                }
+            }
+            DownloadEvent::Warning { kind, message } => {
+                emit_ytdlp_warning(&app_clone, Some(&event_id), kind, message);
+            }
+            DownloadEvent::Retry {
+                attempt,
+                retries,
+                reason,
+            } => {
+                let retry = DownloadRetry {
+                    download_id: event_id.clone(),
+                    attempt,
+                    retries,
+                    wait_seconds: retry_sleep.as_ref().map(|sleep| sleep.delay(attempt)),
+                    reason,
+                };
+                if let Err(e) = app_clone.emit(events::DOWNLOAD_RETRY, &retry) {
+                    warn!(download_id = %event_id, "发送重试事件失败: {}", e);
+                }
+            }
+        }
+    };
+
+    let backend = backend(ytdlp_path);
+    let download = backend
+        .download(&args, startup_timeout, on_event)
+        .instrument(info_span!("download", download_id = %download_id));
+    let DownloadOutcome {
+        status,
+        total_bytes,
+        outputs,
+        warnings,
+        items,
+        errors,
+    } = match download.await {
+        Ok(outcome) => outcome,
+        Err(error @ YtdlpError::Spawn(_)) => {
+            let error = error.to_string();
+            manager.set_status(&download_id, DownloadStatus::Failed, Some(error.clone()));
+            return Err(error);
+        }
+        Err(error) => {
+            let error = error.to_string();
+            warn!(download_id = %download_id, "{}", error);
+            manager.set_status(&download_id, DownloadStatus::Failed, Some(error.clone()));
+            app.state::<HistoryStore>().record(HistoryEntry {
+                error: Some(error.clone()),
+                output_dir: options.output_dir.clone(),
+                ..HistoryEntry::new(&download_id, &canonical_url, DownloadStatus::Failed, OutputFiles::default())
+            });
+            return Err(error);
+        }
+    };
+
+    // 输出中的文件名含无效编码时，用磁盘上的实际文件名还原
+    let mut files = OutputFiles {
+        output_path: outputs.final_path().map(|path| resolve_lossy_path(&path)),
+        sidecar_files: outputs.sidecar_files().iter().map(|path| resolve_lossy_path(path)).collect(),
+    };
+    // 分别下载音视频：视频文件作为主文件，音频文件随附属文件一起管理（删除、移动）
+    let mut stream_files = Vec::new();
+    if options.separate_streams {
+        stream_files = outputs.stream_files().iter().map(|path| resolve_lossy_path(path)).collect();
+        files.output_path = stream_files.first().cloned();
+        files.sidecar_files.extend(stream_files.iter().skip(1).cloned());
+    }
+
+    // 播放列表有条目失败、但其余条目已完成时按部分成功处理，而不是整体失败
+    let partial = (!status.success()).then(|| items.partial_success()).flatten();
+    if let Some(partial) = &partial {
+        warn!(
+            download_id = %download_id,
+            "播放列表部分完成: {} 个完成，{} 个失败 {:?}",
+            partial.completed,
+            partial.failed.len(),
+            partial.failed
+        );
+    }
+
+    if status.success() || partial.is_some() {
+        let mut kept_files: Vec<String> = if options.separate_streams {
+            Vec::new()
+        } else {
+            outputs.kept_files().iter().map(|path| resolve_lossy_path(path)).collect()
+        };
+        let mut thumbnail_path = outputs.thumbnail_path().map(|path| resolve_lossy_path(&path));
+        let downloaded_format = format_report.read();
+
+        // 暂存模式：全部文件移动到下载目录后才算完成
+        if let Some(staging) = &staging_dir {
+            let destination = match &options.output_dir {
+                Some(dir) => PathBuf::from(dir),
+                None => std::env::current_dir().unwrap_or_default(),
+            };
+            // 跨卷移动时会复制整个文件，在阻塞线程中进行，不占用异步运行时
+            let moving = {
+                let staging = staging.clone();
+                tauri::async_runtime::spawn_blocking(move || move_staged_files(&staging, &destination))
+            };
+            let moved = moving.await.unwrap_or_else(|e| Err(format!("文件移动任务失败: {}", e)));
+            match moved {
+                Ok(moved) => {
+                    files.output_path = files.output_path.map(|path| resolve_moved_path(&moved, &path));
+                    files.sidecar_files = files
+                        .sidecar_files
+                        .iter()
+                        .map(|path| resolve_moved_path(&moved, path))
+                        .collect();
+                    kept_files = kept_files
+                        .iter()
+                        .map(|path| resolve_moved_path(&moved, path))
+                        .collect();
+                    thumbnail_path = thumbnail_path.map(|path| resolve_moved_path(&moved, &path));
+                    stream_files = stream_files
+                        .iter()
+                        .map(|path| resolve_moved_path(&moved, path))
+                        .collect();
+                }
+                Err(e) => {
+                    let error = format!("移动文件失败: {}", e);
+                    warn!(download_id = %download_id, "{}", error);
+                    manager.set_status(&download_id, DownloadStatus::Failed, Some(error.clone()));
+                    app.state::<HistoryStore>().record(HistoryEntry {
+                        total_bytes,
+                        error: Some(error.clone()),
+                        warnings,
+                        output_dir: options.output_dir.clone(),
+                        ..HistoryEntry::new(&download_id, &canonical_url, DownloadStatus::Failed, files)
+                    });
+                    let failed = MoveFailed {
+                        download_id: download_id.clone(),
+                        staging_dir: staging.to_string_lossy().into_owned(),
+                        error: error.clone(),
+                    };
+                    if let Err(e) = app.emit(events::DOWNLOAD_MOVE_FAILED, &failed) {
+                        warn!(download_id = %download_id, "发送移动失败事件失败: {}", e);
                     }
+                    return Err(error);
                 }
             }
         }
-        println!("📝 标准输出读取结束，共处理 {} 行", line_count);
-    });
 
-    // 异步读取标准错误
-    tokio::spawn(async move {
-        while let Ok(Some(line)) = stderr_reader.next_line().await {
-            if !line.trim().is_empty() {
-                eprintln!("[yt-dlp-err] {}", line);
+        let audio_language_fallback = match (&options.audio_language, &downloaded_format) {
+            (Some(requested), Some(format)) => !format
+                .audio_language
+                .as_deref()
+                .is_some_and(|language| language.starts_with(requested.as_str())),
+            _ => false,
+        };
+        if audio_language_fallback {
+            warn!(download_id = %download_id, "没有 {:?} 音轨，已使用默认音轨", options.audio_language);
+        }
+
+        // 校验输出文件，进程正常退出不代表文件完整
+        // （分别下载时累计字节数包含音频流，不能作为视频文件的预期大小）
+        let expected_bytes = if options.separate_streams { None } else { total_bytes };
+        let verification = verify_output(files.output_path.as_deref(), expected_bytes).await;
+        if !verification.verified {
+            warn!(download_id = %download_id, "输出文件校验未通过: {:?}", verification.issues);
+            let warning = DownloadWarning {
+                download_id: download_id.clone(),
+                issues: verification.issues.clone(),
+            };
+            if let Err(e) = app.emit(events::DOWNLOAD_WARNING, &warning) {
+                warn!(download_id = %download_id, "发送警告事件失败: {}", e);
             }
         }
-    });
 
-    // 等待进程结束
-    let status = child
-        .wait()
-        .await
-        .map_err(|e| format!("等待下载进程失败: {}", e))?;
+        // 评论写在 .info.json 中，读回实际获取到的条数
+        let comment_count = if options.write_comments {
+            info_json_comment_count(&files.sidecar_files)
+        } else {
+            None
+        };
+
+        // 归档模式：列出实际合并进文件的视频、音频和字幕轨道
+        let archive_tracks = match (options.archive_best, find_ffprobe(), files.output_path.as_deref()) {
+            (true, Some(ffprobe), Some(path)) => match probe_media(&ffprobe, Path::new(path)).await {
+                Ok(probe) => {
+                    info!(
+                        download_id = %download_id,
+                        "归档文件包含 {} 个视频轨、{} 个音频轨、{} 个字幕轨",
+                        probe.video_streams,
+                        probe.audio_streams,
+                        probe.streams.iter().filter(|s| s.codec_type == "subtitle").count()
+                    );
+                    Some(probe)
+                }
+                Err(e) => {
+                    warn!(download_id = %download_id, "无法读取归档文件的轨道: {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        info!(download_id = %download_id, "下载完成: {:?} ({:?})", files.output_path, downloaded_format);
+        let output_path = files.output_path.clone();
+        let (final_status, summary) = match &partial {
+            Some(partial) => (
+                DownloadStatus::PartialSuccess,
+                Some(format!("{} 个条目下载失败", partial.failed.len())),
+            ),
+            None => (DownloadStatus::Completed, None),
+        };
+        manager.set_status(&download_id, final_status, summary);
+        app.state::<HistoryStore>().record(HistoryEntry {
+            total_bytes,
+            verified: Some(verification.verified),
+            suspect: !verification.verified,
+            verification_issues: verification.issues.clone(),
+            warnings,
+            output_dir: options.output_dir.clone(),
+            partial: partial.clone(),
+            ..HistoryEntry::new(&download_id, &canonical_url, final_status, files)
+        });
+
+        // 按设置自动计算校验和（后台进行，不延迟完成事件）
+        if let (Some(algorithm), Some(path)) =
+            (app.state::<SettingsState>().get().auto_checksum, output_path.clone())
+        {
+            let app = app.clone();
+            let id = download_id.clone();
+            tauri::async_runtime::spawn(async move {
+                match checksum_file(app.clone(), path, algorithm).await {
+                    Ok(checksum) => app.state::<HistoryStore>().set_checksum(&id, checksum),
+                    Err(e) => warn!(download_id = %id, "自动计算校验和失败: {}", e),
+                }
+            });
+        }
 
-    if status.success() {
-        println!("下载完成");
         // 发送下载完成事件
-        if let Err(e) = app.emit("download-complete", ()) {
-            eprintln!("发送完成事件失败: {}", e);
+        let complete = DownloadComplete {
+            download_id: download_id.clone(),
+            output_dir: options.output_dir.clone(),
+            output_path,
+            kept_files,
+            downloaded_format,
+            audio_language_fallback,
+            thumbnail_path,
+            stream_files,
+            verified: verification.verified,
+            verification,
+            archive_tracks,
+            warnings,
+            partial,
+            comment_count,
+        };
+        if let Err(e) = app.emit(events::DOWNLOAD_COMPLETE, &complete) {
+            warn!(download_id = %download_id, "发送完成事件失败: {}", e);
         }
         Ok(())
     } else {
-        Err("下载失败: 进程返回非零退出码".to_string())
+        // 音视频流已下载完、合并时出错：保留各个流，之后可用 retry_postprocess 只重新合并
+        let pending_merge = pending_merge(&outputs, &errors, staging_dir.as_deref(), options.output_dir.as_deref());
+        let error = match &pending_merge {
+            Some(_) => format!("合并音视频失败（已下载的文件已保留，可重新合并）: {}", errors.join("\n")),
+            None => "下载失败: 进程返回非零退出码".to_string(),
+        };
+        manager.set_status(&download_id, DownloadStatus::Failed, Some(error.clone()));
+        app.state::<HistoryStore>().record(HistoryEntry {
+            total_bytes,
+            error: Some(error.clone()),
+            warnings,
+            output_dir: options.output_dir.clone(),
+            pending_merge,
+            ..HistoryEntry::new(&download_id, &canonical_url, DownloadStatus::Failed, files)
+        });
+        Err(error)
     }
 }
 
 /***************************************************************************
- * 解析 yt-dlp 进度输出
+ * 判断下载是否在合并阶段失败
  *
- * 格式示例:
- * [download]  42.0% of 125.89MiB at  5.82MiB/s ETA 00:12
+ * yt-dlp 已开始合并（输出了 "[Merger] Merging formats into"）、出错行提到
+ * ffmpeg/Merger/后处理，且各个流仍在磁盘上（合并失败时 yt-dlp 不删除原始流）
  *
- * @param line - yt-dlp 输出的一行文本
- * @return Option<serde_json::Value> - 解析后的进度信息（如果行包含进度）
+ * @param staging_dir - 暂存目录（合并目标改为下载目录中的同名文件）
+ * @return Option<PendingMerge> - 不是合并失败时为 None
  ***************************************************************************/
 
-fn parse_progress_line(line: &str) -> Option<serde_json::Value> {
-    // 增强匹配条件，支持更多格式
-    if !line.contains("[download]") && !line.contains("%") {
+fn pending_merge(
+    outputs: &OutputTracker,
+    errors: &[String],
+    staging_dir: Option<&Path>,
+    output_dir: Option<&str>,
+) -> Option<PendingMerge> {
+    let target = resolve_lossy_path(outputs.merge_target()?);
+    if !errors.iter().any(|line| is_postprocess_error(line)) {
+        return None;
+    }
+    let streams: Vec<String> = outputs.stream_files().iter().map(|path| resolve_lossy_path(path)).collect();
+    if streams.is_empty() || !streams.iter().all(|path| Path::new(path).is_file()) {
         return None;
     }
 
-    println!("解析进度行: {}", line); // 调试输出
+    let target = match (staging_dir, output_dir) {
+        (Some(_), Some(dir)) => Path::new(dir)
+            .join(Path::new(&target).file_name()?)
+            .to_string_lossy()
+            .into_owned(),
+        _ => target,
+    };
+    Some(PendingMerge { streams, target })
+}
 
-    let parts: Vec<&str> = line.split_whitespace().collect();
+/***************************************************************************
+ * 准备下载：规范化链接、构建参数、定位 yt-dlp
+ ***************************************************************************/
 
-    // 查找百分比（包含%的字段）
-    let mut percent: Option<f64> = None;
-    for part in &parts {
-        if part.contains('%') {
-            if let Some(p) = part.trim_end_matches('%').parse::<f64>().ok() {
-                percent = Some(p);
-                break;
-            }
-        }
-    }
+struct PreparedDownload {
+    url: String,                    // 规范化后的链接
+    ytdlp_path: PathBuf,
+    args: Vec<String>,
+    staging_dir: Option<PathBuf>,   // 暂存目录（未开启 stage_downloads 时为 None）
+    format_report: FormatReportFile, // 实际下载格式的报告文件（丢弃时删除）
+}
 
-    let percent = percent?;
+async fn prepare_download(
+    app: &AppHandle,
+    download_id: &str,
+    url: &str,
+    options: &DownloadOptions,
+) -> Result<PreparedDownload, String> {
+    // 下载目录不可用时直接报错，不改用其他目录
+    let destination = download_destination(app, options)?;
+    validate_writable_dir(&destination).map_err(|e| format!("下载目录不可用: {}", e))?;
 
-    // 查找速度 - 支持多种格式
-    let mut speed = "".to_string();
-    for (i, part) in parts.iter().enumerate() {
-        if *part == "at" && i + 1 < parts.len() {
-            speed = parts[i + 1].to_string();
-            // 检查下一个词是否包含/s，如果是则加上
-            if i + 2 < parts.len() {
-                let next_part = parts[i + 2];
-                if next_part.contains("/s") {
-                    speed.push_str(" ");
-                    speed.push_str(next_part);
-                }
-            }
-            break;
+    // 规范化链接（展开短链接/跳转链接）
+    let canonical_url = normalize_url(url).await?;
+
+    let settings = app.state::<SettingsState>().get();
+    let staging_dir = if settings.stage_downloads {
+        Some(staging_dir(&settings, download_id)?)
+    } else {
+        None
+    };
+    // 报告文件放在临时目录而不是暂存目录，避免被当作下载结果移动
+    let format_report = FormatReportFile::new(settings.temp_root()?.join(format!("{}.format", download_id)));
+
+    let args = build_download_args(
+        &canonical_url,
+        options,
+        &settings,
+        staging_dir.as_deref(),
+        Some(format_report.path()),
+    )?;
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    debug!(download_id = %download_id, "使用 yt-dlp 路径: {:?}", ytdlp_path);
+
+    let AssembledArgs { args, overridden, .. } =
+        assemble_download_args(app, &ytdlp_path, &canonical_url, options, &settings, args).await?;
+    debug!(download_id = %download_id, "参数: {:?}", args);
+    for name in overridden {
+        let message = format!("自定义参数 {} 覆盖了应用生成的同名参数", name);
+        warn!(download_id = %download_id, "{}", message);
+        emit_ytdlp_warning(app, Some(download_id), WarningKind::ArgOverride, message);
+    }
+    let support = app.state::<ImpersonationState>().get(&ytdlp_path).await;
+
+    let format_pair = options.video_format_id.is_some() || options.audio_format_id.is_some();
+    // 没有 ffmpeg 时，指定的格式若需要合并就提前报错（有 ffmpeg 时不必额外获取格式列表）
+    let merge_check = options.format_id.as_deref().is_some_and(|id| !id.is_empty())
+        && !options.separate_streams
+        && options.extract_audio.is_none()
+        && find_ffmpeg().is_none();
+    if options.separate_streams || format_pair || merge_check {
+        let json = fetch_video_json(&ytdlp_path, &canonical_url, support.supports("chrome"), None, false, &[]).await?;
+        let formats = parse_formats(&json);
+        if options.separate_streams {
+            ensure_separate_streams(&formats)?;
         }
-        // 也支持直接包含速度单位的词
-        if part.contains("MiB/s") || part.contains("KiB/s") || part.contains("MB/s") || part.contains("KB/s") {
-            speed = part.to_string();
-            break;
+        ensure_format_pair(&formats, options)?;
+        warn_incompatible_container(download_id, &formats, options);
+        if merge_check {
+            ensure_no_merge(&formats, options.format_id.as_deref().unwrap_or_default())?;
         }
     }
 
-    // 查找 ETA - 支持多种格式
-    let mut eta = "".to_string();
-    for (i, part) in parts.iter().enumerate() {
-        if *part == "ETA" && i + 1 < parts.len() {
-            eta = parts[i + 1].to_string();
-            break;
+    Ok(PreparedDownload {
+        url: canonical_url,
+        ytdlp_path,
+        args,
+        staging_dir,
+        format_report,
+    })
+}
+
+/// 插入站点预设和自定义参数后的完整下载参数
+struct AssembledArgs {
+    args: Vec<String>,
+    extra_args: std::ops::Range<usize>, // 自定义参数在 args 中的位置
+    overridden: Vec<String>,        // 被自定义参数覆盖的生成选项
+}
+
+/***************************************************************************
+ * 在 URL 之前依次插入站点预设和自定义参数
+ *
+ * 伪装不可用时移除 --impersonate（只处理生成的参数和站点预设），
+ * 自定义参数（先全局设置，后下载选项）放在最后，同名选项以其为准
+ *
+ * @param args - build_download_args 生成的参数（最后一项为 URL）
+ ***************************************************************************/
+
+async fn assemble_download_args(
+    app: &AppHandle,
+    ytdlp_path: &Path,
+    url: &str,
+    options: &DownloadOptions,
+    settings: &Settings,
+    mut args: Vec<String>,
+) -> Result<AssembledArgs, String> {
+    validate_extra_args(&options.extra_args)?;
+
+    let site_args = site_preset_args(app, ytdlp_path, url, Some(options)).await;
+    let url_index = args.len().saturating_sub(1);
+    args.splice(url_index..url_index, site_args);
+
+    // 伪装不可用时移除 --impersonate，避免下载直接失败
+    let support = app.state::<ImpersonationState>().get(ytdlp_path).await;
+    let mut args = filter_impersonate_args(args, &support);
+
+    let extra: Vec<String> = settings.extra_args.iter().chain(&options.extra_args).cloned().collect();
+    let url_index = args.len().saturating_sub(1);
+    let overridden = overridden_options(&args[..url_index], &extra);
+    let extra_args = url_index..url_index + extra.len();
+    args.splice(url_index..url_index, extra);
+
+    Ok(AssembledArgs {
+        args,
+        extra_args,
+        overridden,
+    })
+}
+
+/// .info.json 中的评论数：优先数实际写入的 comments，没有时取 comment_count 字段
+fn info_json_comment_count(sidecars: &[String]) -> Option<u64> {
+    let path = sidecars.iter().find(|path| path.ends_with(".info.json"))?;
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| warn!("读取 {} 失败: {}", path, e))
+        .ok()?;
+    let json: Value = serde_json::from_str(&content).ok()?;
+    json["comments"]
+        .as_array()
+        .map(|comments| comments.len() as u64)
+        .or_else(|| json["comment_count"].as_u64())
+}
+
+/***************************************************************************
+ * 确认链接同时提供纯视频格式和纯音频格式（分别下载模式的前提）
+ *
+ * 只有合并格式的站点（如部分直播回放）无法拆分，提前报错而不是下载一个合并文件
+ ***************************************************************************/
+
+fn ensure_separate_streams(formats: &[VideoFormat]) -> Result<(), String> {
+    let has_codec = |codec: &Option<String>| codec.as_deref().is_some_and(|c| c != "none");
+
+    let video_only = formats.iter().any(|f| has_codec(&f.vcodec) && !has_codec(&f.acodec));
+    let audio_only = formats.iter().any(|f| has_codec(&f.acodec) && !has_codec(&f.vcodec));
+    match (video_only, audio_only) {
+        (true, true) => Ok(()),
+        (false, _) => Err("该视频没有单独的视频流，无法分别下载音视频".to_string()),
+        (_, false) => Err("该视频没有单独的音频流，无法分别下载音视频".to_string()),
+    }
+}
+
+/***************************************************************************
+ * 确认指定的视频/音频格式ID存在且种类正确
+ *
+ * 视频格式必须包含视频流（纯视频或合并格式），音频格式必须是纯音频，
+ * 例如两个音频格式ID在启动 yt-dlp 之前就报错
+ ***************************************************************************/
+
+fn ensure_format_pair(formats: &[VideoFormat], options: &DownloadOptions) -> Result<(), String> {
+    let kind_of = |id: &str| {
+        formats
+            .iter()
+            .find(|f| f.format_id == id)
+            .map(|f| f.kind)
+            .ok_or_else(|| format!("该视频没有格式 {}", id))
+    };
+
+    if let Some(id) = options.video_format_id.as_deref().filter(|id| !id.is_empty()) {
+        match kind_of(id)? {
+            Some(FormatKind::Video) | Some(FormatKind::Muxed) => {}
+            Some(FormatKind::Audio) => return Err(format!("格式 {} 是音频格式，不能作为视频格式", id)),
+            None => return Err(format!("格式 {} 不包含视频流", id)),
         }
-        // 也支持直接包含时间格式的词
-        if part.chars().filter(|c| *c == ':').count() == 2 {
-            eta = part.to_string();
-            break;
+    }
+    if let Some(id) = options.audio_format_id.as_deref().filter(|id| !id.is_empty()) {
+        match kind_of(id)? {
+            Some(FormatKind::Audio) => {}
+            Some(FormatKind::Video) => return Err(format!("格式 {} 是视频格式，不能作为音频格式", id)),
+            _ => return Err(format!("格式 {} 不是纯音频格式", id)),
         }
     }
+    Ok(())
+}
 
-    let progress = serde_json::json!({
-        "percent": percent,
-        "speed": speed,
-        "eta": eta,
-    });
+/***************************************************************************
+ * 指定的视频/音频格式与合并容器不兼容时记录警告（yt-dlp 仍会尝试合并）
+ ***************************************************************************/
+
+fn warn_incompatible_container(download_id: &str, formats: &[VideoFormat], options: &DownloadOptions) {
+    let Some(container) = options.merge_output_format.as_deref().filter(|f| !f.is_empty()) else {
+        return;
+    };
+
+    let codecs: Vec<&str> = [&options.video_format_id, &options.audio_format_id]
+        .into_iter()
+        .flatten()
+        .filter_map(|id| formats.iter().find(|f| &f.format_id == id))
+        .flat_map(|f| [f.vcodec.as_deref(), f.acodec.as_deref()])
+        .flatten()
+        .collect();
+
+    let incompatible = incompatible_codecs(container, &codecs);
+    if !incompatible.is_empty() {
+        warn!(
+            download_id = %download_id,
+            "编码 {} 与 {} 容器不兼容，合并可能失败或需要重新编码",
+            incompatible.join(", "),
+            container
+        );
+    }
+}
+
+/***************************************************************************
+ * 确认指定的格式无需合并（仅在未安装 ffmpeg 时调用）
+ *
+ * 合并格式ID（如 "137+140"）或纯视频格式都需要 ffmpeg 合并音视频
+ ***************************************************************************/
+
+fn ensure_no_merge(formats: &[VideoFormat], format_id: &str) -> Result<(), String> {
+    let requires_merge = format_id.contains('+')
+        || formats
+            .iter()
+            .find(|f| f.format_id == format_id)
+            .is_some_and(|f| f.requires_merge());
+    if requires_merge {
+        return Err(format!("格式 {} 需要合并音视频，请先安装 ffmpeg", format_id));
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 读取设置
+ ***************************************************************************/
+
+#[command]
+pub fn get_settings(settings: State<'_, SettingsState>) -> Settings {
+    settings.get()
+}
+
+/***************************************************************************
+ * Tauri 命令 - 更新设置
+ *
+ * @param new_settings - 完整的新设置（会先校验目录是否存在且可写）
+ ***************************************************************************/
+
+#[command]
+pub fn update_settings(
+    settings: State<'_, SettingsState>,
+    queue: State<'_, DownloadQueue>,
+    new_settings: Settings,
+) -> Result<(), String> {
+    settings.update(new_settings.clone())?;
+    pin_ytdlp_path(new_settings.ytdlp_path.as_deref());
+    prefer_bundled_ytdlp(new_settings.prefer_bundled_ytdlp);
+    process::configure(&new_settings);
+    i18n::configure(&new_settings);
+    // 下载目录可能已更换，等待目录的任务立即重新检查
+    queue.destination_changed();
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 列出找到的所有 yt-dlp
+ *
+ * 查找范围与 get_ytdlp_path 相同；固定的路径不在搜索范围内时也会列出，
+ * 以 Python 模块方式运行时列出所用的解释器
+ *
+ * @return Vec<YtdlpCandidate> - 按查找顺序排列，附带各自的版本
+ ***************************************************************************/
+
+#[command]
+pub async fn list_ytdlp_candidates(settings: State<'_, SettingsState>) -> Result<Vec<YtdlpCandidate>, String> {
+    let pinned = settings.get().ytdlp_path.filter(|p| !p.is_empty()).map(PathBuf::from);
+    let active = get_ytdlp_path().await.ok();
+
+    let mut paths = ytdlp_candidates();
+    if let Some(pinned) = pinned.as_ref().filter(|p| p.is_file() && !paths.contains(p)) {
+        paths.insert(0, pinned.clone());
+    }
+    if let Some(active) = active.as_ref().filter(|p| !paths.contains(p)) {
+        paths.push(active.clone());
+    }
+
+    let mut candidates = Vec::new();
+    for path in paths {
+        candidates.push(YtdlpCandidate {
+            version: YtDlp::new(&path).version().await,
+            pinned: pinned.as_ref() == Some(&path),
+            active: active.as_ref() == Some(&path),
+            path: path.to_string_lossy().into_owned(),
+        });
+    }
+    Ok(candidates)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 固定使用指定的 yt-dlp
+ *
+ * @param path - yt-dlp 可执行文件路径，None 或空字符串恢复自动查找
+ * @return Option<String> - 固定的 yt-dlp 版本（恢复自动查找时为 None）
+ ***************************************************************************/
+
+#[command]
+pub async fn set_ytdlp_path(
+    settings: State<'_, SettingsState>,
+    path: Option<String>,
+) -> Result<Option<String>, String> {
+    let path = path.filter(|p| !p.trim().is_empty());
+    let version = match &path {
+        Some(p) => Some(
+            YtDlp::new(p)
+                .version()
+                .await
+                .ok_or_else(|| format!("无法运行该 yt-dlp: {}", p))?,
+        ),
+        None => None,
+    };
+
+    let mut new_settings = settings.get();
+    new_settings.ytdlp_path = path.clone();
+    settings.update(new_settings)?;
+    pin_ytdlp_path(path.as_deref());
+
+    info!("yt-dlp 路径: {:?} (版本 {:?})", path, version);
+    Ok(version)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取下载速度历史
+ *
+ * @param download_id - 下载任务ID
+ * @param limit - 返回的最大样本数（默认全部，最多约 10 分钟）
+ * @return SpeedHistory - 样本及最小/平均/最大速度
+ ***************************************************************************/
+
+#[command]
+pub fn get_speed_history(
+    manager: State<'_, DownloadManager>,
+    download_id: String,
+    limit: Option<usize>,
+) -> Result<SpeedHistory, String> {
+    manager
+        .speed_history(&download_id, limit.unwrap_or(usize::MAX))
+        .ok_or_else(|| format!("未找到下载任务: {}", download_id))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取下载任务当前状态
+ *
+ * 前端重新加载或错过事件后，可通过此命令恢复任务状态
+ *
+ * @param download_id - 下载任务ID
+ * @return DownloadState - 状态及最近一次进度快照
+ ***************************************************************************/
+
+#[command]
+pub fn get_download_state(
+    manager: State<'_, DownloadManager>,
+    download_id: String,
+) -> Result<DownloadState, String> {
+    manager
+        .state(&download_id)
+        .ok_or_else(|| format!("未找到下载任务: {}", download_id))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取整体队列进度
+ *
+ * @return QueueProgress - 当前批次的整体完成度和剩余时间
+ ***************************************************************************/
+
+#[command]
+pub fn get_queue_progress(manager: State<'_, DownloadManager>) -> QueueProgress {
+    compute_queue_progress(&manager.queue_snapshot())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 估算整个队列的剩余时间
+ *
+ * 与 get_queue_progress 不同，不限当前批次，且用最近一分钟的平均吞吐量
+ * 而不是瞬时速度，暂停或等待中（没有速度）时也能给出估算
+ *
+ * @return QueueEta - 剩余秒数及显示文本（如 "约 25 分钟"）
+ ***************************************************************************/
+
+#[command]
+pub fn get_queue_eta(manager: State<'_, DownloadManager>) -> QueueEta {
+    compute_queue_eta(&manager.all_snapshots(), manager.recent_throughput())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 检测浏览器伪装（--impersonate）支持
+ *
+ * 每次调用都会重新探测，安装 curl_cffi 后无需重启应用
+ *
+ * @return ImpersonationSupport - 是否可用及全部伪装目标
+ ***************************************************************************/
+
+#[command]
+pub async fn check_impersonation_support(
+    impersonation: State<'_, ImpersonationState>,
+) -> Result<ImpersonationSupport, String> {
+    let ytdlp_path = get_ytdlp_path().await?;
+    Ok(impersonation.refresh(&ytdlp_path).await)
+}
 
-    println!("解析的进度: {}", progress); // 调试输出
-    Some(progress)
+/***************************************************************************
+ * Tauri 命令 - 判断一次失败是否由浏览器伪装不可用引起
+ *
+ * 前端据此决定是否展示 curl_cffi 安装说明（support.install 中的命令
+ * 已按 yt-dlp 的实际安装方式生成）
+ *
+ * @param error - 失败时的错误信息或 yt-dlp 输出
+ * @return ImpersonationDiagnosis - 是否为伪装问题及当前伪装支持情况
+ ***************************************************************************/
+
+#[command]
+pub async fn diagnose_impersonation(
+    impersonation: State<'_, ImpersonationState>,
+    error: String,
+) -> Result<ImpersonationDiagnosis, String> {
+    let ytdlp_path = get_ytdlp_path().await?;
+    let support = impersonation.refresh(&ytdlp_path).await;
+    Ok(ImpersonationDiagnosis {
+        caused_by_impersonation: is_impersonation_error(&error),
+        support,
+    })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取下载历史
+ *
+ * @return Vec<HistoryEntry> - 全部历史记录（最新的在前）
+ ***************************************************************************/
+
+#[command]
+pub fn get_history(history: State<'_, HistoryStore>) -> Vec<HistoryEntry> {
+    history.list()
+}
+
+/***************************************************************************
+ * Tauri 命令 - 加入下载队列
+ *
+ * 同一视频已在队列中或已下载时拒绝入队，force 为 true 时跳过检查
+ *
+ * @param url - 视频URL
+ * @param options - 下载选项；指定了预设时作为覆盖项合并到预设之上
+ * @param preset - 质量预设名称
+ * @param force - 忽略重复检查
+ * @return String - 分配的下载任务ID（进度、完成、失败事件均携带该ID）
+ ***************************************************************************/
+
+#[command]
+pub fn enqueue_download(
+    app: AppHandle,
+    url: String,
+    options: Option<Map<String, Value>>,
+    preset: Option<String>,
+    force: Option<bool>,
+) -> Result<String, String> {
+    let url = validate_url(&url)?;
+    let base = match preset {
+        Some(name) => find_preset(&app.state::<SettingsState>().get(), &name)?.options,
+        None => DownloadOptions::default(),
+    };
+    let options = merge_options(&base, options)?;
+    if !force.unwrap_or(false) {
+        let duplicate = find_duplicate(
+            &url,
+            &app.state::<DownloadQueue>().items(),
+            &app.state::<HistoryStore>().list(),
+        );
+        if matches!(
+            duplicate,
+            DuplicateStatus::AlreadyQueued { .. } | DuplicateStatus::AlreadyDownloaded { .. }
+        ) {
+            return Err(duplicate.describe());
+        }
+    }
+    let manager = app.state::<DownloadManager>();
+    Ok(app.state::<DownloadQueue>().enqueue(&manager, url, options))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 检查视频是否重复
+ *
+ * @param url - 视频URL（youtu.be 与 watch?v= 等形式按同一视频处理）
+ * @return DuplicateStatus - 已在队列中 / 已下载 / 下载过但文件缺失 / 新视频
+ ***************************************************************************/
+
+#[command]
+pub fn check_duplicate(
+    queue: State<'_, DownloadQueue>,
+    history: State<'_, HistoryStore>,
+    url: String,
+) -> Result<DuplicateStatus, String> {
+    let url = validate_url(&url)?;
+    Ok(find_duplicate(&url, &queue.items(), &history.list()))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 定时下载
+ *
+ * 任务以 Scheduled 状态进入队列，到达开始时间后自动转为 Queued；
+ * 定时任务保存在队列文件中，应用重启后仍然有效
+ *
+ * @param url - 视频URL
+ * @param options - 结构化下载选项
+ * @param start_at - 开始时间（Unix 毫秒，已过时立即加入队列）
+ * @return String - 分配的下载任务ID
+ ***************************************************************************/
+
+#[command]
+pub fn schedule_download(
+    queue: State<'_, DownloadQueue>,
+    manager: State<'_, DownloadManager>,
+    url: String,
+    options: DownloadOptions,
+    start_at: u64,
+) -> Result<String, String> {
+    let url = validate_url(&url)?;
+    let id = queue.schedule(&manager, url, options, start_at);
+    info!(download_id = %id, "已添加定时下载，开始时间 {}", start_at);
+    Ok(id)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 首映或直播开始后自动下载
+ *
+ * 获取计划开始时间，按定时下载加入队列（由队列的定时任务计时器启动）；
+ * 附加 --wait-for-video，开始时间到了视频仍不可下载时 yt-dlp 每隔
+ * RELEASE_RECHECK_INTERVAL 秒重新检查。视频已经可以下载时直接加入队列
+ *
+ * @param url - 视频URL
+ * @param options - 结构化下载选项
+ * @return String - 分配的下载任务ID
+ ***************************************************************************/
+
+#[command]
+pub async fn download_when_released(
+    queue: State<'_, DownloadQueue>,
+    manager: State<'_, DownloadManager>,
+    impersonation: State<'_, ImpersonationState>,
+    settings: State<'_, SettingsState>,
+    url: String,
+    mut options: DownloadOptions,
+) -> Result<String, String> {
+    let url = normalize_url(&url).await?;
+    let ytdlp_path = get_ytdlp_path().await?;
+    let impersonate = impersonation.get(&ytdlp_path).await.supports("chrome");
+    let info = fetch_upcoming_info(&ytdlp_path, &url, impersonate, None, &settings.get()).await?;
+
+    // 已有可下载的格式：首映或直播已经开始
+    let start_at = if !info.formats.is_empty() {
+        0
+    } else {
+        info.upcoming
+            .and_then(|upcoming| upcoming.release_at)
+            .ok_or("无法获取首映或直播的开始时间，请稍后重试或手动定时下载")?
+    };
+    options.wait_for_video.get_or_insert(RELEASE_RECHECK_INTERVAL);
+
+    let id = queue.schedule(&manager, url, options, start_at);
+    info!(download_id = %id, "首映或直播开始后下载，开始时间 {}", start_at);
+    Ok(id)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取队列中的任务（含定时任务及其开始时间）和当前优先级
+ ***************************************************************************/
+
+#[command]
+pub fn get_queue(queue: State<'_, DownloadQueue>) -> QueueOverview {
+    QueueOverview {
+        items: queue.items(),
+        priority_mode: process::priority_mode(),
+        paused: queue.is_paused(),
+    }
+}
+
+/***************************************************************************
+ * Tauri 命令 - 暂停队列
+ *
+ * 不再启动等待中的任务，运行中的下载照常完成（与暂停单个下载无关）；
+ * 状态有变化时发送 queue-paused 事件
+ ***************************************************************************/
+
+#[command]
+pub fn pause_queue(app: AppHandle, queue: State<'_, DownloadQueue>) {
+    if let Some(changed) = queue.set_paused(true) {
+        info!("队列已暂停，{} 个任务等待，{} 个任务继续运行", changed.pending, changed.running);
+        if let Err(e) = app.emit(events::QUEUE_PAUSED, &changed) {
+            warn!("发送队列暂停事件失败: {}", e);
+        }
+    }
+}
+
+/***************************************************************************
+ * Tauri 命令 - 继续队列
+ *
+ * 按并发上限启动等待中的任务；状态有变化时发送 queue-resumed 事件
+ ***************************************************************************/
+
+#[command]
+pub fn resume_queue(app: AppHandle, queue: State<'_, DownloadQueue>) {
+    if let Some(changed) = queue.set_paused(false) {
+        info!("队列已继续，{} 个任务等待", changed.pending);
+        if let Err(e) = app.emit(events::QUEUE_RESUMED, &changed) {
+            warn!("发送队列继续事件失败: {}", e);
+        }
+    }
+}
+
+/***************************************************************************
+ * Tauri 命令 - 设置任务的排队优先级
+ *
+ * 等待中的任务按 (优先级从高到低, 加入顺序) 启动，默认优先级为 0；
+ * 已开始的任务不做修改，返回结果中说明原因
+ *
+ * @param id - 下载任务ID
+ * @param priority - 排队优先级（越大越先开始，可为负数）
+ * @return PriorityChange - 修改结果（任务不在队列中时返回错误）
+ ***************************************************************************/
+
+#[command]
+pub fn set_queue_priority(
+    queue: State<'_, DownloadQueue>,
+    id: String,
+    priority: i32,
+) -> Result<PriorityChange, String> {
+    let change = queue.set_priority(&id, priority)?;
+    info!(download_id = %id, "排队优先级: {} (生效: {})", change.priority, change.applied);
+    Ok(change)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 让等待中的任务下一个开始
+ *
+ * 不影响正在运行的下载，有空闲名额时最先启动该任务
+ *
+ * @param id - 下载任务ID
+ * @return PriorityChange - 修改结果（任务不在队列中时返回错误）
+ ***************************************************************************/
+
+#[command]
+pub fn download_next(queue: State<'_, DownloadQueue>, id: String) -> Result<PriorityChange, String> {
+    let change = queue.download_next(&id)?;
+    info!(download_id = %id, "移到队首 (生效: {})", change.applied);
+    Ok(change)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 设置 yt-dlp/ffmpeg 子进程的优先级
+ *
+ * 只影响之后启动的进程，正在运行的下载保持原优先级
+ *
+ * @param mode - normal / low / idle
+ ***************************************************************************/
+
+#[command]
+pub fn set_priority_mode(settings: State<'_, SettingsState>, mode: PriorityMode) -> Result<(), String> {
+    let mut new_settings = settings.get();
+    new_settings.background_priority = mode;
+    settings.update(new_settings)?;
+    process::set_priority_mode(mode);
+    info!("子进程优先级已设置为 {:?}", mode);
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 设置错误信息的语言
+ *
+ * 保存到设置并立即生效
+ *
+ * @param locale - 语言（"zh-CN" / "en"），None 跟随系统语言
+ ***************************************************************************/
+
+#[command]
+pub fn set_locale(settings: State<'_, SettingsState>, locale: Option<Locale>) -> Result<(), String> {
+    let mut new_settings = settings.get();
+    new_settings.locale = locale;
+    settings.update(new_settings.clone())?;
+    i18n::configure(&new_settings);
+    info!("错误信息语言已设置为 {:?}", i18n::locale());
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 取消定时下载
+ ***************************************************************************/
+
+#[command]
+pub fn cancel_schedule(
+    queue: State<'_, DownloadQueue>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<(), String> {
+    if !queue.cancel_schedule(&id) {
+        return Err(format!("未找到定时任务: {}", id));
+    }
+    manager.set_status(&id, DownloadStatus::Cancelled, None);
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 立即开始定时下载（不再等待开始时间）
+ ***************************************************************************/
+
+#[command]
+pub fn start_now(
+    queue: State<'_, DownloadQueue>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<(), String> {
+    if !queue.start_now(&manager, &id) {
+        return Err(format!("未找到定时任务: {}", id));
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 从文本文件批量下载
+ *
+ * 每行一个链接，空行和注释行被忽略；无效的行记录到 errors 中，不影响其余链接入队
+ *
+ * @param file_path - 链接列表文件路径
+ * @param options - 应用于所有链接的下载选项
+ * @return BatchResult - 已入队的任务和各行的错误
+ ***************************************************************************/
+
+#[command]
+pub async fn download_from_file(
+    queue: State<'_, DownloadQueue>,
+    manager: State<'_, DownloadManager>,
+    file_path: String,
+    options: DownloadOptions,
+) -> Result<BatchResult, String> {
+    let content = tokio::fs::read(&file_path)
+        .await
+        .map_err(|e| format!("读取链接文件失败: {} ({})", file_path, e))?;
+    let content = String::from_utf8_lossy(&content);
+
+    let mut result = BatchResult::default();
+    for (line, raw) in parse_batch_lines(&content) {
+        match validate_url(&raw) {
+            Ok(url) => {
+                let download_id = queue.enqueue(&manager, url.clone(), options.clone());
+                result.enqueued.push(BatchEnqueued { line, url, download_id });
+            }
+            Err(error) => {
+                warn!("链接文件第 {} 行无效: {}", line, error);
+                result.errors.push(BatchLineError { line, content: raw, error });
+            }
+        }
+    }
+
+    info!(
+        "批量导入 {}: 入队 {} 个，无效 {} 行",
+        file_path,
+        result.enqueued.len(),
+        result.errors.len()
+    );
+    Ok(result)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 预览输出文件名
+ *
+ * 使用与下载完全相同的文件名参数（ASCII 限制、Windows 兼容、截断长度、
+ * 子目录组织），由 yt-dlp 计算最终路径，保证预览与实际下载一致
+ *
+ * @param url - 视频URL
+ * @param options - 结构化下载选项
+ * @return String - 下载后的文件路径
+ ***************************************************************************/
+
+#[command]
+pub async fn preview_filename(
+    settings: State<'_, SettingsState>,
+    impersonation: State<'_, ImpersonationState>,
+    url: String,
+    options: DownloadOptions,
+) -> Result<String, String> {
+    let url = normalize_url(&url).await?;
+    let settings = settings.get();
+    let selector = FormatSelector::from_settings(&options, &settings);
+
+    let mut args: Vec<String> = vec![
+        "--no-warnings".to_string(),
+        "--skip-download".to_string(),
+        "--no-playlist".to_string(),
+        "--print".to_string(),
+        "filename".to_string(),
+    ];
+    if options.archive_best {
+        // 归档模式固定输出 MKV
+        args.extend(archive_best_args(&options, &settings)?);
+    } else {
+        args.push("-f".to_string());
+        args.push(selector.expression());
+        // 排序不同时选中的格式（扩展名）可能不同
+        if let Some(sort) = selector.sort(options.format_sort.as_deref()) {
+            args.push("-S".to_string());
+            args.push(sort);
+        }
+    }
+    validate_user_agent(options.user_agent.as_deref())?;
+    args.extend(network_args(&options));
+    validate_output_template(&options)?;
+    args.extend(filename_args(&options, &settings, None));
+    args.push(url);
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    let support = impersonation.get(&ytdlp_path).await;
+    let args = filter_impersonate_args(args, &support);
+    debug!("预览文件名参数: {:?}", args);
+
+    let output = ytdlp_command(&ytdlp_path)
+        .args(&args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 yt-dlp: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format_ytdlp_error(&stderr, &ytdlp_path));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| "无法获取文件名".to_string())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 预览下载命令
+ *
+ * 按与下载相同的顺序组装参数：生成的参数、站点预设、自定义参数、URL，
+ * 并标出自定义参数的位置和它覆盖的生成选项；不启动 yt-dlp，
+ * 暂存目录和格式报告文件使用预览专用的路径。
+ * 可直接复制到终端复现问题；Cookie 文件、密码等值替换为占位文字
+ *
+ * @param url - 视频URL
+ * @param options - 结构化下载选项
+ * @return CommandPreview - 完整的 yt-dlp 调用
+ ***************************************************************************/
+
+#[command]
+pub async fn preview_download_command(
+    app: AppHandle,
+    url: String,
+    options: DownloadOptions,
+) -> Result<CommandPreview, String> {
+    const PREVIEW_ID: &str = "preview";
+
+    let url = normalize_url(&url).await?;
+    let settings = app.state::<SettingsState>().get();
+    let staging = if settings.stage_downloads {
+        Some(staging_path(&settings, PREVIEW_ID)?)
+    } else {
+        None
+    };
+    let format_report = settings.temp_root()?.join(format!("{}.format", PREVIEW_ID));
+    let args = build_download_args(&url, &options, &settings, staging.as_deref(), Some(&format_report))?;
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    let AssembledArgs {
+        args,
+        extra_args,
+        overridden,
+    } = assemble_download_args(&app, &ytdlp_path, &url, &options, &settings, args).await?;
+
+    // 配置文件参数由 ytdlp_command 加在最前面
+    let command = ytdlp_command(&ytdlp_path);
+    let prefix: Vec<String> = command
+        .as_std()
+        .get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+
+    let full_args: Vec<String> = prefix.iter().cloned().chain(args).collect();
+    let (args, redacted) = redact_secrets(&full_args);
+
+    Ok(CommandPreview {
+        program: ytdlp_path.to_string_lossy().into_owned(),
+        extra_args_start: prefix.len() + extra_args.start,
+        extra_args_len: extra_args.len(),
+        args,
+        overridden,
+        redacted,
+    })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 扫描残留的临时文件
+ *
+ * 正在进行或暂停的下载所使用的文件、合并失败后等待重新合并的音视频流
+ * 不会出现在结果中
+ *
+ * @param dir - 要扫描的目录（通常为下载目录）
+ * @return Vec<OrphanedFile> - 残留文件及其大小、修改时间
+ ***************************************************************************/
+
+#[command]
+pub fn scan_orphaned_files(
+    manager: State<'_, DownloadManager>,
+    history: State<'_, HistoryStore>,
+    dir: String,
+) -> Result<Vec<OrphanedFile>, String> {
+    cleanup::scan_orphaned_files(Path::new(&dir), &cleanup_exclusions(&manager, &history))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 删除确认的残留临时文件
+ *
+ * @param paths - 用户确认删除的文件
+ * @return CleanupReport - 删除数量、释放空间和失败项
+ ***************************************************************************/
+
+#[command]
+pub fn clean_orphaned_files(
+    manager: State<'_, DownloadManager>,
+    history: State<'_, HistoryStore>,
+    paths: Vec<String>,
+) -> CleanupReport {
+    let report = cleanup::clean_orphaned_files(&paths, &cleanup_exclusions(&manager, &history));
+    info!(
+        "清理残留文件: 删除 {} 个，释放 {} 字节，失败 {} 个",
+        report.deleted,
+        report.reclaimed_bytes,
+        report.failed.len()
+    );
+    report
+}
+
+/// 清理残留文件时保留的文件：未结束下载的目标文件，以及等待重新合并的音视频流
+fn cleanup_exclusions(manager: &DownloadManager, history: &HistoryStore) -> Vec<String> {
+    let mut exclude = manager.active_output_paths();
+    exclude.extend(history.pending_merge_streams());
+    exclude
+}
+
+/***************************************************************************
+ * Tauri 命令 - 查询磁盘空间
+ *
+ * @param path - 下载目录（不存在时按最近的已存在上级目录查询）
+ * @return DiskSpace - 所在卷的可用空间和总空间
+ ***************************************************************************/
+
+#[command]
+pub fn get_free_space(path: String) -> Result<DiskSpace, String> {
+    disk_space(Path::new(&path))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 删除历史记录
+ *
+ * delete_files 为 true 时先把输出文件及附属文件移到回收站，全部成功后
+ * 才删除记录；有文件处理失败时保留记录，便于重试
+ *
+ * @param id - 下载任务ID
+ * @param delete_files - 是否同时删除文件
+ * @return DeleteHistoryResult - 各文件的处理结果及记录是否已删除
+ ***************************************************************************/
+
+#[command]
+pub async fn delete_history_entry(
+    app: AppHandle,
+    id: String,
+    delete_files: bool,
+) -> Result<DeleteHistoryResult, String> {
+    let lock = app.state::<FileLocks>().lock_for(&id);
+    let _guard = lock.lock().await;
+
+    let entry = app
+        .state::<HistoryStore>()
+        .get(&id)
+        .ok_or_else(|| format!("未找到历史记录: {}", id))?;
+
+    let files = if delete_files {
+        tauri::async_runtime::spawn_blocking(move || trash_entry_files(&entry))
+            .await
+            .map_err(|e| format!("删除文件任务失败: {}", e))?
+    } else {
+        Vec::new()
+    };
+
+    let entry_removed = files.iter().all(|file| file.success) && app.state::<HistoryStore>().remove(&id);
+    info!("删除历史记录 {}: 记录已删除 {}，处理文件 {} 个", id, entry_removed, files.len());
+
+    Ok(DeleteHistoryResult { entry_removed, files })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 重命名已完成的下载
+ *
+ * 附属文件（字幕、info.json 等）随主文件一起改名，历史记录中的路径同步更新
+ *
+ * @param id - 下载任务ID
+ * @param new_name - 新文件名（扩展名须与原文件一致，可省略）
+ * @param force - 目标已存在时是否覆盖
+ * @return RelocateResult - 更新后的记录及各文件的处理结果
+ ***************************************************************************/
+
+#[command]
+pub async fn rename_download(
+    app: AppHandle,
+    id: String,
+    new_name: String,
+    force: Option<bool>,
+) -> Result<RelocateResult, String> {
+    relocate_history_entry(app, id, force.unwrap_or(false), move |entry| plan_rename(entry, &new_name)).await
+}
+
+/***************************************************************************
+ * Tauri 命令 - 移动已完成的下载到其他目录
+ *
+ * @param id - 下载任务ID
+ * @param new_dir - 目标目录（必须存在且可写）
+ * @param force - 目标已存在时是否覆盖
+ * @return RelocateResult - 更新后的记录及各文件的处理结果
+ ***************************************************************************/
+
+#[command]
+pub async fn move_download(
+    app: AppHandle,
+    id: String,
+    new_dir: String,
+    force: Option<bool>,
+) -> Result<RelocateResult, String> {
+    relocate_history_entry(app, id, force.unwrap_or(false), move |entry| {
+        plan_move(entry, Path::new(&new_dir))
+    })
+    .await
+}
+
+/***************************************************************************
+ * 按计划重命名/移动记录关联的文件，并把成功的新路径写回历史记录
+ ***************************************************************************/
+
+async fn relocate_history_entry<F>(
+    app: AppHandle,
+    id: String,
+    force: bool,
+    plan: F,
+) -> Result<RelocateResult, String>
+where
+    F: FnOnce(&HistoryEntry) -> Result<Vec<(PathBuf, PathBuf)>, String> + Send + 'static,
+{
+    let lock = app.state::<FileLocks>().lock_for(&id);
+    let _guard = lock.lock().await;
+
+    let mut entry = app
+        .state::<HistoryStore>()
+        .get(&id)
+        .ok_or_else(|| format!("未找到历史记录: {}", id))?;
+
+    let task_entry = entry.clone();
+    let (plan, files) = tauri::async_runtime::spawn_blocking(move || {
+        let plan = plan(&task_entry)?;
+        let files = relocate(&plan, force)?;
+        Ok::<_, String>((plan, files))
+    })
+    .await
+    .map_err(|e| format!("文件操作任务失败: {}", e))??;
+
+    for ((source, target), result) in plan.iter().zip(&files) {
+        if !result.success {
+            continue;
+        }
+        let source = source.to_string_lossy();
+        let target = target.to_string_lossy().into_owned();
+        if entry.output_path.as_deref() == Some(&*source) {
+            entry.output_path = Some(target);
+        } else if let Some(sidecar) = entry.sidecar_files.iter_mut().find(|s| **s == *source) {
+            *sidecar = target;
+        }
+    }
+    app.state::<HistoryStore>().update(entry.clone());
+    info!("已更新下载文件位置 {}: {:?}", id, entry.output_path);
+
+    Ok(RelocateResult { entry, files })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 计算下载文件的校验和
+ *
+ * 参数可以是历史记录ID或文件路径。直接传入的路径必须是某条历史记录的输出文件，
+ * 或位于下载目录内，避免被用来读取任意文件。
+ * 对应历史记录时，结果会保存到该记录中。
+ *
+ * @param id_or_path - 历史记录ID或文件路径
+ * @param algorithm - sha256 或 md5
+ * @return Checksum - 计算结果
+ ***************************************************************************/
+
+#[command]
+pub async fn compute_checksum(
+    app: AppHandle,
+    id_or_path: String,
+    algorithm: ChecksumAlgorithm,
+) -> Result<Checksum, String> {
+    let history = app.state::<HistoryStore>();
+    let (entry_id, path) = match history
+        .get(&id_or_path)
+        .or_else(|| history.find_by_output_path(&id_or_path))
+    {
+        Some(entry) => {
+            let path = entry
+                .output_path
+                .ok_or_else(|| format!("该记录没有输出文件: {}", entry.id))?;
+            (Some(entry.id), path)
+        }
+        None => {
+            ensure_in_download_dir(&app, Path::new(&id_or_path))?;
+            (None, id_or_path)
+        }
+    };
+
+    let checksum = checksum_file(app.clone(), path, algorithm).await?;
+    if let Some(id) = entry_id {
+        history.set_checksum(&id, checksum.clone());
+    }
+    Ok(checksum)
+}
+
+/***************************************************************************
+ * 在阻塞线程中计算校验和，大文件发送 checksum-progress 事件
+ ***************************************************************************/
+
+async fn checksum_file(
+    app: AppHandle,
+    path: String,
+    algorithm: ChecksumAlgorithm,
+) -> Result<Checksum, String> {
+    let hex = tauri::async_runtime::spawn_blocking(move || {
+        hash_file(Path::new(&path), algorithm, |hashed_bytes, total_bytes| {
+            if total_bytes < CHECKSUM_PROGRESS_THRESHOLD {
+                return;
+            }
+            let progress = ChecksumProgress {
+                path: path.clone(),
+                hashed_bytes,
+                total_bytes,
+                percent: hashed_bytes as f64 / total_bytes as f64 * 100.0,
+            };
+            if let Err(e) = app.emit(events::CHECKSUM_PROGRESS, &progress) {
+                warn!("发送校验进度事件失败: {}", e);
+            }
+        })
+        .map_err(|e| format!("计算校验和失败: {} ({})", path, e))
+    })
+    .await
+    .map_err(|e| format!("校验任务失败: {}", e))??;
+
+    Ok(Checksum {
+        algorithm,
+        hex,
+        computed_at: unix_millis(),
+    })
+}
+
+/// 默认下载目录（设置中的下载目录，未设置时为系统下载目录）
+pub fn default_download_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    match app.state::<SettingsState>().get().download_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => app
+            .path()
+            .download_dir()
+            .map_err(|e| format!("无法确定下载目录: {}", e)),
+    }
+}
+
+/// 任务的下载目录（选项中指定的目录，未指定时为默认下载目录）
+pub fn download_destination(app: &AppHandle, options: &DownloadOptions) -> Result<PathBuf, String> {
+    match &options.output_dir {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => default_download_dir(app),
+    }
+}
+
+/// 确认路径位于下载目录内
+fn ensure_in_download_dir(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let root = default_download_dir(app)?;
+
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("文件不可访问: {} ({})", path.display(), e))?;
+    let root = root.canonicalize().unwrap_or(root);
+    if !path.starts_with(&root) {
+        return Err(format!("只能计算下载目录中的文件: {}", root.display()));
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 按标题搜索 YouTube
+ *
+ * 使用与 get_video_info 相同的伪装/Cookie 参数；没有结果时返回空列表
+ *
+ * @param query - 搜索内容
+ * @param count - 结果数（最多 MAX_SEARCH_RESULTS）
+ * @return Vec<SearchResult> - 按 YouTube 返回的顺序
+ ***************************************************************************/
+
+#[command]
+pub async fn search_videos(
+    impersonation: State<'_, ImpersonationState>,
+    query: String,
+    count: usize,
+) -> Result<Vec<SearchResult>, String> {
+    let target = search_target(&query, count)?;
+    info!("搜索视频: {}", target);
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    let impersonate = impersonation.get(&ytdlp_path).await.supports("chrome");
+
+    let output = ytdlp_command(&ytdlp_path)
+        .args(["--dump-json", "--no-warnings", "--flat-playlist"])
+        .args(request_args(impersonate, None))
+        .arg(&target)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 yt-dlp: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format_ytdlp_error(&stderr, &ytdlp_path));
+    }
+
+    let results = parse_search_results(&String::from_utf8_lossy(&output.stdout));
+    debug!("搜索返回 {} 条结果", results.len());
+    Ok(results)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 列出频道最近上传的视频
+ *
+ * 逐行解析 yt-dlp 输出，达到 limit 后结束进程，超大频道也不会长时间卡住
+ *
+ * @param channel_url - 频道/用户主页链接
+ * @param limit - 最多返回的视频数（最多 MAX_CHANNEL_VIDEOS）
+ * @param after_date - 只返回该日期（YYYYMMDD，含当天）之后上传的视频
+ * @return Vec<ChannelVideo> - 按频道列表顺序（通常最新的在前）
+ ***************************************************************************/
+
+#[command]
+pub async fn get_channel_videos(
+    impersonation: State<'_, ImpersonationState>,
+    channel_url: String,
+    limit: usize,
+    after_date: Option<String>,
+) -> Result<Vec<ChannelVideo>, String> {
+    list_channel_videos(&impersonation, &channel_url, limit, after_date.as_deref()).await
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取播放列表信息
+ *
+ * 逐行解析 yt-dlp 输出，每解析出一个条目发送 playlist-entry-parsed，
+ * 前端可以逐步填充列表，全部解析完成后返回完整结果。
+ * 获取过程中可通过 cancel_playlist_info 取消，yt-dlp 进程随之结束，
+ * 返回取消前已解析出的条目（cancelled 为 true）
+ *
+ * @param url - 播放列表链接
+ * @param fetch_id - 本次获取的ID（可选，未提供时自动生成；取消时使用）
+ * @return PlaylistInfo - 播放列表标题及全部条目
+ ***************************************************************************/
+
+#[command]
+pub async fn get_playlist_info(
+    app: AppHandle,
+    fetches: State<'_, PlaylistFetches>,
+    url: String,
+    fetch_id: Option<String>,
+) -> Result<PlaylistInfo, String> {
+    let url = normalize_url(&url).await?;
+    let fetch_id = fetch_id.unwrap_or_else(|| next_download_id().replacen("dl-", "fetch-", 1));
+    info!("获取播放列表信息: {} ({})", url, fetch_id);
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    let impersonate = app.state::<ImpersonationState>().get(&ytdlp_path).await.supports("chrome");
+    let mut args: Vec<String> = ["--dump-json", "--no-warnings", "--flat-playlist"]
+        .into_iter()
+        .chain(request_args(impersonate, None))
+        .map(String::from)
+        .collect();
+    args.extend(site_preset_args(&app, &ytdlp_path, &url, None).await);
+
+    let emit_app = app.clone();
+    let emit_id = fetch_id.clone();
+    let mut index = 0;
+    let on_entry = move |entry: &Value| {
+        let Some(video) = channel_video(entry) else {
+            return;
+        };
+        let parsed = PlaylistEntryParsed {
+            fetch_id: emit_id.clone(),
+            index,
+            entry: video,
+        };
+        index += 1;
+        if let Err(e) = emit_app.emit(events::PLAYLIST_ENTRY_PARSED, &parsed) {
+            debug!("发送播放列表条目事件失败: {}", e);
+        }
+    };
+
+    let cancelled = fetches.start(&fetch_id);
+    let result = collect_entries(&YtDlp::new(&ytdlp_path), &args, &url, cancelled, on_entry).await;
+    fetches.finish(&fetch_id);
+    let (entries, cancelled) = match result {
+        Ok(collected) => collected,
+        Err(YtdlpError::Failed { stderr }) => return Err(format_ytdlp_error(&stderr, &ytdlp_path)),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let title = entries
+        .iter()
+        .find_map(|entry| entry["playlist_title"].as_str().or_else(|| entry["playlist"].as_str()))
+        .map(String::from);
+    let entries: Vec<ChannelVideo> = entries.iter().filter_map(channel_video).collect();
+    if cancelled {
+        info!("播放列表获取已取消，已解析 {} 个条目: {}", entries.len(), fetch_id);
+    } else {
+        info!("播放列表共 {} 个条目: {}", entries.len(), fetch_id);
+    }
+
+    Ok(PlaylistInfo {
+        fetch_id,
+        title,
+        entries,
+        cancelled,
+    })
+}
+
+/***************************************************************************
+ * 逐个获取条目，取消时返回取消前已解析的条目
+ *
+ * @param cancelled - 置位后结束获取
+ * @return (Vec<Value>, bool) - 条目，以及是否被取消
+ ***************************************************************************/
+
+async fn collect_entries<B, F>(
+    backend: &B,
+    args: &[String],
+    url: &str,
+    cancelled: Arc<AtomicBool>,
+    mut on_entry: F,
+) -> Result<(Vec<Value>, bool), YtdlpError>
+where
+    B: MediaBackend,
+    F: FnMut(&Value) + Send,
+{
+    // 已解析的条目另存一份，取消时 fetch_entries 不返回条目
+    let mut received = Vec::new();
+    let result = backend
+        .fetch_entries(args, url, cancelled, |entry: &Value| {
+            received.push(entry.clone());
+            on_entry(entry);
+        })
+        .await;
+    match result {
+        Ok(entries) => Ok((entries, false)),
+        Err(YtdlpError::Cancelled) => Ok((received, true)),
+        Err(e) => Err(e),
+    }
+}
+
+/***************************************************************************
+ * Tauri 命令 - 取消正在进行的播放列表信息获取
+ *
+ * @param fetch_id - get_playlist_info 使用的ID
+ ***************************************************************************/
+
+#[command]
+pub fn cancel_playlist_info(fetches: State<'_, PlaylistFetches>, fetch_id: String) -> Result<(), String> {
+    if !fetches.cancel(&fetch_id) {
+        return Err(format!("未找到进行中的播放列表获取: {}", fetch_id));
+    }
+    info!("已取消播放列表获取: {}", fetch_id);
+    Ok(())
+}
+
+/***************************************************************************
+ * 列出频道视频（get_channel_videos 与订阅检查共用）
+ ***************************************************************************/
+
+pub async fn list_channel_videos(
+    impersonation: &ImpersonationState,
+    channel_url: &str,
+    limit: usize,
+    after_date: Option<&str>,
+) -> Result<Vec<ChannelVideo>, String> {
+    list_videos(impersonation, channel_url, Some(limit.clamp(1, MAX_CHANNEL_VIDEOS)), after_date).await
+}
+
+/***************************************************************************
+ * 列出频道或播放列表的视频
+ *
+ * @param limit - 最多返回的视频数；None 不限制（下载整个播放列表时）
+ * @param after_date - 只返回该日期（YYYYMMDD，含当天）之后上传的视频
+ ***************************************************************************/
+
+async fn list_videos(
+    impersonation: &ImpersonationState,
+    url: &str,
+    limit: Option<usize>,
+    after_date: Option<&str>,
+) -> Result<Vec<ChannelVideo>, String> {
+    let url = normalize_url(url).await?;
+    let after_date = after_date
+        .filter(|d| !d.trim().is_empty())
+        .map(validate_date)
+        .transpose()?;
+    info!("获取视频列表: {} (最多 {:?} 个, 起始日期 {:?})", url, limit, after_date);
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    let impersonate = impersonation.get(&ytdlp_path).await.supports("chrome");
+
+    let mut command = ytdlp_command(&ytdlp_path);
+    command
+        .args(["--dump-json", "--no-warnings", "--flat-playlist"]);
+    if let Some(limit) = limit {
+        command.args(["--playlist-end", &limit.to_string()]);
+    }
+    if let Some(date) = &after_date {
+        command.args(["--dateafter", date]);
+    }
+    let mut child = command
+        .args(request_args(impersonate, None))
+        .arg(&url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("无法执行 yt-dlp: {}", e))?;
+
+    // stderr 单独读取，避免管道写满阻塞 yt-dlp
+    let stderr = child.stderr.take().ok_or("无法读取 yt-dlp 错误输出")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut lines = lossy_lines(BufReader::new(stderr));
+        let mut output = String::new();
+        while let Ok(Some(line)) = lines.next_line().await {
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output
+    });
+
+    let stdout = child.stdout.take().ok_or("无法读取 yt-dlp 输出")?;
+    let mut lines = lossy_lines(BufReader::new(stdout));
+    let mut videos = Vec::new();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Some(video) = parse_channel_entry(&line) else {
+            continue;
+        };
+        if is_after(&video, after_date.as_deref()) {
+            videos.push(video);
+        }
+        if let Some(limit) = limit.filter(|&limit| videos.len() >= limit) {
+            debug!("已达到数量上限 {}，结束 yt-dlp", limit);
+            let _ = child.kill().await;
+            return Ok(videos);
+        }
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("等待 yt-dlp 失败: {}", e))?;
+    if !status.success() && videos.is_empty() {
+        let stderr = stderr_task.await.unwrap_or_default();
+        return Err(format_ytdlp_error(&stderr, &ytdlp_path));
+    }
+
+    debug!("频道共返回 {} 个视频", videos.len());
+    Ok(videos)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 订阅频道
+ *
+ * 首次检查只记录频道现有的视频，之后上传的视频才会触发 new-videos-found
+ *
+ * @param channel_url - 频道/用户主页链接
+ * @param options - 自动下载时使用的下载选项
+ * @param auto_download - 发现新视频时是否自动加入下载队列
+ * @return Subscription - 新建的订阅
+ ***************************************************************************/
+
+#[command]
+pub async fn add_subscription(
+    subscriptions: State<'_, SubscriptionStore>,
+    channel_url: String,
+    options: DownloadOptions,
+    auto_download: bool,
+) -> Result<Subscription, String> {
+    let channel_url = normalize_url(&channel_url).await?;
+    let subscription = subscriptions.add(channel_url, options, auto_download)?;
+    info!("已添加订阅: {} ({})", subscription.channel_url, subscription.id);
+    Ok(subscription)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 取消订阅
+ ***************************************************************************/
+
+#[command]
+pub fn remove_subscription(subscriptions: State<'_, SubscriptionStore>, id: String) -> Result<(), String> {
+    if !subscriptions.remove(&id) {
+        return Err(format!("未找到订阅: {}", id));
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取全部订阅
+ ***************************************************************************/
+
+#[command]
+pub fn list_subscriptions(subscriptions: State<'_, SubscriptionStore>) -> Vec<Subscription> {
+    subscriptions.list()
+}
+
+/***************************************************************************
+ * Tauri 命令 - 下载故事板（预览帧拼图）
+ *
+ * 选择分辨率最高的故事板格式下载，并从 MHTML 中取出拼图图片，
+ * 图片全部取出后删除 MHTML 文件
+ *
+ * @param url - 视频URL
+ * @param output_dir - 保存目录
+ * @return StoryboardResult - 使用的格式及保存的图片
+ ***************************************************************************/
+
+#[command]
+pub async fn download_storyboard(
+    impersonation: State<'_, ImpersonationState>,
+    url: String,
+    output_dir: String,
+) -> Result<StoryboardResult, String> {
+    let url = normalize_url(&url).await?;
+    validate_writable_dir(Path::new(&output_dir)).map_err(|e| format!("保存目录无效: {}", e))?;
+
+    let ytdlp_path = get_ytdlp_path().await?;
+    let impersonate = impersonation.get(&ytdlp_path).await.supports("chrome");
+
+    let formats = parse_formats(&fetch_video_json(&ytdlp_path, &url, impersonate, None, false, &[]).await?);
+    let storyboard = formats
+        .into_iter()
+        .filter(|f| is_storyboard_format(&f.format_id, &f.ext))
+        .max_by_key(|f| f.width.unwrap_or(0) * f.height.unwrap_or(0))
+        .ok_or_else(|| "该视频没有故事板".to_string())?;
+    info!("下载故事板: {} ({})", url, storyboard.format_id);
+
+    let template = Path::new(&output_dir).join("%(title)s.storyboard.%(ext)s");
+    let output = ytdlp_command(&ytdlp_path)
+        .args(["--no-warnings", "-f", &storyboard.format_id])
+        .args(["--print", "after_move:filepath"])
+        .args(request_args(impersonate, None))
+        .arg("-o")
+        .arg(&template)
+        .arg(&url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 yt-dlp: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format_ytdlp_error(&stderr, &ytdlp_path));
+    }
+
+    let mhtml = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .ok_or_else(|| "无法确定故事板文件路径".to_string())?;
+
+    let images = tauri::async_runtime::spawn_blocking({
+        let mhtml = mhtml.clone();
+        move || extract_mhtml_images(&mhtml)
+    })
+    .await
+    .map_err(|e| format!("提取故事板任务失败: {}", e))?
+    .map_err(|e| format!("提取故事板图片失败: {} ({})", mhtml.display(), e))?;
+
+    if images.is_empty() {
+        return Err(format!("故事板文件中没有图片: {}", mhtml.display()));
+    }
+    if let Err(e) = std::fs::remove_file(&mhtml) {
+        warn!("删除故事板文件失败: {} ({})", mhtml.display(), e);
+    }
+
+    debug!("故事板共 {} 张图片", images.len());
+    Ok(StoryboardResult {
+        format_id: storyboard.format_id,
+        width: storyboard.width,
+        height: storyboard.height,
+        images: images
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect(),
+    })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取全部质量预设（内置预设在前）
+ ***************************************************************************/
+
+#[command]
+pub fn list_presets(settings: State<'_, SettingsState>) -> Vec<Preset> {
+    all_presets(&settings.get().presets)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 保存质量预设（同名的用户预设会被替换）
+ *
+ * @param name - 预设名称（不能与内置预设重名）
+ * @param options - 预设包含的下载选项
+ ***************************************************************************/
+
+#[command]
+pub fn save_preset(
+    settings: State<'_, SettingsState>,
+    name: String,
+    options: DownloadOptions,
+) -> Result<Preset, String> {
+    let name = name.trim().to_string();
+    let preset = Preset {
+        name: name.clone(),
+        options,
+        builtin: false,
+    };
+
+    let mut new_settings = settings.get();
+    match new_settings.presets.iter_mut().find(|p| p.name == name) {
+        Some(existing) => *existing = preset.clone(),
+        None => new_settings.presets.push(preset.clone()),
+    }
+    settings.update(new_settings)?;
+
+    info!("已保存预设: {}", name);
+    Ok(preset)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 删除用户预设（内置预设不能删除）
+ ***************************************************************************/
+
+#[command]
+pub fn delete_preset(settings: State<'_, SettingsState>, name: String) -> Result<(), String> {
+    if is_builtin_name(&name) {
+        return Err(format!("不能删除内置预设: {}", name));
+    }
+
+    let mut new_settings = settings.get();
+    let before = new_settings.presets.len();
+    new_settings.presets.retain(|p| p.name != name);
+    if new_settings.presets.len() == before {
+        return Err(format!("未找到预设: {}", name));
+    }
+    settings.update(new_settings)
+}
+
+/// 按名称查找预设（内置预设不区分大小写）
+fn find_preset(settings: &Settings, name: &str) -> Result<Preset, String> {
+    all_presets(&settings.presets)
+        .into_iter()
+        .find(|p| p.name == name || (p.builtin && p.name.eq_ignore_ascii_case(name)))
+        .ok_or_else(|| format!("未找到预设: {}", name))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 并行下载播放列表
+ *
+ * 列出播放列表的全部条目，每个条目作为单独的下载任务加入队列，
+ * 分组内按 options.playlist_concurrency 个任务同时下载（未指定时为 1，即按顺序下载）
+ *
+ * @param url - 播放列表链接
+ * @param options - 应用于每个条目的下载选项
+ * @return PlaylistEnqueued - 分组ID、条目及对应的下载任务ID
+ ***************************************************************************/
+
+#[command]
+pub async fn download_playlist(
+    app: AppHandle,
+    url: String,
+    options: DownloadOptions,
+) -> Result<PlaylistEnqueued, String> {
+    enqueue_playlist(&app, &url, options, &HashSet::new()).await
+}
+
+/***************************************************************************
+ * Tauri 命令 - 只下载播放列表中缺少的视频
+ *
+ * 读取 options.archive_file 指定的下载存档，已记录的条目不加入队列，
+ * 其余条目与 download_playlist 一样按分组下载，完成后由 yt-dlp 记入存档。
+ * 重复执行时只会下载新增或上次未完成的视频
+ *
+ * @param url - 播放列表链接
+ * @param options - 应用于每个条目的下载选项（必须指定 archive_file）
+ * @return PlaylistEnqueued - 加入队列的条目及跳过的条目
+ ***************************************************************************/
+
+#[command]
+pub async fn download_playlist_missing(
+    app: AppHandle,
+    url: String,
+    options: DownloadOptions,
+) -> Result<PlaylistEnqueued, String> {
+    let archive = options
+        .archive_file
+        .as_deref()
+        .filter(|a| !a.is_empty())
+        .ok_or("未指定下载存档文件（archive_file）")?;
+    let archived = read_archive_ids(Path::new(archive))?;
+    enqueue_playlist(&app, &url, options, &archived).await
+}
+
+/***************************************************************************
+ * Tauri 命令 - 只下载频道上次运行以来的新视频
+ *
+ * 列出频道最新的 limit 个视频（--playlist-end），运行过时附加 --dateafter
+ * （提前一天，上传日期只精确到天），再排除下载存档中已记录的视频，
+ * 其余与 download_playlist_missing 一样按分组下载、完成后记入存档。
+ * 加入队列后在历史记录中保存本次运行时间，重复执行时只会下载新上传的视频
+ *
+ * @param url - 频道链接
+ * @param options - 应用于每个视频的下载选项（必须指定 archive_file）
+ * @param limit - 最多检查的最新视频数（默认 CHANNEL_NEW_LIMIT，最多 MAX_CHANNEL_VIDEOS）
+ * @return ChannelNewVideos - 上次运行时间及加入队列的新视频
+ ***************************************************************************/
+
+#[command]
+pub async fn download_channel_new(
+    app: AppHandle,
+    url: String,
+    options: DownloadOptions,
+    limit: Option<usize>,
+) -> Result<ChannelNewVideos, String> {
+    let archive = options
+        .archive_file
+        .as_deref()
+        .filter(|a| !a.is_empty())
+        .ok_or("未指定下载存档文件（archive_file）")?;
+    let archived = read_archive_ids(Path::new(archive))?;
+
+    let url = normalize_url(&url).await?;
+    let history = app.state::<HistoryStore>();
+    let since = history.channel_last_run(&url);
+    let after_date = since.map(|at| date_of(at.saturating_sub(24 * 60 * 60 * 1000)));
+    let run_at = unix_millis();
+
+    let impersonation = app.state::<ImpersonationState>();
+    let limit = limit.unwrap_or(CHANNEL_NEW_LIMIT);
+    let listed = list_channel_videos(&impersonation, &url, limit, after_date.as_deref()).await?;
+    let listed = listed.into_iter().filter(|video| is_after(video, after_date.as_deref())).collect();
+    let enqueued = enqueue_entries(&app, listed, options, &archived)?;
+    history.record_channel_run(&url, run_at);
+
+    info!("频道新视频: {} ({} 个，上次运行 {:?})", url, enqueued.entries.len(), after_date);
+    Ok(ChannelNewVideos {
+        since,
+        new_count: enqueued.entries.len(),
+        enqueued,
+    })
+}
+
+/// 列出播放列表的全部条目（不受 MAX_CHANNEL_VIDEOS 限制），不在 archived 中的条目作为一个分组加入队列
+async fn enqueue_playlist(
+    app: &AppHandle,
+    url: &str,
+    options: DownloadOptions,
+    archived: &HashSet<String>,
+) -> Result<PlaylistEnqueued, String> {
+    let impersonation = app.state::<ImpersonationState>();
+    let listed = list_videos(&impersonation, url, None, None).await?;
+    if listed.is_empty() {
+        return Err("播放列表中没有可下载的视频".to_string());
+    }
+    enqueue_entries(app, listed, options, archived)
+}
+
+/// 不在 archived 中、时长符合选项的条目作为一个分组加入队列（没有这样的条目时分组为空）
+fn enqueue_entries(
+    app: &AppHandle,
+    listed: Vec<ChannelVideo>,
+    options: DownloadOptions,
+    archived: &HashSet<String>,
+) -> Result<PlaylistEnqueued, String> {
+    let (skipped, entries): (Vec<ChannelVideo>, Vec<ChannelVideo>) =
+        listed.into_iter().partition(|entry| archived.contains(&entry.id));
+    let (entries, filtered): (Vec<ChannelVideo>, Vec<ChannelVideo>) =
+        entries.into_iter().partition(|entry| duration_in_range(&options, entry.duration));
+    if entries.is_empty() && !filtered.is_empty() {
+        return Err(format!("播放列表中的 {} 个视频时长都不在指定范围内", filtered.len()));
+    }
+
+    let concurrency = options.playlist_concurrency.unwrap_or(1).clamp(1, MAX_GROUP_CONCURRENCY);
+    let items = entries
+        .iter()
+        .map(|entry| (entry.url.clone(), entry.title.clone()))
+        .collect();
+    let manager = app.state::<DownloadManager>();
+    let queue = app.state::<DownloadQueue>();
+    let (playlist_id, download_ids) = queue.enqueue_group(&manager, items, options, concurrency, skipped.len());
+    info!(
+        "播放列表已加入队列: {} ({} 个视频，跳过 {} 个，时长不符 {} 个，并发 {})",
+        playlist_id,
+        entries.len(),
+        skipped.len(),
+        filtered.len(),
+        concurrency
+    );
+
+    Ok(PlaylistEnqueued {
+        playlist_id,
+        entries,
+        download_ids,
+        concurrency,
+        skipped,
+        filtered,
+    })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取播放列表的整体下载进度
+ *
+ * @param playlist_id - download_playlist 返回的分组ID
+ * @return QueueProgress - 分组内全部任务的整体完成度和剩余时间
+ ***************************************************************************/
+
+#[command]
+pub fn get_playlist_progress(
+    queue: State<'_, DownloadQueue>,
+    manager: State<'_, DownloadManager>,
+    playlist_id: String,
+) -> Result<QueueProgress, String> {
+    let ids = queue
+        .group_download_ids(&playlist_id)
+        .ok_or_else(|| format!("未找到播放列表任务: {}", playlist_id))?;
+    Ok(compute_queue_progress(&manager.snapshots(&ids)))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 检查下载文件是否完整
+ *
+ * @param file_path - 媒体文件路径
+ * @param expected_duration - 预期时长（秒，通常为 VideoInfo.duration），用于发现截断
+ * @return MediaVerification - 判定结果（Valid / Truncated / Corrupt / Unknown）及流信息
+ ***************************************************************************/
+
+#[command]
+pub async fn verify_download(
+    file_path: String,
+    expected_duration: Option<f64>,
+) -> Result<MediaVerification, String> {
+    let path = Path::new(&file_path);
+    if !path.is_file() {
+        return Err(format!("文件不存在: {}", file_path));
+    }
+
+    let verification = verify_media(path, expected_duration).await;
+    info!("文件校验: {} -> {:?}", file_path, verification.verdict);
+    Ok(verification)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 只重新合并合并失败的下载
+ *
+ * 下载完成但 ffmpeg 合并失败（磁盘已满、编码不兼容等）时，历史记录中保留了
+ * 各个流和合并目标；直接用 ffmpeg 重新合并，不重新下载。成功后删除各个流
+ * （暂存模式下连同已空的暂存目录），历史记录改为 Completed 并校验输出文件
+ *
+ * @param id - 下载任务ID
+ * @return HistoryEntry - 更新后的历史记录
+ ***************************************************************************/
+
+#[command]
+pub async fn retry_postprocess(
+    history: State<'_, HistoryStore>,
+    manager: State<'_, DownloadManager>,
+    id: String,
+) -> Result<HistoryEntry, String> {
+    let entry = history.get(&id).ok_or_else(|| format!("未找到下载记录: {}", id))?;
+    let pending = entry
+        .pending_merge
+        .clone()
+        .ok_or_else(|| format!("该下载没有等待重新合并的文件: {}", id))?;
+    if let Some(missing) = pending.streams.iter().find(|path| !Path::new(path).is_file()) {
+        return Err(format!("保留的音视频文件已不存在: {}", missing));
+    }
+    let ffmpeg = find_ffmpeg().ok_or("未找到 ffmpeg，请先安装 ffmpeg")?;
+
+    info!(download_id = %id, "重新合并: {:?} -> {}", pending.streams, pending.target);
+    manager.set_status(&id, DownloadStatus::PostProcessing, None);
+    if let Err(error) = merge_streams(&ffmpeg, &pending.streams, Path::new(&pending.target)).await {
+        warn!(download_id = %id, "{}", error);
+        manager.set_status(&id, DownloadStatus::Failed, Some(error.clone()));
+        history.update(HistoryEntry {
+            error: Some(error.clone()),
+            ..entry
+        });
+        return Err(error);
+    }
+
+    for stream in &pending.streams {
+        if let Err(e) = std::fs::remove_file(stream) {
+            warn!(download_id = %id, "删除已合并的流失败: {} ({})", stream, e);
+        }
+    }
+    // 暂存模式下各个流留在暂存目录中，合并后目录已空
+    if let Some(staging) = pending.streams.first().and_then(|stream| staging_root(Path::new(stream), &id)) {
+        remove_staging_if_empty(&staging);
+    }
+
+    let verification = verify_output(Some(&pending.target), None).await;
+    let entry = HistoryEntry {
+        status: DownloadStatus::Completed,
+        output_path: Some(pending.target),
+        error: None,
+        finished_at: unix_millis(),
+        verified: Some(verification.verified),
+        suspect: !verification.verified,
+        verification_issues: verification.issues,
+        pending_merge: None,
+        ..entry
+    };
+    info!(download_id = %id, "重新合并完成: {:?}", entry.output_path);
+    manager.set_status(&id, DownloadStatus::Completed, None);
+    history.update(entry.clone());
+    Ok(entry)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 列出 yt-dlp 支持的站点
+ *
+ * @param filter - 筛选关键字（如 "bili"），为空时返回全部
+ * @return Vec<String> - 匹配的提取器名称
+ ***************************************************************************/
+
+#[command]
+pub async fn list_supported_sites(
+    extractors: State<'_, ExtractorState>,
+    filter: Option<String>,
+) -> Result<Vec<String>, String> {
+    let ytdlp_path = get_ytdlp_path().await?;
+    let all = extractors.get(&ytdlp_path).await?;
+    Ok(filter_extractors(&all, filter.as_deref().unwrap_or_default()))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 快速检查链接是否受支持
+ *
+ * 以 --simulate --quiet 只解析不下载（播放列表只解析列表本身），
+ * 超过 URL_PROBE_TIMEOUT 时结束 yt-dlp 并返回 timed_out
+ *
+ * @param url - 待检查的链接
+ * @return UrlSupport - 是否受支持及匹配的提取器
+ ***************************************************************************/
+
+#[command]
+pub async fn check_url_supported(
+    impersonation: State<'_, ImpersonationState>,
+    url: String,
+) -> Result<UrlSupport, String> {
+    let url = normalize_url(&url).await?;
+    let ytdlp_path = get_ytdlp_path().await?;
+    let impersonate = impersonation.get(&ytdlp_path).await.supports("chrome");
+
+    let output = ytdlp_command(&ytdlp_path)
+        .args(["--simulate", "--quiet", "--no-warnings", "--flat-playlist"])
+        .args(["--playlist-items", "1", "--print", "%(extractor_key)s"])
+        .args(request_args(impersonate, None))
+        .arg(&url)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+
+    let output = match tokio::time::timeout(URL_PROBE_TIMEOUT, output).await {
+        Ok(output) => output.map_err(|e| format!("无法执行 yt-dlp: {}", e))?,
+        Err(_) => {
+            debug!("检查链接超时: {}", url);
+            return Ok(UrlSupport {
+                supported: false,
+                extractor: None,
+                generic: false,
+                timed_out: true,
+                error: None,
+            });
+        }
+    };
+
+    let extractor = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && *line != "NA")
+        .map(String::from);
+    let supported = output.status.success();
+    let error = (!supported).then(|| String::from_utf8_lossy(&output.stderr).trim().to_string());
+
+    info!("检查链接: {} -> {:?} ({})", url, extractor, supported);
+    Ok(UrlSupport {
+        supported,
+        generic: extractor.as_deref() == Some(GENERIC_EXTRACTOR),
+        extractor,
+        timed_out: false,
+        error,
+    })
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取播放列表的下载清单路径
+ *
+ * @param playlist_id - download_playlist 返回的分组ID
+ * @return Option<String> - 清单路径（尚未全部结束或未开启清单时为 None）
+ ***************************************************************************/
+
+#[command]
+pub fn get_playlist_manifest(queue: State<'_, DownloadQueue>, playlist_id: String) -> Result<Option<String>, String> {
+    queue
+        .group_manifest_path(&playlist_id)
+        .ok_or_else(|| format!("未找到播放列表任务: {}", playlist_id))
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取站点预设
+ *
+ * @return BTreeMap<String, SitePreset> - 站点名 → 预设
+ ***************************************************************************/
+
+#[command]
+pub fn get_site_presets(settings: State<'_, SettingsState>) -> BTreeMap<String, SitePreset> {
+    settings.get().site_presets
+}
+
+/***************************************************************************
+ * Tauri 命令 - 保存站点预设（整体替换）
+ *
+ * @param presets - 站点名 → 预设；传入空表即清空全部预设
+ ***************************************************************************/
+
+#[command]
+pub fn set_site_presets(
+    settings: State<'_, SettingsState>,
+    presets: BTreeMap<String, SitePreset>,
+) -> Result<(), String> {
+    validate_site_presets(&presets)?;
+    let mut new_settings = settings.get();
+    new_settings.site_presets = presets;
+    settings.update(new_settings)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 运行环境自检
+ *
+ * 依次检查 yt-dlp（能否运行及版本）、ffmpeg、默认下载目录的写权限、
+ * 浏览器伪装（curl_cffi）和网络连通性，每项给出修复建议。
+ * 前三项也在启动时检查（见 spawn_startup_health_check）
+ *
+ * @return DiagnosticsReport - 各项检查结果
+ ***************************************************************************/
+
+#[command]
+pub async fn run_diagnostics(app: AppHandle) -> Result<DiagnosticsReport, String> {
+    let mut checks = dependency_checks(&app).await;
+    let ytdlp_path = get_ytdlp_path().await.ok();
+
+    checks.push(match &ytdlp_path {
+        None => DiagnosticCheck::warn("impersonation", "未找到 yt-dlp，跳过检查", "请先安装 yt-dlp"),
+        Some(path) => {
+            let support = app.state::<ImpersonationState>().refresh(path).await;
+            if support.supported {
+                let targets = support.targets.iter().filter(|t| t.available).count();
+                DiagnosticCheck::pass("impersonation", format!("{} 个伪装目标可用", targets))
+            } else {
+                DiagnosticCheck::warn(
+                    "impersonation",
+                    "浏览器伪装不可用（缺少 curl_cffi），部分网站可能拒绝请求",
+                    support
+                        .install
+                        .fix_commands
+                        .first()
+                        .map(|command| format!("请运行: {}", command))
+                        .unwrap_or_else(|| "请为 yt-dlp 安装 curl_cffi".to_string()),
+                )
+            }
+        }
+    });
+
+    checks.push(if is_online().await {
+        DiagnosticCheck::pass("network", "网络连接正常")
+    } else {
+        DiagnosticCheck::fail(
+            "network",
+            "无法访问测试地址",
+            "请检查网络连接；只能通过代理访问外网时请在设置中填写代理地址",
+        )
+    });
+
+    let report = DiagnosticsReport::new(checks);
+    info!("环境自检完成: {}", if report.healthy { "正常" } else { "存在问题" });
+    Ok(report)
+}
+
+/***************************************************************************
+ * 依赖检查：yt-dlp、ffmpeg 和默认下载目录
+ *
+ * 缺少 yt-dlp/ffmpeg 时附带当前平台的安装命令
+ ***************************************************************************/
+
+async fn dependency_checks(app: &AppHandle) -> Vec<DiagnosticCheck> {
+    let mut checks = Vec::new();
+
+    checks.push(match get_ytdlp_path().await {
+        Err(_) => DiagnosticCheck::fail(
+            "ytdlp",
+            "未找到 yt-dlp",
+            "请安装 yt-dlp（brew install yt-dlp 或 pip install yt-dlp），或在设置中指定 yt-dlp 路径",
+        )
+        .with_install(ytdlp_install_command()),
+        Ok(path) => match YtDlp::new(&path).version().await {
+            Some(version) => DiagnosticCheck::pass(
+                "ytdlp",
+                format!("{} ({}，{})", version, path.display(), ytdlp_path_source(&path)),
+            ),
+            None => DiagnosticCheck::fail(
+                "ytdlp",
+                format!("yt-dlp 无法运行: {}", path.display()),
+                "请重新安装 yt-dlp，或运行 yt-dlp -U 更新到最新版本",
+            ),
+        },
+    });
+
+    checks.push(match find_ffmpeg() {
+        Some(path) => DiagnosticCheck::pass("ffmpeg", path.display().to_string()),
+        None => DiagnosticCheck::warn(
+            "ffmpeg",
+            "未找到 ffmpeg，无法合并音视频、截取片段或转换格式",
+            "请安装 ffmpeg（brew install ffmpeg 或从 ffmpeg.org 下载）",
+        )
+        .with_install(ffmpeg_install_command()),
+    });
+
+    checks.push(match default_download_dir(app) {
+        Err(e) => DiagnosticCheck::fail("output_dir", e, "请在设置中指定下载目录"),
+        Ok(dir) => match validate_writable_dir(&dir) {
+            Ok(()) => DiagnosticCheck::pass("output_dir", dir.display().to_string()),
+            Err(e) => DiagnosticCheck::fail(
+                "output_dir",
+                format!("{}: {}", dir.display(), e),
+                "请检查目录权限，或在设置中更换下载目录",
+            ),
+        },
+    });
+
+    checks
+}
+
+/***************************************************************************
+ * 启动时检查依赖并发送 startup-health 事件
+ *
+ * 在后台任务中运行，不阻塞窗口创建；前端据此在首次启动时显示设置页面。
+ * 前端加载晚于检查完成而错过事件时，可调用 run_diagnostics 重新检查
+ ***************************************************************************/
+
+pub fn spawn_startup_health_check(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let report = DiagnosticsReport::new(dependency_checks(&app).await);
+        info!("启动检查完成: {}", if report.healthy { "正常" } else { "存在问题" });
+        if let Err(e) = app.emit(events::STARTUP_HEALTH, &report) {
+            warn!("发送启动检查事件失败: {}", e);
+        }
+    });
+}
+
+/***************************************************************************
+ * Tauri 命令 - 获取应用版本和构建信息
+ *
+ * @return AppInfo - 应用版本、git 提交、构建时间、操作系统和架构
+ ***************************************************************************/
+
+#[command]
+pub fn get_app_info(app: AppHandle) -> AppInfo {
+    AppInfo::new(app.package_info().version.to_string())
+}
+
+/***************************************************************************
+ * Tauri 命令 - 检查应用是否有新版本
+ *
+ * 查询 GitHub 上的最新发布（使用设置中的代理），与 yt-dlp 的更新无关；
+ * 网络错误等情况返回 Unknown 状态，不返回错误
+ *
+ * @return AppUpdate - 检查结果、最新版本及发布说明链接
+ ***************************************************************************/
+
+#[command]
+pub async fn check_app_update(app: AppHandle) -> Result<AppUpdate, String> {
+    let current = app.package_info().version.to_string();
+    let update = check_update(&current).await;
+    info!("应用更新检查: {:?}", update.status);
+    Ok(update)
+}
+
+/***************************************************************************
+ * Tauri 命令 - 检查 Cookie 能否登录
+ *
+ * 用于排查需要登录的下载失败：先确认浏览器 Cookie 或 Cookie 文件能否读取，
+ * 再访问需要登录的页面确认是否处于登录状态
+ *
+ * @param source - 浏览器名（如 "chrome"、"firefox:Profile 1"）或 Cookie 文件路径
+ * @return CookieCheck - 使用的 Cookie 来源、能否读取、是否已登录
+ ***************************************************************************/
+
+#[command]
+pub async fn check_cookies(source: String) -> Result<CookieCheck, String> {
+    let source = CookieSource::parse(&source)?;
+    let ytdlp_path = get_ytdlp_path().await?;
+    cookies::check_cookies(&ytdlp_path, source).await
+}
+
+/***************************************************************************
+ * Tauri 命令 - 检查下载目录是否可用
+ *
+ * 目录不存在（如移动硬盘已拔出）或不可写时返回原因；可用时唤醒
+ * 因下载目录不可用而等待的队列，不必等到下一次定时检查
+ *
+ * @param path - 要检查的目录，None 检查默认下载目录
+ * @return DestinationCheck - 目录及是否可用
+ ***************************************************************************/
+
+#[command]
+pub fn validate_download_dir(app: AppHandle, path: Option<String>) -> Result<DestinationCheck, String> {
+    let dir = match path.filter(|p| !p.is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => default_download_dir(&app)?,
+    };
+    let error = validate_writable_dir(&dir).err();
+    if error.is_none() {
+        app.state::<DownloadQueue>().destination_changed();
+    }
+
+    Ok(DestinationCheck {
+        path: dir.to_string_lossy().into_owned(),
+        available: error.is_none(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ytdlp::mock::MockBackend;
+    use serde_json::json;
+
+    /// 以 mock 后端的 fetch_info 执行 fetch_with_retries，返回结果及每次调用的参数
+    async fn fetch_info_with(
+        results: Vec<Result<Value, YtdlpError>>,
+    ) -> (Result<(Value, bool), String>, Vec<Vec<String>>) {
+        let backend = MockBackend {
+            infos: Mutex::new(results.into()),
+            ..Default::default()
+        };
+        let url = "https://www.youtube.com/watch?v=abc";
+        let result = fetch_with_retries(url, &["--no-playlist"], |args| {
+            let backend = &backend;
+            async move {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                backend.fetch_info(&args, url).await.map_err(|e| e.to_string())
+            }
+        })
+        .await;
+        (result, backend.calls())
+    }
+
+    fn failed(stderr: &str) -> Result<Value, YtdlpError> {
+        Err(YtdlpError::Failed {
+            stderr: stderr.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn bot_detection_retries_without_cache() {
+        let (result, calls) = fetch_info_with(vec![
+            failed("ERROR: [youtube] abc: Sign in to confirm you're not a bot"),
+            Ok(json!({"id": "abc"})),
+        ])
+        .await;
+
+        assert_eq!(result.unwrap(), (json!({"id": "abc"}), true));
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1][..2], ["--no-playlist", "--no-cache-dir"]);
+    }
+
+    #[tokio::test]
+    async fn geo_block_retries_with_reported_country() {
+        let (result, calls) = fetch_info_with(vec![
+            failed(
+                "ERROR: [youtube] abc: The uploader has not made this video available in your country. \
+                 This video is only available in US, CA",
+            ),
+            Ok(json!({"id": "abc"})),
+        ])
+        .await;
+
+        assert_eq!(result.unwrap(), (json!({"id": "abc"}), false));
+        assert_eq!(calls[1][..3], ["--no-playlist", "--geo-bypass-country", "US"]);
+    }
+
+    #[tokio::test]
+    async fn retries_only_once() {
+        let bot = "ERROR: [youtube] abc: Sign in to confirm you're not a bot";
+        let (result, calls) = fetch_info_with(vec![failed(bot), failed(bot), Ok(json!({}))]).await;
+
+        assert!(result.unwrap_err().contains("已刷新浏览器 Cookie 重试一次"));
+        assert_eq!(calls.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let (result, calls) = fetch_info_with(vec![failed("ERROR: [youtube] abc: Video unavailable")]).await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.len(), 1);
+    }
+
+    fn playlist_backend() -> MockBackend {
+        MockBackend {
+            entries: (0..5).map(|i| json!({"id": format!("v{}", i)})).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn collect_entries_returns_all_entries() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let backend = playlist_backend();
+        let collected = collect_entries(&backend, &[], "https://example.com/list", cancelled, |_| {}).await;
+        let (entries, was_cancelled) = collected.unwrap();
+
+        assert_eq!(entries.len(), 5);
+        assert!(!was_cancelled);
+    }
+
+    #[tokio::test]
+    async fn cancelled_collection_keeps_parsed_entries() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let cancel = cancelled.clone();
+        let mut seen = 0;
+        let on_entry = |_: &Value| {
+            seen += 1;
+            if seen == 2 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+        };
+        let backend = playlist_backend();
+        let collected = collect_entries(&backend, &[], "https://example.com/list", cancelled, on_entry).await;
+        let (entries, was_cancelled) = collected.unwrap();
+
+        assert!(was_cancelled);
+        assert_eq!(entries, [json!({"id": "v0"}), json!({"id": "v1"})]);
+    }
+
+    #[tokio::test]
+    async fn retry_warnings_are_forwarded_while_running() {
+        let backend = MockBackend {
+            stdout: vec!["[download] Destination: /downloads/video.mp4".to_string()],
+            stderr: vec![
+                "WARNING: [download] Got error: HTTP Error 429: Too Many Requests. Retrying (1/3)...".to_string(),
+                "WARNING: [download] Got error: timed out. Retrying fragment 5 (2/3)...".to_string(),
+            ],
+            ..Default::default()
+        };
+        let manager = Arc::new(DownloadManager::default());
+        manager.register("dl-retry");
+
+        let retries: Arc<Mutex<Vec<(u32, u32, String)>>> = Arc::default();
+        let (event_manager, recorded) = (manager.clone(), retries.clone());
+        let on_event = move |mut event: DownloadEvent| {
+            event_manager.apply_event("dl-retry", &mut event);
+            if let DownloadEvent::Retry {
+                attempt,
+                retries,
+                reason,
+            } = event
+            {
+                recorded.lock().unwrap().push((attempt, retries, reason));
+            }
+        };
+        let outcome = backend.download(&[], None, on_event).await.unwrap();
+
+        assert!(outcome.status.success());
+        assert_eq!(outcome.warnings, 2);
+        assert_eq!(
+            *retries.lock().unwrap(),
+            [
+                (1, 3, "HTTP Error 429: Too Many Requests".to_string()),
+                (2, 3, "timed out".to_string())
+            ]
+        );
+        assert_eq!(manager.state("dl-retry").unwrap().status, DownloadStatus::Running);
+        assert_eq!(manager.active_output_paths(), ["/downloads/video.mp4"]);
+    }
+
+    #[test]
+    fn waiting_for_release_disables_the_startup_timeout() {
+        let settings = Settings {
+            startup_timeout: Some(120),
+            ..Settings::default()
+        };
+        let options = DownloadOptions::default();
+        assert_eq!(download_startup_timeout(&settings, &options), Some(Duration::from_secs(120)));
+
+        // download_when_released 总是设置 wait_for_video
+        let waiting = DownloadOptions {
+            wait_for_video: Some(RELEASE_RECHECK_INTERVAL),
+            ..Default::default()
+        };
+        assert_eq!(download_startup_timeout(&settings, &waiting), None);
+
+        let unlimited = Settings {
+            startup_timeout: None,
+            ..Settings::default()
+        };
+        assert_eq!(download_startup_timeout(&unlimited, &options), None);
+    }
+
+    /// yt-dlp --flat-playlist -J 的扁平结果（部分站点不带 formats）
+    const FLAT_INFO: &str = include_str!("../tests/fixtures/video_info_flat.json");
+    /// yt-dlp -J 的完整结果（含故事板、纯音频、合一和不同帧率的纯视频格式）
+    const FULL_INFO: &str = include_str!("../tests/fixtures/video_info_full.json");
+
+    fn parse_fixture(fixture: &str, settings: &Settings) -> VideoInfo {
+        parse_video_info(serde_json::from_str(fixture).unwrap(), settings).unwrap()
+    }
+
+    #[test]
+    fn flat_result_without_formats_needs_full_fetch() {
+        // 无法仅凭链接判断为单个视频，先做扁平探测
+        assert!(!is_single_video_url("https://vimeo.com/channels/staffpicks/76979871"));
+
+        let info = parse_fixture(FLAT_INFO, &Settings::default());
+
+        // 没有格式时 get_video_info 改为完整解析
+        assert!(info.formats.is_empty());
+        assert!(info.available_resolutions.is_empty());
+        assert_eq!(info.id, "76979871");
+        assert_eq!(info.extractor_key.as_deref(), Some("VimeoChannel"));
+    }
+
+    #[test]
+    fn full_result_populates_available_resolutions() {
+        assert!(is_single_video_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ"));
+
+        let info = parse_fixture(FULL_INFO, &Settings::default());
+        assert_eq!(info.formats.len(), 7);
+        assert_eq!(info.audio_languages, ["en"]);
+
+        let labels: Vec<&str> = info.available_resolutions.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["1080p60", "1080p", "360p"]);
+
+        let full_hd = &info.available_resolutions[1];
+        // 同一分辨率优先选择有文件大小的格式
+        assert_eq!(full_hd.format_id, "137");
+        assert!(full_hd.requires_merge);
+        let candidates: Vec<(&str, bool)> = full_hd
+            .candidates
+            .iter()
+            .map(|c| (c.format_id.as_str(), c.recommended))
+            .collect();
+        assert_eq!(candidates, [("248", false), ("137", true)]);
+
+        let sd = &info.available_resolutions[2];
+        assert_eq!(sd.format_id, "18");
+        assert!(sd.is_progressive && !sd.requires_merge);
+        assert!(info.available_resolutions.iter().all(|r| !r.above_max_resolution));
+    }
+
+    #[test]
+    fn full_result_respects_max_resolution() {
+        let settings = Settings {
+            max_resolution: Some(720),
+            ..Settings::default()
+        };
+        let info = parse_fixture(FULL_INFO, &settings);
+        let above: Vec<bool> = info.available_resolutions.iter().map(|r| r.above_max_resolution).collect();
+        assert_eq!(above, [true, true, false]);
+
+        let hidden = Settings {
+            hide_above_max_resolution: true,
+            ..settings
+        };
+        let info = parse_fixture(FULL_INFO, &hidden);
+        let labels: Vec<&str> = info.available_resolutions.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["360p"]);
+    }
 }
@@ -0,0 +1,201 @@
+/****************************************************************************
+ *  cookies.rs - Cookie 检查
+ *
+ *  @brief  检查浏览器或 Cookie 文件能否读取，以及能否以登录状态访问 YouTube
+ *  @note   登录检查访问只有登录后才能打开的页面（观看记录），只解析列表第一项，
+ *          不下载任何内容。macOS 读取浏览器 Cookie 时可能弹出钥匙串授权，
+ *          因此超时时间比普通的链接检查长
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tracing::{debug, info};
+use ts_rs::TS;
+
+use crate::process::ytdlp_command;
+
+/// yt-dlp 支持读取 Cookie 的浏览器
+const SUPPORTED_BROWSERS: &[&str] = &[
+    "brave", "chrome", "chromium", "edge", "firefox", "opera", "safari", "vivaldi", "whale",
+];
+
+/// 需要登录才能访问的页面（未登录时 yt-dlp 报错）
+const LOGIN_PROBE_URL: &str = "https://www.youtube.com/feed/history";
+
+/// 登录检查的超时时间
+const COOKIE_PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 无法读取 Cookie 时 yt-dlp 输出的提示（按小写匹配，只看提到 cookie 的行）
+const COOKIE_READ_ERRORS: &[&str] = &[
+    "could not find",
+    "could not copy",
+    "failed to decrypt",
+    "permission denied",
+    "does not look like a netscape format",
+    "unsupported keyring",
+];
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum CookieSource {
+    Browser(String),                // 浏览器（可带配置文件，如 "chrome:Profile 1"）
+    File(String),                   // Netscape 格式的 Cookie 文件
+}
+
+impl CookieSource {
+    /***********************************************************************
+     * 解析 Cookie 来源：支持的浏览器名优先，否则作为文件路径
+     ***********************************************************************/
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let source = source.trim();
+        if source.is_empty() {
+            return Err("请指定浏览器或 Cookie 文件".to_string());
+        }
+
+        let browser = source.split([':', '+']).next().unwrap_or_default().to_lowercase();
+        if SUPPORTED_BROWSERS.contains(&browser.as_str()) {
+            return Ok(Self::Browser(source.to_string()));
+        }
+        if Path::new(source).is_file() {
+            return Ok(Self::File(source.to_string()));
+        }
+        Err(format!(
+            "不支持的浏览器或 Cookie 文件不存在: {}（支持的浏览器: {}）",
+            source,
+            SUPPORTED_BROWSERS.join(", ")
+        ))
+    }
+
+    fn args(&self) -> [&str; 2] {
+        match self {
+            Self::Browser(browser) => ["--cookies-from-browser", browser],
+            Self::File(path) => ["--cookies", path],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CookieCheck {
+    pub source: CookieSource,       // 检查的 Cookie 来源
+    pub readable: bool,             // 能否读取 Cookie（浏览器数据库可访问、文件格式正确）
+    pub cookie_count: Option<usize>, // 读取到的 Cookie 数（yt-dlp 未报告时为 None）
+    pub authenticated: bool,        // 能以登录状态访问需要登录的页面
+    pub detail: Option<String>,     // 未通过时的原因
+}
+
+/***************************************************************************
+ * 检查 Cookie 能否读取并通过登录验证
+ *
+ * @param ytdlp_path - 使用的 yt-dlp
+ * @param source - Cookie 来源
+ * @return CookieCheck - 检查结果（无法执行 yt-dlp 时返回错误）
+ ***************************************************************************/
+
+pub async fn check_cookies(ytdlp_path: &Path, source: CookieSource) -> Result<CookieCheck, String> {
+    let unreadable = |source: CookieSource, detail: String| CookieCheck {
+        source,
+        readable: false,
+        cookie_count: None,
+        authenticated: false,
+        detail: Some(detail),
+    };
+
+    // Cookie 文件先自行检查格式，不对时不必启动 yt-dlp
+    let file_count = match &source {
+        CookieSource::File(path) => match count_file_cookies(Path::new(path)) {
+            Ok(count) => Some(count),
+            Err(e) => return Ok(unreadable(source, e)),
+        },
+        CookieSource::Browser(_) => None,
+    };
+
+    let output = ytdlp_command(ytdlp_path)
+        .args(["--simulate", "--no-warnings", "--flat-playlist", "--playlist-items", "1"])
+        .args(source.args())
+        .arg(LOGIN_PROBE_URL)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+    let output = match tokio::time::timeout(COOKIE_PROBE_TIMEOUT, output).await {
+        Ok(output) => output.map_err(|e| format!("无法执行 yt-dlp: {}", e))?,
+        Err(_) => return Ok(unreadable(source, "检查超时（读取浏览器 Cookie 可能需要授权）".to_string())),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    debug!("Cookie 检查输出: {}{}", stdout, stderr);
+
+    if let Some(error) = cookie_read_error(&stderr) {
+        return Ok(unreadable(source, error));
+    }
+
+    let authenticated = output.status.success();
+    let detail = (!authenticated).then(|| last_error_line(&stderr).unwrap_or_else(|| "未登录".to_string()));
+    let cookie_count = file_count.or_else(|| extracted_cookie_count(&stdout).or_else(|| extracted_cookie_count(&stderr)));
+    info!("Cookie 检查: {:?} -> 已登录: {}", source, authenticated);
+
+    Ok(CookieCheck {
+        source,
+        readable: true,
+        cookie_count,
+        authenticated,
+        detail,
+    })
+}
+
+/***************************************************************************
+ * 统计 Cookie 文件中的条目
+ *
+ * yt-dlp 只接受以 "# Netscape HTTP Cookie File" 或 "# HTTP Cookie File"
+ * 开头的文件；"#HttpOnly_" 开头的行是条目而不是注释
+ ***************************************************************************/
+
+fn count_file_cookies(path: &Path) -> Result<usize, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("无法读取 Cookie 文件: {}", e))?;
+    let header = content.lines().next().unwrap_or_default().trim();
+    if header != "# Netscape HTTP Cookie File" && header != "# HTTP Cookie File" {
+        return Err("Cookie 文件不是 Netscape 格式（应以 \"# Netscape HTTP Cookie File\" 开头）".to_string());
+    }
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && (!line.starts_with('#') || line.starts_with("#HttpOnly_")))
+        .count())
+}
+
+/// 无法读取 Cookie 时的错误行
+fn cookie_read_error(stderr: &str) -> Option<String> {
+    stderr
+        .lines()
+        .find(|line| {
+            let line = line.to_lowercase();
+            line.contains("cookie") && COOKIE_READ_ERRORS.iter().any(|error| line.contains(error))
+        })
+        .map(|line| line.trim().to_string())
+}
+
+/// 从 "Extracted 123 cookies from chrome" 中取出数量
+fn extracted_cookie_count(output: &str) -> Option<usize> {
+    output.lines().find_map(|line| {
+        let rest = &line[line.find("Extracted ")? + "Extracted ".len()..];
+        let (count, rest) = rest.split_once(' ')?;
+        rest.starts_with("cookies").then(|| count.parse().ok()).flatten()
+    })
+}
+
+fn last_error_line(stderr: &str) -> Option<String> {
+    stderr
+        .lines()
+        .rev()
+        .find(|line| line.starts_with("ERROR:"))
+        .map(|line| line.trim().to_string())
+}
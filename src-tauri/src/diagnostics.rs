@@ -0,0 +1,100 @@
+/****************************************************************************
+ *  diagnostics.rs - 运行环境自检
+ *
+ *  @brief  汇总 yt-dlp、ffmpeg、浏览器伪装、下载目录和网络的检查结果
+ *  @note   每项检查独立给出通过/警告/失败及修复建议，前端直接展示；
+ *          检查本身由 run_diagnostics 命令执行，这里只定义报告结构。
+ *          缺少的依赖附带当前平台的安装命令，首次启动的设置页面可直接运行
+ *****************************************************************************/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,                           // 可以下载，但部分功能不可用
+    Fail,                           // 无法正常下载
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DiagnosticCheck {
+    pub name: String,               // 检查项标识，如 "ytdlp"、"ffmpeg"
+    pub status: CheckStatus,
+    pub detail: String,             // 检查结果说明（版本、路径、错误信息）
+    pub remediation: Option<String>, // 未通过时的修复建议
+    pub install_command: Option<String>, // 缺少依赖时当前平台的安装命令
+}
+
+impl DiagnosticCheck {
+    pub fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            remediation: None,
+            install_command: None,
+        }
+    }
+
+    pub fn warn(name: &str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            remediation: Some(remediation.into()),
+            ..Self::pass(name, detail)
+        }
+    }
+
+    pub fn fail(name: &str, detail: impl Into<String>, remediation: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            ..Self::warn(name, detail, remediation)
+        }
+    }
+
+    /// 附加安装命令（当前平台没有合适的命令时不变）
+    pub fn with_install(self, command: Option<&str>) -> Self {
+        Self {
+            install_command: command.map(str::to_string),
+            ..self
+        }
+    }
+}
+
+/// 当前平台安装 yt-dlp 的命令
+pub fn ytdlp_install_command() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        Some("brew install yt-dlp")
+    } else if cfg!(target_os = "windows") {
+        Some("winget install yt-dlp.yt-dlp")
+    } else {
+        Some("python3 -m pip install --user -U yt-dlp")
+    }
+}
+
+/// 当前平台安装 ffmpeg 的命令（Linux 发行版的包管理器不同，不给出命令）
+pub fn ffmpeg_install_command() -> Option<&'static str> {
+    if cfg!(target_os = "macos") {
+        Some("brew install ffmpeg")
+    } else if cfg!(target_os = "windows") {
+        Some("winget install Gyan.FFmpeg")
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DiagnosticsReport {
+    pub healthy: bool,              // 没有失败项（警告不影响）
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+impl DiagnosticsReport {
+    pub fn new(checks: Vec<DiagnosticCheck>) -> Self {
+        Self {
+            healthy: checks.iter().all(|check| check.status != CheckStatus::Fail),
+            checks,
+        }
+    }
+}
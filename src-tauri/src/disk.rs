@@ -0,0 +1,55 @@
+/****************************************************************************
+ *  disk.rs - 磁盘空间查询
+ *
+ *  @brief  查询路径所在卷的可用空间和总空间（fs4 跨平台实现）
+ *  @note   路径不存在时（如尚未创建的下载子目录）向上查找最近的已存在目录
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DiskSpace {
+    pub path: String,               // 实际查询的路径（最近的已存在目录）
+    #[ts(type = "number")]
+    pub free_bytes: u64,            // 当前用户可用的空间
+    #[ts(type = "number")]
+    pub total_bytes: u64,           // 卷总空间
+}
+
+/***************************************************************************
+ * 查询路径所在卷的空间
+ *
+ * @param path - 任意路径（可以不存在）
+ * @return DiskSpace - 可用空间与总空间
+ ***************************************************************************/
+
+pub fn disk_space(path: &Path) -> Result<DiskSpace, String> {
+    let existing = nearest_existing_ancestor(path)
+        .ok_or_else(|| format!("找不到已存在的上级目录: {}", path.display()))?;
+
+    let stats = fs4::statvfs(&existing)
+        .map_err(|e| format!("查询磁盘空间失败: {} ({})", existing.display(), e))?;
+
+    Ok(DiskSpace {
+        path: existing.to_string_lossy().into_owned(),
+        free_bytes: stats.available_space(),
+        total_bytes: stats.total_space(),
+    })
+}
+
+/// 从 path 开始向上查找第一个存在的路径（相对路径基于当前工作目录）
+fn nearest_existing_ancestor(path: &Path) -> Option<PathBuf> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().ok()?.join(path)
+    };
+
+    path.ancestors().find(|p| p.exists()).map(Path::to_path_buf)
+}
@@ -0,0 +1,669 @@
+/****************************************************************************
+ *  downloads.rs - 下载任务管理
+ *
+ *  @brief  以托管状态记录每个下载任务的运行时数据（状态、最近进度、速度历史）
+ *  @note   由 download_video 在解析进度时写入，供查询类命令读取；
+ *          同一批次（从空闲开始登记的一组任务）的进度可汇总为整体进度
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+use ts_rs::TS;
+
+use crate::events;
+use crate::progress::ProgressInfo;
+use crate::ytdlp::DownloadEvent;
+
+/// 速度采样间隔
+const SPEED_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 每个下载最多保留的速度样本数（1 Hz 下约 10 分钟）
+const SPEED_HISTORY_CAPACITY: usize = 600;
+
+/// 整体进度事件的发送间隔
+pub const QUEUE_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 估算队列剩余时间时参考的最近吞吐量窗口
+const RECENT_THROUGHPUT_WINDOW: Duration = Duration::from_secs(60);
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub enum DownloadStatus {
+    Scheduled,                      // 定时下载，等待到达开始时间
+    Queued,
+    WaitingForNetwork,              // 网络断开，恢复后自动开始
+    WaitingForDestination,          // 下载目录不存在或不可写，恢复后自动开始
+    Running,
+    FetchingComments,               // 获取评论（下载开始前，评论多时可能持续数分钟）
+    Paused,
+    PostProcessing,                 // 合并、转码等后处理阶段
+    Completed,
+    PartialSuccess,                 // 播放列表部分条目失败，其余已完成
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DownloadState {
+    pub download_id: String,
+    pub status: DownloadStatus,
+    pub progress: Option<ProgressInfo>, // 最近一次进度事件的内容
+    pub error: Option<String>,      // 失败原因
+    #[ts(type = "number")]
+    pub updated_at: u64,            // 状态最后更新时间（Unix 毫秒）
+}
+
+impl DownloadStatus {
+    /// 是否仍在队列中（尚未结束）
+    pub fn is_active(self) -> bool {
+        matches!(
+            self,
+            DownloadStatus::Queued
+                | DownloadStatus::WaitingForNetwork
+                | DownloadStatus::WaitingForDestination
+                | DownloadStatus::Running
+                | DownloadStatus::FetchingComments
+                | DownloadStatus::Paused
+                | DownloadStatus::PostProcessing
+        )
+    }
+}
+
+/// 用于计算整体进度的单个任务快照
+#[derive(Debug, Clone)]
+pub struct QueueItemSnapshot {
+    pub status: DownloadStatus,
+    pub total_bytes: Option<u64>,   // 已知的总大小
+    pub percent: f64,               // 当前任务进度（0-100）
+    pub bytes_per_sec: Option<f64>, // 当前速度
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct QueueProgress {
+    pub percent: f64,               // 整体完成百分比
+    pub total: usize,               // 参与统计的任务数（不含失败/取消）
+    pub completed: usize,
+    pub active: usize,              // 下载中或后处理中
+    pub pending: usize,             // 排队或暂停
+    pub speed: f64,                 // 合计速度（字节/秒）
+    pub eta_seconds: Option<f64>,   // 整体剩余时间（无法估算大小时为 None）
+}
+
+/// get_queue_eta 的返回值：全部未结束任务的剩余时间
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct QueueEta {
+    pub eta_seconds: Option<f64>,   // 剩余秒数（没有吞吐量或大小信息时为 None）
+    pub formatted: Option<String>,  // 如 "约 25 分钟"
+    pub remaining: usize,           // 参与估算的任务数（进行中、排队和暂停）
+    pub estimated_sizes: usize,     // 大小未知、按已知任务平均大小估算的任务数
+    #[ts(type = "number | null")]
+    pub remaining_bytes: Option<u64>, // 剩余字节数
+    pub throughput: Option<f64>,    // 最近的平均吞吐量（字节/秒）
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SpeedSample {
+    #[ts(type = "number")]
+    pub timestamp: u64,             // 采样时间（Unix 毫秒）
+    pub bytes_per_sec: f64,         // 下载速度（字节/秒）
+}
+
+#[derive(Debug, Serialize, TS)]
+pub struct SpeedHistory {
+    pub samples: Vec<SpeedSample>,  // 按时间升序
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+}
+
+/***************************************************************************
+ * 速度环形缓冲区
+ *
+ * 只在收到进度时采样（且两次采样至少间隔 1 秒），暂停或停滞期间
+ * 没有进度帧，自然不会积累样本；恢复后直接从新的进度继续记录
+ ***************************************************************************/
+
+#[derive(Debug, Default)]
+struct SpeedBuffer {
+    samples: VecDeque<SpeedSample>,
+    last_sampled_at: Option<Instant>,
+}
+
+impl SpeedBuffer {
+    fn record(&mut self, now: Instant, bytes_per_sec: f64) {
+        if self
+            .last_sampled_at
+            .is_some_and(|last| now.duration_since(last) < SPEED_SAMPLE_INTERVAL)
+        {
+            return;
+        }
+
+        if self.samples.len() == SPEED_HISTORY_CAPACITY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(SpeedSample {
+            timestamp: unix_millis(),
+            bytes_per_sec,
+        });
+        self.last_sampled_at = Some(now);
+    }
+
+    /***********************************************************************
+     * 汇总最近 limit 个样本
+     ***********************************************************************/
+    fn summarize(&self, limit: usize) -> SpeedHistory {
+        let skip = self.samples.len().saturating_sub(limit);
+        let samples: Vec<SpeedSample> = self.samples.iter().skip(skip).cloned().collect();
+
+        let (min, max, sum) = samples.iter().fold(
+            (f64::INFINITY, 0.0_f64, 0.0_f64),
+            |(min, max, sum), s| (min.min(s.bytes_per_sec), max.max(s.bytes_per_sec), sum + s.bytes_per_sec),
+        );
+
+        if samples.is_empty() {
+            return SpeedHistory { samples, min: 0.0, avg: 0.0, max: 0.0 };
+        }
+
+        let avg = sum / samples.len() as f64;
+        SpeedHistory { samples, min, avg, max }
+    }
+}
+
+#[derive(Debug)]
+struct DownloadEntry {
+    status: DownloadStatus,
+    progress: Option<ProgressInfo>,
+    error: Option<String>,
+    updated_at: u64,
+    speed: SpeedBuffer,
+    batch: u64,                     // 所属批次
+    percent: f64,
+    total_bytes: Option<u64>,
+    bytes_per_sec: Option<f64>,
+    output_paths: Vec<String>,      // yt-dlp 报告的目标文件（临时文件以此为前缀）
+}
+
+impl DownloadEntry {
+    fn new(batch: u64) -> Self {
+        Self {
+            status: DownloadStatus::Queued,
+            progress: None,
+            error: None,
+            updated_at: unix_millis(),
+            speed: SpeedBuffer::default(),
+            batch,
+            percent: 0.0,
+            total_bytes: None,
+            bytes_per_sec: None,
+            output_paths: Vec::new(),
+        }
+    }
+
+    fn snapshot(&self) -> QueueItemSnapshot {
+        QueueItemSnapshot {
+            status: self.status,
+            total_bytes: self.total_bytes,
+            percent: self.percent,
+            bytes_per_sec: self.bytes_per_sec,
+        }
+    }
+}
+
+/***************************************************************************
+ * 下载管理器（Tauri 托管状态）
+ ***************************************************************************/
+
+#[derive(Default)]
+pub struct DownloadManager {
+    entries: Mutex<HashMap<String, DownloadEntry>>,
+    batch: Mutex<u64>,
+}
+
+impl DownloadManager {
+    /***********************************************************************
+     * 登记一个新的下载任务（初始状态为 Queued）
+     *
+     * 没有未结束的任务时开启新批次，之前已结束的任务不再计入整体进度
+     ***********************************************************************/
+    pub fn register(&self, id: &str) {
+        let (Ok(mut entries), Ok(mut batch)) = (self.entries.lock(), self.batch.lock()) else {
+            return;
+        };
+        if !entries.values().any(|entry| entry.status.is_active()) {
+            *batch += 1;
+        }
+        entries.insert(id.to_string(), DownloadEntry::new(*batch));
+    }
+
+    /***********************************************************************
+     * 更新任务状态
+     *
+     * @param error - 失败原因（仅 Failed 时有意义）
+     ***********************************************************************/
+    pub fn set_status(&self, id: &str, status: DownloadStatus, error: Option<String>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if let Some(entry) = entries.get_mut(id) {
+                entry.status = status;
+                entry.error = error;
+                entry.updated_at = unix_millis();
+            }
+        }
+    }
+
+    /***********************************************************************
+     * 记录一帧进度：保存快照，并按需采样速度
+     *
+     * @param progress - 进度事件内容
+     * @param bytes_per_sec - 解析出的速度（无法解析时为 None）
+     ***********************************************************************/
+    pub fn record_progress(&self, id: &str, progress: &ProgressInfo, bytes_per_sec: Option<f64>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if let Some(entry) = entries.get_mut(id) {
+                entry.progress = Some(progress.clone());
+                entry.updated_at = unix_millis();
+                entry.percent = progress.percent;
+                entry.bytes_per_sec = bytes_per_sec;
+                if progress.total_bytes.is_some() {
+                    entry.total_bytes = progress.total_bytes;
+                }
+                if let Some(bytes_per_sec) = bytes_per_sec {
+                    entry.speed.record(Instant::now(), bytes_per_sec);
+                }
+            }
+        }
+    }
+
+    /// 记录任务的一个目标文件
+    pub fn record_output_path(&self, id: &str, path: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if let Some(entry) = entries.get_mut(id) {
+                entry.output_paths.push(path.to_string());
+            }
+        }
+    }
+
+    /***********************************************************************
+     * 按下载事件更新任务状态（状态、进度快照、速度样本、目标文件）
+     *
+     * 进度事件会补上任务ID；发送前端事件由调用方在此之后进行
+     ***********************************************************************/
+    pub fn apply_event(&self, id: &str, event: &mut DownloadEvent) {
+        match event {
+            DownloadEvent::Started => self.set_status(id, DownloadStatus::Running, None),
+            DownloadEvent::Destination(path) => {
+                // 评论获取完毕，开始下载
+                if self.state(id).map(|s| s.status) == Some(DownloadStatus::FetchingComments) {
+                    self.set_status(id, DownloadStatus::Running, None);
+                }
+                self.record_output_path(id, path);
+            }
+            DownloadEvent::Progress {
+                progress,
+                bytes_per_sec,
+                ..
+            } => {
+                progress.download_id = id.to_string();
+                self.record_progress(id, progress, *bytes_per_sec);
+            }
+            DownloadEvent::FetchingComments { .. } => self.set_status(id, DownloadStatus::FetchingComments, None),
+            DownloadEvent::PostProcessing { .. } => self.set_status(id, DownloadStatus::PostProcessing, None),
+            DownloadEvent::Throttled | DownloadEvent::Warning { .. } | DownloadEvent::Retry { .. } => {}
+        }
+    }
+
+    /// 所有未结束任务（含暂停）的目标文件，清理临时文件时需要排除
+    pub fn active_output_paths(&self) -> Vec<String> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        entries
+            .values()
+            .filter(|entry| entry.status.is_active())
+            .flat_map(|entry| entry.output_paths.iter().cloned())
+            .collect()
+    }
+
+    /// 获取任务当前状态与最近进度
+    pub fn state(&self, id: &str) -> Option<DownloadState> {
+        let entries = self.entries.lock().ok()?;
+        entries.get(id).map(|entry| DownloadState {
+            download_id: id.to_string(),
+            status: entry.status,
+            progress: entry.progress.clone(),
+            error: entry.error.clone(),
+            updated_at: entry.updated_at,
+        })
+    }
+
+    /// 指定任务的快照（未登记的任务忽略）
+    pub fn snapshots(&self, ids: &[String]) -> Vec<QueueItemSnapshot> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        ids.iter()
+            .filter_map(|id| entries.get(id))
+            .map(DownloadEntry::snapshot)
+            .collect()
+    }
+
+    /// 当前批次所有任务的快照
+    pub fn queue_snapshot(&self) -> Vec<QueueItemSnapshot> {
+        let (Ok(entries), Ok(batch)) = (self.entries.lock(), self.batch.lock()) else {
+            return Vec::new();
+        };
+        entries
+            .values()
+            .filter(|entry| entry.batch == *batch)
+            .map(DownloadEntry::snapshot)
+            .collect()
+    }
+
+    /// 全部任务（不限批次）的快照
+    pub fn all_snapshots(&self) -> Vec<QueueItemSnapshot> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        entries.values().map(DownloadEntry::snapshot).collect()
+    }
+
+    /***********************************************************************
+     * 最近的平均吞吐量
+     *
+     * 把窗口内全部任务的速度样本按秒合计（并发下载的速度相加），
+     * 再对有样本的秒取平均
+     *
+     * @return Option<f64> - 字节/秒，窗口内没有样本时为 None
+     ***********************************************************************/
+    pub fn recent_throughput(&self) -> Option<f64> {
+        let entries = self.entries.lock().ok()?;
+        let since = unix_millis().saturating_sub(RECENT_THROUGHPUT_WINDOW.as_millis() as u64);
+        let mut per_second: HashMap<u64, f64> = HashMap::new();
+        for sample in entries.values().flat_map(|entry| entry.speed.samples.iter()) {
+            if sample.timestamp >= since {
+                *per_second.entry(sample.timestamp / 1000).or_default() += sample.bytes_per_sec;
+            }
+        }
+        let average = per_second.values().sum::<f64>() / per_second.len().max(1) as f64;
+        (average > 0.0).then_some(average)
+    }
+
+    /***********************************************************************
+     * 获取速度历史
+     *
+     * @param id - 下载任务ID
+     * @param limit - 返回的最大样本数
+     * @return Option<SpeedHistory> - 未知任务返回 None
+     ***********************************************************************/
+    pub fn speed_history(&self, id: &str, limit: usize) -> Option<SpeedHistory> {
+        let entries = self.entries.lock().ok()?;
+        entries.get(id).map(|entry| entry.speed.summarize(limit))
+    }
+}
+
+/***************************************************************************
+ * 计算整体队列进度
+ *
+ * 每个任务按大小加权：已知大小用实际值，未知大小用已知任务的平均值，
+ * 全部未知时等权。已完成任务计满，进行中任务按百分比计入，排队任务计 0；
+ * 定时、失败和取消的任务不参与统计。整体 ETA = 剩余字节 / 当前合计速度。
+ *
+ * @param items - 队列任务快照
+ * @return QueueProgress - 整体进度
+ ***************************************************************************/
+
+pub fn compute_queue_progress(items: &[QueueItemSnapshot]) -> QueueProgress {
+    let items: Vec<&QueueItemSnapshot> = items
+        .iter()
+        .filter(|item| {
+            !matches!(
+                item.status,
+                DownloadStatus::Scheduled | DownloadStatus::Failed | DownloadStatus::Cancelled
+            )
+        })
+        .collect();
+
+    let known_sizes: Vec<f64> = items
+        .iter()
+        .filter_map(|item| item.total_bytes.map(|bytes| bytes as f64))
+        .collect();
+    let sizes_known = !known_sizes.is_empty();
+    let fallback_size = if sizes_known {
+        known_sizes.iter().sum::<f64>() / known_sizes.len() as f64
+    } else {
+        1.0
+    };
+
+    let mut progress = QueueProgress {
+        percent: 0.0,
+        total: items.len(),
+        completed: 0,
+        active: 0,
+        pending: 0,
+        speed: 0.0,
+        eta_seconds: None,
+    };
+
+    let mut total_weight = 0.0;
+    let mut done_weight = 0.0;
+    for item in &items {
+        let weight = item.total_bytes.map(|bytes| bytes as f64).unwrap_or(fallback_size);
+        total_weight += weight;
+
+        match item.status {
+            DownloadStatus::Completed | DownloadStatus::PartialSuccess => {
+                progress.completed += 1;
+                done_weight += weight;
+            }
+            DownloadStatus::Running | DownloadStatus::FetchingComments | DownloadStatus::PostProcessing => {
+                progress.active += 1;
+                done_weight += weight * item.percent.clamp(0.0, 100.0) / 100.0;
+                progress.speed += item.bytes_per_sec.unwrap_or(0.0);
+            }
+            _ => progress.pending += 1,
+        }
+    }
+
+    if total_weight > 0.0 {
+        progress.percent = done_weight / total_weight * 100.0;
+    }
+
+    // 只有至少知道一个任务的大小时，权重才是字节数，才能换算时间
+    if sizes_known && progress.speed > 0.0 {
+        progress.eta_seconds = Some((total_weight - done_weight).max(0.0) / progress.speed);
+    }
+
+    progress
+}
+
+/***************************************************************************
+ * 估算全部未结束任务的剩余时间
+ *
+ * 进行中的任务按剩余百分比计入，排队和暂停的任务计入全部大小；大小未知的
+ * 任务用已知大小（含已完成任务）的平均值估算。剩余字节 / 最近平均吞吐量即为
+ * 剩余时间；定时任务尚未开始，不参与估算。每次调用都按当前状态重新计算，
+ * 任务完成后自然从估算中移除
+ *
+ * @param items - 全部任务快照（DownloadManager::all_snapshots）
+ * @param throughput - 最近的平均吞吐量（DownloadManager::recent_throughput）
+ * @return QueueEta - 剩余时间及估算依据
+ ***************************************************************************/
+
+pub fn compute_queue_eta(items: &[QueueItemSnapshot], throughput: Option<f64>) -> QueueEta {
+    let known_sizes: Vec<f64> = items
+        .iter()
+        .filter_map(|item| item.total_bytes.map(|bytes| bytes as f64))
+        .collect();
+    let average_size =
+        (!known_sizes.is_empty()).then(|| known_sizes.iter().sum::<f64>() / known_sizes.len() as f64);
+
+    let mut eta = QueueEta {
+        eta_seconds: None,
+        formatted: None,
+        remaining: 0,
+        estimated_sizes: 0,
+        remaining_bytes: None,
+        throughput,
+    };
+    let mut remaining_bytes = 0.0;
+    for item in items.iter().filter(|item| item.status.is_active()) {
+        eta.remaining += 1;
+        if item.total_bytes.is_none() {
+            eta.estimated_sizes += 1;
+        }
+        let size = item.total_bytes.map(|bytes| bytes as f64).or(average_size).unwrap_or(0.0);
+        let done = match item.status {
+            DownloadStatus::PostProcessing => 1.0,
+            DownloadStatus::Running | DownloadStatus::Paused => item.percent.clamp(0.0, 100.0) / 100.0,
+            _ => 0.0,
+        };
+        remaining_bytes += size * (1.0 - done);
+    }
+
+    if eta.remaining == 0 {
+        eta.eta_seconds = Some(0.0);
+        eta.formatted = Some("已完成".to_string());
+    } else if average_size.is_some() {
+        eta.remaining_bytes = Some(remaining_bytes as u64);
+        eta.eta_seconds = throughput.map(|speed| remaining_bytes / speed);
+        eta.formatted = eta.eta_seconds.map(format_eta);
+    }
+    eta
+}
+
+/// 剩余时间的显示文本（按分钟向上取整）
+fn format_eta(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).ceil() as u64;
+    match minutes {
+        0 | 1 => "不到 1 分钟".to_string(),
+        2..=59 => format!("约 {} 分钟", minutes),
+        _ if minutes.is_multiple_of(60) => format!("约 {} 小时", minutes / 60),
+        _ => format!("约 {} 小时 {} 分钟", minutes / 60, minutes % 60),
+    }
+}
+
+/***************************************************************************
+ * 启动整体进度事件的定时发送任务
+ *
+ * 有未结束任务时每秒发送一次 queue-progress 事件；
+ * 批次结束后再补发一次，让前端看到最终结果
+ ***************************************************************************/
+
+pub fn spawn_queue_progress_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(QUEUE_PROGRESS_INTERVAL);
+        let mut was_active = false;
+        loop {
+            interval.tick().await;
+
+            let items = app.state::<DownloadManager>().queue_snapshot();
+            let is_active = items.iter().any(|item| item.status.is_active());
+            if !is_active && !was_active {
+                continue;
+            }
+            was_active = is_active;
+
+            let progress = compute_queue_progress(&items);
+            if let Err(e) = app.emit(events::QUEUE_PROGRESS, &progress) {
+                warn!("发送整体进度事件失败: {}", e);
+            }
+        }
+    });
+}
+
+/// 当前时间（Unix 毫秒）
+pub fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1_000_000;
+
+    fn item(status: DownloadStatus, total_bytes: Option<u64>, percent: f64, speed: Option<f64>) -> QueueItemSnapshot {
+        QueueItemSnapshot {
+            status,
+            total_bytes,
+            percent,
+            bytes_per_sec: speed,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn empty_queue_has_no_progress() {
+        let progress = compute_queue_progress(&[]);
+
+        assert_eq!(progress.total, 0);
+        assert_eq!((progress.completed, progress.active, progress.pending), (0, 0, 0));
+        assert_close(progress.percent, 0.0);
+        assert_close(progress.speed, 0.0);
+        assert_eq!(progress.eta_seconds, None);
+    }
+
+    #[test]
+    fn unknown_sizes_are_weighted_by_the_known_average() {
+        let items = [
+            item(DownloadStatus::Completed, Some(100 * MB), 100.0, None),
+            // 大小未知，按已知平均值 200MB 计：完成 100MB
+            item(DownloadStatus::Running, None, 50.0, Some(10.0 * MB as f64)),
+            item(DownloadStatus::Queued, Some(300 * MB), 0.0, None),
+            // 以下不参与统计
+            item(DownloadStatus::Scheduled, Some(1000 * MB), 0.0, None),
+            item(DownloadStatus::Failed, Some(1000 * MB), 40.0, None),
+            item(DownloadStatus::Cancelled, None, 10.0, None),
+        ];
+        let progress = compute_queue_progress(&items);
+
+        assert_eq!(progress.total, 3);
+        assert_eq!((progress.completed, progress.active, progress.pending), (1, 1, 1));
+        assert_close(progress.percent, 200.0 / 600.0 * 100.0);
+        assert_close(progress.speed, 10.0 * MB as f64);
+        // 剩余 400MB / 10MB/s
+        assert_close(progress.eta_seconds.unwrap(), 40.0);
+    }
+
+    #[test]
+    fn completed_items_count_in_full() {
+        let items = [
+            item(DownloadStatus::Completed, None, 100.0, None),
+            item(DownloadStatus::PartialSuccess, None, 80.0, None),
+            item(DownloadStatus::Paused, None, 30.0, None),
+            item(DownloadStatus::Queued, None, 0.0, None),
+        ];
+        let progress = compute_queue_progress(&items);
+
+        assert_eq!(progress.total, 4);
+        assert_eq!((progress.completed, progress.active, progress.pending), (2, 0, 2));
+        // 大小全部未知时等权，暂停任务计 0
+        assert_close(progress.percent, 50.0);
+        assert_eq!(progress.eta_seconds, None);
+    }
+
+    #[test]
+    fn all_completed_reaches_full_progress() {
+        let items = [
+            item(DownloadStatus::Completed, Some(5 * MB), 100.0, None),
+            item(DownloadStatus::Completed, None, 100.0, None),
+        ];
+        let progress = compute_queue_progress(&items);
+
+        assert_eq!(progress.completed, 2);
+        assert_close(progress.percent, 100.0);
+        assert_eq!(progress.eta_seconds, None);
+    }
+}
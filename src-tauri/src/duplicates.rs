@@ -0,0 +1,84 @@
+/****************************************************************************
+ *  duplicates.rs - 重复下载检测
+ *
+ *  @brief  入队前检查同一视频是否已在队列中，或已经下载过
+ *  @note   比较的是 urls::video_key 生成的视频标识，
+ *          youtu.be 短链接与 watch?v= 链接视为同一视频
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::path::Path;
+use ts_rs::TS;
+
+use crate::downloads::DownloadStatus;
+use crate::history::HistoryEntry;
+use crate::queue::QueueItem;
+use crate::urls::video_key;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum DuplicateStatus {
+    AlreadyQueued { download_id: String },     // 已在队列中（定时、等待或下载中）
+    AlreadyDownloaded { path: String },        // 已下载且文件仍在磁盘上
+    PreviouslyDownloadedFileMissing,           // 下载过，但文件已被删除或移走
+    New,
+}
+
+impl DuplicateStatus {
+    /// 说明文字（enqueue_download 拒绝重复时作为错误信息）
+    pub fn describe(&self) -> String {
+        match self {
+            DuplicateStatus::AlreadyQueued { download_id } => {
+                format!("该视频已在下载队列中: {}", download_id)
+            }
+            DuplicateStatus::AlreadyDownloaded { path } => format!("该视频已下载: {}", path),
+            DuplicateStatus::PreviouslyDownloadedFileMissing => {
+                "该视频曾经下载过，但文件已不存在".to_string()
+            }
+            DuplicateStatus::New => "新视频".to_string(),
+        }
+    }
+}
+
+/***************************************************************************
+ * 检查视频是否重复
+ *
+ * 依次检查：队列中的任务 → 下载成功的历史记录（文件存在 / 已不存在）
+ *
+ * @param url - 待下载的链接
+ * @param queue - 当前队列中的任务
+ * @param history - 全部历史记录（最新的在前）
+ ***************************************************************************/
+
+pub fn find_duplicate(url: &str, queue: &[QueueItem], history: &[HistoryEntry]) -> DuplicateStatus {
+    let Some(key) = video_key(url) else {
+        return DuplicateStatus::New;
+    };
+    let matches = |other: &str| video_key(other).as_deref() == Some(key.as_str());
+
+    if let Some(item) = queue.iter().find(|item| matches(&item.url)) {
+        return DuplicateStatus::AlreadyQueued {
+            download_id: item.download_id.clone(),
+        };
+    }
+
+    let mut downloaded = history
+        .iter()
+        .filter(|entry| entry.status == DownloadStatus::Completed && matches(&entry.url))
+        .peekable();
+    if downloaded.peek().is_none() {
+        return DuplicateStatus::New;
+    }
+
+    match downloaded
+        .filter_map(|entry| entry.output_path.as_ref())
+        .find(|path| Path::new(path).exists())
+    {
+        Some(path) => DuplicateStatus::AlreadyDownloaded { path: path.clone() },
+        None => DuplicateStatus::PreviouslyDownloadedFileMissing,
+    }
+}
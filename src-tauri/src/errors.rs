@@ -0,0 +1,193 @@
+/****************************************************************************
+ *  errors.rs - 结构化错误
+ *
+ *  @brief  把 yt-dlp 的错误输出归类为错误类型，并附带当前语言的说明和建议
+ *  @note   错误类型对应 i18n.rs 中的消息键（error.<kind>.suggestion），
+ *          message/suggestion 在创建时按当前语言生成
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use ts_rs::TS;
+
+use crate::i18n::tr;
+use crate::impersonation::{detect_install, is_impersonation_error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    BotDetection,                   // 站点要求验证不是机器人
+    GeoBlocked,                     // 视频在当前地区不可用
+    RateLimited,                    // 请求过多（429）
+    LoginRequired,                  // 需要登录或 Cookie
+    ImpersonationUnavailable,       // 浏览器伪装目标不可用（缺少 curl_cffi）
+    ExtractorFailed,                // 提取器解析失败
+    Unknown,
+}
+
+impl ErrorKind {
+    /// 建议文本的消息键（没有通用建议的类型为 None）
+    fn suggestion_key(self) -> Option<&'static str> {
+        match self {
+            Self::BotDetection => Some("error.bot_detection.suggestion"),
+            Self::GeoBlocked => Some("error.geo_blocked.suggestion"),
+            Self::RateLimited => Some("error.rate_limited.suggestion"),
+            Self::LoginRequired => Some("error.login_required.suggestion"),
+            Self::ImpersonationUnavailable => Some("error.impersonation_unavailable.suggestion"),
+            Self::ExtractorFailed => Some("error.extractor_failed.suggestion"),
+            Self::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct AppError {
+    pub kind: ErrorKind,
+    pub message: String,            // 错误说明（当前语言）
+    pub suggestion: Option<String>, // 解决建议（当前语言）
+    pub detail: String,             // 原始错误输出
+    pub region: Option<String>,     // 地区限制时 yt-dlp 报告的可观看地区（如 "US"）
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n\n{}:\n{}", tr("error.suggestion_heading", &[]), suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// 站点要求验证不是机器人（通常刷新登录 Cookie 即可通过）
+pub fn is_bot_detection_error(stderr: &str) -> bool {
+    stderr.contains("Sign in to confirm you're not a bot")
+        || stderr.contains("Sign in to confirm you’re not a bot")
+}
+
+/// 尚未开始的首映或直播的提示语（按小写匹配）
+const UPCOMING_PHRASES: &[&str] = &[
+    "premieres in",
+    "premiere will begin",
+    "live event will begin",
+    "will begin in a few moments",
+];
+
+/// 视频是尚未开始的首映或直播预告（yt-dlp 找不到可下载的格式而报错）
+pub fn is_upcoming_error(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    UPCOMING_PHRASES.iter().any(|phrase| stderr.contains(phrase))
+}
+
+/// 后处理（ffmpeg 合并）出错的提示语（按小写匹配）
+const POSTPROCESS_PHRASES: &[&str] = &["postprocessing", "ffmpeg", "merger", "conversion failed"];
+
+/// 出错发生在下载完成后的后处理阶段（如 ffmpeg 合并音视频失败）
+pub fn is_postprocess_error(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    POSTPROCESS_PHRASES.iter().any(|phrase| stderr.contains(phrase))
+}
+
+/// 地区限制的提示语（各提取器措辞不同，按小写匹配）
+const GEO_BLOCK_PHRASES: &[&str] = &[
+    "not available in your country",
+    "not made this video available in your country",
+    "not available from your location",
+    "not available in your region",
+    "geo restriction",
+    "geo-restricted",
+];
+
+/// 视频在当前地区不可用
+pub fn is_geo_blocked_error(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    GEO_BLOCK_PHRASES.iter().any(|phrase| stderr.contains(phrase))
+}
+
+/***************************************************************************
+ * 提取 yt-dlp 报告的可观看地区
+ *
+ * 如 "This video is only available in US, CA"；"available in your country"
+ * 等不指明地区的措辞跳过
+ *
+ * @return Option<String> - 地区（原样返回，可能是国家代码或国家名）
+ ***************************************************************************/
+
+pub fn geo_available_region(stderr: &str) -> Option<String> {
+    const MARKER: &str = "available in ";
+    stderr.match_indices(MARKER).find_map(|(index, _)| {
+        let rest = &stderr[index + MARKER.len()..];
+        let region = rest.split(['.', '\n', ';']).next()?.trim();
+        (!region.is_empty() && !region.starts_with("your")).then(|| region.to_string())
+    })
+}
+
+/// 可用于 --geo-bypass-country 的两位国家代码（取报告地区中的第一个）
+pub fn geo_bypass_country(region: &str) -> Option<String> {
+    let code = region.split([',', ' ']).next()?.trim();
+    (code.len() == 2 && code.chars().all(|c| c.is_ascii_uppercase())).then(|| code.to_string())
+}
+
+/// 按 yt-dlp 标准错误输出判断错误类型
+pub fn classify_ytdlp_error(stderr: &str) -> ErrorKind {
+    if is_bot_detection_error(stderr) {
+        ErrorKind::BotDetection
+    } else if is_geo_blocked_error(stderr) {
+        ErrorKind::GeoBlocked
+    } else if stderr.contains("429") || stderr.contains("Too Many Requests") {
+        ErrorKind::RateLimited
+    } else if stderr.contains("cookies") || stderr.contains("login") {
+        ErrorKind::LoginRequired
+    } else if is_impersonation_error(stderr) {
+        ErrorKind::ImpersonationUnavailable
+    } else if stderr.contains("ERROR: [youtube]") {
+        ErrorKind::ExtractorFailed
+    } else {
+        ErrorKind::Unknown
+    }
+}
+
+/***************************************************************************
+ * 由 yt-dlp 错误输出生成结构化错误
+ *
+ * @param stderr - yt-dlp 标准错误输出
+ * @param ytdlp_path - 出错的 yt-dlp（用于给出对应安装环境的修复命令）
+ ***************************************************************************/
+
+pub fn ytdlp_error(stderr: &str, ytdlp_path: &Path) -> AppError {
+    let kind = classify_ytdlp_error(stderr);
+    let mut suggestion = kind.suggestion_key().map(|key| tr(key, &[]));
+
+    // 浏览器伪装：附加对应安装方式的修复命令
+    if kind == ErrorKind::ImpersonationUnavailable {
+        let install = detect_install(ytdlp_path);
+        let steps: Vec<String> = install
+            .fix_commands
+            .iter()
+            .enumerate()
+            .map(|(index, command)| {
+                tr(
+                    "error.impersonation_unavailable.step",
+                    &[("index", &(index + 1).to_string()), ("command", command)],
+                )
+            })
+            .collect();
+        suggestion = suggestion.map(|text| format!("{}\n{}", text, steps.join("\n")));
+    }
+
+    // 地区限制：附上 yt-dlp 报告的可观看地区
+    let region = (kind == ErrorKind::GeoBlocked).then(|| geo_available_region(stderr)).flatten();
+    if let Some(region) = &region {
+        let line = tr("error.geo_blocked.region", &[("region", region)]);
+        suggestion = suggestion.map(|text| format!("{}\n{}", text, line));
+    }
+
+    AppError {
+        kind,
+        message: tr("error.ytdlp_failed", &[("detail", stderr)]),
+        suggestion,
+        detail: stderr.to_string(),
+        region,
+    }
+}
@@ -0,0 +1,74 @@
+/****************************************************************************
+ *  events.rs - 前端事件名称
+ *
+ *  @brief  所有通过 AppHandle::emit 发送的事件名称
+ *  @note   前端使用 bindings.rs 生成的 events.ts 中的同名常量，
+ *          新增事件时同时在 bindings::event_table 中登记事件内容的类型
+ *****************************************************************************/
+
+/// 获取视频信息的提取步骤（InfoExtractionProgress）
+pub const INFO_EXTRACTION_PROGRESS: &str = "info-extraction-progress";
+
+/// 获取播放列表信息时解析出一个条目（PlaylistEntryParsed）
+pub const PLAYLIST_ENTRY_PARSED: &str = "playlist-entry-parsed";
+
+/// 下载进度（ProgressInfo）
+pub const DOWNLOAD_PROGRESS: &str = "download-progress";
+
+/// 合并、转码等后处理阶段的进度（PostProcessingProgress）
+pub const DOWNLOAD_POSTPROCESSING: &str = "download-postprocessing";
+
+/// 获取评论的进度（CommentsProgress）
+pub const DOWNLOAD_COMMENTS: &str = "download-comments";
+
+/// 下载被限速（DownloadThrottled）
+pub const DOWNLOAD_THROTTLED: &str = "download-throttled";
+
+/// 下载出错后即将重试（DownloadRetry）
+pub const DOWNLOAD_RETRY: &str = "download-retry";
+
+/// 暂存文件移动到下载目录失败（MoveFailed）
+pub const DOWNLOAD_MOVE_FAILED: &str = "download-move-failed";
+
+/// 输出文件校验未通过（DownloadWarning）
+pub const DOWNLOAD_WARNING: &str = "download-warning";
+
+/// yt-dlp 输出警告（YtdlpWarning），下载中的警告带 download_id；
+/// 不是错误，下载继续进行（如请求的格式不可用、已回退到其他格式）
+pub const YTDLP_WARNING: &str = "ytdlp-warning";
+
+/// 下载完成（DownloadComplete）
+pub const DOWNLOAD_COMPLETE: &str = "download-complete";
+
+/// 队列中的下载失败（DownloadFailed）
+pub const DOWNLOAD_FAILED: &str = "download-failed";
+
+/// 校验和计算进度（ChecksumProgress）
+pub const CHECKSUM_PROGRESS: &str = "checksum-progress";
+
+/// 队列整体进度（QueueProgress）
+pub const QUEUE_PROGRESS: &str = "queue-progress";
+
+/// 播放列表分组全部结束（BatchCompleted）
+pub const BATCH_COMPLETED: &str = "batch-completed";
+
+/// 网络恢复（NetworkRestored）
+pub const NETWORK_RESTORED: &str = "network-restored";
+
+/// 下载目录不可用，任务暂停等待（DestinationUnavailable）
+pub const DESTINATION_UNAVAILABLE: &str = "destination-unavailable";
+
+/// 下载目录恢复可用（DestinationRestored）
+pub const DESTINATION_RESTORED: &str = "destination-restored";
+
+/// 订阅频道有新视频（NewVideosFound）
+pub const NEW_VIDEOS_FOUND: &str = "new-videos-found";
+
+/// 启动时的依赖检查结果（DiagnosticsReport）
+pub const STARTUP_HEALTH: &str = "startup-health";
+
+/// 队列已暂停，不再启动新任务（QueuePauseChanged）
+pub const QUEUE_PAUSED: &str = "queue-paused";
+
+/// 队列已继续（QueuePauseChanged）
+pub const QUEUE_RESUMED: &str = "queue-resumed";
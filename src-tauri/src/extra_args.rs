@@ -0,0 +1,223 @@
+/****************************************************************************
+ *  extra_args.rs - 自定义 yt-dlp 参数
+ *
+ *  @brief  校验用户附加的原始 yt-dlp 参数，并找出覆盖了应用生成参数的选项
+ *  @note   自定义参数放在生成的参数（含站点预设）之后、URL 之前，
+ *          同名选项以后出现的为准，即自定义参数优先。
+ *          会执行外部命令、读取其他参数来源、改写参数（别名、后处理器参数）、
+ *          写入任意文件或更新 yt-dlp 本身的选项一律拒绝。
+ *          预览命令时隐藏 Cookie 文件、密码等敏感值，只保留选项名
+ *****************************************************************************/
+
+/// 不允许作为自定义参数的选项
+const DENIED_OPTIONS: &[&str] = &[
+    "--exec",
+    "--exec-before-download",
+    "--batch-file",
+    "--config-locations",
+    "--config-location",
+    "--ignore-config",
+    "--update",
+    "--update-to",
+    "--load-info-json",
+    "--netrc-cmd",
+    "--plugin-dirs",
+    "--use-postprocessor",
+    "--downloader",
+    "--external-downloader",
+    "--ffmpeg-location",
+    "--alias",
+    "--print-to-file",
+    "--postprocessor-args",
+    "--ppa",
+];
+
+/// 短选项与对应的长选项（比较是否覆盖时统一为长选项）
+const SHORT_OPTIONS: &[(&str, &str)] = &[
+    ("-a", "--batch-file"),
+    ("-U", "--update"),
+    ("-o", "--output"),
+    ("-P", "--paths"),
+    ("-f", "--format"),
+    ("-S", "--format-sort"),
+    ("-x", "--extract-audio"),
+    ("-k", "--keep-video"),
+    ("-r", "--limit-rate"),
+    ("-R", "--retries"),
+    ("-N", "--concurrent-fragments"),
+];
+
+/// 值需要在预览中隐藏的选项
+const SECRET_OPTIONS: &[&str] = &["--cookies", "--password", "--video-password", "--ap-password"];
+
+/// 隐藏后的占位文字
+const REDACTED: &str = "<已隐藏>";
+
+/***************************************************************************
+ * 选项名（短选项换成长选项）；不是选项时为 None
+ *
+ * 长选项去掉 "=值"；短选项只取前两个字符，值可以直接跟在后面（如 "-a/path"）
+ ***************************************************************************/
+
+fn option_name(arg: &str) -> Option<&str> {
+    if !arg.starts_with('-') || arg == "-" || arg == "--" {
+        return None;
+    }
+    let name = if arg.starts_with("--") {
+        arg.split('=').next().unwrap_or(arg)
+    } else {
+        arg.get(..2).unwrap_or(arg)
+    };
+    Some(
+        SHORT_OPTIONS
+            .iter()
+            .find(|(short, _)| *short == name)
+            .map_or(name, |(_, long)| *long),
+    )
+}
+
+/***************************************************************************
+ * 校验自定义参数
+ *
+ * @param args - 自定义参数（每项为一个参数，不做 shell 拆分）
+ ***************************************************************************/
+
+pub fn validate_extra_args(args: &[String]) -> Result<(), String> {
+    for arg in args {
+        if arg.trim().is_empty() {
+            return Err("自定义参数不能为空".to_string());
+        }
+        if let Some(name) = option_name(arg).filter(|name| DENIED_OPTIONS.contains(name)) {
+            return Err(format!("不允许使用自定义参数: {}", name));
+        }
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * 找出被自定义参数覆盖的生成参数
+ *
+ * @param generated - 应用生成的参数（不含 URL）
+ * @param extra - 自定义参数
+ * @return Vec<String> - 同时出现在两者中的选项名（长选项形式，不重复）
+ ***************************************************************************/
+
+pub fn overridden_options(generated: &[String], extra: &[String]) -> Vec<String> {
+    let mut overridden: Vec<String> = Vec::new();
+    for name in extra.iter().filter_map(|arg| option_name(arg)) {
+        let generated_too = generated.iter().any(|arg| option_name(arg) == Some(name));
+        if generated_too && !overridden.iter().any(|o| o == name) {
+            overridden.push(name.to_string());
+        }
+    }
+    overridden
+}
+
+/***************************************************************************
+ * 隐藏参数中的敏感值
+ *
+ * Cookie 文件、密码类选项的值，以及 "--add-header Cookie:..." 的值替换为占位文字，
+ * 选项名保留；"--选项=值" 和 "--选项 值" 两种写法都处理
+ *
+ * @param args - 完整参数
+ * @return (Vec<String>, Vec<String>) - (隐藏后的参数, 被隐藏值的选项名，不重复)
+ ***************************************************************************/
+
+pub fn redact_secrets(args: &[String]) -> (Vec<String>, Vec<String>) {
+    let is_secret = |name: &str, value: &str| {
+        SECRET_OPTIONS.contains(&name)
+            || (name == "--add-header" && value.trim_start().to_lowercase().starts_with("cookie:"))
+    };
+
+    let mut redacted_args = Vec::with_capacity(args.len());
+    let mut redacted: Vec<String> = Vec::new();
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        let Some(name) = option_name(arg) else {
+            redacted_args.push(arg.clone());
+            continue;
+        };
+        let hidden = match arg.split_once('=') {
+            Some((flag, value)) if is_secret(name, value) => {
+                redacted_args.push(format!("{}={}", flag, REDACTED));
+                true
+            }
+            Some(_) => {
+                redacted_args.push(arg.clone());
+                false
+            }
+            None => {
+                redacted_args.push(arg.clone());
+                match iter.peek() {
+                    Some(value) if is_secret(name, value) => {
+                        iter.next();
+                        redacted_args.push(REDACTED.to_string());
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        };
+        if hidden && !redacted.iter().any(|r| r == name) {
+            redacted.push(name.to_string());
+        }
+    }
+    (redacted_args, redacted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn short_options_with_attached_values_are_normalized() {
+        assert_eq!(option_name("-a/tmp/urls.txt"), Some("--batch-file"));
+        assert_eq!(option_name("-abatch.txt"), Some("--batch-file"));
+        assert_eq!(option_name("-o%(title)s.%(ext)s"), Some("--output"));
+        assert_eq!(option_name("-f=best"), Some("--format"));
+        assert_eq!(option_name("--format=best"), Some("--format"));
+        assert_eq!(option_name("-"), None);
+        assert_eq!(option_name("value"), None);
+    }
+
+    #[test]
+    fn denies_attached_short_option_values() {
+        let error = validate_extra_args(&args(&["-a/tmp/urls.txt"])).unwrap_err();
+        assert!(error.contains("--batch-file"));
+        assert!(validate_extra_args(&args(&["-Unightly"])).is_err());
+    }
+
+    #[test]
+    fn denies_options_that_run_commands_or_rewrite_arguments() {
+        for arg in [
+            "--alias",
+            "--alias=get-audio",
+            "--print-to-file",
+            "--print-to-file=%(id)s",
+            "--postprocessor-args",
+            "--postprocessor-args=ffmpeg:-i /etc/passwd",
+            "--ppa",
+            "--ppa=ffmpeg:-y",
+            "--exec=rm -rf ~",
+        ] {
+            assert!(validate_extra_args(&args(&[arg])).is_err(), "{} 应被拒绝", arg);
+        }
+    }
+
+    #[test]
+    fn allows_ordinary_options() {
+        assert!(validate_extra_args(&args(&["-o", "%(title)s.%(ext)s", "--embed-chapters", "-N4"])).is_ok());
+        assert!(validate_extra_args(&args(&[" "])).is_err());
+    }
+
+    #[test]
+    fn overridden_options_compare_normalized_names() {
+        let generated = args(&["-f", "bv*+ba", "--output", "%(title)s.%(ext)s"]);
+        let extra = args(&["-fbest", "-o%(id)s.%(ext)s", "--format=worst", "--embed-chapters"]);
+        assert_eq!(overridden_options(&generated, &extra), ["--format", "--output"]);
+    }
+}
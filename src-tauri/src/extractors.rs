@@ -0,0 +1,85 @@
+/****************************************************************************
+ *  extractors.rs - yt-dlp 支持的站点
+ *
+ *  @brief  缓存 yt-dlp --list-extractors 的结果（约 2000 行），按关键字筛选
+ *  @note   缓存与 yt-dlp 路径绑定，切换 yt-dlp 后重新获取
+ *****************************************************************************/
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::process::ytdlp_command;
+
+/// 通用提取器（任何网页都会匹配，不代表站点真正受支持）
+pub const GENERIC_EXTRACTOR: &str = "Generic";
+
+/***************************************************************************
+ * 提取器列表托管状态
+ ***************************************************************************/
+
+#[derive(Default)]
+pub struct ExtractorState {
+    cached: Mutex<Option<(PathBuf, Vec<String>)>>,
+}
+
+impl ExtractorState {
+    /***********************************************************************
+     * 获取提取器列表，首次调用（或 yt-dlp 路径变化）时运行 yt-dlp 并缓存
+     ***********************************************************************/
+    pub async fn get(&self, ytdlp_path: &Path) -> Result<Vec<String>, String> {
+        let mut cached = self.cached.lock().await;
+        if let Some((path, extractors)) = cached.as_ref() {
+            if path == ytdlp_path {
+                return Ok(extractors.clone());
+            }
+        }
+
+        let extractors = list_extractors(ytdlp_path).await?;
+        debug!("yt-dlp 共有 {} 个提取器", extractors.len());
+        *cached = Some((ytdlp_path.to_path_buf(), extractors.clone()));
+        Ok(extractors)
+    }
+}
+
+/***************************************************************************
+ * 运行 yt-dlp --list-extractors，每行一个提取器名称
+ ***************************************************************************/
+
+async fn list_extractors(ytdlp_path: &Path) -> Result<Vec<String>, String> {
+    let output = ytdlp_command(ytdlp_path)
+        .arg("--list-extractors")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 yt-dlp: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "获取支持的站点失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/***************************************************************************
+ * 按关键字筛选提取器（不区分大小写，空关键字返回全部）
+ ***************************************************************************/
+
+pub fn filter_extractors(extractors: &[String], filter: &str) -> Vec<String> {
+    let filter = filter.trim().to_lowercase();
+    extractors
+        .iter()
+        .filter(|name| filter.is_empty() || name.to_lowercase().contains(&filter))
+        .cloned()
+        .collect()
+}
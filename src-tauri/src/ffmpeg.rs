@@ -0,0 +1,196 @@
+/****************************************************************************
+ *  ffmpeg.rs - ffmpeg / ffprobe 定位与调用
+ *
+ *  @brief  查找 ffmpeg、ffprobe 可执行文件，用 ffprobe 读取媒体文件的基本信息，
+ *          以及合并失败后直接用 ffmpeg 重新合并已下载的音视频流
+ *  @note   查找顺序与 get_ytdlp_path 一致：PATH → 常见安装路径 → 应用同目录
+ *****************************************************************************/
+
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use ts_rs::TS;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct MediaProbe {
+    pub duration: Option<f64>,      // 容器时长（秒）
+    pub video_streams: usize,
+    pub audio_streams: usize,
+    pub streams: Vec<StreamInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct StreamInfo {
+    pub codec_type: String,         // video / audio / subtitle ...
+    pub codec_name: Option<String>, // 如 "h264"、"opus"
+    #[ts(type = "number | null")]
+    pub width: Option<i64>,
+    #[ts(type = "number | null")]
+    pub height: Option<i64>,
+}
+
+/// 查找 ffmpeg
+pub fn find_ffmpeg() -> Option<PathBuf> {
+    find_tool("ffmpeg")
+}
+
+/// 查找 ffprobe
+pub fn find_ffprobe() -> Option<PathBuf> {
+    find_tool("ffprobe")
+}
+
+/***************************************************************************
+ * 按名称查找可执行文件
+ ***************************************************************************/
+
+fn find_tool(name: &str) -> Option<PathBuf> {
+    let file_name = if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    };
+
+    // 1. PATH 环境变量
+    if let Some(path_var) = std::env::var_os("PATH") {
+        if let Some(path) = std::env::split_paths(&path_var)
+            .map(|dir| dir.join(&file_name))
+            .find(|path| path.is_file())
+        {
+            return Some(path);
+        }
+    }
+
+    // 2. 常见安装路径（GUI 应用在 macOS 上不继承 shell 的 PATH）
+    let common_dirs: &[&str] = if cfg!(target_os = "macos") {
+        &["/opt/homebrew/bin", "/usr/local/bin"]
+    } else if cfg!(target_os = "linux") {
+        &["/usr/bin", "/usr/local/bin", "/snap/bin"]
+    } else {
+        &["C:\\ProgramData\\chocolatey\\bin", "C:\\ffmpeg\\bin"]
+    };
+    if let Some(path) = common_dirs
+        .iter()
+        .map(|dir| Path::new(dir).join(&file_name))
+        .find(|path| path.is_file())
+    {
+        return Some(path);
+    }
+
+    // 3. 与应用可执行文件同目录（sidecar）
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    Some(exe_dir.join(&file_name)).filter(|path| path.is_file())
+}
+
+/***************************************************************************
+ * 用 ffprobe 读取媒体文件的时长和流数量
+ *
+ * @param ffprobe - ffprobe 路径
+ * @param path - 媒体文件
+ * @return MediaProbe - ffprobe 无法解析文件时返回错误
+ ***************************************************************************/
+
+pub async fn probe_media(ffprobe: &Path, path: &Path) -> Result<MediaProbe, String> {
+    let output = Command::new(ffprobe)
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration:stream=codec_type,codec_name,width,height",
+            "-of",
+            "json",
+        ])
+        .arg(path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 ffprobe: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "ffprobe 无法解析文件: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let json: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("ffprobe 输出解析失败: {}", e))?;
+
+    let streams: Vec<StreamInfo> = json["streams"]
+        .as_array()
+        .map(|streams| {
+            streams
+                .iter()
+                .map(|s| StreamInfo {
+                    codec_type: s["codec_type"].as_str().unwrap_or("unknown").to_string(),
+                    codec_name: s["codec_name"].as_str().map(String::from),
+                    width: s["width"].as_i64(),
+                    height: s["height"].as_i64(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let count_streams = |kind: &str| streams.iter().filter(|s| s.codec_type == kind).count();
+
+    Ok(MediaProbe {
+        // ffprobe 把时长输出为字符串
+        duration: json["format"]["duration"]
+            .as_str()
+            .and_then(|d| d.parse().ok()),
+        video_streams: count_streams("video"),
+        audio_streams: count_streams("audio"),
+        streams,
+    })
+}
+
+/***************************************************************************
+ * 合并音视频流（与 yt-dlp 的 Merger 相同，只重新封装不转码）
+ *
+ * 先写入 "name.temp.ext"，成功后改名为目标文件；容器由目标文件的扩展名决定，
+ * MP4 类容器附加 +faststart
+ *
+ * @param ffmpeg - ffmpeg 路径
+ * @param inputs - 已下载的各个流（按下载顺序）
+ * @param target - 合并后的文件
+ ***************************************************************************/
+
+pub async fn merge_streams(ffmpeg: &Path, inputs: &[String], target: &Path) -> Result<(), String> {
+    let ext = target
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .ok_or_else(|| format!("无法确定输出容器: {}", target.display()))?;
+    let temp_path = target.with_extension(format!("temp.{}", ext));
+
+    let mut command = Command::new(ffmpeg);
+    command.args(["-y", "-loglevel", "error"]);
+    for input in inputs {
+        command.arg("-i").arg(input);
+    }
+    command.args(["-c", "copy"]);
+    for index in 0..inputs.len() {
+        command.arg("-map").arg(index.to_string());
+    }
+    if matches!(ext.as_str(), "mp4" | "m4a" | "mov") {
+        command.args(["-movflags", "+faststart"]);
+    }
+
+    let output = command
+        .arg(&temp_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("无法执行 ffmpeg: {}", e))?;
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(format!("ffmpeg 合并失败: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    std::fs::rename(&temp_path, target).map_err(|e| format!("重命名合并结果失败: {}", e))
+}
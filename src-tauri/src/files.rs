@@ -0,0 +1,306 @@
+/****************************************************************************
+ *  files.rs - 已完成下载的文件操作
+ *
+ *  @brief  对历史记录中的输出文件及其附属文件执行删除、重命名、移动
+ *  @note   删除时移到系统回收站（trash crate），而不是永久删除；
+ *          同一条记录上的文件操作通过 FileLocks 串行执行
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+use ts_rs::TS;
+
+use crate::history::HistoryEntry;
+use crate::settings::validate_writable_dir;
+
+/// 跨设备复制时使用的临时后缀（复制完成后再重命名为最终文件名）
+const COPY_SUFFIX: &str = ".youtudown-moving";
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct FileOperationResult {
+    pub path: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl FileOperationResult {
+    fn ok(path: String) -> Self {
+        Self {
+            path,
+            success: true,
+            error: None,
+        }
+    }
+
+    fn failed(path: String, error: String) -> Self {
+        Self {
+            path,
+            success: false,
+            error: Some(error),
+        }
+    }
+}
+
+/***************************************************************************
+ * 按记录ID串行化文件操作（Tauri 托管状态）
+ *
+ * 同一条记录的重命名、移动、删除同时发起时，后来的请求等待前一个完成，
+ * 再基于更新后的记录执行，避免操作已经不存在的路径
+ ***************************************************************************/
+
+#[derive(Default)]
+pub struct FileLocks {
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl FileLocks {
+    pub fn lock_for(&self, id: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        locks.entry(id.to_string()).or_default().clone()
+    }
+}
+
+/// 历史记录关联的全部文件：输出文件在前，附属文件在后
+pub fn entry_files(entry: &HistoryEntry) -> Vec<String> {
+    entry
+        .output_path
+        .iter()
+        .chain(entry.sidecar_files.iter())
+        .cloned()
+        .collect()
+}
+
+/***************************************************************************
+ * 把历史记录关联的文件移到回收站
+ *
+ * 文件已不存在时视为已删除，不计为失败
+ *
+ * @param entry - 历史记录
+ * @return Vec<FileOperationResult> - 每个文件的处理结果
+ ***************************************************************************/
+
+pub fn trash_entry_files(entry: &HistoryEntry) -> Vec<FileOperationResult> {
+    entry_files(entry)
+        .into_iter()
+        .map(|path| {
+            if !Path::new(&path).exists() {
+                debug!("文件已不存在，视为已删除: {}", path);
+                return FileOperationResult::ok(path);
+            }
+
+            match trash::delete(&path) {
+                Ok(()) => {
+                    debug!("已移到回收站: {}", path);
+                    FileOperationResult::ok(path)
+                }
+                Err(e) => {
+                    warn!("移到回收站失败: {} ({})", path, e);
+                    FileOperationResult::failed(path, e.to_string())
+                }
+            }
+        })
+        .collect()
+}
+
+/***************************************************************************
+ * 规划重命名：主文件改名，附属文件随主文件名前缀一起改名
+ *
+ * 如 "旧标题.mp4" → "新标题.mp4" 时，"旧标题.en.srt" → "新标题.en.srt"
+ *
+ * @param entry - 历史记录
+ * @param new_name - 新文件名（不含扩展名时沿用原扩展名，扩展名必须一致）
+ * @return Vec<(PathBuf, PathBuf)> - (原路径, 新路径)，主文件在第一个
+ ***************************************************************************/
+
+pub fn plan_rename(
+    entry: &HistoryEntry,
+    new_name: &str,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let output = output_path(entry)?;
+    let new_name = new_name.trim();
+    if new_name.is_empty() || new_name == "." || new_name == ".." || new_name.contains(['/', '\\'])
+    {
+        return Err(format!("无效的文件名: {}", new_name));
+    }
+
+    let old_stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = output.extension().map(|e| e.to_string_lossy().into_owned());
+
+    // 新文件名带扩展名时必须与原扩展名一致，否则把整个名称视为主干
+    let new_stem = match (&extension, Path::new(new_name).extension()) {
+        (Some(ext), Some(new_ext)) if new_ext.to_string_lossy().eq_ignore_ascii_case(ext) => {
+            Path::new(new_name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        }
+        (Some(ext), Some(new_ext)) if is_media_extension(&new_ext.to_string_lossy()) => {
+            return Err(format!(
+                "不能修改扩展名: .{} → .{}",
+                ext,
+                new_ext.to_string_lossy()
+            ));
+        }
+        _ => new_name.to_string(),
+    };
+
+    let mut plan = vec![(
+        output.clone(),
+        output.with_file_name(with_extension(&new_stem, extension.as_deref())),
+    )];
+    for sidecar in &entry.sidecar_files {
+        let sidecar = PathBuf::from(sidecar);
+        let Some(name) = sidecar
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+        if let Some(suffix) = name.strip_prefix(&old_stem) {
+            let renamed = sidecar.with_file_name(format!("{}{}", new_stem, suffix));
+            plan.push((sidecar, renamed));
+        }
+    }
+
+    Ok(plan)
+}
+
+/***************************************************************************
+ * 规划移动：主文件及附属文件移到新目录，文件名不变
+ ***************************************************************************/
+
+pub fn plan_move(entry: &HistoryEntry, new_dir: &Path) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    validate_writable_dir(new_dir).map_err(|e| format!("目标目录无效: {}", e))?;
+
+    let output = output_path(entry)?;
+    std::iter::once(output)
+        .chain(entry.sidecar_files.iter().map(PathBuf::from))
+        .map(|path| {
+            let name = path
+                .file_name()
+                .ok_or_else(|| format!("无效的文件路径: {}", path.display()))?
+                .to_owned();
+            Ok((path.clone(), new_dir.join(name)))
+        })
+        .collect()
+}
+
+/// 记录的主文件路径（必须存在）
+fn output_path(entry: &HistoryEntry) -> Result<PathBuf, String> {
+    let path = entry
+        .output_path
+        .as_ref()
+        .map(PathBuf::from)
+        .ok_or_else(|| "该记录没有输出文件".to_string())?;
+    if !path.exists() {
+        return Err(format!("文件不存在: {}", path.display()));
+    }
+    Ok(path)
+}
+
+fn with_extension(stem: &str, extension: Option<&str>) -> String {
+    match extension {
+        Some(ext) => format!("{}.{}", stem, ext),
+        None => stem.to_string(),
+    }
+}
+
+/// 常见媒体扩展名（用于区分"改了扩展名"和"文件名里恰好有点"）
+fn is_media_extension(ext: &str) -> bool {
+    const MEDIA_EXTENSIONS: &[&str] = &[
+        "mp4", "mkv", "webm", "m4a", "mp3", "opus", "ogg", "flac", "wav", "mov", "avi", "flv",
+        "aac",
+    ];
+    MEDIA_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+}
+
+/***************************************************************************
+ * 执行重命名/移动计划
+ *
+ * 未指定 force 时目标已存在即报错（在移动任何文件之前检查）；
+ * 主文件失败时整体失败，附属文件失败只记录在结果中
+ *
+ * @param plan - plan_rename / plan_move 的结果
+ * @param force - 是否覆盖已存在的目标文件
+ * @return Vec<FileOperationResult> - 每个文件的处理结果（path 为新路径）
+ ***************************************************************************/
+
+pub fn relocate(
+    plan: &[(PathBuf, PathBuf)],
+    force: bool,
+) -> Result<Vec<FileOperationResult>, String> {
+    if !force {
+        if let Some((_, target)) = plan
+            .iter()
+            .find(|(source, target)| source != target && target.exists())
+        {
+            return Err(format!("目标文件已存在: {}", target.display()));
+        }
+    }
+
+    let mut results = Vec::with_capacity(plan.len());
+    for (index, (source, target)) in plan.iter().enumerate() {
+        let target_display = target.to_string_lossy().into_owned();
+        if source == target {
+            results.push(FileOperationResult::ok(target_display));
+            continue;
+        }
+
+        match move_file(source, target) {
+            Ok(()) => {
+                debug!("已移动: {} -> {}", source.display(), target.display());
+                results.push(FileOperationResult::ok(target_display));
+            }
+            Err(e) if index == 0 => {
+                return Err(format!("移动文件失败: {} ({})", source.display(), e));
+            }
+            Err(e) => {
+                warn!("移动附属文件失败: {} ({})", source.display(), e);
+                results.push(FileOperationResult::failed(target_display, e.to_string()));
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/***************************************************************************
+ * 移动单个文件
+ *
+ * 优先直接重命名；跨设备（源与目标不在同一磁盘）时重命名会失败，
+ * 此时先复制为临时文件名，再在目标目录内重命名，最后删除源文件，
+ * 保证目标目录中不会出现写了一半的文件
+ ***************************************************************************/
+
+pub fn move_file(source: &Path, target: &Path) -> io::Result<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    match fs::rename(source, target) {
+        Ok(()) => return Ok(()),
+        Err(e) => debug!("重命名失败，改为复制: {}", e),
+    }
+
+    let mut partial = target.as_os_str().to_owned();
+    partial.push(COPY_SUFFIX);
+    let partial = PathBuf::from(partial);
+
+    if let Err(e) = fs::copy(source, &partial).and_then(|_| fs::rename(&partial, target)) {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::remove_file(source)
+}
@@ -0,0 +1,286 @@
+/****************************************************************************
+ *  history.rs - 下载历史
+ *
+ *  @brief  记录每次下载的结果（成功或失败），持久化到应用数据目录
+ *  @note   与 settings.rs 相同，使用 JSON 文件 + 托管状态；
+ *          频道"只下载新视频"的上次运行时间保存在同目录的 channel_runs.json
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::warn;
+use ts_rs::TS;
+
+use crate::checksum::Checksum;
+use crate::downloads::{unix_millis, DownloadStatus};
+use crate::playlist_items::PartialSuccess;
+
+/// 历史记录文件名
+pub const HISTORY_FILE: &str = "history.json";
+
+/// 频道上次运行时间文件名（与历史记录同目录）
+const CHANNEL_RUNS_FILE: &str = "channel_runs.json";
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct HistoryEntry {
+    pub id: String,                 // 下载任务ID
+    pub url: String,
+    pub status: DownloadStatus,     // 最终状态（Completed / Failed / Cancelled）
+    #[ts(type = "number | null")]
+    pub total_bytes: Option<u64>,   // 最终下载的字节数
+    #[serde(default)]
+    pub output_path: Option<String>, // 最终输出文件
+    #[serde(default)]
+    pub sidecar_files: Vec<String>, // 附属文件（字幕、info.json、缩略图等）
+    pub error: Option<String>,      // 失败原因
+    #[ts(type = "number")]
+    pub finished_at: u64,           // 结束时间（Unix 毫秒）
+    #[serde(default)]
+    pub verified: Option<bool>,     // 输出文件校验结果（未校验时为 None）
+    #[serde(default)]
+    pub suspect: bool,              // 进程正常退出但校验失败，文件可能不完整
+    #[serde(default)]
+    pub verification_issues: Vec<String>,
+    #[serde(default)]
+    pub checksum: Option<Checksum>, // 输出文件的校验和（计算后写入）
+    #[serde(default)]
+    pub warnings: u32,              // 下载过程中 yt-dlp 输出的警告数
+    #[serde(default)]
+    pub output_dir: Option<String>, // 下载目录（任务指定的目录或默认下载目录）
+    #[serde(default)]
+    pub partial: Option<PartialSuccess>, // 播放列表部分成功时各条目的结果（状态为 PartialSuccess）
+    #[serde(default)]
+    pub pending_merge: Option<PendingMerge>, // 合并失败时保留的音视频流（可用 retry_postprocess 重新合并）
+}
+
+/// 下载完成但合并失败、等待重新合并的文件
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PendingMerge {
+    pub streams: Vec<String>,       // 已下载的各个流（按下载顺序）
+    pub target: String,             // 合并后的文件（暂存模式下为下载目录中的路径）
+}
+
+impl HistoryEntry {
+    /// 以结束时间为当前时间创建记录，其余字段为空
+    pub fn new(id: &str, url: &str, status: DownloadStatus, files: OutputFiles) -> Self {
+        Self {
+            id: id.to_string(),
+            url: url.to_string(),
+            status,
+            total_bytes: None,
+            output_path: files.output_path,
+            sidecar_files: files.sidecar_files,
+            error: None,
+            finished_at: unix_millis(),
+            verified: None,
+            suspect: false,
+            verification_issues: Vec::new(),
+            checksum: None,
+            warnings: 0,
+            output_dir: None,
+            partial: None,
+            pending_merge: None,
+        }
+    }
+}
+
+/// 一次下载产生的文件
+#[derive(Debug, Clone, Default)]
+pub struct OutputFiles {
+    pub output_path: Option<String>,
+    pub sidecar_files: Vec<String>,
+}
+
+/***************************************************************************
+ * 历史记录托管状态
+ ***************************************************************************/
+
+pub struct HistoryStore {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<HistoryEntry>>,
+    channel_runs: Mutex<HashMap<String, u64>>, // 频道链接 → 上次只下载新视频的时间（Unix 毫秒）
+}
+
+impl HistoryStore {
+    /***********************************************************************
+     * 从历史文件加载，文件不存在或损坏时从空记录开始
+     ***********************************************************************/
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read_to_string(p) {
+                Ok(content) => serde_json::from_str(&content)
+                    .map_err(|e| warn!("历史记录解析失败，从空记录开始: {}", e))
+                    .ok(),
+                Err(e) => {
+                    warn!("读取历史记录失败，从空记录开始: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        let channel_runs = path
+            .as_ref()
+            .map(|p| p.with_file_name(CHANNEL_RUNS_FILE))
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read_to_string(p) {
+                Ok(content) => serde_json::from_str(&content)
+                    .map_err(|e| warn!("频道运行记录解析失败，从空记录开始: {}", e))
+                    .ok(),
+                Err(e) => {
+                    warn!("读取频道运行记录失败，从空记录开始: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            entries: Mutex::new(entries),
+            channel_runs: Mutex::new(channel_runs),
+        }
+    }
+
+    /// 获取全部历史记录（最新的在前）
+    pub fn list(&self) -> Vec<HistoryEntry> {
+        let mut entries = self
+            .entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default();
+        entries.reverse();
+        entries
+    }
+
+    /// 全部等待重新合并的音视频流（清理残留文件时需要保留）
+    pub fn pending_merge_streams(&self) -> Vec<String> {
+        let Ok(entries) = self.entries.lock() else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .filter_map(|e| e.pending_merge.as_ref())
+            .flat_map(|pending| pending.streams.iter().cloned())
+            .collect()
+    }
+
+    /// 按ID查找记录
+    pub fn get(&self, id: &str) -> Option<HistoryEntry> {
+        let entries = self.entries.lock().ok()?;
+        entries.iter().find(|e| e.id == id).cloned()
+    }
+
+    /// 按输出文件路径查找记录
+    pub fn find_by_output_path(&self, path: &str) -> Option<HistoryEntry> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .iter()
+            .find(|e| e.output_path.as_deref() == Some(path))
+            .cloned()
+    }
+
+    /// 保存记录的校验和
+    pub fn set_checksum(&self, id: &str, checksum: Checksum) {
+        if let Some(mut entry) = self.get(id) {
+            entry.checksum = Some(checksum);
+            self.update(entry);
+        }
+    }
+
+    /***********************************************************************
+     * 原位更新一条记录并保存（不改变记录顺序）
+     ***********************************************************************/
+    pub fn update(&self, entry: HistoryEntry) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        let Some(existing) = entries.iter_mut().find(|e| e.id == entry.id) else {
+            return;
+        };
+        *existing = entry;
+
+        if let Err(e) = self.save(&entries) {
+            warn!("保存历史记录失败: {}", e);
+        }
+    }
+
+    /***********************************************************************
+     * 删除一条记录并保存
+     *
+     * @return bool - 记录是否存在
+     ***********************************************************************/
+    pub fn remove(&self, id: &str) -> bool {
+        let Ok(mut entries) = self.entries.lock() else {
+            return false;
+        };
+        let before = entries.len();
+        entries.retain(|e| e.id != id);
+        if entries.len() == before {
+            return false;
+        }
+
+        if let Err(e) = self.save(&entries) {
+            warn!("保存历史记录失败: {}", e);
+        }
+        true
+    }
+
+    /***********************************************************************
+     * 追加一条记录并保存（同一ID的旧记录会被替换）
+     ***********************************************************************/
+    pub fn record(&self, entry: HistoryEntry) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|e| e.id != entry.id);
+        entries.push(entry);
+
+        if let Err(e) = self.save(&entries) {
+            warn!("保存历史记录失败: {}", e);
+        }
+    }
+
+    /// 频道上次只下载新视频的时间（从未运行时为 None）
+    pub fn channel_last_run(&self, channel_url: &str) -> Option<u64> {
+        let runs = self.channel_runs.lock().ok()?;
+        runs.get(channel_url).copied()
+    }
+
+    /// 记录频道本次只下载新视频的时间并保存
+    pub fn record_channel_run(&self, channel_url: &str, at: u64) {
+        let Ok(mut runs) = self.channel_runs.lock() else {
+            return;
+        };
+        runs.insert(channel_url.to_string(), at);
+
+        let Some(path) = self.path.as_ref().map(|p| p.with_file_name(CHANNEL_RUNS_FILE)) else {
+            return;
+        };
+        let result = serde_json::to_string_pretty(&*runs)
+            .map_err(|e| format!("序列化频道运行记录失败: {}", e))
+            .and_then(|content| fs::write(&path, content).map_err(|e| format!("写入频道运行记录失败: {}", e)));
+        if let Err(e) = result {
+            warn!("保存频道运行记录失败: {}", e);
+        }
+    }
+
+    fn save(&self, entries: &[HistoryEntry]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建数据目录: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("序列化历史记录失败: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("写入历史记录失败: {}", e))
+    }
+}
@@ -0,0 +1,182 @@
+/****************************************************************************
+ *  i18n.rs - 错误信息本地化
+ *
+ *  @brief  按当前语言把消息键翻译为内置的 zh-CN / en 文本
+ *  @note   语言由设置中的 locale 决定，未设置时跟随系统语言；
+ *          当前语言缺少某个键时回退到英文，英文也没有时原样返回键名，不会 panic。
+ *          新增消息时在 ZH_CN 和 EN 两张表中同时添加
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use ts_rs::TS;
+
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub enum Locale {
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en")]
+    En,
+}
+
+/// 当前语言（启动时和修改设置时更新）
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::En);
+
+/***************************************************************************
+ * 翻译表：(消息键, 文本)，文本中的 {name} 由调用方替换
+ ***************************************************************************/
+
+const ZH_CN: &[(&str, &str)] = &[
+    ("error.ytdlp_failed", "yt-dlp 执行失败: {detail}"),
+    ("error.suggestion_heading", "🔧 解决方案"),
+    (
+        "error.bot_detection.suggestion",
+        "1. 确保您的 Chrome 浏览器已登录 YouTube\n\
+         2. 尝试使用不同的视频链接\n\
+         3. 在高级设置中调整反检测选项\n\
+         4. 如果问题持续，请等待一段时间后重试",
+    ),
+    (
+        "error.geo_blocked.suggestion",
+        "1. 在设置中填写位于可观看地区的代理（proxy）\n\
+         2. 或开启位于该地区的 VPN 后重试\n\
+         3. 报告了可观看地区时，获取信息会自动使用 --geo-bypass-country 重试一次",
+    ),
+    ("error.geo_blocked.region", "yt-dlp 报告的可观看地区: {region}"),
+    (
+        "error.rate_limited.suggestion",
+        "1. 在高级设置中增加请求间隔时间\n\
+         2. 等待几分钟后重试\n\
+         3. 尝试使用代理连接",
+    ),
+    (
+        "error.login_required.suggestion",
+        "1. 确保浏览器中已登录相应账号\n\
+         2. 检查浏览器 Cookie 权限\n\
+         3. 尝试手动导出 Cookie 文件",
+    ),
+    ("error.impersonation_unavailable.suggestion", "浏览器伪装需要 curl_cffi"),
+    ("error.impersonation_unavailable.step", "{index}. 请运行: {command}"),
+    (
+        "error.extractor_failed.suggestion",
+        "1. 检查视频链接是否正确\n\
+         2. 尝试刷新网页获取最新链接\n\
+         3. 视频可能受地区限制或已被删除",
+    ),
+];
+
+const EN: &[(&str, &str)] = &[
+    ("error.ytdlp_failed", "yt-dlp failed: {detail}"),
+    ("error.suggestion_heading", "🔧 Suggestions"),
+    (
+        "error.bot_detection.suggestion",
+        "1. Make sure you are signed in to YouTube in Chrome\n\
+         2. Try a different video link\n\
+         3. Adjust the anti-detection options in advanced settings\n\
+         4. If the problem persists, wait a while and try again",
+    ),
+    (
+        "error.geo_blocked.suggestion",
+        "1. Set a proxy in settings that is located where the video is available\n\
+         2. Or connect to a VPN in that region and try again\n\
+         3. When yt-dlp reports the available region, fetching info retries once with --geo-bypass-country",
+    ),
+    ("error.geo_blocked.region", "Regions reported as available by yt-dlp: {region}"),
+    (
+        "error.rate_limited.suggestion",
+        "1. Increase the sleep interval in advanced settings\n\
+         2. Wait a few minutes and try again\n\
+         3. Try connecting through a proxy",
+    ),
+    (
+        "error.login_required.suggestion",
+        "1. Make sure you are signed in to the site in your browser\n\
+         2. Check the browser's cookie permissions\n\
+         3. Try exporting a cookies file manually",
+    ),
+    ("error.impersonation_unavailable.suggestion", "Browser impersonation requires curl_cffi"),
+    ("error.impersonation_unavailable.step", "{index}. Run: {command}"),
+    (
+        "error.extractor_failed.suggestion",
+        "1. Check that the video link is correct\n\
+         2. Refresh the web page to get an up-to-date link\n\
+         3. The video may be region-restricted or removed",
+    ),
+];
+
+/// 按设置更新当前语言（未设置时跟随系统）
+pub fn configure(settings: &Settings) {
+    set_locale(settings.locale.unwrap_or_else(system_locale));
+}
+
+pub fn set_locale(locale: Locale) {
+    if let Ok(mut current) = LOCALE.write() {
+        *current = locale;
+    }
+}
+
+pub fn locale() -> Locale {
+    LOCALE.read().map(|locale| *locale).unwrap_or(Locale::En)
+}
+
+/***************************************************************************
+ * 系统语言
+ *
+ * 依次读取 LC_ALL、LC_MESSAGES、LANG；从 Finder 启动的 macOS 应用没有这些变量，
+ * 再读取系统偏好中的 AppleLocale。中文（zh_*）使用 zh-CN，其余使用英文
+ ***************************************************************************/
+
+pub fn system_locale() -> Locale {
+    let from_env = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX");
+    let name = from_env.or_else(apple_locale).unwrap_or_default();
+
+    if name.to_lowercase().starts_with("zh") {
+        Locale::ZhCn
+    } else {
+        Locale::En
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn apple_locale() -> Option<String> {
+    let output = std::process::Command::new("defaults")
+        .args(["read", "-g", "AppleLocale"])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn apple_locale() -> Option<String> {
+    None
+}
+
+/***************************************************************************
+ * 翻译消息键
+ *
+ * @param key - 消息键
+ * @param args - 替换文本中的 {name} 占位符
+ * @return String - 当前语言的文本（缺少时回退到英文，再回退到键名）
+ ***************************************************************************/
+
+pub fn tr(key: &str, args: &[(&str, &str)]) -> String {
+    let table = match locale() {
+        Locale::ZhCn => ZH_CN,
+        Locale::En => EN,
+    };
+    let lookup = |table: &[(&str, &'static str)]| table.iter().find(|(k, _)| *k == key).map(|(_, text)| *text);
+    let mut text = lookup(table).or_else(|| lookup(EN)).unwrap_or(key).to_string();
+
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
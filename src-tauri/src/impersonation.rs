@@ -0,0 +1,336 @@
+/****************************************************************************
+ *  impersonation.rs - 浏览器伪装（--impersonate）支持检测
+ *
+ *  @brief  解析 yt-dlp --list-impersonate-targets 输出，判断伪装是否可用
+ *  @note   伪装依赖 curl_cffi，未安装时传入 --impersonate 会直接失败，
+ *          因此检测结果缓存在托管状态中，供获取信息和下载时决定是否附加该参数；
+ *          同时识别 yt-dlp 的安装方式，给出对应环境的 curl_cffi 安装命令
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+use ts_rs::TS;
+
+use crate::process::{is_module_interpreter, ytdlp_command};
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ImpersonateTarget {
+    pub client: String,             // 如 "Chrome-124"
+    pub os: String,                 // 如 "Macos-14"
+    pub source: String,             // 提供者（如 "curl_cffi"）
+    pub available: bool,            // 当前环境是否可用
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ImpersonationSupport {
+    pub supported: bool,            // 至少有一个可用目标
+    pub targets: Vec<ImpersonateTarget>,
+    pub install: YtdlpInstall,      // yt-dlp 安装方式及安装 curl_cffi 的命令
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallMethod {
+    Homebrew,                       // brew install yt-dlp（独立虚拟环境）
+    Pipx,                           // pipx install yt-dlp
+    Pip,                            // pip 安装的脚本，解释器来自 shebang
+    Standalone,                     // 官方发布的独立可执行文件（已内置 curl_cffi）
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct YtdlpInstall {
+    pub method: InstallMethod,
+    pub ytdlp_path: String,
+    pub python: Option<String>,     // 运行 yt-dlp 的 Python 解释器（能确定时）
+    pub fix_commands: Vec<String>,  // 安装 curl_cffi 的命令，按推荐顺序
+}
+
+impl ImpersonationSupport {
+    /***********************************************************************
+     * 判断请求的伪装目标是否可用
+     *
+     * @param target - 如 "chrome"、"chrome-120"（不区分大小写，按前缀匹配）
+     ***********************************************************************/
+    pub fn supports(&self, target: &str) -> bool {
+        let target = target.to_lowercase();
+        self.targets
+            .iter()
+            .filter(|t| t.available)
+            .any(|t| t.client.to_lowercase().starts_with(&target))
+    }
+}
+
+/***************************************************************************
+ * 伪装支持缓存（Tauri 托管状态）
+ ***************************************************************************/
+
+#[derive(Default)]
+pub struct ImpersonationState {
+    cached: Mutex<Option<ImpersonationSupport>>,
+}
+
+impl ImpersonationState {
+    /***********************************************************************
+     * 获取伪装支持情况，首次调用时探测并缓存
+     ***********************************************************************/
+    pub async fn get(&self, ytdlp_path: &Path) -> ImpersonationSupport {
+        let mut cached = self.cached.lock().await;
+        if let Some(support) = cached.as_ref() {
+            return support.clone();
+        }
+
+        let support = probe(ytdlp_path).await;
+        *cached = Some(support.clone());
+        support
+    }
+
+    /***********************************************************************
+     * 重新探测并刷新缓存（用户安装 curl_cffi 后调用）
+     ***********************************************************************/
+    pub async fn refresh(&self, ytdlp_path: &Path) -> ImpersonationSupport {
+        let support = probe(ytdlp_path).await;
+        *self.cached.lock().await = Some(support.clone());
+        support
+    }
+}
+
+/***************************************************************************
+ * 运行 yt-dlp --list-impersonate-targets 并解析
+ *
+ * 旧版 yt-dlp 不认识该参数，执行失败时视为不支持伪装
+ ***************************************************************************/
+
+async fn probe(ytdlp_path: &Path) -> ImpersonationSupport {
+    let output = ytdlp_command(ytdlp_path)
+        .arg("--list-impersonate-targets")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await;
+
+    let targets = match output {
+        Ok(output) if output.status.success() => {
+            parse_impersonate_targets(&String::from_utf8_lossy(&output.stdout))
+        }
+        Ok(output) => {
+            warn!(
+                "列出伪装目标失败: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            Vec::new()
+        }
+        Err(e) => {
+            warn!("无法执行 yt-dlp --list-impersonate-targets: {}", e);
+            Vec::new()
+        }
+    };
+
+    let supported = targets.iter().any(|t| t.available);
+    debug!("伪装支持: {}，共 {} 个目标", supported, targets.len());
+
+    ImpersonationSupport {
+        supported,
+        targets,
+        install: detect_install(ytdlp_path),
+    }
+}
+
+/***************************************************************************
+ * 判断 yt-dlp 的错误是否由伪装不可用（缺少 curl_cffi）引起
+ ***************************************************************************/
+
+pub fn is_impersonation_error(stderr: &str) -> bool {
+    (stderr.contains("Impersonate target") && stderr.contains("not available"))
+        || stderr.contains("curl_cffi")
+}
+
+/***************************************************************************
+ * 识别 yt-dlp 的安装方式
+ *
+ * 1. 路径位于 Homebrew Cellar / pipx 虚拟环境中
+ * 2. 文件是带 shebang 的 Python 脚本（pip 安装），解释器即 shebang 指向的 Python
+ * 3. Windows 下 pip 生成的 Scripts\yt-dlp.exe，解释器在上一级目录
+ * 4. 其余可执行文件视为官方独立版本
+ * 以 Python 模块方式运行时 ytdlp_path 即解释器，视为 pip 安装
+ *
+ * @param ytdlp_path - get_ytdlp_path 找到的路径
+ ***************************************************************************/
+
+pub fn detect_install(ytdlp_path: &Path) -> YtdlpInstall {
+    let resolved = ytdlp_path
+        .canonicalize()
+        .unwrap_or_else(|_| ytdlp_path.to_path_buf());
+    let resolved_str = resolved.to_string_lossy().replace('\\', "/");
+
+    let (method, python) = if is_module_interpreter(ytdlp_path) {
+        (InstallMethod::Pip, Some(ytdlp_path.to_string_lossy().into_owned()))
+    } else if let Some(prefix) = resolved_str
+        .find("/Cellar/yt-dlp/")
+        .and_then(|index| homebrew_prefix(&resolved_str, index))
+    {
+        (InstallMethod::Homebrew, Some(format!("{}/libexec/bin/python", prefix)))
+    } else if resolved_str.contains("/pipx/venvs/") {
+        (InstallMethod::Pipx, None)
+    } else if let Some(python) = script_interpreter(&resolved) {
+        (InstallMethod::Pip, Some(python))
+    } else if let Some(python) = windows_scripts_python(&resolved) {
+        (InstallMethod::Pip, Some(python.to_string_lossy().into_owned()))
+    } else if resolved.is_file() {
+        (InstallMethod::Standalone, None)
+    } else {
+        (InstallMethod::Unknown, None)
+    };
+
+    let fix_commands = fix_commands(method, python.as_deref(), &ytdlp_path.to_string_lossy());
+    debug!("yt-dlp 安装方式: {:?} ({:?})", method, python);
+
+    YtdlpInstall {
+        method,
+        ytdlp_path: ytdlp_path.to_string_lossy().into_owned(),
+        python,
+        fix_commands,
+    }
+}
+
+/// Cellar 中的版本目录，如 "/opt/homebrew/Cellar/yt-dlp/2024.08.06"
+fn homebrew_prefix(path: &str, cellar_index: usize) -> Option<String> {
+    let version_start = cellar_index + "/Cellar/yt-dlp/".len();
+    let version_len = path[version_start..].find('/')?;
+    Some(path[..version_start + version_len].to_string())
+}
+
+/***************************************************************************
+ * 读取脚本的 shebang 解释器
+ *
+ * "#!/usr/bin/env python3" 形式返回 "python3"；不是脚本时返回 None
+ ***************************************************************************/
+
+fn script_interpreter(path: &Path) -> Option<String> {
+    let mut head = [0u8; 256];
+    let read = File::open(path).ok()?.read(&mut head).ok()?;
+    let head = std::str::from_utf8(&head[..read]).ok()?;
+    let shebang = head.strip_prefix("#!")?.lines().next()?.trim();
+
+    let mut parts = shebang.split_whitespace();
+    let interpreter = parts.next()?;
+    if interpreter.ends_with("/env") {
+        return parts.find(|part| !part.starts_with('-')).map(str::to_string);
+    }
+    Some(interpreter.to_string())
+}
+
+/// Windows 下 pip 生成的启动器位于 <Python>\Scripts\yt-dlp.exe
+fn windows_scripts_python(path: &Path) -> Option<PathBuf> {
+    let scripts = path.parent()?;
+    if !scripts.file_name()?.to_string_lossy().eq_ignore_ascii_case("Scripts") {
+        return None;
+    }
+    let python = scripts.parent()?.join("python.exe");
+    python.is_file().then_some(python)
+}
+
+/// 按安装方式生成安装 curl_cffi 的命令
+fn fix_commands(method: InstallMethod, python: Option<&str>, ytdlp_path: &str) -> Vec<String> {
+    let pip_install = |python: &str| {
+        vec![
+            format!("\"{}\" -m pip install --upgrade \"yt-dlp[default,curl-cffi]\"", python),
+            format!("\"{}\" -m pip install curl_cffi", python),
+        ]
+    };
+
+    match (method, python) {
+        (InstallMethod::Homebrew, Some(python)) => {
+            let mut commands = vec!["brew upgrade yt-dlp".to_string()];
+            commands.extend(pip_install(python).into_iter().skip(1));
+            commands
+        }
+        (InstallMethod::Pipx, _) => vec![
+            "pipx inject yt-dlp curl_cffi".to_string(),
+            "pipx install --force \"yt-dlp[default,curl-cffi]\"".to_string(),
+        ],
+        (InstallMethod::Pip, Some(python)) => pip_install(python),
+        // 官方独立版本已内置 curl_cffi，旧版本更新即可
+        (InstallMethod::Standalone, _) => vec![format!("\"{}\" -U", ytdlp_path)],
+        _ => pip_install(if cfg!(windows) { "python" } else { "python3" }),
+    }
+}
+
+/***************************************************************************
+ * 解析伪装目标表格
+ *
+ * 输出示例:
+ * [info] Available impersonate targets
+ * Client      OS          Source
+ * ---------------------------------------
+ * Chrome-124  Macos-14    curl_cffi
+ * Chrome-99   Windows-10  curl_cffi (unavailable)
+ ***************************************************************************/
+
+pub fn parse_impersonate_targets(stdout: &str) -> Vec<ImpersonateTarget> {
+    stdout
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("---"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let client = columns.next()?.to_string();
+            let os = columns.next()?.to_string();
+            let source = columns.collect::<Vec<_>>().join(" ");
+            let available = !source.contains("unavailable") && !source.contains("not available");
+            Some(ImpersonateTarget {
+                client,
+                os,
+                source,
+                available,
+            })
+        })
+        .collect()
+}
+
+/***************************************************************************
+ * 按伪装支持情况过滤参数
+ *
+ * 请求的目标不可用时，移除 --impersonate 及其取值，避免 yt-dlp 直接报错
+ *
+ * @param args - 原始参数
+ * @param support - 伪装支持情况
+ * @return Vec<String> - 过滤后的参数
+ ***************************************************************************/
+
+pub fn filter_impersonate_args(args: Vec<String>, support: &ImpersonationSupport) -> Vec<String> {
+    let mut filtered = Vec::with_capacity(args.len());
+    let mut iter = args.into_iter();
+
+    while let Some(arg) = iter.next() {
+        if arg == "--impersonate" {
+            match iter.next() {
+                Some(target) if support.supports(&target) => {
+                    filtered.push(arg);
+                    filtered.push(target);
+                }
+                Some(target) => debug!("伪装目标 {} 不可用，已移除 --impersonate", target),
+                None => {}
+            }
+        } else if let Some(target) = arg.strip_prefix("--impersonate=") {
+            if support.supports(target) {
+                filtered.push(arg);
+            } else {
+                debug!("伪装目标 {} 不可用，已移除 --impersonate", target);
+            }
+        } else {
+            filtered.push(arg);
+        }
+    }
+
+    filtered
+}
@@ -0,0 +1,89 @@
+/****************************************************************************
+ *  json_lines.rs - yt-dlp JSON 输出解析
+ *
+ *  @brief  解析 --dump-json 的输出：通常每行一个 JSON 对象，
+ *          但也兼容跨多行（格式化输出）的 JSON，并统计无法解析的行数
+ *  @note   播放列表会额外输出 "_type": "playlist" 的列表信息，
+ *          选择视频条目时跳过这类对象
+ *****************************************************************************/
+
+use serde_json::Value;
+use tracing::debug;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct JsonLines {
+    pub values: Vec<Value>,         // 按出现顺序解析出的对象
+    pub failed_lines: usize,        // 无法解析的行数
+}
+
+impl JsonLines {
+    /***********************************************************************
+     * 第一个视频条目
+     *
+     * 跳过播放列表信息；只有 -J 形式的播放列表对象时，取其 entries 中的第一项
+     ***********************************************************************/
+    pub fn first_entry(&self) -> Option<&Value> {
+        self.values
+            .iter()
+            .find(|value| !is_playlist(value))
+            .or_else(|| {
+                self.values
+                    .iter()
+                    .filter_map(|value| value["entries"].as_array())
+                    .flatten()
+                    .find(|entry| entry.is_object() && !is_playlist(entry))
+            })
+    }
+}
+
+fn is_playlist(value: &Value) -> bool {
+    value["_type"].as_str() == Some("playlist")
+}
+
+/***************************************************************************
+ * 解析 yt-dlp 的 JSON 输出
+ *
+ * 单行无法解析且是"未结束"错误时，与后续行拼接后继续尝试，
+ * 直到得到完整对象；其他错误计入 failed_lines 后跳过
+ *
+ * @param output - yt-dlp 标准输出
+ * @return JsonLines - 解析结果
+ ***************************************************************************/
+
+pub fn parse_json_lines(output: &str) -> JsonLines {
+    let mut result = JsonLines::default();
+    let mut pending = String::new();
+    let mut pending_lines = 0;
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if !pending.is_empty() {
+            pending.push('\n');
+        }
+        pending.push_str(line);
+        pending_lines += 1;
+
+        match serde_json::from_str::<Value>(&pending) {
+            Ok(value) => result.values.push(value),
+            // 对象尚未结束（跨行的 JSON），继续读取下一行
+            Err(e) if e.is_eof() => continue,
+            Err(e) => {
+                debug!("跳过无法解析的 JSON ({} 行): {}", pending_lines, e);
+                result.failed_lines += pending_lines;
+            }
+        }
+        pending.clear();
+        pending_lines = 0;
+    }
+
+    // 输出在对象中途结束
+    result.failed_lines += pending_lines;
+    result
+}
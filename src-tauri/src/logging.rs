@@ -0,0 +1,31 @@
+/****************************************************************************
+ *  logging.rs - 日志初始化
+ *
+ *  @brief  基于 tracing 的统一日志输出
+ *  @note   通过环境变量 YOUTUDOWN_LOG 调整级别（如 "debug"、"youtudown=trace"）；
+ *          未设置时调试构建输出 debug，发布构建只输出 info 及以上，
+ *          被过滤的日志不会产生任何格式化开销
+ *****************************************************************************/
+
+use tracing_subscriber::EnvFilter;
+
+/// 控制日志级别的环境变量
+const LOG_ENV_VAR: &str = "YOUTUDOWN_LOG";
+
+/***************************************************************************
+ * 初始化全局日志订阅者
+ *
+ * 重复调用是安全的（后续调用会被忽略）
+ ***************************************************************************/
+
+pub fn init() {
+    let default_level = if cfg!(debug_assertions) { "debug" } else { "info" };
+
+    let filter = EnvFilter::try_from_env(LOG_ENV_VAR)
+        .unwrap_or_else(|_| EnvFilter::new(default_level));
+
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .try_init();
+}
@@ -7,20 +7,171 @@
 
 use tauri::Manager;
 
+mod app_info;
+mod archive;
+#[cfg(test)]
+mod bindings;
+mod channel;
+mod checksum;
+mod cleanup;
 mod commands;
+mod cookies;
+mod diagnostics;
+mod disk;
+mod downloads;
+mod duplicates;
+mod errors;
+mod events;
+mod extra_args;
+mod extractors;
+mod ffmpeg;
+mod files;
+mod history;
+mod i18n;
+mod impersonation;
+mod json_lines;
+mod logging;
+mod managed_child;
+mod manifest;
+mod network;
+mod options;
+mod output_lines;
+mod playlist_items;
+mod presets;
+mod process;
+mod progress;
+mod queue;
+mod search;
+mod settings;
+mod site_presets;
+mod staging;
+mod storyboard;
+mod subscriptions;
+mod thumbnails;
+mod urls;
+mod verify;
+mod warnings;
+mod ytdlp;
 
 /***************************************************************************
  * 应用生命周期处理
  ***************************************************************************/
 fn main() {
+    logging::init();
+
     tauri::Builder::default()
         // 注册 Tauri 命令
         .invoke_handler(tauri::generate_handler![
             commands::get_video_info,
-            commands::download_video
+            commands::get_best_thumbnail,
+            commands::download_video,
+            commands::get_settings,
+            commands::update_settings,
+            commands::get_speed_history,
+            commands::get_download_state,
+            commands::get_queue_progress,
+            commands::get_queue_eta,
+            commands::check_impersonation_support,
+            commands::diagnose_impersonation,
+            commands::get_history,
+            commands::enqueue_download,
+            commands::check_duplicate,
+            commands::schedule_download,
+            commands::download_when_released,
+            commands::get_queue,
+            commands::pause_queue,
+            commands::resume_queue,
+            commands::cancel_schedule,
+            commands::start_now,
+            commands::download_from_file,
+            commands::preview_filename,
+            commands::preview_download_command,
+            commands::scan_orphaned_files,
+            commands::clean_orphaned_files,
+            commands::get_free_space,
+            commands::delete_history_entry,
+            commands::rename_download,
+            commands::move_download,
+            commands::compute_checksum,
+            commands::search_videos,
+            commands::get_channel_videos,
+            commands::add_subscription,
+            commands::remove_subscription,
+            commands::list_subscriptions,
+            commands::download_storyboard,
+            commands::list_presets,
+            commands::save_preset,
+            commands::delete_preset,
+            commands::download_playlist,
+            commands::download_playlist_missing,
+            commands::download_channel_new,
+            commands::get_playlist_progress,
+            commands::verify_download,
+            commands::retry_postprocess,
+            commands::list_ytdlp_candidates,
+            commands::set_ytdlp_path,
+            commands::list_supported_sites,
+            commands::check_url_supported,
+            commands::get_playlist_manifest,
+            commands::get_site_presets,
+            commands::set_site_presets,
+            commands::set_queue_priority,
+            commands::download_next,
+            commands::set_priority_mode,
+            commands::run_diagnostics,
+            commands::get_app_info,
+            commands::check_app_update,
+            commands::check_cookies,
+            commands::validate_download_dir,
+            commands::set_locale,
+            commands::get_playlist_info,
+            commands::cancel_playlist_info
         ])
         // 应用生命周期事件
         .setup(|app| {
+            // 加载持久化设置
+            let settings_path = app
+                .path()
+                .app_config_dir()
+                .ok()
+                .map(|dir| dir.join(settings::SETTINGS_FILE));
+            let settings = settings::SettingsState::load(settings_path);
+            ytdlp::set_resource_dir(app.path().resource_dir().ok());
+            ytdlp::pin_ytdlp_path(settings.get().ytdlp_path.as_deref());
+            ytdlp::prefer_bundled_ytdlp(settings.get().prefer_bundled_ytdlp);
+            process::configure(&settings.get());
+            i18n::configure(&settings.get());
+            app.manage(settings);
+            let history_path = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .map(|dir| dir.join(history::HISTORY_FILE));
+            app.manage(history::HistoryStore::load(history_path));
+            let subscriptions_path = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .map(|dir| dir.join(subscriptions::SUBSCRIPTIONS_FILE));
+            app.manage(subscriptions::SubscriptionStore::load(subscriptions_path));
+            app.manage(downloads::DownloadManager::default());
+            app.manage(files::FileLocks::default());
+            app.manage(impersonation::ImpersonationState::default());
+            app.manage(extractors::ExtractorState::default());
+            app.manage(channel::PlaylistFetches::default());
+            let queue_path = app
+                .path()
+                .app_data_dir()
+                .ok()
+                .map(|dir| dir.join(queue::QUEUE_FILE));
+            let queue = queue::DownloadQueue::load(queue_path, &app.state::<downloads::DownloadManager>());
+            app.manage(queue);
+            downloads::spawn_queue_progress_task(app.handle().clone());
+            queue::spawn_queue_dispatcher(app.handle().clone());
+            queue::spawn_schedule_timer(app.handle().clone());
+            subscriptions::spawn_subscription_watcher(app.handle().clone());
+            commands::spawn_startup_health_check(app.handle().clone());
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
@@ -33,7 +184,7 @@ fn main() {
         .on_window_event(|_app_handle, event| match event {
             tauri::WindowEvent::CloseRequested { .. } => {
                 // 处理关闭逻辑
-                println!("窗口关闭请求");
+                tracing::info!("窗口关闭请求");
             }
             _ => {}
         })
@@ -0,0 +1,236 @@
+/****************************************************************************
+ *  managed_child.rs - 受管子进程
+ *
+ *  @brief  结束 yt-dlp 时连同它启动的 ffmpeg 等子进程一起结束
+ *  @note   只结束 yt-dlp 本身时，正在合并的 ffmpeg 会继续运行并占用输出文件。
+ *          Unix 上子进程放入独立的进程组，结束时向整个进程组发送 SIGKILL；
+ *          Windows 上子进程加入 Job Object，结束 Job 即结束全部后代进程。
+ *          ManagedChild 被丢弃时（任务中止、应用退出）同样会清理，
+ *          即使子进程已经退出，留下的后代进程也会被结束
+ *****************************************************************************/
+
+use std::io;
+use std::ops::{Deref, DerefMut};
+use tokio::process::{Child, Command};
+use tracing::warn;
+
+/***************************************************************************
+ * 受管子进程（通过 Deref 使用 tokio Child 的其余方法）
+ ***************************************************************************/
+
+pub struct ManagedChild {
+    child: Child,
+    #[cfg(unix)]
+    group: Option<u32>,             // 子进程所在的进程组号（子进程被回收后仍用于结束后代进程）
+    #[cfg(windows)]
+    job: Option<job::JobObject>,    // 子进程所在的 Job Object（创建失败时为 None）
+}
+
+impl ManagedChild {
+    /***********************************************************************
+     * 启动子进程
+     *
+     * Unix 上放入以子进程 PID 为组号的新进程组；Windows 上启动后加入新的
+     * Job Object（加入失败时只记录日志，退化为只结束子进程本身）
+     ***********************************************************************/
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        #[cfg(unix)]
+        command.process_group(0);
+
+        let child = command.spawn()?;
+
+        #[cfg(windows)]
+        let job = match child.raw_handle().map(job::JobObject::assign) {
+            Some(Ok(job)) => Some(job),
+            Some(Err(e)) => {
+                warn!("无法将子进程加入 Job Object: {}", e);
+                None
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            #[cfg(unix)]
+            group: child.id(),
+            child,
+            #[cfg(windows)]
+            job,
+        })
+    }
+
+    /// 结束子进程及其全部后代进程，并等待子进程退出
+    pub async fn kill(&mut self) -> io::Result<()> {
+        self.kill_tree();
+        self.child.kill().await
+    }
+
+    /// 向整个进程组 / Job Object 发送结束信号（进程组中已没有进程时不做任何事）
+    fn kill_tree(&mut self) {
+        #[cfg(unix)]
+        if let Some(pid) = self.group {
+            // process_group(0) 使进程组号等于子进程 PID
+            let result = unsafe { libc::killpg(pid as libc::pid_t, libc::SIGKILL) };
+            if result != 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() != Some(libc::ESRCH) {
+                    warn!("结束进程组 {} 失败: {}", pid, error);
+                }
+            }
+        }
+
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            if let Err(e) = job.terminate() {
+                warn!("结束 Job Object 失败: {}", e);
+            }
+        }
+    }
+}
+
+impl Deref for ManagedChild {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.child
+    }
+}
+
+impl DerefMut for ManagedChild {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.child
+    }
+}
+
+impl Drop for ManagedChild {
+    fn drop(&mut self) {
+        // 子进程已退出时后代进程可能仍在运行，始终结束整个进程组 / Job
+        self.kill_tree();
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.start_kill();
+        }
+    }
+}
+
+/***************************************************************************
+ * Windows Job Object
+ ***************************************************************************/
+
+#[cfg(windows)]
+mod job {
+    use std::ffi::c_void;
+    use std::io;
+    use std::os::windows::io::RawHandle;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// Job Object 句柄（保存为整数，HANDLE 本身不是 Send）
+    pub struct JobObject(isize);
+
+    impl JobObject {
+        /// 创建关闭句柄时结束全部进程的 Job Object，并把进程加入其中
+        pub fn assign(process: RawHandle) -> io::Result<Self> {
+            let handle = unsafe { CreateJobObjectW(None, PCWSTR::null()) }.map_err(io::Error::other)?;
+            let job = Self(handle.0 as isize);
+
+            let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            unsafe {
+                SetInformationJobObject(
+                    job.handle(),
+                    JobObjectExtendedLimitInformation,
+                    &limits as *const _ as *const c_void,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                )
+                .map_err(io::Error::other)?;
+                AssignProcessToJobObject(job.handle(), HANDLE(process as *mut c_void))
+                    .map_err(io::Error::other)?;
+            }
+            Ok(job)
+        }
+
+        /// 结束 Job 中的全部进程
+        pub fn terminate(&self) -> io::Result<()> {
+            unsafe { TerminateJobObject(self.handle(), 1) }.map_err(io::Error::other)
+        }
+
+        fn handle(&self) -> HANDLE {
+            HANDLE(self.0 as *mut c_void)
+        }
+    }
+
+    impl Drop for JobObject {
+        fn drop(&mut self) {
+            let _ = unsafe { CloseHandle(self.handle()) };
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    /// 启动 sh，由它在后台启动 sleep 并输出 sleep 的 PID
+    async fn spawn_with_grandchild(script: &str) -> (ManagedChild, libc::pid_t) {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]).stdout(Stdio::piped());
+        let mut child = ManagedChild::spawn(&mut command).unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let line = BufReader::new(stdout).lines().next_line().await.unwrap().unwrap();
+        (child, line.trim().parse().unwrap())
+    }
+
+    /// 进程是否仍在运行（已结束、等待回收的僵尸进程不算）
+    fn is_running(pid: libc::pid_t) -> bool {
+        if unsafe { libc::kill(pid, 0) } != 0 {
+            return false;
+        }
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap_or_default();
+        !stat.rsplit(')').next().unwrap_or("").trim_start().starts_with('Z')
+    }
+
+    async fn wait_until_gone(pid: libc::pid_t) -> bool {
+        for _ in 0..50 {
+            if !is_running(pid) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn kill_ends_grandchild() {
+        let (mut child, grandchild) = spawn_with_grandchild("sleep 30 & echo $!; wait").await;
+        assert!(is_running(grandchild));
+
+        child.kill().await.unwrap();
+        assert!(wait_until_gone(grandchild).await);
+    }
+
+    #[tokio::test]
+    async fn drop_ends_grandchild() {
+        let (child, grandchild) = spawn_with_grandchild("sleep 30 & echo $!; wait").await;
+        assert!(is_running(grandchild));
+
+        drop(child);
+        assert!(wait_until_gone(grandchild).await);
+    }
+
+    #[tokio::test]
+    async fn drop_ends_grandchild_after_child_exited() {
+        let (mut child, grandchild) = spawn_with_grandchild("sleep 30 & echo $!").await;
+        assert!(child.wait().await.unwrap().success());
+        assert!(is_running(grandchild));
+
+        drop(child);
+        assert!(wait_until_gone(grandchild).await);
+    }
+}
@@ -0,0 +1,109 @@
+/****************************************************************************
+ *  manifest.rs - 批量下载清单
+ *
+ *  @brief  播放列表等批量任务全部结束后，在下载目录写出一份清单，
+ *          列出每个视频的标题、链接、输出文件、大小、状态和错误
+ *  @note   支持 JSON 和 CSV 两种格式，文件名为 "manifest-<分组ID>.<扩展名>"
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+use crate::downloads::DownloadStatus;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestFormat {
+    Json,
+    Csv,
+}
+
+impl ManifestFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ManifestFormat::Json => "json",
+            ManifestFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct ManifestEntry {
+    pub download_id: String,
+    pub title: String,
+    pub url: String,
+    pub output_path: Option<String>,
+    #[ts(type = "number | null")]
+    pub size: Option<u64>,          // 输出文件大小（字节）
+    pub status: Option<DownloadStatus>, // 最终状态（未找到记录时为 None）
+    pub error: Option<String>,
+}
+
+/***************************************************************************
+ * 写出清单文件
+ *
+ * @param dir - 清单所在目录（批量任务的下载目录）
+ * @param group_id - 分组ID，用于生成文件名
+ * @param format - 清单格式
+ * @param entries - 按入队顺序排列的条目
+ * @return PathBuf - 写出的清单路径
+ ***************************************************************************/
+
+pub fn write_manifest(
+    dir: &Path,
+    group_id: &str,
+    format: ManifestFormat,
+    entries: &[ManifestEntry],
+) -> Result<PathBuf, String> {
+    let content = match format {
+        ManifestFormat::Json => serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("序列化清单失败: {}", e))?,
+        ManifestFormat::Csv => to_csv(entries),
+    };
+
+    fs::create_dir_all(dir).map_err(|e| format!("无法创建下载目录: {}", e))?;
+    let path = dir.join(format!("manifest-{}.{}", group_id, format.extension()));
+    fs::write(&path, content).map_err(|e| format!("写入清单失败: {}", e))?;
+    Ok(path)
+}
+
+fn to_csv(entries: &[ManifestEntry]) -> String {
+    let mut csv = String::from("download_id,title,url,output_path,size,status,error\n");
+    for entry in entries {
+        let status = entry
+            .status
+            .and_then(|s| serde_json::to_value(s).ok())
+            .and_then(|v| v.as_str().map(String::from));
+        let fields = [
+            Some(entry.download_id.clone()),
+            Some(entry.title.clone()),
+            Some(entry.url.clone()),
+            entry.output_path.clone(),
+            entry.size.map(|s| s.to_string()),
+            status,
+            entry.error.clone(),
+        ];
+        let row: Vec<String> = fields
+            .iter()
+            .map(|field| csv_field(field.as_deref().unwrap_or_default()))
+            .collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// 含逗号、引号或换行的字段用引号包围，内部引号写两次
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
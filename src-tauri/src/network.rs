@@ -0,0 +1,97 @@
+/****************************************************************************
+ *  network.rs - 网络连通性检查
+ *
+ *  @brief  启动 yt-dlp 之前确认网络可用，断网时立即报错，而不是等 yt-dlp 超时
+ *  @note   向几个常用地址并行发送 HEAD 请求，任意一个有响应即视为在线；
+ *          设置了代理时通过该代理探测，否则 reqwest 使用系统代理环境变量。
+ *          只能通过代理访问目标站点的环境可在设置中关闭该检查
+ *****************************************************************************/
+
+use std::time::Duration;
+use tracing::debug;
+
+use crate::process::proxy;
+
+/// 断网错误的标识（错误信息以此开头，前端据此识别）
+pub const NETWORK_OFFLINE: &str = "NetworkOffline";
+
+/// 探测地址（任意一个可达即视为在线）
+const PROBE_ENDPOINTS: &[&str] = &[
+    "https://www.gstatic.com/generate_204",
+    "https://cloudflare.com/cdn-cgi/trace",
+];
+
+/// 单次探测的超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 断网期间重新探测的间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// 断网时返回的错误信息
+pub fn offline_error() -> String {
+    format!("{}: 网络未连接，请检查网络后重试", NETWORK_OFFLINE)
+}
+
+/***************************************************************************
+ * 创建使用设置中代理的 HTTP 客户端
+ *
+ * 代理地址无效时直接连接
+ *
+ * @param timeout - 单次请求的超时时间
+ ***************************************************************************/
+
+pub fn http_client(timeout: Duration) -> reqwest::Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(proxy) = proxy() {
+        match reqwest::Proxy::all(&proxy) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(e) => debug!("代理地址无效，直接连接: {}", e),
+        }
+    }
+    builder.build()
+}
+
+/***************************************************************************
+ * 检查网络是否可用
+ *
+ * 收到任何 HTTP 响应（包括错误状态码）都视为在线
+ ***************************************************************************/
+
+pub async fn is_online() -> bool {
+    let client = match http_client(PROBE_TIMEOUT) {
+        Ok(client) => client,
+        // 无法创建客户端时不阻止下载
+        Err(_) => return true,
+    };
+
+    let probes: Vec<_> = PROBE_ENDPOINTS
+        .iter()
+        .map(|endpoint| {
+            let request = client.head(*endpoint);
+            tauri::async_runtime::spawn(async move { request.send().await })
+        })
+        .collect();
+
+    let mut online = false;
+    for (endpoint, probe) in PROBE_ENDPOINTS.iter().zip(probes) {
+        match probe.await {
+            Ok(Ok(_)) => online = true,
+            Ok(Err(e)) => debug!("连通性探测失败: {} ({})", endpoint, e),
+            Err(e) => debug!("连通性探测任务异常: {}", e),
+        }
+    }
+    online
+}
+
+/***************************************************************************
+ * 等待网络恢复（每 RETRY_INTERVAL 探测一次）
+ ***************************************************************************/
+
+pub async fn wait_for_network() {
+    loop {
+        tokio::time::sleep(RETRY_INTERVAL).await;
+        if is_online().await {
+            return;
+        }
+    }
+}
@@ -0,0 +1,1123 @@
+/****************************************************************************
+ *  options.rs - 下载选项与 yt-dlp 参数构建
+ *
+ *  @brief  把前端传入的结构化下载选项和全局设置转换为 yt-dlp 命令行参数
+ *  @note   参数顺序：基础参数 → 设置 → 格式 → 时间段 → 字幕 → 评论 → 缩略图 → 反检测 → 下载存档 → 文件名/输出模板 → 格式报告 → URL
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ts_rs::TS;
+
+use crate::archive::validate_archive_file;
+use crate::ffmpeg::find_ffmpeg;
+use crate::manifest::ManifestFormat;
+use crate::progress::FORMAT_REPORT_TEMPLATE;
+use crate::settings::{OrganizeBy, Settings};
+use crate::urls::{is_playlist_url, is_watch_with_list};
+
+/// 文件名模板
+const FILENAME_TEMPLATE: &str = "%(title)s.%(ext)s";
+
+/// 分别下载音视频时的文件名模板（两个流扩展名可能相同，用格式ID区分）
+const SEPARATE_FILENAME_TEMPLATE: &str = "%(title)s.f%(format_id)s.%(ext)s";
+
+/// 需要做 Windows 兼容处理的目录字段
+const FOLDER_FIELDS: &str = "uploader,playlist_title,extractor_key";
+
+/// 分辨率回退阶梯（请求的分辨率不可用时依次尝试更低的分辨率）
+const RESOLUTION_LADDER: &[i64] = &[4320, 2880, 2160, 1440, 1080, 720, 480, 360, 240, 144];
+
+/// 缩略图可转换的格式
+const THUMBNAIL_FORMATS: &[&str] = &["png", "jpg", "webp"];
+
+/// 可提取的音频格式（-x --audio-format）
+const AUDIO_FORMATS: &[&str] = &["mp3", "m4a", "opus", "flac", "wav", "aac", "vorbis"];
+
+/// 合并音视频时可选的容器（--merge-output-format）
+const MERGE_FORMATS: &[&str] = &["mp4", "mkv", "webm"];
+
+/// 默认 User-Agent（桌面版 Chrome），获取信息和下载时未指定 user_agent 则使用
+pub const DEFAULT_USER_AGENT: &str =
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+
+/// 未指定容器时的合并格式：编码兼容时用 mp4，否则由 yt-dlp 回退到 mkv
+const DEFAULT_MERGE_FORMAT: &str = "mp4/mkv";
+
+/// 归档模式的字幕语言：全部字幕，但不含直播聊天记录（不是字幕，无法嵌入）
+const ARCHIVE_SUB_LANGS: &str = "all,-live_chat";
+
+/// 默认最多获取的评论数（评论提取很慢，避免热门视频耗时过长）
+pub const DEFAULT_MAX_COMMENTS: u32 = 100;
+
+/// 完整路径长度上限（Windows 未启用长路径时为 260，留出余量）
+const MAX_PATH_LENGTH: usize = 255;
+
+/// 为扩展名及下载中的临时后缀（如 ".f137.mp4.part"）预留的长度
+const EXTENSION_RESERVE: usize = 24;
+
+/// 按子目录组织时为子目录名预留的长度
+const FOLDER_RESERVE: usize = 64;
+
+/// 自动计算的文件名长度下限（下载目录过深时也保留可读的标题）
+const MIN_FILENAME_LENGTH: usize = 32;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct DownloadOptions {
+    pub format_id: Option<String>,              // 指定格式ID（来自 available_resolutions）
+    #[ts(type = "number | null")]
+    pub max_height: Option<i64>,                // 最大分辨率高度（未指定格式ID时使用）
+    pub start_time: Option<f64>,                // 时间段开始（秒）
+    pub end_time: Option<f64>,                  // 时间段结束（秒）
+    pub subtitle_langs: Option<String>,         // 字幕语言（如 "en,zh-Hans"），None 不下载字幕
+    pub output_dir: Option<String>,             // 下载目录，未指定时为设置中的默认下载目录
+    pub output_template: Option<String>,        // 文件名模板（如 "%(artist)s - %(title)s.%(ext)s"），未指定时为 "%(title)s.%(ext)s"
+    pub impersonate: Option<String>,            // 浏览器伪装目标（如 "chrome"）
+    pub cookies_from_browser: Option<String>,   // 读取 Cookie 的浏览器
+    pub sleep_interval: Option<u32>,            // 请求间隔（秒）
+    pub retries: Option<u32>,                   // 重试次数
+    pub retry_sleep: Option<RetrySleep>,        // 重试前的等待（--retry-sleep），None 不等待
+    pub min_duration: Option<f64>,              // 只下载时长大于该值（秒）的视频，其余跳过
+    pub max_duration: Option<f64>,              // 只下载时长小于该值（秒）的视频，其余跳过
+    pub user_agent: Option<String>,             // 覆盖默认 User-Agent（单行），部分站点按 UA 返回不同内容
+    pub write_comments: bool,                   // 获取评论并写入 .info.json 附属文件（耗时较长）
+    pub max_comments: Option<u32>,              // 最多获取的评论数，默认 DEFAULT_MAX_COMMENTS
+    pub audio_language: Option<String>,         // 音轨语言（如 "en"），不存在时回退到默认音轨
+    pub write_thumbnail: bool,                  // 保存缩略图文件（--write-thumbnail）
+    pub embed_thumbnail: bool,                  // 把缩略图嵌入媒体文件（--embed-thumbnail）
+    pub convert_thumbnails: Option<String>,     // 缩略图转换格式（png/jpg/webp，需要 ffmpeg）
+    pub separate_streams: bool,                 // 视频流和音频流分别保存为两个文件，不合并（不需要 ffmpeg）
+    pub format: Option<String>,                 // 直接指定 -f 表达式（质量预设使用），优先于 format_id/max_height
+    pub format_sort: Option<String>,            // 格式排序（-S，如 "res:480,+size,+br"）
+    pub extract_audio: Option<String>,          // 只保留音频并转换为该格式（-x --audio-format，需要 ffmpeg）
+    pub playlist_concurrency: Option<usize>,    // 播放列表同时下载的视频数（download_playlist 使用）
+    pub video_format_id: Option<String>,        // 指定视频格式ID（与 audio_format_id 组成 "视频+音频"）
+    pub audio_format_id: Option<String>,        // 指定音频格式ID
+    pub merge_output_format: Option<String>,    // 合并后的容器（mp4/mkv/webm），未指定时优先 mp4
+    pub manifest_format: Option<ManifestFormat>, // 批量任务结束后写出下载清单（json/csv）
+    pub archive_file: Option<String>,           // 下载存档（--download-archive），已记录的视频不再下载
+    pub format_goal: FormatGoal,                // 选择格式的目标（最佳画质/最小文件/按码率折中）
+    pub extra_args: Vec<String>,                // 自定义 yt-dlp 参数（放在生成的参数之后，同名选项以此为准）
+    pub archive_best: bool,                     // 归档模式：最佳视频 + 全部音轨、字幕、章节和元数据合并为一个 MKV（需要 ffmpeg）
+    pub treat_as_playlist: bool,                // 带 list 参数的视频链接按播放列表下载（默认只下载该视频）
+    pub wait_for_video: Option<u32>,            // 视频尚未开始（首映/直播预告）时每隔多少秒重新检查（--wait-for-video）
+}
+
+/// 重试等待的上限（秒）
+const MAX_RETRY_SLEEP: f64 = 3600.0;
+
+/***************************************************************************
+ * 重试等待
+ *
+ * 对应 yt-dlp 的 --retry-sleep，第 n 次重试（从 1 开始）前等待：
+ * - 固定：seconds
+ * - 线性：start + step × (n - 1)，step 默认为 1
+ * - 指数：start × base^(n - 1)，base 默认为 2
+ * 线性和指数指定了 end 时不超过 end；同时用于 HTTP 和分片重试
+ ***************************************************************************/
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RetrySleep {
+    Fixed {
+        seconds: f64,
+    },
+    Linear {
+        start: f64,
+        end: Option<f64>,
+        step: Option<f64>,
+    },
+    Exponential {
+        start: f64,
+        end: Option<f64>,
+        base: Option<f64>,
+    },
+}
+
+impl RetrySleep {
+    pub fn validate(&self) -> Result<(), String> {
+        let in_range = |value: f64| (0.0..=MAX_RETRY_SLEEP).contains(&value);
+        let (start, end) = match self {
+            RetrySleep::Fixed { seconds } => (*seconds, None),
+            RetrySleep::Linear { start, end, step } => {
+                if step.is_some_and(|step| !(step > 0.0 && in_range(step))) {
+                    return Err(format!("重试等待的步长必须在 0 到 {} 秒之间", MAX_RETRY_SLEEP));
+                }
+                (*start, *end)
+            }
+            RetrySleep::Exponential { start, end, base } => {
+                if base.is_some_and(|base| !(base > 1.0 && base <= 10.0)) {
+                    return Err("重试等待的指数底数必须大于 1 且不超过 10".to_string());
+                }
+                (*start, *end)
+            }
+        };
+        if !in_range(start) || end.is_some_and(|end| !in_range(end)) {
+            return Err(format!("重试等待必须在 0 到 {} 秒之间", MAX_RETRY_SLEEP));
+        }
+        if end.is_some_and(|end| end < start) {
+            return Err("重试等待的上限不能小于初始值".to_string());
+        }
+        Ok(())
+    }
+
+    /// --retry-sleep 的取值（不含类型前缀），如 "exp=1:120:2"
+    fn expression(&self) -> String {
+        let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        match self {
+            RetrySleep::Fixed { seconds } => seconds.to_string(),
+            RetrySleep::Linear { start, end, step } => {
+                format!("linear={}:{}:{}", start, optional(*end), step.unwrap_or(1.0))
+            }
+            RetrySleep::Exponential { start, end, base } => {
+                format!("exp={}:{}:{}", start, optional(*end), base.unwrap_or(2.0))
+            }
+        }
+    }
+
+    /// 第 attempt 次重试（从 1 开始）前的等待秒数，与 yt-dlp 的计算方式一致
+    pub fn delay(&self, attempt: u32) -> f64 {
+        let n = attempt.saturating_sub(1) as f64;
+        let (delay, end) = match self {
+            RetrySleep::Fixed { seconds } => (*seconds, None),
+            RetrySleep::Linear { start, end, step } => (start + step.unwrap_or(1.0) * n, *end),
+            RetrySleep::Exponential { start, end, base } => (start * base.unwrap_or(2.0).powf(n), *end),
+        };
+        end.map_or(delay, |end| delay.min(end))
+    }
+}
+
+/// 重试等待参数（HTTP 请求和分片各一份）
+fn retry_sleep_args(options: &DownloadOptions) -> Result<Vec<String>, String> {
+    let Some(sleep) = &options.retry_sleep else {
+        return Ok(Vec::new());
+    };
+    sleep.validate()?;
+    let expression = sleep.expression();
+    Ok(vec![
+        "--retry-sleep".to_string(),
+        format!("http:{}", expression),
+        "--retry-sleep".to_string(),
+        format!("fragment:{}", expression),
+    ])
+}
+
+/***************************************************************************
+ * 时长过滤参数
+ *
+ * 对应 --match-filters "duration > X & duration < Y"，不在范围内的视频由 yt-dlp 跳过
+ * （时长未知的视频同样不满足条件）
+ ***************************************************************************/
+
+fn duration_filter_args(options: &DownloadOptions) -> Result<Vec<String>, String> {
+    let bounds = [(options.min_duration, "duration >"), (options.max_duration, "duration <")];
+    let mut filters = Vec::new();
+    for (bound, filter) in bounds {
+        let Some(seconds) = bound else {
+            continue;
+        };
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(format!("时长限制必须是非负数: {}", seconds));
+        }
+        filters.push(format!("{} {}", filter, seconds));
+    }
+    if let (Some(min), Some(max)) = (options.min_duration, options.max_duration) {
+        if min >= max {
+            return Err("最短时长必须小于最长时长".to_string());
+        }
+    }
+
+    if filters.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(vec!["--match-filters".to_string(), filters.join(" & ")])
+}
+
+/// 时长是否在选项的范围内（时长未知时视为在范围内，由下载时的 --match-filters 判断）
+pub fn duration_in_range(options: &DownloadOptions, duration: Option<f64>) -> bool {
+    let Some(duration) = duration else {
+        return true;
+    };
+    options.min_duration.is_none_or(|min| duration > min) && options.max_duration.is_none_or(|max| duration < max)
+}
+
+/***************************************************************************
+ * 格式选择目标
+ *
+ * 通过 -S 改变 yt-dlp 对候选格式的排序，-f 表达式不变；
+ * 文件大小取自 yt-dlp 解析的 filesize（没有时为 filesize_approx）
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum FormatGoal {
+    #[default]
+    BestQuality,                    // yt-dlp 默认排序
+    SmallestSize,                   // 文件最小（指定了最大高度时为该分辨率下最小）
+    BalancedByBitrate,              // 分辨率最高的格式中码率最低的（通常是编码效率更高的格式）
+}
+
+impl FormatGoal {
+    /// 对应的 -S 排序（BestQuality 为 None）
+    pub fn sort_expression(self, max_height: Option<i64>) -> Option<String> {
+        let resolution = match max_height {
+            Some(height) => format!("res:{}", height),
+            None => "res".to_string(),
+        };
+        match self {
+            FormatGoal::BestQuality => None,
+            FormatGoal::SmallestSize => Some(match max_height {
+                Some(_) => format!("{},+size,+br", resolution),
+                None => "+size,+br".to_string(),
+            }),
+            FormatGoal::BalancedByBitrate => Some(format!("{},fps,+tbr,+size", resolution)),
+        }
+    }
+}
+
+/***************************************************************************
+ * 格式选择器
+ *
+ * 根据选项生成 -f 表达式：
+ * - 指定格式ID：优先使用该格式，同时指定了最大高度时附加回退阶梯
+ * - 指定最大高度：从该高度开始逐级回退，如
+ *   bv*[height<=2160]+ba/bv*[height<=1440]+ba/.../b
+ * - 都未指定：bestvideo+bestaudio/best
+ * 指定音轨语言时，每一级先尝试 ba[language^=xx]，再回退到默认音轨
+ * 分别下载音视频时用逗号连接纯视频和纯音频选择器，yt-dlp 依次下载而不合并
+ * 直接指定了表达式（format）时原样使用；只提取音频时默认为 ba/b
+ * 指定了视频/音频格式ID对时优先于 format_id/max_height：
+ * 视频+音频 → v+a；只有视频 → v+bestaudio/v；只有音频 → a
+ * 优先合一格式（prefer_progressive）时，回退阶梯的每一级先尝试高度不低于
+ * 下一级的合一格式 b[height<=H][height>=L]，再尝试合并
+ * 选择目标（goal）不改变表达式，只决定 -S 排序（见 sort）
+ * 设置了分辨率上限时，未明确选择格式或最大高度的下载以上限作为最大高度
+ * （见 from_settings），明确的选择优先于上限
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Default)]
+pub struct FormatSelector {
+    pub format: Option<String>,
+    pub audio_only: bool,
+    pub format_id: Option<String>,
+    pub max_height: Option<i64>,
+    pub audio_language: Option<String>,
+    pub separate: bool,
+    pub video_format_id: Option<String>,
+    pub audio_format_id: Option<String>,
+    pub prefer_progressive: bool,
+    pub goal: FormatGoal,
+}
+
+impl FormatSelector {
+    pub fn from_options(options: &DownloadOptions) -> Self {
+        Self {
+            format: options.format.clone().filter(|f| !f.is_empty()),
+            audio_only: options.extract_audio.as_deref().is_some_and(|f| !f.is_empty()),
+            format_id: options.format_id.clone(),
+            max_height: options.max_height,
+            audio_language: options.audio_language.clone().filter(|l| !l.is_empty()),
+            separate: options.separate_streams,
+            video_format_id: options.video_format_id.clone().filter(|id| !id.is_empty()),
+            audio_format_id: options.audio_format_id.clone().filter(|id| !id.is_empty()),
+            prefer_progressive: false,
+            goal: options.format_goal,
+        }
+    }
+
+    /// 下载使用的选择器：套用设置中的合一格式偏好和分辨率上限
+    pub fn from_settings(options: &DownloadOptions, settings: &Settings) -> Self {
+        let mut selector = Self {
+            prefer_progressive: settings.prefer_progressive,
+            ..Self::from_options(options)
+        };
+        let explicit = selector.format.is_some()
+            || selector.format_id.is_some()
+            || selector.video_format_id.is_some()
+            || selector.audio_format_id.is_some();
+        if selector.max_height.is_none() && !explicit {
+            selector.max_height = settings.max_resolution;
+        }
+        selector
+    }
+
+    /// -S 排序：用户指定的排序优先，否则按选择目标生成（只提取音频时不需要）
+    pub fn sort(&self, format_sort: Option<&str>) -> Option<String> {
+        if let Some(sort) = format_sort.filter(|s| !s.is_empty()) {
+            return Some(sort.to_string());
+        }
+        if self.audio_only {
+            return match self.goal {
+                FormatGoal::SmallestSize => Some("+size,+br".to_string()),
+                _ => None,
+            };
+        }
+        self.goal.sort_expression(self.max_height)
+    }
+
+    /// 生成 -f 参数的取值
+    pub fn expression(&self) -> String {
+        if let Some(format) = &self.format {
+            return format.clone();
+        }
+        if let Some(pair) = self.pair_expression() {
+            return pair;
+        }
+        if self.audio_only && self.format_id.is_none() {
+            return match &self.audio_language {
+                Some(language) => format!("ba[language^={}]/ba/b", language),
+                None => "ba/b".to_string(),
+            };
+        }
+        if self.separate {
+            return self.separate_expression();
+        }
+
+        let mut alternatives = Vec::new();
+
+        if let Some(format_id) = &self.format_id {
+            if let Some(language) = &self.audio_language {
+                alternatives.push(format!("{}+ba[language^={}]", format_id, language));
+            }
+            alternatives.push(format_id.clone());
+        }
+
+        match self.max_height {
+            Some(height) => alternatives.extend(self.fallback_ladder(height)),
+            None if self.format_id.is_none() => {
+                alternatives.extend(self.with_audio("bestvideo"));
+                alternatives.push("best".to_string());
+            }
+            None => {}
+        }
+
+        alternatives.join("/")
+    }
+
+    /// 指定的视频/音频格式ID对（分别下载时用逗号连接）
+    fn pair_expression(&self) -> Option<String> {
+        let join = if self.separate { "," } else { "+" };
+        match (&self.video_format_id, &self.audio_format_id) {
+            (Some(video), Some(audio)) => Some(format!("{}{}{}", video, join, audio)),
+            (Some(video), None) if self.separate => Some(format!("{},ba", video)),
+            (Some(video), None) => Some(format!("{}+bestaudio/{}", video, video)),
+            (None, Some(audio)) => Some(audio.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// 分别下载：纯视频 + 纯音频（如 "bv[height<=1080]/bv,ba[language^=en]/ba"）
+    fn separate_expression(&self) -> String {
+        // 指定的格式ID为合并格式（如 "137+140"）时拆成两个下载
+        if let Some(format_id) = &self.format_id {
+            if format_id.contains('+') {
+                return format_id.replace('+', ",");
+            }
+        }
+
+        let mut video = Vec::new();
+        if let Some(format_id) = &self.format_id {
+            video.push(format_id.clone());
+        }
+        if let Some(height) = self.max_height {
+            video.push(format!("bv[height<={}]", height));
+        }
+        video.push("bv".to_string());
+
+        let mut audio = Vec::new();
+        if let Some(language) = &self.audio_language {
+            audio.push(format!("ba[language^={}]", language));
+        }
+        audio.push("ba".to_string());
+
+        format!("{},{}", video.join("/"), audio.join("/"))
+    }
+
+    /// 从 max_height 开始的回退阶梯（请求的高度不在阶梯中时也作为第一级）
+    fn fallback_ladder(&self, max_height: i64) -> Vec<String> {
+        let heights: Vec<i64> = std::iter::once(max_height)
+            .chain(RESOLUTION_LADDER.iter().copied().filter(|&h| h < max_height))
+            .collect();
+
+        let mut rungs = Vec::new();
+        for (index, &height) in heights.iter().enumerate() {
+            if self.prefer_progressive {
+                let lower = heights.get(index + 1).copied().unwrap_or(height);
+                rungs.push(format!("b[height<={}][height>={}]", height, lower));
+            }
+            rungs.extend(self.with_audio(&format!("bv*[height<={}]", height)));
+        }
+        rungs.push("b".to_string());
+        rungs
+    }
+
+    /// 视频选择器搭配音频：指定语言时先尝试该语言，再回退到最佳音频
+    fn with_audio(&self, video: &str) -> Vec<String> {
+        // 与视频选择器的写法保持一致（bestvideo+bestaudio / bv*+ba）
+        let audio = if video == "bestvideo" { "bestaudio" } else { "ba" };
+        match &self.audio_language {
+            Some(language) => vec![
+                format!("{}+{}[language^={}]", video, audio, language),
+                format!("{}+{}", video, audio),
+            ],
+            None => vec![format!("{}+{}", video, audio)],
+        }
+    }
+}
+
+/***************************************************************************
+ * 生成输出模板
+ *
+ * 按 organize_by 设置在下载目录下增加一级子目录：
+ * Site → %(extractor_key)s/，Uploader → %(uploader)s/，Playlist → %(playlist_title)s/
+ * 字段缺失时使用占位名称，避免生成 "NA" 目录
+ ***************************************************************************/
+
+pub fn output_template(
+    output_dir: Option<&str>,
+    filename: Option<&str>,
+    organize_by: OrganizeBy,
+    separate: bool,
+) -> String {
+    let folder = match organize_by {
+        OrganizeBy::None => None,
+        OrganizeBy::Site => Some("%(extractor_key|Unknown Site)s"),
+        OrganizeBy::Uploader => Some("%(uploader,channel|Unknown Uploader)s"),
+        OrganizeBy::Playlist => Some("%(playlist_title|No Playlist)s"),
+    };
+
+    let mut template = match output_dir {
+        Some(dir) => Path::new(dir).to_path_buf(),
+        None => Default::default(),
+    };
+    if let Some(folder) = folder {
+        template.push(folder);
+    }
+    template.push(filename.unwrap_or(if separate { SEPARATE_FILENAME_TEMPLATE } else { FILENAME_TEMPLATE }));
+
+    template.to_string_lossy().into_owned()
+}
+
+/***************************************************************************
+ * 目录名兼容处理参数
+ *
+ * 上传者、播放列表名可能是 Windows 保留设备名（CON、NUL 等）或以点、空格结尾，
+ * 这样的目录在 Windows 上无法正常访问。通过 --replace-in-metadata 在填充模板前
+ * 修正这些字段（所有平台都处理，保证下载目录可以拷贝到 Windows 上使用）
+ ***************************************************************************/
+
+fn folder_sanitize_args() -> Vec<String> {
+    vec![
+        // 保留设备名前加下划线
+        "--replace-in-metadata".to_string(),
+        FOLDER_FIELDS.to_string(),
+        r"(?i)^(CON|PRN|AUX|NUL|COM[0-9]|LPT[0-9])$".to_string(),
+        r"_\1".to_string(),
+        // 去掉结尾的点和空格
+        "--replace-in-metadata".to_string(),
+        FOLDER_FIELDS.to_string(),
+        r"[. ]+$".to_string(),
+        String::new(),
+    ]
+}
+
+/***************************************************************************
+ * 计算默认的文件名截断长度
+ *
+ * 从路径上限中扣除下载目录、子目录和扩展名占用的长度，
+ * 保证完整路径不超过 255 个字符（未指定目录时按当前工作目录计算）
+ ***************************************************************************/
+
+pub fn default_trim_length(output_dir: Option<&str>, organize_by: OrganizeBy) -> usize {
+    let dir_length = match output_dir {
+        Some(dir) => dir.chars().count(),
+        None => std::env::current_dir()
+            .map(|dir| dir.to_string_lossy().chars().count())
+            .unwrap_or(0),
+    };
+    let folder_length = match organize_by {
+        OrganizeBy::None => 0,
+        _ => FOLDER_RESERVE + 1,
+    };
+
+    MAX_PATH_LENGTH
+        .saturating_sub(dir_length + 1 + folder_length + EXTENSION_RESERVE)
+        .max(MIN_FILENAME_LENGTH)
+}
+
+/***************************************************************************
+ * 文件名相关参数（下载和文件名预览共用，保证预览与实际一致）
+ *
+ * @param staging_dir - 暂存目录（输出模板改为写入暂存目录，截断长度仍按下载目录计算）
+ * @return Vec<String> - 文件名处理参数及 -o 输出模板
+ ***************************************************************************/
+
+pub fn filename_args(
+    options: &DownloadOptions,
+    settings: &Settings,
+    staging_dir: Option<&Path>,
+) -> Vec<String> {
+    let mut args = Vec::new();
+
+    if settings.restrict_filenames {
+        args.push("--restrict-filenames".to_string());
+    }
+    if settings.windows_safe_filenames {
+        args.push("--windows-filenames".to_string());
+    }
+
+    let trim_length = settings.max_filename_length.unwrap_or_else(|| {
+        default_trim_length(options.output_dir.as_deref(), settings.organize_by)
+    });
+    args.push("--trim-filenames".to_string());
+    args.push(trim_length.to_string());
+
+    if settings.organize_by != OrganizeBy::None {
+        args.extend(folder_sanitize_args());
+    }
+    let output_root = match staging_dir {
+        Some(dir) => Some(dir.to_string_lossy().into_owned()),
+        None => options.output_dir.clone(),
+    };
+    args.push("-o".to_string());
+    args.push(output_template(
+        output_root.as_deref(),
+        options.output_template.as_deref().filter(|t| !t.is_empty()),
+        settings.organize_by,
+        options.separate_streams,
+    ));
+
+    args
+}
+
+/***************************************************************************
+ * 缩略图相关参数
+ *
+ * 指定转换格式但既不保存也不嵌入时，默认保存缩略图文件（否则转换不会生效）
+ ***************************************************************************/
+
+fn thumbnail_args(options: &DownloadOptions) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+
+    let convert = options.convert_thumbnails.as_deref().filter(|f| !f.is_empty());
+    if let Some(format) = convert {
+        if !THUMBNAIL_FORMATS.contains(&format) {
+            return Err(format!(
+                "不支持的缩略图格式: {}（可选 {}）",
+                format,
+                THUMBNAIL_FORMATS.join("/")
+            ));
+        }
+        if find_ffmpeg().is_none() {
+            return Err("转换缩略图需要 ffmpeg，请先安装 ffmpeg".to_string());
+        }
+    }
+
+    if options.write_thumbnail || (convert.is_some() && !options.embed_thumbnail) {
+        args.push("--write-thumbnail".to_string());
+    }
+    if options.embed_thumbnail {
+        args.push("--embed-thumbnail".to_string());
+    }
+    if let Some(format) = convert {
+        args.push("--convert-thumbnails".to_string());
+        args.push(format.to_string());
+    }
+
+    Ok(args)
+}
+
+/***************************************************************************
+ * 检查视频/音频格式ID对的写法
+ *
+ * 每一项必须是单个格式ID（不能再含 + , / 等选择器语法），且两者不能相同；
+ * 格式种类（视频/音频）在下载前按实际格式列表检查
+ ***************************************************************************/
+
+fn validate_format_pair(options: &DownloadOptions) -> Result<(), String> {
+    let ids = [&options.video_format_id, &options.audio_format_id];
+    for id in ids.into_iter().flatten().filter(|id| !id.is_empty()) {
+        if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(format!("格式ID无效: {}", id));
+        }
+    }
+    if options.video_format_id.is_some() && options.video_format_id == options.audio_format_id {
+        return Err("视频格式和音频格式不能是同一个格式".to_string());
+    }
+    let pair = options.video_format_id.is_some() && options.audio_format_id.is_some();
+    if pair && !options.separate_streams && find_ffmpeg().is_none() {
+        return Err("合并视频格式和音频格式需要 ffmpeg，请先安装 ffmpeg".to_string());
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * 归档模式参数
+ *
+ * 最佳视频与全部纯音频格式合并（bv*+mergeall[vcodec=none]，需要
+ * --audio-multistreams），嵌入全部字幕、章节和元数据，输出 MKV；
+ * 指定了最大高度（或设置中的分辨率上限）时视频不超过该高度。
+ * 不能与只提取音频、分别下载或直接指定格式同时使用
+ ***************************************************************************/
+
+pub fn archive_best_args(options: &DownloadOptions, settings: &Settings) -> Result<Vec<String>, String> {
+    let explicit_format = options.format.is_some()
+        || options.format_id.is_some()
+        || options.video_format_id.is_some()
+        || options.audio_format_id.is_some();
+    let conflicts = [
+        (options.extract_audio.as_deref().is_some_and(|f| !f.is_empty()), "只提取音频"),
+        (options.separate_streams, "分别保存音视频"),
+        (explicit_format, "指定格式"),
+    ];
+    if let Some((_, name)) = conflicts.iter().find(|(conflict, _)| *conflict) {
+        return Err(format!("归档模式不能与{}同时使用", name));
+    }
+    if find_ffmpeg().is_none() {
+        return Err("归档模式需要 ffmpeg 合并全部音轨并嵌入字幕和章节，请先安装 ffmpeg".to_string());
+    }
+
+    let video = match options.max_height.or(settings.max_resolution) {
+        Some(height) => format!("bv*[height<={}]", height),
+        None => "bv*".to_string(),
+    };
+    Ok([
+        "-f",
+        &format!("{}+mergeall[vcodec=none]", video),
+        "--audio-multistreams",
+        "--merge-output-format",
+        "mkv",
+        "--embed-subs",
+        "--sub-langs",
+        ARCHIVE_SUB_LANGS,
+        "--embed-chapters",
+        "--embed-metadata",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect())
+}
+
+/***************************************************************************
+ * 合并容器参数
+ *
+ * 分别下载或只提取音频时不会合并，不附加该参数
+ ***************************************************************************/
+
+fn merge_format_args(options: &DownloadOptions) -> Result<Vec<String>, String> {
+    if options.separate_streams || options.extract_audio.as_deref().is_some_and(|f| !f.is_empty()) {
+        return Ok(Vec::new());
+    }
+
+    let format = match options.merge_output_format.as_deref().filter(|f| !f.is_empty()) {
+        Some(format) if MERGE_FORMATS.contains(&format) => format,
+        Some(format) => {
+            return Err(format!(
+                "不支持的合并格式: {}（可选 {}）",
+                format,
+                MERGE_FORMATS.join("/")
+            ))
+        }
+        None => DEFAULT_MERGE_FORMAT,
+    };
+
+    Ok(vec!["--merge-output-format".to_string(), format.to_string()])
+}
+
+/***************************************************************************
+ * 检查编码能否放入指定容器
+ *
+ * mkv 可容纳任意编码；mp4 不支持 vp8/vp9/vorbis 等 WebM 编码，
+ * webm 只支持 vp8/vp9/av1 视频和 opus/vorbis 音频
+ *
+ * @param container - 合并容器（mp4/mkv/webm）
+ * @param codecs - 参与合并的编码（如 "vp9"、"mp4a.40.2"）
+ * @return Vec<String> - 与容器不兼容的编码
+ ***************************************************************************/
+
+pub fn incompatible_codecs(container: &str, codecs: &[&str]) -> Vec<String> {
+    let supported: &[&str] = match container {
+        "mp4" => &["avc", "h264", "hev", "hvc", "h265", "av01", "mp4a", "aac", "mp3", "opus", "ac-3", "ec-3"],
+        "webm" => &["vp8", "vp9", "vp09", "av01", "opus", "vorbis"],
+        _ => return Vec::new(),
+    };
+
+    codecs
+        .iter()
+        .filter(|codec| !codec.is_empty() && **codec != "none")
+        .filter(|codec| {
+            let codec = codec.to_ascii_lowercase();
+            !supported.iter().any(|prefix| codec.starts_with(prefix))
+        })
+        .map(|codec| codec.to_string())
+        .collect()
+}
+
+/***************************************************************************
+ * 校验自定义 User-Agent
+ *
+ * 值作为 --user-agent 的参数原样传给 yt-dlp，换行会让请求头被截断或注入其他头
+ *
+ * @param user_agent - 下载选项或获取信息时指定的 User-Agent（None 使用默认值）
+ ***************************************************************************/
+
+pub fn validate_user_agent(user_agent: Option<&str>) -> Result<(), String> {
+    let Some(user_agent) = user_agent else {
+        return Ok(());
+    };
+    if user_agent.trim().is_empty() {
+        return Err("User-Agent 不能为空".to_string());
+    }
+    if user_agent.contains(['\n', '\r']) {
+        return Err("User-Agent 只能是一行".to_string());
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * 校验自定义文件名模板
+ *
+ * 模板只决定下载目录内的文件名（可含子目录），不能是绝对路径或用 ".." 跳出下载目录；
+ * 分别下载音视频时两个流需要用 %(format_id)s 区分，否则后下载的会覆盖先下载的
+ ***************************************************************************/
+
+pub fn validate_output_template(options: &DownloadOptions) -> Result<(), String> {
+    let Some(template) = options.output_template.as_deref().filter(|t| !t.is_empty()) else {
+        return Ok(());
+    };
+    let path = Path::new(template);
+    if path.is_absolute() || path.has_root() {
+        return Err(format!("文件名模板不能是绝对路径: {}", template));
+    }
+    if path.components().any(|c| matches!(c, std::path::Component::ParentDir)) {
+        return Err(format!("文件名模板不能包含 \"..\": {}", template));
+    }
+    if !template.contains("%(ext)s") {
+        return Err("文件名模板需要包含 %(ext)s".to_string());
+    }
+    if options.separate_streams && !template.contains("%(format_id)s") {
+        return Err("分别下载音视频时文件名模板需要包含 %(format_id)s".to_string());
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * 提取音频参数（转换格式需要 ffmpeg）
+ ***************************************************************************/
+
+fn extract_audio_args(options: &DownloadOptions) -> Result<Vec<String>, String> {
+    let Some(format) = options.extract_audio.as_deref().filter(|f| !f.is_empty()) else {
+        return Ok(Vec::new());
+    };
+    if !AUDIO_FORMATS.contains(&format) {
+        return Err(format!(
+            "不支持的音频格式: {}（可选 {}）",
+            format,
+            AUDIO_FORMATS.join("/")
+        ));
+    }
+    if find_ffmpeg().is_none() {
+        return Err("提取音频需要 ffmpeg，请先安装 ffmpeg".to_string());
+    }
+
+    Ok(vec![
+        "-x".to_string(),
+        "--audio-format".to_string(),
+        format.to_string(),
+    ])
+}
+
+/***************************************************************************
+ * 反检测与网络相关参数
+ ***************************************************************************/
+
+pub fn network_args(options: &DownloadOptions) -> Vec<String> {
+    let mut args = Vec::new();
+
+    if let Some(target) = &options.impersonate {
+        args.push("--impersonate".to_string());
+        args.push(target.clone());
+    }
+    args.push("--user-agent".to_string());
+    args.push(options.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT).to_string());
+    if let Some(browser) = &options.cookies_from_browser {
+        args.push("--cookies-from-browser".to_string());
+        args.push(browser.clone());
+    }
+    if let Some(interval) = options.sleep_interval {
+        args.push("--sleep-interval".to_string());
+        args.push(interval.to_string());
+    }
+    if let Some(retries) = options.retries {
+        args.push("--retries".to_string());
+        args.push(retries.to_string());
+    }
+
+    args
+}
+
+/***************************************************************************
+ * 构建下载命令参数
+ *
+ * @param url - 视频URL
+ * @param options - 结构化下载选项
+ * @param settings - 全局设置
+ * @param staging_dir - 暂存目录（未开启 stage_downloads 时为 None）
+ * @param format_report - 实际下载格式的报告文件（见 FORMAT_REPORT_TEMPLATE）
+ * @return Result<Vec<String>, String> - 完整的 yt-dlp 参数
+ ***************************************************************************/
+
+pub fn build_download_args(
+    url: &str,
+    options: &DownloadOptions,
+    settings: &Settings,
+    staging_dir: Option<&Path>,
+    format_report: Option<&Path>,
+) -> Result<Vec<String>, String> {
+    // 不加 --no-warnings：警告行由下载过程解析并发送 ytdlp-warning 事件
+    let mut args: Vec<String> = vec!["--progress".to_string()];
+
+    // 全局设置（临时目录、缓存目录等）
+    args.extend(settings.download_args(staging_dir)?);
+
+    // 质量选择（归档模式使用固定的格式和容器）
+    if options.archive_best {
+        args.extend(archive_best_args(options, settings)?);
+    } else {
+        args.push("-f".to_string());
+        let selector = FormatSelector::from_settings(options, settings);
+        args.push(selector.expression());
+        if let Some(sort) = selector.sort(options.format_sort.as_deref()) {
+            args.push("-S".to_string());
+            args.push(sort);
+        }
+
+        validate_format_pair(options)?;
+
+        // 合并后的容器
+        args.extend(merge_format_args(options)?);
+    }
+
+    // 只保留音频
+    args.extend(extract_audio_args(options)?);
+
+    // 时间段下载（核心功能）
+    if options.start_time.is_some() || options.end_time.is_some() {
+        let start = options.start_time.unwrap_or(0.0);
+        let end = options
+            .end_time
+            .map(|end| end.to_string())
+            .unwrap_or_else(|| "inf".to_string());
+        args.push("--download-sections".to_string());
+        args.push(format!("*{}-{}", start, end));
+    }
+
+    // 字幕下载（归档模式已嵌入全部字幕）
+    if let Some(langs) = options.subtitle_langs.as_deref().filter(|l| !options.archive_best && !l.is_empty()) {
+        args.push("--write-subs".to_string());
+        args.push("--sub-langs".to_string());
+        args.push(langs.to_string());
+        args.push("--sub-format".to_string());
+        args.push("srt".to_string());
+    }
+
+    // 评论（写入与媒体文件同名的 .info.json）
+    if options.write_comments {
+        let max_comments = options.max_comments.unwrap_or(DEFAULT_MAX_COMMENTS);
+        args.push("--write-comments".to_string());
+        args.push("--write-info-json".to_string());
+        args.push("--extractor-args".to_string());
+        args.push(format!("youtube:max_comments={}", max_comments));
+    }
+
+    // 缩略图
+    args.extend(thumbnail_args(options)?);
+
+    // 反检测参数
+    validate_user_agent(options.user_agent.as_deref())?;
+    args.extend(network_args(options));
+    args.extend(retry_sleep_args(options)?);
+    if let Some(interval) = options.wait_for_video {
+        args.push("--wait-for-video".to_string());
+        args.push(interval.max(1).to_string());
+    }
+
+    // 时长过滤
+    args.extend(duration_filter_args(options)?);
+
+    // 下载存档：完成后记录视频ID，已记录的视频直接跳过
+    if let Some(archive) = options.archive_file.as_deref().filter(|a| !a.is_empty()) {
+        validate_archive_file(archive)?;
+        args.push("--download-archive".to_string());
+        args.push(archive.to_string());
+    }
+
+    // 带 list 参数的视频链接默认只下载该视频；
+    // 播放列表：跳过出错的条目继续下载其余条目（结束时按部分成功处理）
+    if is_watch_with_list(url) && !options.treat_as_playlist {
+        args.push("--no-playlist".to_string());
+    } else if is_playlist_url(url) {
+        args.push("--ignore-errors".to_string());
+    }
+
+    // 文件名处理与输出路径
+    validate_output_template(options)?;
+    args.extend(filename_args(options, settings, staging_dir));
+
+    // 记录实际下载的格式（--print-to-file 不会像 --print 那样隐含 --quiet）
+    if let Some(path) = format_report {
+        args.push("--print-to-file".to_string());
+        args.push(FORMAT_REPORT_TEMPLATE.to_string());
+        args.push(path.to_string_lossy().into_owned());
+    }
+
+    // URL
+    args.push(url.to_string());
+
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const URL: &str = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+
+    fn capped(max_resolution: Option<i64>) -> Settings {
+        Settings {
+            max_resolution,
+            ..Settings::default()
+        }
+    }
+
+    /// 回退阶梯：从 heights 的第一级开始逐级回退，最后为 b
+    fn ladder(heights: &[i64]) -> String {
+        heights.iter().map(|height| format!("bv*[height<={}]+ba/", height)).collect::<String>() + "b"
+    }
+
+    /// 预览命令（与下载使用同一组参数）中某个选项的取值
+    fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+        let index = args.iter().position(|arg| arg == name)?;
+        args.get(index + 1).map(String::as_str)
+    }
+
+    fn preview_args(options: &DownloadOptions, settings: &Settings) -> Vec<String> {
+        let staging = PathBuf::from("/tmp/youtudown-preview");
+        build_download_args(URL, options, settings, Some(&staging), None).unwrap()
+    }
+
+    #[test]
+    fn without_max_resolution_selects_best() {
+        let selector = FormatSelector::from_settings(&DownloadOptions::default(), &capped(None));
+
+        assert_eq!(selector.max_height, None);
+        assert_eq!(selector.expression(), "bestvideo+bestaudio/best");
+        assert_eq!(selector.sort(None), None);
+    }
+
+    #[test]
+    fn max_resolution_caps_unselected_downloads() {
+        let selector = FormatSelector::from_settings(&DownloadOptions::default(), &capped(Some(1080)));
+
+        assert_eq!(selector.max_height, Some(1080));
+        assert_eq!(selector.expression(), ladder(&[1080, 720, 480, 360, 240, 144]));
+
+        let smallest = DownloadOptions {
+            format_goal: FormatGoal::SmallestSize,
+            ..Default::default()
+        };
+        let selector = FormatSelector::from_settings(&smallest, &capped(Some(1080)));
+        assert_eq!(selector.sort(None).as_deref(), Some("res:1080,+size,+br"));
+    }
+
+    #[test]
+    fn explicit_max_height_beats_the_cap() {
+        let options = DownloadOptions {
+            max_height: Some(2160),
+            ..Default::default()
+        };
+        let selector = FormatSelector::from_settings(&options, &capped(Some(720)));
+
+        assert_eq!(selector.max_height, Some(2160));
+        assert_eq!(selector.expression(), ladder(&[2160, 1440, 1080, 720, 480, 360, 240, 144]));
+    }
+
+    #[test]
+    fn explicit_formats_are_not_capped() {
+        let cases = [
+            (
+                DownloadOptions {
+                    format: Some("bv*+ba/b".to_string()),
+                    ..Default::default()
+                },
+                "bv*+ba/b",
+            ),
+            (
+                DownloadOptions {
+                    format_id: Some("137".to_string()),
+                    ..Default::default()
+                },
+                "137",
+            ),
+            (
+                DownloadOptions {
+                    video_format_id: Some("401".to_string()),
+                    audio_format_id: Some("251".to_string()),
+                    ..Default::default()
+                },
+                "401+251",
+            ),
+        ];
+        for (options, expected) in cases {
+            let selector = FormatSelector::from_settings(&options, &capped(Some(720)));
+            assert_eq!(selector.max_height, None, "{}", expected);
+            assert_eq!(selector.expression(), expected);
+        }
+    }
+
+    #[test]
+    fn prefer_progressive_comes_from_settings() {
+        let settings = Settings {
+            prefer_progressive: true,
+            ..capped(Some(480))
+        };
+        let selector = FormatSelector::from_settings(&DownloadOptions::default(), &settings);
+
+        assert_eq!(
+            selector.expression(),
+            "b[height<=480][height>=360]/bv*[height<=480]+ba/b[height<=360][height>=240]/bv*[height<=360]+ba/\
+             b[height<=240][height>=144]/bv*[height<=240]+ba/b[height<=144][height>=144]/bv*[height<=144]+ba/b"
+        );
+    }
+
+    #[test]
+    fn preview_command_uses_the_capped_selector() {
+        let args = preview_args(&DownloadOptions::default(), &capped(Some(720)));
+
+        assert_eq!(option_value(&args, "-f"), Some(ladder(&[720, 480, 360, 240, 144]).as_str()));
+        assert_eq!(option_value(&args, "-S"), None);
+        assert_eq!(args.last().map(String::as_str), Some(URL));
+    }
+
+    #[test]
+    fn preview_command_keeps_explicit_choices() {
+        let options = DownloadOptions {
+            format_id: Some("137".to_string()),
+            format_sort: Some("+size".to_string()),
+            ..Default::default()
+        };
+        let args = preview_args(&options, &capped(Some(720)));
+
+        assert_eq!(option_value(&args, "-f"), Some("137"));
+        assert_eq!(option_value(&args, "-S"), Some("+size"));
+
+        let uncapped = preview_args(&DownloadOptions::default(), &capped(None));
+        assert_eq!(option_value(&uncapped, "-f"), Some("bestvideo+bestaudio/best"));
+    }
+}
@@ -0,0 +1,234 @@
+/****************************************************************************
+ *  output_lines.rs - 按行读取子进程输出
+ *
+ *  @brief  按原始字节读取 yt-dlp 的输出，以 \n 或 \r 分行，
+ *          再按 UTF-8 宽松解码（无效字节替换为 U+FFFD）
+ *  @note   Windows 部分代码页或非 UTF-8 编码的文件名会让 yt-dlp 输出无效的
+ *          UTF-8，BufReader::lines() 遇到时返回错误，读取循环随之结束，
+ *          进度上报在下载中途中断；这里不会因编码问题停止读取。
+ *          空行（包括 \r\n 之间的空行）直接跳过
+ *****************************************************************************/
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// 宽松解码时替换无效字节的字符
+const REPLACEMENT: char = '\u{FFFD}';
+
+/***************************************************************************
+ * 宽松解码的行读取器
+ ***************************************************************************/
+
+pub struct LossyLines<R> {
+    reader: R,
+    buffer: Vec<u8>,
+}
+
+/// 包装一个缓冲读取器，接口与 AsyncBufReadExt::lines() 一致
+pub fn lossy_lines<R: AsyncBufRead + Unpin>(reader: R) -> LossyLines<R> {
+    LossyLines {
+        reader,
+        buffer: Vec::new(),
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> LossyLines<R> {
+    /***********************************************************************
+     * 读取下一行（不含换行符）
+     *
+     * @return Ok(None) - 输出已结束
+     ***********************************************************************/
+    pub async fn next_line(&mut self) -> io::Result<Option<String>> {
+        loop {
+            if let Some(line) = take_line(&mut self.buffer) {
+                return Ok(Some(line));
+            }
+
+            let available = self.reader.fill_buf().await?;
+            if available.is_empty() {
+                // 最后一行没有换行符
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                let rest = std::mem::take(&mut self.buffer);
+                return Ok(Some(String::from_utf8_lossy(&rest).into_owned()));
+            }
+            let length = available.len();
+            self.buffer.extend_from_slice(available);
+            self.reader.consume(length);
+        }
+    }
+}
+
+/// 从缓冲区取出一个非空的完整行（缓冲区中没有完整行时返回 None）
+fn take_line(buffer: &mut Vec<u8>) -> Option<String> {
+    while let Some(end) = buffer.iter().position(|&b| b == b'\n' || b == b'\r') {
+        let line: Vec<u8> = buffer.drain(..=end).collect();
+        let line = &line[..end];
+        if !line.is_empty() {
+            return Some(String::from_utf8_lossy(line).into_owned());
+        }
+    }
+    None
+}
+
+/***************************************************************************
+ * 用文件系统中的实际文件名还原含替换字符的路径
+ *
+ * 把文件名中的每段 U+FFFD 当作通配符，在所在目录中查找；
+ * 恰好一个文件匹配时返回该文件，否则原样返回
+ *
+ * @param path - 从 yt-dlp 输出中解析出的路径
+ * @return String - 文件系统中的实际路径
+ ***************************************************************************/
+
+pub fn resolve_lossy_path(path: &str) -> String {
+    if !path.contains(REPLACEMENT) {
+        return path.to_string();
+    }
+
+    let path_ref = Path::new(path);
+    let (Some(parent), Some(name)) = (path_ref.parent(), path_ref.file_name()) else {
+        return path.to_string();
+    };
+    let name = name.to_string_lossy().into_owned();
+    let segments: Vec<&str> = name.split(REPLACEMENT).collect();
+
+    let Ok(entries) = fs::read_dir(parent) else {
+        return path.to_string();
+    };
+    let mut matches = entries
+        .flatten()
+        .filter(|entry| matches_segments(&entry.file_name().to_string_lossy(), &segments));
+
+    match (matches.next(), matches.next()) {
+        (Some(entry), None) => entry.path().to_string_lossy().into_owned(),
+        _ => path.to_string(),
+    }
+}
+
+/// 文件名依次包含各段（首段为前缀、末段为后缀），段之间可以是任意字符
+fn matches_segments(candidate: &str, segments: &[&str]) -> bool {
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return false;
+    };
+    if !candidate.starts_with(first) || !candidate[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &candidate[first.len()..candidate.len() - last.len()];
+    for segment in &segments[1..segments.len() - 1] {
+        match rest.find(segment) {
+            Some(index) => rest = &rest[index + segment.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tokio::io::BufReader;
+
+    async fn read_all(input: &[u8], capacity: usize) -> Vec<String> {
+        let mut lines = lossy_lines(BufReader::with_capacity(capacity, input));
+        let mut result = Vec::new();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            result.push(line);
+        }
+        result
+    }
+
+    /// 测试用的空目录（以测试名区分，重复运行时先清空）
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("youtudown-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn invalid_utf8_is_replaced_and_reading_continues() {
+        let input = b"[download] Destination: \xffvideo\xfe.mp4\n[download]  10.0% of 1.00MiB\nlast";
+        let lines = read_all(input, 8).await;
+
+        assert_eq!(
+            lines,
+            [
+                "[download] Destination: \u{FFFD}video\u{FFFD}.mp4",
+                "[download]  10.0% of 1.00MiB",
+                "last"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn splits_on_mixed_line_endings_and_skips_empty_lines() {
+        let input = "第一行\r\n进度 1%\r进度 2%\r\n\n\r最后一行\n".as_bytes();
+        // 容量很小时多字节字符会跨两次读取
+        let lines = read_all(input, 2).await;
+
+        assert_eq!(lines, ["第一行", "进度 1%", "进度 2%", "最后一行"]);
+    }
+
+    #[test]
+    fn matches_segments_in_order() {
+        let segments = ["video-", "-", ".mp4"];
+        assert!(matches_segments("video-视频-x.mp4", &segments));
+        assert!(matches_segments("video--.mp4", &segments));
+        assert!(!matches_segments("video-视频.mp4", &segments));
+        assert!(!matches_segments("audio-视频-x.mp4", &segments));
+        assert!(!matches_segments("video-视频-x.webm", &segments));
+        assert!(!matches_segments("video", &[]));
+    }
+
+    #[test]
+    fn resolves_replacement_characters_to_the_single_match() {
+        let dir = test_dir("resolve-single");
+        fs::write(dir.join("视频 [abc].mp4"), b"").unwrap();
+        fs::write(dir.join("视频 [abc].webm"), b"").unwrap();
+
+        let lossy = dir.join("\u{FFFD}\u{FFFD} [abc].mp4");
+        let resolved = resolve_lossy_path(&lossy.to_string_lossy());
+        assert_eq!(resolved, dir.join("视频 [abc].mp4").to_string_lossy());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ambiguous_or_missing_matches_keep_the_original_path() {
+        let dir = test_dir("resolve-ambiguous");
+        fs::write(dir.join("a1.mp4"), b"").unwrap();
+        fs::write(dir.join("a2.mp4"), b"").unwrap();
+
+        let ambiguous = dir.join("a\u{FFFD}.mp4").to_string_lossy().into_owned();
+        assert_eq!(resolve_lossy_path(&ambiguous), ambiguous);
+        let missing = dir.join("b\u{FFFD}.mp4").to_string_lossy().into_owned();
+        assert_eq!(resolve_lossy_path(&missing), missing);
+        let plain = dir.join("a1.mp4").to_string_lossy().into_owned();
+        assert_eq!(resolve_lossy_path(&plain), plain);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_non_utf8_file_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = test_dir("resolve-non-utf8");
+        let actual = dir.join(OsStr::from_bytes(b"clip-\xff\xfe.mp4"));
+        fs::write(&actual, b"").unwrap();
+
+        let lossy = dir.join("clip-\u{FFFD}\u{FFFD}.mp4");
+        let resolved = resolve_lossy_path(&lossy.to_string_lossy());
+        assert_eq!(resolved, actual.to_string_lossy());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+/****************************************************************************
+ *  playlist_items.rs - 单进程播放列表下载的逐条结果
+ *
+ *  @brief  从 yt-dlp 输出中记录每个条目的开始和出错，进程以非零退出码结束时
+ *          区分"全部失败"和"部分成功"
+ *  @note   播放列表链接直接交给 download_video 时由一个 yt-dlp 进程依次下载全部条目，
+ *          附加 --ignore-errors 后出错的条目被跳过，但只要有一个出错退出码就非零。
+ *          标准输出和标准错误由两个任务分别读取，出错行归到最近开始的条目
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// 错误行前缀
+const ERROR_PREFIX: &str = "ERROR:";
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct ItemFailure {
+    pub index: u32,                 // 条目在播放列表中的序号（从 1 开始）
+    pub reason: String,             // yt-dlp 的错误信息（不含 "ERROR:" 前缀）
+}
+
+/// 部分条目下载失败，其余已完成
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PartialSuccess {
+    pub completed: u32,             // 已完成的条目数
+    pub failed: Vec<ItemFailure>,   // 失败的条目
+}
+
+#[derive(Debug, Default)]
+pub struct PlaylistItems {
+    total: Option<u32>,             // 播放列表条目数（不是播放列表时为 None）
+    started: Vec<u32>,              // 已开始的条目序号
+    failed: Vec<ItemFailure>,
+}
+
+impl PlaylistItems {
+    /// 处理一行标准输出，识别条目开始
+    pub fn record_output(&mut self, line: &str) {
+        if let Some((index, total)) = parse_item_line(line) {
+            self.total = Some(total);
+            if !self.started.contains(&index) {
+                self.started.push(index);
+            }
+        }
+    }
+
+    /// 处理一行标准错误，错误行归到最近开始的条目（还没有条目开始时忽略）
+    pub fn record_error(&mut self, line: &str) {
+        let Some(reason) = line.trim_start().strip_prefix(ERROR_PREFIX) else {
+            return;
+        };
+        let Some(&index) = self.started.last() else {
+            return;
+        };
+        // 同一条目的后续错误行不重复记录
+        if !self.failed.iter().any(|failure| failure.index == index) {
+            self.failed.push(ItemFailure {
+                index,
+                reason: reason.trim().to_string(),
+            });
+        }
+    }
+
+    /***********************************************************************
+     * 部分成功的结果
+     *
+     * @return Option<PartialSuccess> - 是播放列表且至少一个条目完成时为 Some
+     ***********************************************************************/
+    pub fn partial_success(&self) -> Option<PartialSuccess> {
+        self.total?;
+        let completed = self
+            .started
+            .iter()
+            .filter(|index| !self.failed.iter().any(|failure| failure.index == **index))
+            .count() as u32;
+        (completed > 0).then(|| PartialSuccess {
+            completed,
+            failed: self.failed.clone(),
+        })
+    }
+}
+
+/***************************************************************************
+ * 解析条目开始行
+ *
+ * 格式示例:
+ * [download] Downloading item 3 of 25
+ * [download] Downloading video 3 of 25（旧版本 yt-dlp）
+ *
+ * @return Option<(u32, u32)> - (条目序号, 条目总数)
+ ***************************************************************************/
+
+fn parse_item_line(line: &str) -> Option<(u32, u32)> {
+    let rest = line.trim().strip_prefix("[download] Downloading ")?;
+    let rest = rest.strip_prefix("item ").or_else(|| rest.strip_prefix("video "))?;
+    let (index, total) = rest.split_once(" of ")?;
+    Some((index.trim().parse().ok()?, total.trim().parse().ok()?))
+}
@@ -0,0 +1,209 @@
+/****************************************************************************
+ *  presets.rs - 质量预设
+ *
+ *  @brief  命名的下载选项组合：四个内置预设 + 用户保存的预设（存放在设置中）
+ *  @note   入队时可以只传预设名，本次调用的选项作为覆盖项合并到预设之上
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use ts_rs::TS;
+
+use crate::options::DownloadOptions;
+
+/// 内置预设名称
+pub const PRESET_BEST: &str = "Best available";
+pub const PRESET_1080P: &str = "1080p max";
+pub const PRESET_DATA_SAVER: &str = "Data saver";
+pub const PRESET_AUDIO_MP3: &str = "Audio only mp3";
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Preset {
+    pub name: String,
+    pub options: DownloadOptions,
+    #[serde(default)]
+    pub builtin: bool,              // 内置预设不能修改或删除
+}
+
+/***************************************************************************
+ * 内置预设
+ *
+ * - Best available：最佳视频 + 最佳音频
+ * - 1080p max：bestvideo[height<=1080]+bestaudio/best[height<=1080]
+ * - Data saver：优先 480p 及以下、体积最小的格式
+ * - Audio only mp3：只保留最佳音频并转为 mp3
+ ***************************************************************************/
+
+pub fn builtin_presets() -> Vec<Preset> {
+    let builtin = |name: &str, options: DownloadOptions| Preset {
+        name: name.to_string(),
+        options,
+        builtin: true,
+    };
+
+    vec![
+        builtin(PRESET_BEST, DownloadOptions::default()),
+        builtin(
+            PRESET_1080P,
+            DownloadOptions {
+                format: Some("bestvideo[height<=1080]+bestaudio/best[height<=1080]".to_string()),
+                ..Default::default()
+            },
+        ),
+        builtin(
+            PRESET_DATA_SAVER,
+            DownloadOptions {
+                format: Some("bv*+ba/b".to_string()),
+                format_sort: Some("res:480,+size,+br".to_string()),
+                ..Default::default()
+            },
+        ),
+        builtin(
+            PRESET_AUDIO_MP3,
+            DownloadOptions {
+                extract_audio: Some("mp3".to_string()),
+                ..Default::default()
+            },
+        ),
+    ]
+}
+
+/// 内置预设 + 用户预设（内置在前）
+pub fn all_presets(user_presets: &[Preset]) -> Vec<Preset> {
+    builtin_presets()
+        .into_iter()
+        .chain(user_presets.iter().cloned())
+        .collect()
+}
+
+/// 判断名称是否属于内置预设（不区分大小写）
+pub fn is_builtin_name(name: &str) -> bool {
+    builtin_presets()
+        .iter()
+        .any(|preset| preset.name.eq_ignore_ascii_case(name))
+}
+
+/***************************************************************************
+ * 把覆盖项合并到预设选项之上
+ *
+ * 覆盖项中出现的字段替换预设中的同名字段（包括显式传入的 null），
+ * 未出现的字段沿用预设
+ *
+ * @param base - 预设选项（未指定预设时为默认选项）
+ * @param overrides - 本次调用传入的选项字段
+ ***************************************************************************/
+
+pub fn merge_options(
+    base: &DownloadOptions,
+    overrides: Option<Map<String, Value>>,
+) -> Result<DownloadOptions, String> {
+    let Some(overrides) = overrides else {
+        return Ok(base.clone());
+    };
+
+    let mut merged = match serde_json::to_value(base) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) => Map::new(),
+        Err(e) => return Err(format!("序列化预设失败: {}", e)),
+    };
+    merged.extend(overrides);
+
+    serde_json::from_value(Value::Object(merged)).map_err(|e| format!("下载选项无效: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::FormatSelector;
+    use crate::settings::Settings;
+    use serde_json::json;
+
+    /// 下载时生成的 -f / -S 参数（与 build_download_args 的质量选择部分一致）
+    fn format_args(options: &DownloadOptions) -> Vec<String> {
+        let selector = FormatSelector::from_settings(options, &Settings::default());
+        let mut args = vec!["-f".to_string(), selector.expression()];
+        if let Some(sort) = selector.sort(options.format_sort.as_deref()) {
+            args.push("-S".to_string());
+            args.push(sort);
+        }
+        args
+    }
+
+    fn preset(name: &str) -> DownloadOptions {
+        builtin_presets()
+            .into_iter()
+            .find(|preset| preset.name == name)
+            .map(|preset| preset.options)
+            .expect("内置预设应存在")
+    }
+
+    fn overrides(value: Value) -> Option<Map<String, Value>> {
+        match value {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn builtin_presets_generate_expected_format_args() {
+        let cases: [(&str, &[&str]); 4] = [
+            (PRESET_BEST, &["-f", "bestvideo+bestaudio/best"]),
+            (PRESET_1080P, &["-f", "bestvideo[height<=1080]+bestaudio/best[height<=1080]"]),
+            (PRESET_DATA_SAVER, &["-f", "bv*+ba/b", "-S", "res:480,+size,+br"]),
+            (PRESET_AUDIO_MP3, &["-f", "ba/b"]),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(format_args(&preset(name)), expected, "{}", name);
+        }
+        assert_eq!(preset(PRESET_AUDIO_MP3).extract_audio.as_deref(), Some("mp3"));
+    }
+
+    #[test]
+    fn builtin_presets_are_marked_and_listed_first() {
+        let user = Preset {
+            name: "My preset".to_string(),
+            options: DownloadOptions::default(),
+            builtin: false,
+        };
+        let names: Vec<String> = all_presets(&[user]).into_iter().map(|preset| preset.name).collect();
+
+        assert_eq!(names, [PRESET_BEST, PRESET_1080P, PRESET_DATA_SAVER, PRESET_AUDIO_MP3, "My preset"]);
+        assert!(builtin_presets().iter().all(|preset| preset.builtin));
+        assert!(is_builtin_name("data SAVER"));
+        assert!(!is_builtin_name("My preset"));
+    }
+
+    #[test]
+    fn merge_without_overrides_keeps_the_preset() {
+        let merged = merge_options(&preset(PRESET_DATA_SAVER), None).unwrap();
+        assert_eq!(format_args(&merged), ["-f", "bv*+ba/b", "-S", "res:480,+size,+br"]);
+    }
+
+    #[test]
+    fn overrides_replace_only_the_given_fields() {
+        let base = preset(PRESET_DATA_SAVER);
+        let merged = merge_options(&base, overrides(json!({ "format_sort": "res:360,+size" }))).unwrap();
+
+        assert_eq!(format_args(&merged), ["-f", "bv*+ba/b", "-S", "res:360,+size"]);
+    }
+
+    #[test]
+    fn null_override_clears_the_preset_field() {
+        let base = preset(PRESET_1080P);
+        let merged = merge_options(&base, overrides(json!({ "format": null, "max_height": 720 }))).unwrap();
+
+        assert_eq!(merged.format, None);
+        let ladder = [720, 480, 360, 240, 144].map(|height| format!("bv*[height<={}]+ba/", height)).concat() + "b";
+        assert_eq!(format_args(&merged), ["-f", ladder.as_str()]);
+    }
+
+    #[test]
+    fn invalid_override_is_rejected() {
+        let result = merge_options(&preset(PRESET_BEST), overrides(json!({ "max_height": "hd" })));
+        assert!(result.unwrap_err().starts_with("下载选项无效"));
+    }
+}
@@ -0,0 +1,242 @@
+/****************************************************************************
+ *  process.rs - 子进程环境
+ *
+ *  @brief  统一构建 yt-dlp 子进程：固定 UTF-8 区域设置、去掉 Python 路径变量，
+ *          按设置决定代理、配置文件和进程优先级；以 Python 模块方式运行时
+ *          在解释器之后插入 -m yt_dlp
+ *  @note   用户终端中的 PYTHONPATH、LC_ALL 等变量会让应用内的 yt-dlp 行为不同，
+ *          本地化的错误信息也会让 stderr 匹配失效；所有 yt-dlp 调用都应通过
+ *          ytdlp_command 创建，新增的调用自动获得相同的环境
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tokio::process::Command;
+use ts_rs::TS;
+
+use crate::settings::Settings;
+
+/// 子进程使用的区域设置（macOS 没有 C.UTF-8）
+const UTF8_LOCALE: &str = if cfg!(target_os = "macos") { "en_US.UTF-8" } else { "C.UTF-8" };
+
+/// 会改变 yt-dlp 所用 Python 环境的变量
+const PYTHON_VARS: &[&str] = &["PYTHONPATH", "PYTHONHOME"];
+
+/// 代理相关的环境变量（大小写两种写法都会被读取）
+const PROXY_VARS: &[&str] = &[
+    "HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY", "NO_PROXY",
+    "http_proxy", "https_proxy", "all_proxy", "no_proxy",
+];
+
+/***************************************************************************
+ * 子进程代理配置（启动时和修改设置时更新）
+ ***************************************************************************/
+
+struct ProxyConfig {
+    proxy: Option<String>,          // 设置中的代理（--proxy）
+    inherit_env: bool,              // 未设置代理时是否沿用环境变量中的代理
+}
+
+static PROXY_CONFIG: RwLock<ProxyConfig> = RwLock::new(ProxyConfig {
+    proxy: None,
+    inherit_env: true,
+});
+
+/***************************************************************************
+ * yt-dlp 配置文件
+ *
+ * 配置文件参数放在命令行最前面，之后由应用生成的参数覆盖其中的同名选项，
+ * 即优先级为：应用内的选项 > ytdlp_config > 全局/用户配置（未忽略时）
+ ***************************************************************************/
+
+struct ConfigFiles {
+    location: Option<String>,       // --config-location
+    ignore_global: bool,            // --ignore-config
+}
+
+static CONFIG_FILES: RwLock<ConfigFiles> = RwLock::new(ConfigFiles {
+    location: None,
+    ignore_global: false,
+});
+
+/***************************************************************************
+ * 以 Python 模块方式运行 yt-dlp
+ *
+ * 只通过 pip 安装了 yt_dlp 模块、没有 yt-dlp 可执行文件时，get_ytdlp_path
+ * 返回 Python 解释器的路径并在此登记；ytdlp_command 收到该路径时展开为
+ * "解释器 -m yt_dlp"，调用方仍按可执行文件路径传递，不需要区分两种方式
+ ***************************************************************************/
+
+/// 解释器之后、其他参数之前的参数
+const MODULE_ARGS: &[&str] = &["-m", "yt_dlp"];
+
+static MODULE_INTERPRETER: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 登记以模块方式运行 yt-dlp 的解释器，None 表示使用可执行文件
+pub fn set_module_interpreter(python: Option<PathBuf>) {
+    if let Ok(mut interpreter) = MODULE_INTERPRETER.write() {
+        *interpreter = python;
+    }
+}
+
+/// program 是否为登记的解释器（即以模块方式运行）
+pub fn is_module_interpreter(program: &Path) -> bool {
+    MODULE_INTERPRETER
+        .read()
+        .is_ok_and(|interpreter| interpreter.as_deref() == Some(program))
+}
+
+/// 检查 python 能否导入 yt_dlp 的命令（环境与 ytdlp_command 相同地去掉 Python 路径变量）
+pub fn module_probe_command(python: &Path) -> std::process::Command {
+    let mut command = std::process::Command::new(python);
+    command.args(["-c", "import yt_dlp, sys; print(yt_dlp.version.__version__)"]);
+    for var in PYTHON_VARS {
+        command.env_remove(var);
+    }
+    command
+}
+
+/// 按设置更新子进程的代理配置、配置文件和优先级
+pub fn configure(settings: &Settings) {
+    if let Ok(mut config) = PROXY_CONFIG.write() {
+        config.proxy = settings.proxy.clone().filter(|p| !p.trim().is_empty());
+        config.inherit_env = settings.inherit_proxy_env;
+    }
+    if let Ok(mut config) = CONFIG_FILES.write() {
+        config.location = settings.ytdlp_config.clone().filter(|p| !p.is_empty());
+        config.ignore_global = settings.ignore_ytdlp_config;
+    }
+    set_priority_mode(settings.background_priority);
+}
+
+/// 设置中的代理（未设置时为 None）
+pub fn proxy() -> Option<String> {
+    PROXY_CONFIG.read().ok().and_then(|config| config.proxy.clone())
+}
+
+/***************************************************************************
+ * 子进程优先级（ffmpeg 由 yt-dlp 启动，继承相同的优先级）
+ *
+ * - Normal: 不调整
+ * - Low: Unix 上 nice 10、ionice best-effort 7；Windows 上 BELOW_NORMAL_PRIORITY_CLASS
+ * - Idle: Unix 上 nice 19、ionice idle；Windows 上 IDLE_PRIORITY_CLASS
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityMode {
+    #[default]
+    Normal,
+    Low,
+    Idle,
+}
+
+static PRIORITY_MODE: RwLock<PriorityMode> = RwLock::new(PriorityMode::Normal);
+
+/// 修改之后新启动的子进程的优先级（已运行的进程不受影响）
+pub fn set_priority_mode(mode: PriorityMode) {
+    if let Ok(mut current) = PRIORITY_MODE.write() {
+        *current = mode;
+    }
+}
+
+/// 当前的子进程优先级
+pub fn priority_mode() -> PriorityMode {
+    PRIORITY_MODE.read().map(|mode| *mode).unwrap_or_default()
+}
+
+/// 在 fork 之后、exec 之前降低优先级（失败时保持原优先级，不影响启动）
+#[cfg(unix)]
+fn apply_priority(command: &mut Command, mode: PriorityMode) {
+    let (nice, io_class, io_level) = match mode {
+        PriorityMode::Normal => return,
+        PriorityMode::Low => (10, 2, 7),    // best-effort 最低级
+        PriorityMode::Idle => (19, 3, 0),   // idle
+    };
+    unsafe {
+        command.pre_exec(move || {
+            libc::setpriority(libc::PRIO_PROCESS, 0, nice);
+            #[cfg(target_os = "linux")]
+            {
+                const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+                const IOPRIO_CLASS_SHIFT: libc::c_long = 13;
+                libc::syscall(
+                    libc::SYS_ioprio_set,
+                    IOPRIO_WHO_PROCESS,
+                    0 as libc::c_long,
+                    (io_class << IOPRIO_CLASS_SHIFT) | io_level,
+                );
+            }
+            #[cfg(not(target_os = "linux"))]
+            let _ = (io_class, io_level);
+            Ok(())
+        });
+    }
+}
+
+/// 以较低的优先级类启动
+#[cfg(windows)]
+fn apply_priority(command: &mut Command, mode: PriorityMode) {
+    use windows::Win32::System::Threading::{BELOW_NORMAL_PRIORITY_CLASS, IDLE_PRIORITY_CLASS};
+    match mode {
+        PriorityMode::Normal => {}
+        PriorityMode::Low => {
+            command.creation_flags(BELOW_NORMAL_PRIORITY_CLASS.0);
+        }
+        PriorityMode::Idle => {
+            command.creation_flags(IDLE_PRIORITY_CLASS.0);
+        }
+    }
+}
+
+/***************************************************************************
+ * 创建 yt-dlp 子进程命令
+ *
+ * - program 为登记的 Python 解释器时先附加 -m yt_dlp
+ * - LANG/LC_ALL 固定为 UTF-8 区域设置，PYTHONIOENCODING 固定为 utf-8
+ * - 去掉 PYTHONPATH/PYTHONHOME
+ * - 设置了代理时去掉代理环境变量并附加 --proxy；
+ *   未设置代理且关闭了"沿用环境代理"时同样去掉代理环境变量
+ * - 按设置附加 --ignore-config / --config-location（在其他参数之前）
+ * - 按当前的 PriorityMode 降低进程优先级
+ ***************************************************************************/
+
+pub fn ytdlp_command(program: impl AsRef<OsStr>) -> Command {
+    let mut command = Command::new(&program);
+    if is_module_interpreter(Path::new(program.as_ref())) {
+        command.args(MODULE_ARGS);
+    }
+    command
+        .env("LANG", UTF8_LOCALE)
+        .env("LC_ALL", UTF8_LOCALE)
+        .env("PYTHONIOENCODING", "utf-8");
+    if let Ok(config) = CONFIG_FILES.read() {
+        if config.ignore_global {
+            command.arg("--ignore-config");
+        }
+        if let Some(location) = &config.location {
+            command.arg("--config-location").arg(location);
+        }
+    }
+    for var in PYTHON_VARS {
+        command.env_remove(var);
+    }
+
+    let (proxy, inherit_env) = PROXY_CONFIG
+        .read()
+        .map(|config| (config.proxy.clone(), config.inherit_env))
+        .unwrap_or((None, true));
+    if proxy.is_some() || !inherit_env {
+        for var in PROXY_VARS {
+            command.env_remove(var);
+        }
+    }
+    if let Some(proxy) = proxy {
+        command.arg("--proxy").arg(proxy);
+    }
+    apply_priority(&mut command, priority_mode());
+
+    command
+}
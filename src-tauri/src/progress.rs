@@ -0,0 +1,864 @@
+/****************************************************************************
+ *  progress.rs - 下载进度辅助计算
+ *
+ *  @brief  解析 yt-dlp 进度中的字节数，并基于吞吐量重新估算剩余时间
+ *  @note   yt-dlp 自带的 ETA 在下载初期波动很大，这里提供滑动窗口和
+ *          指数加权移动平均（EWMA）两种平滑方式
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/***************************************************************************
+ * 下载进度帧（download-progress 事件内容）
+ *
+ * 字段名固定为 snake_case，是与前端约定的格式；删除字段、改名或改变含义时
+ * 递增 PROGRESS_SCHEMA_VERSION，前端据此发现不兼容的变化（新增字段不递增）
+ ***************************************************************************/
+
+/// 进度帧格式版本
+pub const PROGRESS_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub struct ProgressInfo {
+    pub schema_version: u32,        // 格式版本（PROGRESS_SCHEMA_VERSION）
+    pub download_id: String,
+    pub percent: f64,
+    pub speed: String,              // yt-dlp 输出的速度（如 "5.82MiB/s"）
+    pub eta: String,                // yt-dlp 输出的剩余时间（如 "00:12"）
+    #[ts(type = "number | null")]
+    pub downloaded_bytes: Option<u64>, // 按百分比推算的已下载字节数
+    #[ts(type = "number | null")]
+    pub total_bytes: Option<u64>,
+    pub total_bytes_estimated: bool, // 总大小为估算值（"~" 前缀）
+    pub computed_eta_seconds: Option<f64>, // 基于滑动窗口吞吐量估算的剩余秒数
+    pub eta_seconds_raw: Option<f64>, // yt-dlp 原始 ETA（秒）
+    pub eta_seconds_smoothed: Option<f64>, // EWMA 平滑后的剩余秒数
+}
+
+/// 吞吐量滑动窗口长度，窗口越短对速度变化越敏感
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+
+/// EWMA 平滑系数，越大越偏向最新速度
+const EWMA_ALPHA: f64 = 0.2;
+
+/// 两帧进度间隔超过该值视为暂停/重试后恢复，丢弃旧速度
+const EWMA_RESUME_GAP: Duration = Duration::from_secs(10);
+
+/// 速度持续低于限速阈值多久才判定为被限速（避免短暂波动误报）
+const THROTTLE_WINDOW: Duration = Duration::from_secs(30);
+
+/***************************************************************************
+ * 解析带单位的文件大小
+ *
+ * 支持 yt-dlp 输出的二进制单位（KiB/MiB/GiB/TiB）和十进制单位（KB/MB/GB/TB）
+ *
+ * @param text - 如 "125.89MiB"
+ * @return Option<u64> - 字节数
+ ***************************************************************************/
+
+pub fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split_at = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split_at);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier: f64 = match unit.trim() {
+        "B" | "" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        "KB" | "kB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+
+    Some((number * multiplier).round() as u64)
+}
+
+/***************************************************************************
+ * 解析速度字符串
+ *
+ * @param speed - 如 "5.82MiB/s"（"Unknown B/s" 等无法解析的值返回 None）
+ * @return Option<f64> - 字节/秒
+ ***************************************************************************/
+
+pub fn parse_speed(speed: &str) -> Option<f64> {
+    let size = speed.trim().strip_suffix("/s")?;
+    parse_size(size).map(|bytes| bytes as f64)
+}
+
+/***************************************************************************
+ * 解析 yt-dlp 的 ETA 字符串
+ *
+ * @param eta - 如 "00:12"、"01:02:03"（"Unknown" 等返回 None）
+ * @return Option<f64> - 剩余秒数
+ ***************************************************************************/
+
+pub fn parse_eta(eta: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in eta.trim().split(':') {
+        let value: u64 = part.parse().ok()?;
+        seconds = seconds * 60.0 + value as f64;
+    }
+    Some(seconds)
+}
+
+/***************************************************************************
+ * 从进度行中提取总大小
+ *
+ * 格式示例:
+ * [download]  42.0% of 125.89MiB at  5.82MiB/s ETA 00:12
+ * [download]  42.0% of ~125.89MiB at  5.82MiB/s ETA 00:12 (frag 3/50)
+ * [download]  42.0% of ~ 125.89MiB at  5.82MiB/s ETA 00:12 (frag 3/50)
+ *
+ * "~" 表示 yt-dlp 根据分片估算的大小
+ *
+ * @return Option<(u64, bool)> - (字节数, 是否为估算值)
+ ***************************************************************************/
+
+pub fn parse_total_size(line: &str) -> Option<(u64, bool)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let index = parts.iter().position(|part| *part == "of")?;
+
+    let mut size = *parts.get(index + 1)?;
+    let estimated = size.starts_with('~');
+    if size == "~" {
+        size = parts.get(index + 2)?;
+    }
+
+    parse_size(size.trim_start_matches('~')).map(|bytes| (bytes, estimated))
+}
+
+/***************************************************************************
+ * 输出文件跟踪
+ *
+ * 从 yt-dlp 输出中收集目标文件路径：
+ * [download] Destination: /path/video.f137.mp4
+ * [download] /path/video.mp4 has already been downloaded
+ * [Merger] Merging formats into "/path/video.mp4"
+ * [ExtractAudio] Destination: /path/video.mp3
+ * Deleting original file /path/video.f137.mp4 (pass -k to keep)
+ ***************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct OutputTracker {
+    destinations: Vec<String>,      // 按出现顺序记录的所有目标文件
+    merged_into: Option<String>,    // 合并后的文件
+    deleted: Vec<String>,           // yt-dlp 已删除的中间文件
+    sidecars: Vec<String>,          // 字幕、info.json、缩略图、简介等附属文件
+    thumbnail: Option<String>,      // 缩略图文件（转换格式后为转换后的文件）
+}
+
+impl OutputTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一行输出，识别其中的文件路径
+    pub fn record(&mut self, line: &str) {
+        let line = line.trim();
+
+        if let Some(rest) = line.strip_prefix("[Merger] Merging formats into ") {
+            self.merged_into = Some(rest.trim_matches('"').to_string());
+        } else if let Some(path) = line
+            .strip_prefix("[info] Writing ")
+            .and_then(|rest| rest.split_once(" to: "))
+            .map(|(_, path)| path.trim().trim_matches('"').to_string())
+        {
+            // 如 "[info] Writing video subtitles to: a.en.vtt"
+            if line.starts_with("[info] Writing video thumbnail") {
+                self.thumbnail = Some(path.clone());
+            }
+            if !self.sidecars.contains(&path) {
+                self.sidecars.push(path);
+            }
+        } else if let Some(rest) = line.strip_prefix("[ThumbnailsConvertor] Converting thumbnail ") {
+            // 如 "[ThumbnailsConvertor] Converting thumbnail "a.webp" to png"
+            if let Some((original, format)) = rest.rsplit_once(" to ") {
+                let original = original.trim().trim_matches('"');
+                let converted = Path::new(original)
+                    .with_extension(format.trim())
+                    .to_string_lossy()
+                    .into_owned();
+                self.thumbnail = Some(converted.clone());
+                if !self.sidecars.contains(&converted) {
+                    self.sidecars.push(converted);
+                }
+            }
+        } else if let Some(path) = line
+            .find("Destination: ")
+            .filter(|_| line.starts_with('['))
+            .map(|index| &line[index + "Destination: ".len()..])
+        {
+            self.push_destination(path);
+        } else if let Some(rest) = line.strip_prefix("[download] ") {
+            if let Some(path) = rest.strip_suffix(" has already been downloaded") {
+                self.push_destination(path);
+            }
+        } else if let Some(rest) = line.strip_prefix("Deleting original file ") {
+            let path = rest.split(" (pass -k to keep)").next().unwrap_or(rest);
+            self.deleted.push(path.trim().to_string());
+        }
+    }
+
+    fn push_destination(&mut self, path: &str) {
+        let path = path.trim().trim_matches('"').to_string();
+        if !self.destinations.contains(&path) {
+            self.destinations.push(path);
+        }
+    }
+
+    /// 合并的目标文件（yt-dlp 开始合并后才有）
+    pub fn merge_target(&self) -> Option<&str> {
+        self.merged_into.as_deref()
+    }
+
+    /// 按出现顺序记录的所有目标文件
+    pub fn destinations(&self) -> &[String] {
+        &self.destinations
+    }
+
+    /// 最终输出文件：合并结果优先，否则为最后一个目标文件
+    pub fn final_path(&self) -> Option<String> {
+        self.merged_into
+            .clone()
+            .or_else(|| self.destinations.last().cloned())
+    }
+
+    /// 保留在磁盘上的中间文件（未被 yt-dlp 删除的非最终文件，不含附属文件）
+    pub fn kept_files(&self) -> Vec<String> {
+        let final_path = self.final_path();
+        self.destinations
+            .iter()
+            .filter(|path| Some(*path) != final_path.as_ref() && !self.deleted.contains(path))
+            .filter(|path| !self.sidecars.contains(path))
+            .cloned()
+            .collect()
+    }
+
+    /// 分别下载音视频时的媒体文件（按下载顺序，不含附属文件和缩略图）
+    pub fn stream_files(&self) -> Vec<String> {
+        self.destinations
+            .iter()
+            .filter(|path| !self.deleted.contains(path) && !self.sidecars.contains(path))
+            .filter(|path| self.thumbnail.as_ref() != Some(*path))
+            .cloned()
+            .collect()
+    }
+
+    /// 缩略图文件（嵌入后被删除时为 None）
+    pub fn thumbnail_path(&self) -> Option<String> {
+        self.thumbnail.clone().filter(|path| !self.deleted.contains(path))
+    }
+
+    /// 写出的附属文件（已被 yt-dlp 删除的除外，如转换格式前的原始字幕）
+    pub fn sidecar_files(&self) -> Vec<String> {
+        self.sidecars
+            .iter()
+            .filter(|path| !self.deleted.contains(path))
+            .cloned()
+            .collect()
+    }
+}
+
+/***************************************************************************
+ * 实际下载的格式
+ *
+ * 请求的分辨率或音轨语言可能不可用而回退，下载完成后由 yt-dlp 通过
+ * --print-to-file 写出实际格式，每个视频一行，字段以制表符分隔：
+ * "<format_id>\t<height>\t<音轨语言>"（合并格式取最后一个流即音频流的语言）
+ ***************************************************************************/
+
+pub const FORMAT_REPORT_TEMPLATE: &str =
+    "after_move:%(format_id)s\t%(height|)s\t%(requested_formats.-1.language,language|)s";
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DownloadedFormat {
+    pub format_id: String,          // 如 "299+140"
+    #[ts(type = "number | null")]
+    pub height: Option<i64>,        // 实际分辨率高度（纯音频时为 None）
+    pub audio_language: Option<String>, // 实际音轨语言
+}
+
+/// 解析格式报告，取最后一个视频的结果
+pub fn parse_format_report(content: &str) -> Option<DownloadedFormat> {
+    let line = content.lines().rev().find(|line| !line.trim().is_empty())?;
+    let mut parts = line.trim_end_matches('\r').split('\t');
+    let format_id = parts.next()?.trim().to_string();
+    let height = parts.next().and_then(|h| h.trim().parse().ok());
+    let audio_language = parts
+        .next()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string);
+    Some(DownloadedFormat {
+        format_id,
+        height,
+        audio_language,
+    })
+}
+
+/***************************************************************************
+ * 格式报告文件
+ *
+ * 创建时删除同一任务上次留下的文件，丢弃时删除文件：下载成功、失败、
+ * 取消或启动超时结束时都不会留在临时目录中
+ ***************************************************************************/
+
+pub struct FormatReportFile {
+    path: PathBuf,
+}
+
+impl FormatReportFile {
+    pub fn new(path: PathBuf) -> Self {
+        let _ = fs::remove_file(&path);
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 读取实际下载的格式（文件不存在或无法解析时为 None）
+    pub fn read(&self) -> Option<DownloadedFormat> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| parse_format_report(&content))
+    }
+}
+
+impl Drop for FormatReportFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/***************************************************************************
+ * 判断已下载字节数的回退是否意味着开始了新的流
+ *
+ * 估算大小会随分片下载不断修正，已下载字节可能略有回退；
+ * 只有大幅回退（不足之前的一半）才视为 yt-dlp 切换到下一个文件
+ ***************************************************************************/
+
+fn is_new_stream(previous: u64, current: u64) -> bool {
+    current < previous / 2
+}
+
+/***************************************************************************
+ * 多个流的字节累计
+ *
+ * 视频+音频分开下载时，yt-dlp 会依次输出两个流的进度；
+ * 这里把已结束流的字节数累加，得到整个下载的最终字节数
+ ***************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct ByteTally {
+    finished: u64,                  // 已结束的流的字节数之和
+    current: u64,                   // 当前流已下载字节数
+}
+
+impl ByteTally {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录当前流的已下载字节数
+    pub fn record(&mut self, downloaded: u64) {
+        if is_new_stream(self.current, downloaded) {
+            self.finished += self.current;
+        }
+        self.current = downloaded;
+    }
+
+    /// 已下载的总字节数（没有任何进度时为 None）
+    pub fn total(&self) -> Option<u64> {
+        let total = self.finished + self.current;
+        (total > 0).then_some(total)
+    }
+}
+
+/// yt-dlp 后处理器输出行的前缀
+const POSTPROCESSOR_TAGS: &[&str] = &[
+    "[Merger]",
+    "[ExtractAudio]",
+    "[VideoConvertor]",
+    "[VideoRemuxer]",
+    "[EmbedSubtitle]",
+    "[EmbedThumbnail]",
+    "[Metadata]",
+    "[FixupM3u8]",
+    "[FixupM4a]",
+    "[FixupStretched]",
+    "[FixupDuplicateMoov]",
+    "[ModifyChapters]",
+    "[SplitChapters]",
+    "[ThumbnailsConvertor]",
+    "[SponsorBlock]",
+];
+
+/***************************************************************************
+ * 输出行对应的后处理步骤（合并、转码、嵌入字幕等）
+ *
+ * @return Option<&str> - 去掉方括号的步骤名（如 "Merger"），不是后处理行时为 None
+ ***************************************************************************/
+
+pub fn postprocessor_stage(line: &str) -> Option<&'static str> {
+    let line = line.trim_start();
+    POSTPROCESSOR_TAGS
+        .iter()
+        .find(|tag| line.starts_with(**tag))
+        .map(|tag| tag.trim_start_matches('[').trim_end_matches(']'))
+}
+
+/// 后处理阶段的进度（download-postprocessing 事件内容），该阶段没有 [download] 进度行
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PostProcessingProgress {
+    pub download_id: String,
+    pub stage: String,              // 当前后处理步骤（如 "Merger"）
+    pub elapsed_seconds: f64,       // 当前步骤已进行的时间
+    pub percent: Option<f64>,       // 合并进度估算，其余步骤为 None（前端显示为不确定进度）
+}
+
+/// 获取评论的进度（download-comments 事件内容），评论在下载开始前获取，可能持续数分钟
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct CommentsProgress {
+    pub download_id: String,
+    pub fetched: Option<u32>,       // 已获取的评论数（yt-dlp 未报告时为 None）
+    pub estimated_total: Option<u32>, // yt-dlp 估计的评论总数
+}
+
+/***************************************************************************
+ * 解析获取评论的输出行
+ *
+ * 格式示例:
+ * [youtube] Downloading comment section API JSON
+ * [youtube] Downloading ~1234 comments
+ * [youtube]     Downloading comment API JSON page 3 (40/~1234)
+ * [youtube]        Downloading comment replies API JSON page 1 (45/~1234)
+ *
+ * @return Option<(已获取数, 估计总数)> - 不是获取评论的行时为 None
+ ***************************************************************************/
+
+pub fn parse_comment_line(line: &str) -> Option<(Option<u32>, Option<u32>)> {
+    let line = line.trim();
+    if let Some((_, rest)) = line.split_once("] Downloading ~") {
+        let (total, rest) = rest.split_once(' ')?;
+        return rest.starts_with("comments").then(|| (None, total.parse().ok()));
+    }
+    if !line.contains("Downloading comment") {
+        return None;
+    }
+
+    let counts = line
+        .rsplit_once('(')
+        .and_then(|(_, rest)| rest.strip_suffix(')'))
+        .and_then(|counts| counts.split_once('/'));
+    Some(match counts {
+        Some((fetched, total)) => (fetched.parse().ok(), total.trim_start_matches('~').parse().ok()),
+        None => (None, None),
+    })
+}
+
+/// 合并进度估算的上限（改名为最终文件之前不显示 100%）
+const MAX_MERGE_PERCENT: f64 = 99.0;
+
+/***************************************************************************
+ * 合并进度估算
+ *
+ * ffmpeg 合并时 yt-dlp 不输出进度。合并只是重新封装，输出大小与输入之和接近，
+ * 因此按临时输出文件（yt-dlp 先写入 "name.temp.ext"，完成后改名）的大小估算
+ ***************************************************************************/
+
+#[derive(Debug, Clone)]
+pub struct MergeJob {
+    temp_path: PathBuf,             // 合并中的临时文件
+    input_bytes: u64,               // 输入流大小之和
+}
+
+impl MergeJob {
+    /// target 为合并后的文件，inputs 为已下载的各个流
+    pub fn new(target: &str, inputs: &[String]) -> Self {
+        let target = Path::new(target);
+        let temp_path = match target.extension() {
+            Some(ext) => target.with_extension(format!("temp.{}", ext.to_string_lossy())),
+            None => target.with_extension("temp"),
+        };
+        let input_bytes = inputs
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|meta| meta.len())
+            .sum();
+        Self { temp_path, input_bytes }
+    }
+
+    /// 估算的合并进度（临时文件尚未创建或输入大小未知时为 None）
+    pub fn percent(&self) -> Option<f64> {
+        if self.input_bytes == 0 {
+            return None;
+        }
+        let written = fs::metadata(&self.temp_path).ok()?.len();
+        Some((written as f64 / self.input_bytes as f64 * 100.0).min(MAX_MERGE_PERCENT))
+    }
+}
+
+/***************************************************************************
+ * 当前的后处理步骤（由下载事件更新，定时生成 PostProcessingProgress）
+ ***************************************************************************/
+
+#[derive(Debug)]
+pub struct PostProcessingStage {
+    name: String,
+    started_at: Instant,
+    merge: Option<MergeJob>,        // 合并步骤的进度估算
+}
+
+impl PostProcessingStage {
+    pub fn new(name: &str, merge: Option<MergeJob>) -> Self {
+        Self {
+            name: name.to_string(),
+            started_at: Instant::now(),
+            merge,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn snapshot(&self, download_id: &str) -> PostProcessingProgress {
+        PostProcessingProgress {
+            download_id: download_id.to_string(),
+            stage: self.name.clone(),
+            elapsed_seconds: self.started_at.elapsed().as_secs_f64(),
+            percent: self.merge.as_ref().and_then(MergeJob::percent),
+        }
+    }
+}
+
+/***************************************************************************
+ * 吞吐量 ETA 估算器
+ *
+ * 记录最近一段时间内的 (时间, 已下载字节) 样本，用窗口内的平均速度
+ * 推算剩余时间。字节数大幅回退（yt-dlp 切换到下一个流）时重置。
+ ***************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct ThroughputEstimator {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl ThroughputEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 清空样本，开始新的估算
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    /***********************************************************************
+     * 记录一个进度样本并返回估算的剩余秒数
+     *
+     * @param now - 样本时间
+     * @param downloaded - 已下载字节数
+     * @param total - 总字节数
+     * @return Option<f64> - 剩余秒数（样本不足或速度为 0 时为 None）
+     ***********************************************************************/
+    pub fn record(&mut self, now: Instant, downloaded: u64, total: u64) -> Option<f64> {
+        let stream_changed = self
+            .samples
+            .back()
+            .is_some_and(|&(_, last)| is_new_stream(last, downloaded));
+        if stream_changed {
+            self.reset();
+        }
+
+        self.samples.push_back((now, downloaded));
+
+        // 丢弃窗口外的旧样本，但至少保留两个样本用于计算速度
+        while self.samples.len() > 2 {
+            match self.samples.front() {
+                Some(&(time, _)) if now.duration_since(time) > THROUGHPUT_WINDOW => {
+                    self.samples.pop_front();
+                }
+                _ => break,
+            }
+        }
+
+        let &(first_time, first_bytes) = self.samples.front()?;
+        let elapsed = now.duration_since(first_time).as_secs_f64();
+        if self.samples.len() < 2 || elapsed <= 0.0 {
+            return None;
+        }
+
+        let rate = downloaded.saturating_sub(first_bytes) as f64 / elapsed;
+        if rate <= 0.0 {
+            return None;
+        }
+
+        Some(total.saturating_sub(downloaded) as f64 / rate)
+    }
+}
+
+/***************************************************************************
+ * EWMA 速度平滑器
+ *
+ * 对相邻两帧之间的瞬时速度做指数加权移动平均，用平滑后的速度推算 ETA。
+ * 字节数回退（切换到下一个流）或长时间没有进度（暂停、重试）时重置，
+ * 避免暂停前的速度影响恢复后的估算。
+ ***************************************************************************/
+
+#[derive(Debug, Default)]
+pub struct EwmaSmoother {
+    rate: Option<f64>,
+    last: Option<(Instant, u64)>,
+}
+
+impl EwmaSmoother {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 丢弃已有速度，从下一帧重新开始平滑
+    pub fn reset(&mut self) {
+        self.rate = None;
+        self.last = None;
+    }
+
+    /***********************************************************************
+     * 记录一个进度样本并返回平滑后的剩余秒数
+     *
+     * @param now - 样本时间
+     * @param downloaded - 已下载字节数
+     * @param total - 总字节数（未知时返回 None，但仍更新速度）
+     * @return Option<f64> - 剩余秒数
+     ***********************************************************************/
+    pub fn record(&mut self, now: Instant, downloaded: u64, total: Option<u64>) -> Option<f64> {
+        if let Some((last_time, last_bytes)) = self.last {
+            let elapsed = now.duration_since(last_time);
+            if is_new_stream(last_bytes, downloaded) || elapsed > EWMA_RESUME_GAP {
+                self.reset();
+            } else if !elapsed.is_zero() {
+                let instant_rate = downloaded.saturating_sub(last_bytes) as f64 / elapsed.as_secs_f64();
+                self.rate = Some(match self.rate {
+                    Some(rate) => EWMA_ALPHA * instant_rate + (1.0 - EWMA_ALPHA) * rate,
+                    None => instant_rate,
+                });
+            }
+        }
+        self.last = Some((now, downloaded));
+
+        let rate = self.rate.filter(|rate| *rate > 0.0)?;
+        let total = total?;
+        Some(total.saturating_sub(downloaded) as f64 / rate)
+    }
+}
+
+/***************************************************************************
+ * 限速检测
+ *
+ * 速度连续 THROTTLE_WINDOW 低于阈值时判定为被限速；每次限速只报告一次，
+ * 速度恢复到阈值以上后重新开始计时
+ ***************************************************************************/
+
+#[derive(Debug)]
+pub struct ThrottleDetector {
+    threshold: f64,                 // 限速阈值（字节/秒）
+    slow_since: Option<Instant>,    // 本次低速开始的时间
+    reported: bool,                 // 本次低速已报告
+}
+
+impl ThrottleDetector {
+    pub fn new(threshold: u64) -> Self {
+        Self {
+            threshold: threshold as f64,
+            slow_since: None,
+            reported: false,
+        }
+    }
+
+    /***********************************************************************
+     * 记录一个速度样本
+     *
+     * @return bool - 本次样本确认进入限速状态时返回 true（同一次限速只返回一次）
+     ***********************************************************************/
+    pub fn record(&mut self, now: Instant, bytes_per_sec: f64) -> bool {
+        if bytes_per_sec >= self.threshold {
+            self.slow_since = None;
+            self.reported = false;
+            return false;
+        }
+
+        let since = *self.slow_since.get_or_insert(now);
+        if self.reported || now.duration_since(since) < THROTTLE_WINDOW {
+            return false;
+        }
+        self.reported = true;
+        true
+    }
+}
+
+/// yt-dlp 自身报告的限速（--throttled-rate 触发重新提取时输出）
+pub fn is_throttled_line(line: &str) -> bool {
+    line.to_ascii_lowercase().contains("throttl")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1_000_000;
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.expect("应有剩余时间");
+        assert!((actual - expected).abs() < expected * 0.01, "{} 与 {} 相差过大", actual, expected);
+    }
+
+    #[test]
+    fn ewma_first_sample_has_no_estimate() {
+        let mut smoother = EwmaSmoother::new();
+        assert_eq!(smoother.record(Instant::now(), MB, Some(10 * MB)), None);
+    }
+
+    #[test]
+    fn ewma_second_sample_uses_instant_rate() {
+        let start = Instant::now();
+        let mut smoother = EwmaSmoother::new();
+        smoother.record(start, 0, Some(10 * MB));
+        // 1 秒下载 1 MB，剩余 9 MB
+        assert_close(smoother.record(start + Duration::from_secs(1), MB, Some(10 * MB)), 9.0);
+    }
+
+    /// 以固定速度每秒记录一个样本，共 seconds 个，返回最后的剩余时间
+    fn feed(smoother: &mut EwmaSmoother, clock: &mut (Instant, u64), rate: u64, seconds: u32) -> Option<f64> {
+        let mut eta = None;
+        for _ in 0..seconds {
+            clock.0 += Duration::from_secs(1);
+            clock.1 += rate;
+            eta = smoother.record(clock.0, clock.1, Some(1000 * MB));
+        }
+        eta
+    }
+
+    #[test]
+    fn ewma_converges_to_new_rate() {
+        let mut smoother = EwmaSmoother::new();
+        let mut clock = (Instant::now(), 0);
+        smoother.record(clock.0, 0, Some(1000 * MB));
+        let eta = feed(&mut smoother, &mut clock, MB, 10);
+        assert_close(eta, (1000 * MB - clock.1) as f64 / MB as f64);
+
+        // 速度提高到 4 MB/s：一个样本只向新速度移动 EWMA_ALPHA
+        let eta = feed(&mut smoother, &mut clock, 4 * MB, 1);
+        let blended = EWMA_ALPHA * (4 * MB) as f64 + (1.0 - EWMA_ALPHA) * MB as f64;
+        assert_close(eta, (1000 * MB - clock.1) as f64 / blended);
+
+        let eta = feed(&mut smoother, &mut clock, 4 * MB, 40);
+        assert_close(eta, (1000 * MB - clock.1) as f64 / (4 * MB) as f64);
+    }
+
+    #[test]
+    fn ewma_keeps_rate_without_total() {
+        let start = Instant::now();
+        let mut smoother = EwmaSmoother::new();
+        smoother.record(start, 0, None);
+        assert_eq!(smoother.record(start + Duration::from_secs(1), 2 * MB, None), None);
+        assert_close(smoother.record(start + Duration::from_secs(2), 4 * MB, Some(10 * MB)), 3.0);
+    }
+
+    #[test]
+    fn ewma_resets_on_new_stream_and_after_gap() {
+        let start = Instant::now();
+        let mut smoother = EwmaSmoother::new();
+        smoother.record(start, 0, Some(100 * MB));
+        assert!(smoother.record(start + Duration::from_secs(1), 50 * MB, Some(100 * MB)).is_some());
+
+        // 下一个流从头开始计数
+        assert_eq!(smoother.record(start + Duration::from_secs(2), MB, Some(10 * MB)), None);
+        assert_close(smoother.record(start + Duration::from_secs(3), 2 * MB, Some(10 * MB)), 8.0);
+
+        // 长时间没有进度（暂停、重试）后不沿用之前的速度
+        let resumed = start + Duration::from_secs(3) + EWMA_RESUME_GAP + Duration::from_secs(1);
+        assert_eq!(smoother.record(resumed, 3 * MB, Some(10 * MB)), None);
+    }
+
+    #[test]
+    fn parse_size_handles_every_unit() {
+        let cases = [
+            ("512", 512),
+            ("512B", 512),
+            ("1.5KiB", 1536),
+            ("2MiB", 2 * 1024 * 1024),
+            ("1GiB", 1024 * 1024 * 1024),
+            ("1TiB", 1024 * 1024 * 1024 * 1024),
+            ("2KB", 2_000),
+            ("2kB", 2_000),
+            ("1.25MB", 1_250_000),
+            ("3GB", 3_000_000_000),
+            ("1TB", 1_000_000_000_000),
+            (" 10.00MiB ", 10 * 1024 * 1024),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_size(text), Some(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_size_rejects_unknown_values() {
+        for text in ["", "Unknown", "NA", "10XB", "MiB", "1.2.3MiB"] {
+            assert_eq!(parse_size(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn parse_total_size_reads_exact_and_estimated_sizes() {
+        let exact = "[download]  42.0% of 125.89MiB at  5.82MiB/s ETA 00:12";
+        assert_eq!(parse_total_size(exact), Some((132_005_233, false)));
+
+        let estimated = "[download]  42.0% of ~125.89MiB at  5.82MiB/s ETA 00:12 (frag 3/10)";
+        assert_eq!(parse_total_size(estimated), Some((132_005_233, true)));
+
+        let spaced = "[download]  42.0% of ~ 125.89MiB at  5.82MiB/s ETA 00:12 (frag 3/10)";
+        assert_eq!(parse_total_size(spaced), Some((132_005_233, true)));
+    }
+
+    #[test]
+    fn parse_total_size_without_a_size() {
+        assert_eq!(parse_total_size("[download]  42.0% of Unknown at  5.82MiB/s"), None);
+        assert_eq!(parse_total_size("[download]  42.0% of ~"), None);
+        assert_eq!(parse_total_size("[download] Destination: video.mp4"), None);
+    }
+
+    #[test]
+    fn format_report_file_is_removed_on_drop() {
+        let path = std::env::temp_dir().join(format!("youtudown-format-report-{}.format", std::process::id()));
+        fs::write(&path, "stale\t1080\t\n").unwrap();
+
+        // 创建时删除上次留下的文件
+        let report = FormatReportFile::new(path.clone());
+        assert!(!path.exists());
+        assert!(report.read().is_none());
+
+        fs::write(report.path(), "299+140\t1080\ten\n").unwrap();
+        let format = report.read().unwrap();
+        assert_eq!(format.format_id, "299+140");
+        assert_eq!(format.height, Some(1080));
+        assert_eq!(format.audio_language.as_deref(), Some("en"));
+
+        // 失败、取消时直接丢弃，同样删除
+        drop(report);
+        assert!(!path.exists());
+    }
+}
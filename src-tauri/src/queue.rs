@@ -0,0 +1,1254 @@
+/****************************************************************************
+ *  queue.rs - 下载队列
+ *
+ *  @brief  先进先出的下载队列，由后台调度任务按并发上限依次启动下载
+ *  @note   入队时即在 DownloadManager 中登记为 Queued，启动后由 run_download
+ *          负责状态更新；队列本身只关心"定时"、"等待中"和"运行中"三组任务。
+ *          等待中的任务按 (排队优先级从高到低, 加入顺序) 启动。
+ *          定时任务和等待中的任务（含排队优先级）持久化到队列文件，重启后恢复；
+ *          播放列表分组不保存，恢复的等待中任务按单独的任务调度。
+ *          播放列表拆分出的任务属于同一分组，按分组自己的并发数调度；
+ *          分组全部结束时发送 batch-completed，需要时写出下载清单。
+ *          下载目录消失或变为只读时暂停调度，目录恢复可用后继续，不会改用其他目录。
+ *          用户暂停队列时不再启动新任务，运行中的任务照常完成
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+use ts_rs::TS;
+
+use crate::commands::{default_download_dir, download_destination, next_download_id, run_download_with};
+use crate::downloads::{unix_millis, DownloadManager, DownloadStatus};
+use crate::events;
+use crate::history::HistoryStore;
+use crate::manifest::{write_manifest, ManifestEntry};
+use crate::network::{is_online, wait_for_network};
+use crate::options::DownloadOptions;
+use crate::process::PriorityMode;
+use crate::settings::{validate_writable_dir, SettingsState};
+use crate::ytdlp::{MediaBackend, YtDlp};
+
+/// 队列持久化文件名
+pub const QUEUE_FILE: &str = "queue.json";
+
+/// 播放列表分组的最大并发数
+pub const MAX_GROUP_CONCURRENCY: usize = 8;
+
+/// 定时任务计时器的最长休眠时间：不依赖一次长时间休眠，
+/// 系统睡眠唤醒或调整时钟后，最迟在该间隔内按当前时间重新检查
+const SCHEDULE_TICK: Duration = Duration::from_secs(15);
+
+/// 下载目录不可用时重新检查的间隔（修改设置时立即检查）
+const DESTINATION_RECHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QueuedDownload {
+    id: String,
+    url: String,
+    options: DownloadOptions,
+    #[serde(skip)]
+    group: Option<String>,          // 所属分组（播放列表）ID（不保存）
+    #[serde(default)]
+    priority: i32,                  // 排队优先级（越大越先开始）
+}
+
+#[derive(Debug, Clone)]
+struct RunningDownload {
+    url: String,
+    output_dir: Option<String>,
+    group: Option<String>,
+    priority: i32,
+}
+
+#[derive(Debug, Clone)]
+struct DownloadGroup {
+    concurrency: usize,             // 分组内同时进行的下载数
+    download_ids: Vec<String>,
+    titles: Vec<String>,            // 与 download_ids 顺序一致（写入清单）
+    urls: Vec<String>,
+    options: DownloadOptions,       // 分组共用的下载选项（清单格式、下载目录）
+    completed: bool,                // 全部任务已结束
+    manifest_path: Option<String>,  // 写出的清单文件
+    skipped: usize,                 // 已在下载存档中、没有加入队列的条目数
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScheduledDownload {
+    id: String,
+    url: String,
+    options: DownloadOptions,
+    start_at: u64,                  // 计划开始时间（Unix 毫秒）
+    #[serde(default)]
+    priority: i32,                  // 加入队列后的排队优先级
+}
+
+/// 队列文件内容（运行中的任务不保存）
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueFile {
+    #[serde(default)]
+    scheduled: Vec<ScheduledDownload>,
+    #[serde(default)]
+    pending: Vec<QueuedDownload>,   // 按加入顺序
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueItemState {
+    Scheduled,
+    Pending,
+    Running,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct QueueItem {
+    pub download_id: String,
+    pub url: String,
+    pub state: QueueItemState,
+    #[ts(type = "number | null")]
+    pub start_at: Option<u64>,      // 定时任务的开始时间（Unix 毫秒）
+    pub output_dir: Option<String>, // 任务指定的下载目录（未指定时为 None，使用默认下载目录）
+    pub priority: i32,              // 排队优先级（越大越先开始，相同时按加入顺序）
+}
+
+/// set_queue_priority / download_next 的返回值
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct PriorityChange {
+    pub download_id: String,
+    pub state: QueueItemState,      // 任务当前所在的分组
+    pub priority: i32,              // 修改后的排队优先级（未修改时为原值）
+    pub applied: bool,              // 是否生效（已开始的任务不受优先级影响，不做修改）
+    pub detail: Option<String>,     // 未生效的原因
+}
+
+/// get_queue 的返回值：队列中的任务及当前的子进程优先级
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct QueueOverview {
+    pub items: Vec<QueueItem>,
+    pub priority_mode: PriorityMode, // 新启动的下载使用的优先级
+    pub paused: bool,               // 队列已暂停，不启动新任务
+}
+
+/// 队列暂停或继续时发送的事件（queue-paused / queue-resumed）
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct QueuePauseChanged {
+    pub paused: bool,
+    pub pending: usize,             // 等待中的任务数
+    pub running: usize,             // 运行中的任务数（暂停不影响）
+}
+
+/// 分组（播放列表）内全部任务结束时发送的事件
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct BatchCompleted {
+    pub playlist_id: String,
+    pub manifest_path: Option<String>, // 写出的清单（未开启清单或写入失败时为 None）
+    pub downloaded: usize,          // 新下载的视频数（成功且产生了输出文件）
+    pub skipped: usize,             // 已在下载存档中而跳过的视频数
+}
+
+/// 网络恢复、暂停的任务重新开始时发送的事件
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct NetworkRestored {
+    pub download_ids: Vec<String>,  // 恢复排队的任务
+}
+
+/// 下载目录不存在或不可写、任务暂停等待时发送的事件
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+pub struct DestinationUnavailable {
+    pub path: String,               // 不可用的下载目录
+    pub error: String,              // 不可用的原因
+    pub download_ids: Vec<String>,  // 等待该目录的任务
+}
+
+/// 下载目录恢复可用、等待的任务重新排队时发送的事件
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DestinationRestored {
+    pub download_ids: Vec<String>,  // 恢复排队的任务
+}
+
+/// 队列中的下载失败时发送的事件（直接调用 download_video 时错误由命令返回）
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct DownloadFailed {
+    pub download_id: String,
+    pub error: String,
+}
+
+#[derive(Default)]
+struct QueueInner {
+    scheduled: Vec<ScheduledDownload>,
+    pending: VecDeque<QueuedDownload>,
+    running: HashMap<String, RunningDownload>,
+    groups: HashMap<String, DownloadGroup>,
+    paused: bool,                   // 用户暂停了队列
+}
+
+/***************************************************************************
+ * 下载队列（Tauri 托管状态）
+ ***************************************************************************/
+
+#[derive(Default)]
+pub struct DownloadQueue {
+    path: Option<PathBuf>,
+    inner: Mutex<QueueInner>,
+    notify: Notify,
+    destination_changed: Notify,    // 下载目录设置变化或被确认可用时唤醒等待中的检查
+}
+
+impl DownloadQueue {
+    /***********************************************************************
+     * 从队列文件恢复定时任务和等待中的任务，文件不存在或损坏时从空队列开始
+     *
+     * 恢复的任务在 manager 中重新登记；有等待中的任务时唤醒调度任务
+     ***********************************************************************/
+    pub fn load(path: Option<PathBuf>, manager: &DownloadManager) -> Self {
+        let QueueFile { scheduled, pending } = path
+            .as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read_to_string(p) {
+                Ok(content) => parse_queue_file(&content)
+                    .map_err(|e| warn!("队列文件解析失败，从空队列开始: {}", e))
+                    .ok(),
+                Err(e) => {
+                    warn!("读取队列文件失败，从空队列开始: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        for item in &scheduled {
+            manager.register(&item.id);
+            manager.set_status(&item.id, DownloadStatus::Scheduled, None);
+        }
+        for item in &pending {
+            manager.register(&item.id);
+        }
+        if !pending.is_empty() {
+            info!("恢复 {} 个等待中的下载任务", pending.len());
+        }
+
+        let queue = Self {
+            path,
+            inner: Mutex::new(QueueInner {
+                pending: pending.into(),
+                scheduled,
+                ..Default::default()
+            }),
+            notify: Notify::new(),
+            destination_changed: Notify::new(),
+        };
+        queue.notify.notify_one();
+        queue
+    }
+
+    /***********************************************************************
+     * 加入队列并唤醒调度任务
+     *
+     * @return String - 分配的下载任务ID
+     ***********************************************************************/
+    pub fn enqueue(&self, manager: &DownloadManager, url: String, options: DownloadOptions) -> String {
+        let id = next_download_id();
+        manager.register(&id);
+
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.pending.push_back(QueuedDownload {
+                id: id.clone(),
+                url,
+                options,
+                group: None,
+                priority: 0,
+            });
+            if let Err(e) = self.save(&inner) {
+                warn!("保存队列文件失败: {}", e);
+            }
+        }
+        self.notify.notify_one();
+
+        id
+    }
+
+    /***********************************************************************
+     * 把一组链接作为一个分组加入队列（播放列表并行下载）
+     *
+     * 分组内的任务最多同时进行 concurrency 个，不占用全局并发名额；
+     * 没有任何链接时分组直接标记为已结束
+     *
+     * @param entries - (链接, 标题)，标题用于下载清单
+     * @param skipped - 已在下载存档中、没有加入的条目数（计入 batch-completed）
+     * @return (String, Vec<String>) - 分组ID 及各链接的下载任务ID（与 entries 顺序一致）
+     ***********************************************************************/
+    pub fn enqueue_group(
+        &self,
+        manager: &DownloadManager,
+        entries: Vec<(String, String)>,
+        options: DownloadOptions,
+        concurrency: usize,
+        skipped: usize,
+    ) -> (String, Vec<String>) {
+        let group_id = next_download_id().replacen("dl-", "group-", 1);
+        let (urls, titles): (Vec<String>, Vec<String>) = entries.into_iter().unzip();
+        let items: Vec<QueuedDownload> = urls
+            .iter()
+            .map(|url| {
+                let id = next_download_id();
+                manager.register(&id);
+                QueuedDownload {
+                    id,
+                    url: url.clone(),
+                    options: options.clone(),
+                    group: Some(group_id.clone()),
+                    priority: 0,
+                }
+            })
+            .collect();
+        let download_ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.groups.insert(
+                group_id.clone(),
+                DownloadGroup {
+                    concurrency: concurrency.clamp(1, MAX_GROUP_CONCURRENCY),
+                    download_ids: download_ids.clone(),
+                    titles,
+                    urls,
+                    options,
+                    completed: download_ids.is_empty(),
+                    manifest_path: None,
+                    skipped,
+                },
+            );
+            inner.pending.extend(items);
+            if let Err(e) = self.save(&inner) {
+                warn!("保存队列文件失败: {}", e);
+            }
+        }
+        self.notify.notify_one();
+
+        (group_id, download_ids)
+    }
+
+    /// 分组内全部任务的ID
+    pub fn group_download_ids(&self, group_id: &str) -> Option<Vec<String>> {
+        let inner = self.inner.lock().ok()?;
+        inner.groups.get(group_id).map(|group| group.download_ids.clone())
+    }
+
+    /***********************************************************************
+     * 取出下一个可以启动的任务
+     *
+     * 不属于分组的任务受全局并发上限限制，分组任务受分组自身的并发数限制；
+     * 前面的任务被限制时，后面可以启动的任务不必等待。
+     * 可以启动的任务中取优先级最高的，相同时取最早加入的
+     ***********************************************************************/
+    fn next_ready(&self, max_concurrent: usize) -> Option<QueuedDownload> {
+        let mut inner = self.inner.lock().ok()?;
+        if inner.paused {
+            return None;
+        }
+        let running_in = |group: Option<&String>| {
+            inner
+                .running
+                .values()
+                .filter(|running| running.group.as_ref() == group)
+                .count()
+        };
+
+        let position = inner
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| match &item.group {
+                None => running_in(None) < max_concurrent,
+                Some(group) => {
+                    let limit = inner.groups.get(group).map_or(1, |g| g.concurrency);
+                    running_in(Some(group)) < limit
+                }
+            })
+            .max_by_key(|(position, item)| (item.priority, Reverse(*position)))
+            .map(|(position, _)| position)?;
+        let item = inner.pending.remove(position)?;
+        if let Err(e) = self.save(&inner) {
+            warn!("保存队列文件失败: {}", e);
+        }
+        inner.running.insert(
+            item.id.clone(),
+            RunningDownload {
+                url: item.url.clone(),
+                output_dir: item.options.output_dir.clone(),
+                group: item.group.clone(),
+                priority: item.priority,
+            },
+        );
+        Some(item)
+    }
+
+    /***********************************************************************
+     * 按并发上限取出全部可以开始的任务，逐个交给 start 启动
+     *
+     * @return usize - 启动的任务数
+     ***********************************************************************/
+    fn dispatch_ready(&self, max_concurrent: usize, mut start: impl FnMut(QueuedDownload)) -> usize {
+        let mut started = 0;
+        while let Some(item) = self.next_ready(max_concurrent) {
+            debug!(download_id = %item.id, "从队列启动下载");
+            start(item);
+            started += 1;
+        }
+        started
+    }
+
+    /***********************************************************************
+     * 暂停或继续队列
+     *
+     * 暂停后调度任务不再启动等待中的任务，运行中的任务不受影响；
+     * 继续时唤醒调度任务
+     *
+     * @return Option<QueuePauseChanged> - 状态有变化时为 Some
+     ***********************************************************************/
+    pub fn set_paused(&self, paused: bool) -> Option<QueuePauseChanged> {
+        let mut inner = self.inner.lock().ok()?;
+        if inner.paused == paused {
+            return None;
+        }
+        inner.paused = paused;
+        let changed = QueuePauseChanged {
+            paused,
+            pending: inner.pending.len(),
+            running: inner.running.len(),
+        };
+        drop(inner);
+        if !paused {
+            self.notify.notify_one();
+        }
+        Some(changed)
+    }
+
+    /// 队列是否已暂停
+    pub fn is_paused(&self) -> bool {
+        self.inner.lock().is_ok_and(|inner| inner.paused)
+    }
+
+    /// 等待中（尚未开始）的任务ID
+    fn pending_ids(&self) -> Vec<String> {
+        self.inner
+            .lock()
+            .map(|inner| inner.pending.iter().map(|item| item.id.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /***********************************************************************
+     * 修改排队优先级
+     *
+     * 等待中和定时的任务立即生效（优先级随队列文件保存）；
+     * 已开始的任务不做修改，返回 applied = false 及原因
+     *
+     * @return PriorityChange - 任务不在队列中时返回错误
+     ***********************************************************************/
+    pub fn set_priority(&self, id: &str, priority: i32) -> Result<PriorityChange, String> {
+        let mut inner = self.inner.lock().map_err(|_| "队列状态异常".to_string())?;
+        let change = |state: QueueItemState, priority: i32, applied: bool, detail: Option<&str>| PriorityChange {
+            download_id: id.to_string(),
+            state,
+            priority,
+            applied,
+            detail: detail.map(String::from),
+        };
+
+        if let Some(item) = inner.pending.iter_mut().find(|item| item.id == id) {
+            item.priority = priority;
+            if let Err(e) = self.save(&inner) {
+                warn!("保存队列文件失败: {}", e);
+            }
+            return Ok(change(QueueItemState::Pending, priority, true, None));
+        }
+        if let Some(item) = inner.scheduled.iter_mut().find(|item| item.id == id) {
+            item.priority = priority;
+            if let Err(e) = self.save(&inner) {
+                warn!("保存队列文件失败: {}", e);
+            }
+            return Ok(change(QueueItemState::Scheduled, priority, true, None));
+        }
+        if let Some(running) = inner.running.get(id) {
+            let detail = Some("任务已开始下载，排队优先级不再影响它");
+            return Ok(change(QueueItemState::Running, running.priority, false, detail));
+        }
+        Err(format!("未找到队列中的任务: {}", id))
+    }
+
+    /***********************************************************************
+     * 让等待中的任务下一个开始
+     *
+     * 优先级提高到等待中任务的最高值并移到队首，正在运行的任务不受影响；
+     * 定时任务和已开始的任务不做修改，返回 applied = false 及原因
+     ***********************************************************************/
+    pub fn download_next(&self, id: &str) -> Result<PriorityChange, String> {
+        let mut inner = self.inner.lock().map_err(|_| "队列状态异常".to_string())?;
+        if let Some(position) = inner.pending.iter().position(|item| item.id == id) {
+            let top = inner.pending.iter().map(|item| item.priority).max().unwrap_or(0);
+            if let Some(mut item) = inner.pending.remove(position) {
+                item.priority = top;
+                inner.pending.push_front(item);
+            }
+            if let Err(e) = self.save(&inner) {
+                warn!("保存队列文件失败: {}", e);
+            }
+            return Ok(PriorityChange {
+                download_id: id.to_string(),
+                state: QueueItemState::Pending,
+                priority: top,
+                applied: true,
+                detail: None,
+            });
+        }
+
+        let (state, priority, detail) = if let Some(item) = inner.scheduled.iter().find(|item| item.id == id) {
+            (QueueItemState::Scheduled, item.priority, "定时任务尚未加入队列，可使用 start_now 立即开始")
+        } else if let Some(running) = inner.running.get(id) {
+            (QueueItemState::Running, running.priority, "任务已开始下载")
+        } else {
+            return Err(format!("未找到队列中的任务: {}", id));
+        };
+        Ok(PriorityChange {
+            download_id: id.to_string(),
+            state,
+            priority,
+            applied: false,
+            detail: Some(detail.to_string()),
+        })
+    }
+
+    /// 下载目录可能已恢复（修改了设置或前端确认目录可用），立即重新检查
+    pub fn destination_changed(&self) {
+        self.destination_changed.notify_one();
+    }
+
+    /***********************************************************************
+     * 找出等待中的任务里下载目录不可用的
+     *
+     * @return Vec<DestinationUnavailable> - 每个不可用的目录一项（都可用时为空）
+     ***********************************************************************/
+    fn unavailable_destinations(&self, app: &AppHandle) -> Vec<DestinationUnavailable> {
+        let pending: Vec<(String, DownloadOptions)> = self
+            .inner
+            .lock()
+            .map(|inner| inner.pending.iter().map(|item| (item.id.clone(), item.options.clone())).collect())
+            .unwrap_or_default();
+
+        let mut unavailable: Vec<DestinationUnavailable> = Vec::new();
+        let mut available: Vec<PathBuf> = Vec::new();
+        for (id, options) in pending {
+            let (path, error) = match download_destination(app, &options) {
+                Ok(dir) if available.contains(&dir) => continue,
+                Ok(dir) => match validate_writable_dir(&dir) {
+                    Ok(()) => {
+                        available.push(dir);
+                        continue;
+                    }
+                    Err(e) => (dir.to_string_lossy().into_owned(), e),
+                },
+                Err(e) => (String::new(), e),
+            };
+            match unavailable.iter_mut().find(|item| item.path == path) {
+                Some(item) => item.download_ids.push(id),
+                None => unavailable.push(DestinationUnavailable {
+                    path,
+                    error,
+                    download_ids: vec![id],
+                }),
+            }
+        }
+        unavailable
+    }
+
+    /// 分组的下载清单路径（尚未写出时为 None）
+    pub fn group_manifest_path(&self, group_id: &str) -> Option<Option<String>> {
+        let inner = self.inner.lock().ok()?;
+        inner.groups.get(group_id).map(|group| group.manifest_path.clone())
+    }
+
+    /***********************************************************************
+     * 任务结束，释放并发名额
+     *
+     * @return Option<String> - 该任务是所在分组的最后一个任务时返回分组ID
+     ***********************************************************************/
+    fn finish(&self, id: &str) -> Option<String> {
+        let completed = self.inner.lock().ok().and_then(|mut inner| {
+            let group_id = inner.running.remove(id)?.group?;
+            let busy = inner.pending.iter().any(|item| item.group.as_ref() == Some(&group_id))
+                || inner.running.values().any(|running| running.group.as_ref() == Some(&group_id));
+            let group = inner.groups.get_mut(&group_id)?;
+            if busy || group.completed {
+                return None;
+            }
+            group.completed = true;
+            Some(group_id)
+        });
+        self.notify.notify_one();
+        completed
+    }
+
+    /***********************************************************************
+     * 加入定时任务
+     *
+     * 开始时间已过时直接加入队列
+     *
+     * @param start_at - 计划开始时间（Unix 毫秒）
+     * @return String - 分配的下载任务ID
+     ***********************************************************************/
+    pub fn schedule(
+        &self,
+        manager: &DownloadManager,
+        url: String,
+        options: DownloadOptions,
+        start_at: u64,
+    ) -> String {
+        if start_at <= unix_millis() {
+            return self.enqueue(manager, url, options);
+        }
+
+        let id = next_download_id();
+        manager.register(&id);
+        manager.set_status(&id, DownloadStatus::Scheduled, None);
+
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.scheduled.push(ScheduledDownload {
+                id: id.clone(),
+                url,
+                options,
+                start_at,
+                priority: 0,
+            });
+            if let Err(e) = self.save(&inner) {
+                warn!("保存队列文件失败: {}", e);
+            }
+        }
+
+        id
+    }
+
+    /***********************************************************************
+     * 取消定时任务
+     *
+     * @return bool - 任务是否存在（已开始的任务不能再取消定时）
+     ***********************************************************************/
+    pub fn cancel_schedule(&self, id: &str) -> bool {
+        let Ok(mut inner) = self.inner.lock() else {
+            return false;
+        };
+        let before = inner.scheduled.len();
+        inner.scheduled.retain(|item| item.id != id);
+        if inner.scheduled.len() == before {
+            return false;
+        }
+        if let Err(e) = self.save(&inner) {
+            warn!("保存队列文件失败: {}", e);
+        }
+        true
+    }
+
+    /***********************************************************************
+     * 把定时任务提前加入队列
+     *
+     * @return bool - 任务是否存在
+     ***********************************************************************/
+    pub fn start_now(&self, manager: &DownloadManager, id: &str) -> bool {
+        self.promote(manager, |item| item.id == id) > 0
+    }
+
+    /// 把开始时间已到的定时任务加入队列，返回加入的任务数
+    fn promote_due(&self, manager: &DownloadManager, now: u64) -> usize {
+        self.promote(manager, |item| item.start_at <= now)
+    }
+
+    fn promote(&self, manager: &DownloadManager, due: impl Fn(&ScheduledDownload) -> bool) -> usize {
+        let Ok(mut inner) = self.inner.lock() else {
+            return 0;
+        };
+        let (ready, waiting): (Vec<_>, Vec<_>) = inner.scheduled.drain(..).partition(|item| due(item));
+        inner.scheduled = waiting;
+        if ready.is_empty() {
+            return 0;
+        }
+
+        for item in &ready {
+            // 重新登记，使任务计入开始时所在的批次
+            manager.register(&item.id);
+            debug!(download_id = %item.id, "定时任务加入队列");
+        }
+        let count = ready.len();
+        inner.pending.extend(ready.into_iter().map(|item| QueuedDownload {
+            id: item.id,
+            url: item.url,
+            options: item.options,
+            group: None,
+            priority: item.priority,
+        }));
+        if let Err(e) = self.save(&inner) {
+            warn!("保存队列文件失败: {}", e);
+        }
+        self.notify.notify_one();
+
+        count
+    }
+
+    /// 最早的定时任务开始时间
+    fn next_start(&self) -> Option<u64> {
+        let inner = self.inner.lock().ok()?;
+        inner.scheduled.iter().map(|item| item.start_at).min()
+    }
+
+    /***********************************************************************
+     * 队列中的全部任务：定时任务（按开始时间）→ 等待中（按启动顺序）→ 运行中
+     ***********************************************************************/
+    pub fn items(&self) -> Vec<QueueItem> {
+        let Ok(inner) = self.inner.lock() else {
+            return Vec::new();
+        };
+
+        let mut scheduled: Vec<&ScheduledDownload> = inner.scheduled.iter().collect();
+        scheduled.sort_by_key(|item| item.start_at);
+        // 稳定排序，相同优先级保持加入顺序
+        let mut pending: Vec<&QueuedDownload> = inner.pending.iter().collect();
+        pending.sort_by_key(|item| Reverse(item.priority));
+
+        scheduled
+            .into_iter()
+            .map(|item| QueueItem {
+                download_id: item.id.clone(),
+                url: item.url.clone(),
+                state: QueueItemState::Scheduled,
+                start_at: Some(item.start_at),
+                output_dir: item.options.output_dir.clone(),
+                priority: item.priority,
+            })
+            .chain(pending.into_iter().map(|item| QueueItem {
+                download_id: item.id.clone(),
+                url: item.url.clone(),
+                state: QueueItemState::Pending,
+                start_at: None,
+                output_dir: item.options.output_dir.clone(),
+                priority: item.priority,
+            }))
+            .chain(inner.running.iter().map(|(id, running)| QueueItem {
+                download_id: id.clone(),
+                url: running.url.clone(),
+                state: QueueItemState::Running,
+                start_at: None,
+                output_dir: running.output_dir.clone(),
+                priority: running.priority,
+            }))
+            .collect()
+    }
+
+    /// 保存定时任务和等待中的任务
+    fn save(&self, inner: &QueueInner) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建数据目录: {}", e))?;
+        }
+        let file = QueueFile {
+            scheduled: inner.scheduled.clone(),
+            pending: inner.pending.iter().cloned().collect(),
+        };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|e| format!("序列化队列失败: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("写入队列文件失败: {}", e))
+    }
+}
+
+/// 解析队列文件（旧版本的队列文件是只含定时任务的数组）
+fn parse_queue_file(content: &str) -> Result<QueueFile, serde_json::Error> {
+    serde_json::from_str(content).or_else(|error| {
+        serde_json::from_str(content)
+            .map(|scheduled| QueueFile {
+                scheduled,
+                ..Default::default()
+            })
+            .map_err(|_| error)
+    })
+}
+
+/***************************************************************************
+ * 启动队列调度任务
+ *
+ * 入队、任务结束时被唤醒，按设置中的并发上限启动等待中的任务
+ ***************************************************************************/
+
+pub fn spawn_queue_dispatcher(app: AppHandle) {
+    spawn_queue_dispatcher_with(app, YtDlp::new);
+}
+
+/// 启动队列调度任务，由 backend 根据定位到的 yt-dlp 创建每个下载的媒体后端
+pub fn spawn_queue_dispatcher_with<B, F>(app: AppHandle, backend: F)
+where
+    B: MediaBackend + Send + Sync + 'static,
+    F: Fn(PathBuf) -> B + Copy + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let queue = app.state::<DownloadQueue>();
+        loop {
+            queue.notify.notified().await;
+            if queue.is_paused() {
+                continue;
+            }
+
+            let settings = app.state::<SettingsState>().get();
+            if settings.network_check && !queue.pending_ids().is_empty() && !is_online().await {
+                hold_for_network(&app, &queue).await;
+            }
+            hold_for_destination(&app, &queue).await;
+
+            let max_concurrent = settings.max_concurrent_downloads.max(1);
+            queue.dispatch_ready(max_concurrent, |item| {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let download = run_download_with(app.clone(), item.id.clone(), item.url, item.options, backend);
+                    if let Err(error) = download.await {
+                        warn!(download_id = %item.id, "队列下载失败: {}", error);
+                        let failed = DownloadFailed {
+                            download_id: item.id.clone(),
+                            error,
+                        };
+                        if let Err(e) = app.emit(events::DOWNLOAD_FAILED, &failed) {
+                            warn!(download_id = %item.id, "发送失败事件失败: {}", e);
+                        }
+                    }
+                    if let Some(group_id) = app.state::<DownloadQueue>().finish(&item.id) {
+                        finish_group(&app, &group_id);
+                    }
+                });
+            });
+        }
+    });
+}
+
+/***************************************************************************
+ * 分组全部结束：按需写出下载清单，并发送 batch-completed 事件（含新下载和跳过的数量）
+ *
+ * 清单写在分组的下载目录（未指定时为默认下载目录）
+ ***************************************************************************/
+
+fn finish_group(app: &AppHandle, group_id: &str) {
+    let queue = app.state::<DownloadQueue>();
+    let group = queue
+        .inner
+        .lock()
+        .ok()
+        .and_then(|inner| inner.groups.get(group_id).cloned());
+    let Some(group) = group else {
+        return;
+    };
+    info!("播放列表下载结束: {} ({} 个视频)", group_id, group.download_ids.len());
+
+    let entries = manifest_entries(app, &group);
+    let manifest_path = group.options.manifest_format.and_then(|format| {
+        let dir = match &group.options.output_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => default_download_dir(app).map_err(|e| warn!("无法写出下载清单: {}", e)).ok()?,
+        };
+        match write_manifest(&dir, group_id, format, &entries) {
+            Ok(path) => Some(path.to_string_lossy().into_owned()),
+            Err(e) => {
+                warn!("写出下载清单失败: {}", e);
+                None
+            }
+        }
+    });
+
+    if let Ok(mut inner) = queue.inner.lock() {
+        if let Some(group) = inner.groups.get_mut(group_id) {
+            group.manifest_path = manifest_path.clone();
+        }
+    }
+
+    // 下载时才记入存档的视频（如另一个任务刚下载完）由 yt-dlp 跳过，成功但没有输出文件
+    let completed_entries = || entries.iter().filter(|e| e.status == Some(DownloadStatus::Completed));
+    let downloaded = completed_entries().filter(|e| e.output_path.is_some()).count();
+    let skipped = match group.options.archive_file {
+        Some(_) => group.skipped + completed_entries().filter(|e| e.output_path.is_none()).count(),
+        None => group.skipped,
+    };
+
+    let completed = BatchCompleted {
+        playlist_id: group_id.to_string(),
+        manifest_path,
+        downloaded,
+        skipped,
+    };
+    if let Err(e) = app.emit(events::BATCH_COMPLETED, &completed) {
+        warn!("发送批量完成事件失败: {}", e);
+    }
+}
+
+/// 按入队顺序生成清单条目：优先使用历史记录，没有记录时使用任务状态
+fn manifest_entries(app: &AppHandle, group: &DownloadGroup) -> Vec<ManifestEntry> {
+    let history = app.state::<HistoryStore>();
+    let manager = app.state::<DownloadManager>();
+
+    group
+        .download_ids
+        .iter()
+        .zip(group.titles.iter().zip(&group.urls))
+        .map(|(id, (title, url))| {
+            let entry = history.get(id);
+            let state = manager.state(id);
+            let output_path = entry.as_ref().and_then(|e| e.output_path.clone());
+            let size = output_path
+                .as_ref()
+                .and_then(|path| fs::metadata(path).ok())
+                .map(|meta| meta.len())
+                .or_else(|| entry.as_ref().and_then(|e| e.total_bytes));
+
+            ManifestEntry {
+                download_id: id.clone(),
+                title: title.clone(),
+                url: url.clone(),
+                output_path,
+                size,
+                status: entry.as_ref().map(|e| e.status).or(state.as_ref().map(|s| s.status)),
+                error: entry
+                    .as_ref()
+                    .and_then(|e| e.error.clone())
+                    .or_else(|| state.and_then(|s| s.error)),
+            }
+        })
+        .collect()
+}
+
+/***************************************************************************
+ * 断网时暂停调度
+ *
+ * 等待中的任务标记为 WaitingForNetwork，网络恢复后重新标记为 Queued
+ * 并发送 network-restored 事件
+ ***************************************************************************/
+
+async fn hold_for_network(app: &AppHandle, queue: &DownloadQueue) {
+    let manager = app.state::<DownloadManager>();
+    let waiting = queue.pending_ids();
+    warn!("网络未连接，{} 个任务等待网络恢复", waiting.len());
+    for id in &waiting {
+        manager.set_status(id, DownloadStatus::WaitingForNetwork, None);
+    }
+
+    wait_for_network().await;
+
+    // 断网期间新加入的任务也一并恢复
+    let download_ids = queue.pending_ids();
+    for id in &download_ids {
+        manager.set_status(id, DownloadStatus::Queued, None);
+    }
+    info!("网络已恢复，{} 个任务继续排队", download_ids.len());
+    if let Err(e) = app.emit(events::NETWORK_RESTORED, &NetworkRestored { download_ids }) {
+        warn!("发送网络恢复事件失败: {}", e);
+    }
+}
+
+/***************************************************************************
+ * 下载目录不可用时暂停调度
+ *
+ * 受影响的任务标记为 WaitingForDestination，并按目录发送 destination-unavailable 事件；
+ * 每 DESTINATION_RECHECK_INTERVAL 或修改设置后重新检查，全部可用后重新标记为 Queued
+ * 并发送 destination-restored 事件
+ ***************************************************************************/
+
+async fn hold_for_destination(app: &AppHandle, queue: &DownloadQueue) {
+    let manager = app.state::<DownloadManager>();
+    let mut reported: Vec<DestinationUnavailable> = Vec::new();
+    let mut held: Vec<String> = Vec::new();
+
+    loop {
+        let unavailable = queue.unavailable_destinations(app);
+        if unavailable.is_empty() {
+            break;
+        }
+        for destination in unavailable.iter().filter(|d| !reported.contains(d)) {
+            warn!("下载目录不可用，{} 个任务等待: {}", destination.download_ids.len(), destination.error);
+            for id in &destination.download_ids {
+                manager.set_status(id, DownloadStatus::WaitingForDestination, None);
+                if !held.contains(id) {
+                    held.push(id.clone());
+                }
+            }
+            if let Err(e) = app.emit(events::DESTINATION_UNAVAILABLE, destination) {
+                warn!("发送下载目录不可用事件失败: {}", e);
+            }
+        }
+        reported = unavailable;
+
+        let _ = tokio::time::timeout(DESTINATION_RECHECK_INTERVAL, queue.destination_changed.notified()).await;
+    }
+
+    if held.is_empty() {
+        return;
+    }
+    // 等待期间已取消的任务不再恢复
+    let pending = queue.pending_ids();
+    let download_ids: Vec<String> = held.into_iter().filter(|id| pending.contains(id)).collect();
+    for id in &download_ids {
+        manager.set_status(id, DownloadStatus::Queued, None);
+    }
+    info!("下载目录已可用，{} 个任务继续排队", download_ids.len());
+    if let Err(e) = app.emit(events::DESTINATION_RESTORED, &DestinationRestored { download_ids }) {
+        warn!("发送下载目录恢复事件失败: {}", e);
+    }
+}
+
+/***************************************************************************
+ * 启动定时任务计时器
+ *
+ * 恢复上次保存的定时任务后，每次醒来都按当前时间检查是否到期；
+ * 休眠时长不超过 SCHEDULE_TICK，系统睡眠或时钟调整后也能及时开始
+ ***************************************************************************/
+
+pub fn spawn_schedule_timer(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let queue = app.state::<DownloadQueue>();
+        let manager = app.state::<DownloadManager>();
+
+        for item in queue.items() {
+            manager.register(&item.download_id);
+            manager.set_status(&item.download_id, DownloadStatus::Scheduled, None);
+        }
+
+        loop {
+            let now = unix_millis();
+            let promoted = queue.promote_due(&manager, now);
+            if promoted > 0 {
+                info!("{} 个定时任务已加入下载队列", promoted);
+            }
+
+            let wait = queue
+                .next_start()
+                .map(|start_at| Duration::from_millis(start_at.saturating_sub(now)))
+                .map_or(SCHEDULE_TICK, |until| until.min(SCHEDULE_TICK));
+            tokio::time::sleep(wait).await;
+        }
+    });
+}
+
+/***************************************************************************
+ * 批量导入
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct BatchEnqueued {
+    pub line: usize,                // 行号（从 1 开始）
+    pub url: String,
+    pub download_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct BatchLineError {
+    pub line: usize,                // 行号（从 1 开始）
+    pub content: String,            // 原始内容
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct BatchResult {
+    pub enqueued: Vec<BatchEnqueued>,
+    pub errors: Vec<BatchLineError>,
+}
+
+/***************************************************************************
+ * 解析批量链接文件
+ *
+ * 与 yt-dlp --batch-file 一致：忽略空行以及以 #、;、] 开头的注释行
+ *
+ * @param content - 文件内容
+ * @return 每个有效行的 (行号, 原始内容)
+ ***************************************************************************/
+
+pub fn parse_batch_lines(content: &str) -> Vec<(usize, String)> {
+    content
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with(['#', ';', ']']))
+        .map(|(number, line)| (number, line.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ytdlp::mock::MockBackend;
+    use crate::ytdlp::DownloadEvent;
+    use std::sync::Arc;
+
+    /// 与 spawn_queue_dispatcher 相同的调度循环，下载由 backend 执行，结束后按退出码设置状态
+    async fn run_queue(
+        queue: Arc<DownloadQueue>,
+        manager: Arc<DownloadManager>,
+        backend: Arc<MockBackend>,
+        max_concurrent: usize,
+    ) -> Vec<String> {
+        let mut started = Vec::new();
+        loop {
+            queue.dispatch_ready(max_concurrent, |item| {
+                started.push(item.id.clone());
+                let (queue, manager, backend) = (queue.clone(), manager.clone(), backend.clone());
+                tokio::spawn(async move {
+                    let (event_manager, event_id) = (manager.clone(), item.id.clone());
+                    let on_event = move |mut event: DownloadEvent| event_manager.apply_event(&event_id, &mut event);
+                    let status = match backend.download(&[item.url], None, on_event).await {
+                        Ok(outcome) if outcome.status.success() => DownloadStatus::Completed,
+                        _ => DownloadStatus::Failed,
+                    };
+                    manager.set_status(&item.id, status, None);
+                    queue.finish(&item.id);
+                });
+            });
+            if queue.items().is_empty() {
+                return started;
+            }
+            queue.notify.notified().await;
+        }
+    }
+
+    fn backend(delay_ms: u64) -> Arc<MockBackend> {
+        Arc::new(MockBackend {
+            stdout: vec![
+                "[download] Destination: /downloads/video.mp4".to_string(),
+                "[download]  50.0% of 10.00MiB at  1.00MiB/s ETA 00:05".to_string(),
+            ],
+            delay: Duration::from_millis(delay_ms),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn dispatches_by_priority_within_concurrency_limit() {
+        let manager = Arc::new(DownloadManager::default());
+        let queue = Arc::new(DownloadQueue::load(None, &manager));
+        let ids: Vec<String> = (0..4)
+            .map(|i| queue.enqueue(&manager, format!("https://example.com/{}", i), DownloadOptions::default()))
+            .collect();
+        queue.set_priority(&ids[3], 5).unwrap();
+
+        let backend = backend(20);
+        let started = run_queue(queue.clone(), manager.clone(), backend.clone(), 2).await;
+
+        assert_eq!(started[0], ids[3]);
+        assert_eq!(&started[1..], &ids[..3]);
+        assert_eq!(backend.max_running.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(backend.calls().len(), 4);
+        for id in &ids {
+            let state = manager.state(id).unwrap();
+            assert_eq!(state.status, DownloadStatus::Completed);
+            assert_eq!(state.progress.unwrap().download_id, *id);
+        }
+        assert!(queue.items().is_empty());
+    }
+
+    #[tokio::test]
+    async fn group_uses_its_own_concurrency() {
+        let manager = Arc::new(DownloadManager::default());
+        let queue = Arc::new(DownloadQueue::load(None, &manager));
+        let entries = (0..3).map(|i| (format!("https://example.com/{}", i), format!("第 {} 个", i))).collect();
+        let (group_id, ids) = queue.enqueue_group(&manager, entries, DownloadOptions::default(), 1, 0);
+
+        let backend = backend(10);
+        let started = run_queue(queue.clone(), manager.clone(), backend.clone(), 4).await;
+
+        assert_eq!(started, ids);
+        assert_eq!(backend.max_running.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(queue.inner.lock().unwrap().groups[&group_id].completed);
+    }
+
+    #[test]
+    fn paused_queue_starts_nothing() {
+        let manager = DownloadManager::default();
+        let queue = DownloadQueue::load(None, &manager);
+        queue.enqueue(&manager, "https://example.com/a".to_string(), DownloadOptions::default());
+        queue.enqueue(&manager, "https://example.com/b".to_string(), DownloadOptions::default());
+
+        assert!(queue.set_paused(true).is_some());
+        assert_eq!(queue.dispatch_ready(4, |_| {}), 0);
+        assert_eq!(queue.pending_ids().len(), 2);
+
+        assert!(queue.set_paused(false).is_some());
+        assert_eq!(queue.dispatch_ready(1, |_| {}), 1);
+        assert_eq!(queue.pending_ids().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_download_still_frees_its_slot() {
+        let manager = Arc::new(DownloadManager::default());
+        let queue = Arc::new(DownloadQueue::load(None, &manager));
+        let first = queue.enqueue(&manager, "https://example.com/a".to_string(), DownloadOptions::default());
+        let second = queue.enqueue(&manager, "https://example.com/b".to_string(), DownloadOptions::default());
+
+        let backend = Arc::new(MockBackend {
+            stderr: vec!["ERROR: [youtube] abc: Video unavailable".to_string()],
+            exit_code: 1,
+            ..Default::default()
+        });
+        run_queue(queue.clone(), manager.clone(), backend, 1).await;
+
+        assert_eq!(manager.state(&first).unwrap().status, DownloadStatus::Failed);
+        assert_eq!(manager.state(&second).unwrap().status, DownloadStatus::Failed);
+        assert!(queue.items().is_empty());
+    }
+
+    #[test]
+    fn pending_items_are_restored_with_priority() {
+        let path = std::env::temp_dir().join(format!("youtudown-queue-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let manager = DownloadManager::default();
+        let queue = DownloadQueue::load(Some(path.clone()), &manager);
+        let first = queue.enqueue(&manager, "https://example.com/a".to_string(), DownloadOptions::default());
+        let second = queue.enqueue(&manager, "https://example.com/b".to_string(), DownloadOptions::default());
+        let url = "https://example.com/c".to_string();
+        let later = queue.schedule(&manager, url, DownloadOptions::default(), u64::MAX);
+        queue.set_priority(&second, 3).unwrap();
+
+        let manager = DownloadManager::default();
+        let restored = DownloadQueue::load(Some(path.clone()), &manager);
+        let items: Vec<(String, QueueItemState, i32)> = restored
+            .items()
+            .into_iter()
+            .map(|item| (item.download_id, item.state, item.priority))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                (later.clone(), QueueItemState::Scheduled, 0),
+                (second.clone(), QueueItemState::Pending, 3),
+                (first.clone(), QueueItemState::Pending, 0),
+            ]
+        );
+        assert_eq!(manager.state(&first).unwrap().status, DownloadStatus::Queued);
+        assert_eq!(manager.state(&later).unwrap().status, DownloadStatus::Scheduled);
+
+        // 开始的任务不再保存
+        assert_eq!(restored.dispatch_ready(1, |_| {}), 1);
+        let restored = DownloadQueue::load(Some(path.clone()), &DownloadManager::default());
+        assert_eq!(restored.pending_ids(), [first]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn old_queue_files_only_hold_scheduled_items() {
+        let content = r#"[{"id": "dl-1-0", "url": "https://example.com/a", "options": {}, "start_at": 5}]"#;
+        let file = parse_queue_file(content).unwrap();
+        assert_eq!(file.scheduled.len(), 1);
+        assert_eq!(file.scheduled[0].priority, 0);
+        assert!(file.pending.is_empty());
+        assert!(parse_queue_file("{").is_err());
+    }
+}
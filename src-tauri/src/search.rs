@@ -0,0 +1,114 @@
+/****************************************************************************
+ *  search.rs - 站内搜索
+ *
+ *  @brief  没有链接时按标题搜索 YouTube（yt-dlp 的 ytsearchN: 前缀）
+ *  @note   查询词作为单个参数传给 yt-dlp，不经过 shell；
+ *          这里只做长度限制和控制字符清理
+ *****************************************************************************/
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::debug;
+use ts_rs::TS;
+
+/// 单次搜索最多返回的结果数
+pub const MAX_SEARCH_RESULTS: usize = 30;
+
+/// 查询词最大长度（字符）
+const MAX_QUERY_LENGTH: usize = 200;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct SearchResult {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub duration: Option<f64>,      // 时长（秒）
+    pub uploader: Option<String>,
+    pub thumbnail: Option<String>,
+    #[ts(type = "number | null")]
+    pub view_count: Option<u64>,
+}
+
+/***************************************************************************
+ * 构建 yt-dlp 搜索目标
+ *
+ * 去除控制字符并合并空白，结果数限制在 1..=MAX_SEARCH_RESULTS
+ *
+ * @param query - 用户输入的查询词
+ * @param count - 期望的结果数
+ * @return String - 如 "ytsearch10:查询词"
+ ***************************************************************************/
+
+pub fn search_target(query: &str, count: usize) -> Result<String, String> {
+    let query = query
+        .split(|c: char| c.is_whitespace() || c.is_control())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if query.is_empty() {
+        return Err("搜索内容不能为空".to_string());
+    }
+    if query.chars().count() > MAX_QUERY_LENGTH {
+        return Err(format!("搜索内容过长（最多 {} 个字符）", MAX_QUERY_LENGTH));
+    }
+
+    let count = count.clamp(1, MAX_SEARCH_RESULTS);
+    Ok(format!("ytsearch{}:{}", count, query))
+}
+
+/***************************************************************************
+ * 解析 --dump-json --flat-playlist 的输出（每行一条结果，保持原顺序）
+ ***************************************************************************/
+
+pub fn parse_search_results(stdout: &str) -> Vec<SearchResult> {
+    stdout
+        .lines()
+        .filter_map(|line| match serde_json::from_str::<Value>(line) {
+            Ok(json) => parse_search_result(&json),
+            Err(e) => {
+                debug!("跳过无法解析的搜索结果行: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_search_result(json: &Value) -> Option<SearchResult> {
+    let id = json["id"].as_str()?.to_string();
+
+    // 扁平结果的 url 可能缺失，按视频ID拼出观看地址
+    let url = json["url"]
+        .as_str()
+        .or_else(|| json["webpage_url"].as_str())
+        .map(String::from)
+        .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={}", id));
+
+    // 扁平结果没有 thumbnail 字段，取 thumbnails 列表的最后一项（分辨率最高）
+    let thumbnail = json["thumbnail"]
+        .as_str()
+        .or_else(|| {
+            json["thumbnails"]
+                .as_array()
+                .and_then(|thumbs| thumbs.last())
+                .and_then(|thumb| thumb["url"].as_str())
+        })
+        .map(String::from);
+
+    Some(SearchResult {
+        title: json["title"].as_str().unwrap_or("未知标题").to_string(),
+        url,
+        duration: json["duration"].as_f64(),
+        uploader: json["uploader"]
+            .as_str()
+            .or_else(|| json["channel"].as_str())
+            .map(String::from),
+        thumbnail,
+        view_count: json["view_count"].as_u64(),
+        id,
+    })
+}
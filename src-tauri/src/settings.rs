@@ -0,0 +1,344 @@
+/****************************************************************************
+ *  settings.rs - 应用设置
+ *
+ *  @brief  持久化的全局设置（JSON 文件，位于应用配置目录）
+ *  @note   设置以 RwLock 包装后注册为 Tauri 托管状态，命令通过 State 读取
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::warn;
+use ts_rs::TS;
+
+use crate::checksum::ChecksumAlgorithm;
+use crate::extra_args::validate_extra_args;
+use crate::i18n::Locale;
+use crate::presets::{is_builtin_name, Preset};
+use crate::process::PriorityMode;
+use crate::site_presets::{default_site_presets, validate_site_presets, SitePreset};
+
+/// 设置文件名
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// 默认限速阈值（100 KiB/s）
+const DEFAULT_THROTTLE_THRESHOLD: u64 = 100 * 1024;
+
+/// 低优先级模式下 ffmpeg 默认使用的线程数
+const DEFAULT_BACKGROUND_FFMPEG_THREADS: u32 = 2;
+
+/// 默认启动超时（秒）
+const DEFAULT_STARTUP_TIMEOUT: u64 = 120;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+/// 下载目录下的子目录组织方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub enum OrganizeBy {
+    #[default]
+    None,                           // 直接保存在下载目录
+    Site,                           // 按站点（extractor_key）
+    Uploader,                       // 按上传者
+    Playlist,                       // 按播放列表标题
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct Settings {
+    pub temp_dir: Option<String>,   // 临时分片目录（--paths temp:DIR），未设置时使用系统临时目录
+    pub cache_dir: Option<String>,  // yt-dlp 缓存目录（--cache-dir）
+    pub keep_fragments: bool,       // 合并后保留单独的视频/音频文件及分片（--keep-video/--keep-fragments）
+    pub organize_by: OrganizeBy,    // 按站点/上传者/播放列表分子目录保存
+    pub max_concurrent_downloads: usize, // 队列同时进行的下载数
+    pub restrict_filenames: bool,   // 文件名仅使用 ASCII 字符（--restrict-filenames）
+    pub windows_safe_filenames: bool, // 文件名兼容 Windows（--windows-filenames）
+    pub max_filename_length: Option<usize>, // 文件名最大长度（--trim-filenames），未设置时按下载目录深度自动计算
+    pub stage_downloads: bool,      // 先下载到暂存目录，完成后再移动到下载目录
+    pub download_dir: Option<String>, // 默认下载目录，未设置时使用系统下载目录
+    pub auto_checksum: Option<ChecksumAlgorithm>, // 下载完成后自动计算校验和
+    pub subscription_check_hours: u32, // 订阅频道的检查间隔（小时），0 表示不自动检查
+    pub presets: Vec<Preset>,       // 用户保存的质量预设（内置预设不保存在这里）
+    pub prefer_progressive: bool,   // 优先使用音视频合一的格式（无需合并，画质最多低一档）
+    pub ytdlp_path: Option<String>, // 固定使用的 yt-dlp，未设置时自动查找
+    pub prefer_bundled_ytdlp: bool, // 自动查找时优先使用应用内置的 yt-dlp（sidecar），关闭时优先使用系统安装的
+    pub ytdlp_config: Option<String>, // yt-dlp 配置文件（--config-location），应用内的选项优先于其中的同名选项
+    pub ignore_ytdlp_config: bool,  // 不加载 yt-dlp 的全局/用户配置（--ignore-config），ytdlp_config 仍然生效
+    pub network_check: bool,        // 启动 yt-dlp 前检查网络（只能通过代理访问外网时可关闭）
+    #[ts(type = "number | null")]
+    pub throttle_threshold: Option<u64>, // 限速阈值（字节/秒），速度持续低于该值时发送 download-throttled，None 不检测
+    pub restart_throttled: bool,    // 被限速时由 yt-dlp 重新提取并重启分片（--throttled-rate）
+    pub proxy: Option<String>,      // yt-dlp 使用的代理（--proxy），设置后忽略环境变量中的代理
+    pub inherit_proxy_env: bool,    // 未设置代理时沿用 HTTP_PROXY 等环境变量
+    pub site_presets: BTreeMap<String, SitePreset>, // 站点名 → 站点预设（按链接域名自动套用）
+    pub background_priority: PriorityMode, // yt-dlp/ffmpeg 子进程优先级
+    pub background_ffmpeg_threads: Option<u32>, // 非 Normal 优先级时限制 ffmpeg 线程数，None 不限制
+    #[ts(type = "number | null")]
+    pub startup_timeout: Option<u64>, // 启动超时（秒）：超过该时间仍未开始下载或等待则结束进程，None 不限制
+    pub locale: Option<Locale>,     // 错误信息的语言，None 跟随系统语言
+    pub max_resolution: Option<i64>, // 分辨率上限（高度），未明确选择格式或最大高度的下载不超过该高度，None 不限制
+    pub hide_above_max_resolution: bool, // 获取信息时隐藏超过上限的分辨率选项（关闭时只标记）
+    pub extra_args: Vec<String>,    // 所有下载附加的自定义 yt-dlp 参数（在下载选项的 extra_args 之前）
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            temp_dir: None,
+            cache_dir: None,
+            keep_fragments: false,
+            organize_by: OrganizeBy::None,
+            max_concurrent_downloads: 2,
+            restrict_filenames: false,
+            windows_safe_filenames: cfg!(windows),
+            max_filename_length: None,
+            stage_downloads: false,
+            download_dir: None,
+            auto_checksum: None,
+            subscription_check_hours: 6,
+            presets: Vec::new(),
+            prefer_progressive: false,
+            ytdlp_path: None,
+            prefer_bundled_ytdlp: true,
+            ytdlp_config: None,
+            ignore_ytdlp_config: false,
+            network_check: true,
+            throttle_threshold: Some(DEFAULT_THROTTLE_THRESHOLD),
+            restart_throttled: false,
+            proxy: None,
+            inherit_proxy_env: true,
+            site_presets: default_site_presets(),
+            background_priority: PriorityMode::Normal,
+            background_ffmpeg_threads: Some(DEFAULT_BACKGROUND_FFMPEG_THREADS),
+            startup_timeout: Some(DEFAULT_STARTUP_TIMEOUT),
+            locale: None,
+            max_resolution: None,
+            hide_above_max_resolution: false,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /***********************************************************************
+     * 校验设置中的各项取值
+     ***********************************************************************/
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrent_downloads == 0 {
+            return Err("同时下载数必须大于 0".to_string());
+        }
+        if let Some(proxy) = self.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+            if !proxy.contains("://") {
+                return Err(format!("代理地址无效（应为 http://、socks5:// 等形式）: {}", proxy));
+            }
+        }
+        validate_site_presets(&self.site_presets)?;
+        validate_extra_args(&self.extra_args)?;
+        if self.background_ffmpeg_threads == Some(0) {
+            return Err("ffmpeg 线程数必须大于 0".to_string());
+        }
+        if self.startup_timeout == Some(0) {
+            return Err("启动超时必须大于 0".to_string());
+        }
+        if self.throttle_threshold == Some(0) {
+            return Err("限速阈值必须大于 0".to_string());
+        }
+        if self.restart_throttled && self.throttle_threshold.is_none() {
+            return Err("自动重启限速下载需要设置限速阈值".to_string());
+        }
+        if self.max_resolution.is_some_and(|height| height <= 0) {
+            return Err("分辨率上限必须大于 0".to_string());
+        }
+        if self.max_filename_length == Some(0) {
+            return Err("文件名最大长度必须大于 0".to_string());
+        }
+        if let Some(dir) = &self.temp_dir {
+            validate_writable_dir(Path::new(dir)).map_err(|e| format!("临时目录无效: {}", e))?;
+        }
+        if let Some(dir) = &self.cache_dir {
+            validate_writable_dir(Path::new(dir)).map_err(|e| format!("缓存目录无效: {}", e))?;
+        }
+        if let Some(path) = self.ytdlp_path.as_deref().filter(|p| !p.is_empty()) {
+            if !Path::new(path).is_file() {
+                return Err(format!("yt-dlp 路径不存在: {}", path));
+            }
+        }
+        if let Some(path) = self.ytdlp_config.as_deref().filter(|p| !p.is_empty()) {
+            if !Path::new(path).is_file() {
+                return Err(format!("yt-dlp 配置文件不存在: {}", path));
+            }
+        }
+        for (index, preset) in self.presets.iter().enumerate() {
+            if preset.name.trim().is_empty() {
+                return Err("预设名称不能为空".to_string());
+            }
+            if is_builtin_name(&preset.name) {
+                return Err(format!("不能覆盖内置预设: {}", preset.name));
+            }
+            if self.presets[..index].iter().any(|p| p.name == preset.name) {
+                return Err(format!("预设名称重复: {}", preset.name));
+            }
+        }
+        if let Some(dir) = &self.download_dir {
+            validate_writable_dir(Path::new(dir)).map_err(|e| format!("下载目录无效: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /***********************************************************************
+     * 临时目录根路径：未设置时回退到系统临时目录，避免在慢速输出盘上写分片
+     ***********************************************************************/
+    pub fn temp_root(&self) -> Result<PathBuf, String> {
+        match &self.temp_dir {
+            Some(dir) => {
+                let dir = PathBuf::from(dir);
+                validate_writable_dir(&dir).map_err(|e| format!("临时目录无效: {}", e))?;
+                Ok(dir)
+            }
+            None => {
+                let dir = std::env::temp_dir().join("youtudown");
+                fs::create_dir_all(&dir).map_err(|e| format!("无法创建临时目录: {}", e))?;
+                Ok(dir)
+            }
+        }
+    }
+
+    /***********************************************************************
+     * 生成由设置决定的 yt-dlp 参数
+     *
+     * @param staging_dir - 暂存目录（开启 stage_downloads 时分片也写在这里）
+     * @return Vec<String> - 需要附加到下载命令的参数
+     ***********************************************************************/
+    pub fn download_args(&self, staging_dir: Option<&Path>) -> Result<Vec<String>, String> {
+        let mut args = Vec::new();
+
+        let temp_dir = match staging_dir {
+            Some(dir) => dir.to_path_buf(),
+            None => self.temp_root()?,
+        };
+        args.push("--paths".to_string());
+        args.push(format!("temp:{}", temp_dir.display()));
+
+        if let Some(dir) = &self.cache_dir {
+            validate_writable_dir(Path::new(dir)).map_err(|e| format!("缓存目录无效: {}", e))?;
+            args.push("--cache-dir".to_string());
+            args.push(dir.clone());
+        }
+
+        if let Some(threshold) = self.throttle_threshold.filter(|_| self.restart_throttled) {
+            args.push("--throttled-rate".to_string());
+            args.push(threshold.to_string());
+        }
+
+        // 低优先级时限制 ffmpeg 线程数，减轻合并/转码时的 CPU 占用
+        if let Some(threads) = self
+            .background_ffmpeg_threads
+            .filter(|_| self.background_priority != PriorityMode::Normal)
+        {
+            args.push("--postprocessor-args".to_string());
+            args.push(format!("ffmpeg:-threads {}", threads));
+        }
+
+        // 默认与 yt-dlp 一致：合并后删除中间文件
+        if self.keep_fragments {
+            args.push("--keep-video".to_string());
+            args.push("--keep-fragments".to_string());
+        }
+
+        Ok(args)
+    }
+}
+
+/***************************************************************************
+ * 校验目录存在且可写
+ *
+ * 通过创建并删除一个探测文件来确认写权限（只读挂载、权限不足都能发现）
+ ***************************************************************************/
+
+pub fn validate_writable_dir(dir: &Path) -> Result<(), String> {
+    if !dir.exists() {
+        return Err(format!("目录不存在: {}", dir.display()));
+    }
+    if !dir.is_dir() {
+        return Err(format!("不是目录: {}", dir.display()));
+    }
+
+    let probe = dir.join(".youtudown-write-test");
+    fs::write(&probe, b"")
+        .map_err(|e| format!("目录不可写: {} ({})", dir.display(), e))?;
+    let _ = fs::remove_file(&probe);
+
+    Ok(())
+}
+
+/***************************************************************************
+ * 设置托管状态
+ ***************************************************************************/
+
+pub struct SettingsState {
+    path: Option<PathBuf>,
+    settings: RwLock<Settings>,
+}
+
+impl SettingsState {
+    /***********************************************************************
+     * 从设置文件加载，文件不存在或损坏时使用默认值
+     *
+     * @param path - 设置文件路径（无法确定配置目录时为 None，仅保存在内存中）
+     ***********************************************************************/
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let settings = path
+            .as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read_to_string(p) {
+                Ok(content) => serde_json::from_str(&content)
+                    .map_err(|e| warn!("设置文件解析失败，使用默认设置: {}", e))
+                    .ok(),
+                Err(e) => {
+                    warn!("读取设置文件失败，使用默认设置: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            settings: RwLock::new(settings),
+        }
+    }
+
+    /// 获取当前设置的副本
+    pub fn get(&self) -> Settings {
+        self.settings
+            .read()
+            .map(|s| s.clone())
+            .unwrap_or_default()
+    }
+
+    /***********************************************************************
+     * 校验并保存新设置
+     ***********************************************************************/
+    pub fn update(&self, settings: Settings) -> Result<(), String> {
+        settings.validate()?;
+
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("无法创建配置目录: {}", e))?;
+            }
+            let content = serde_json::to_string_pretty(&settings)
+                .map_err(|e| format!("序列化设置失败: {}", e))?;
+            fs::write(path, content).map_err(|e| format!("保存设置失败: {}", e))?;
+        }
+
+        let mut current = self
+            .settings
+            .write()
+            .map_err(|_| "设置状态已损坏".to_string())?;
+        *current = settings;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+/****************************************************************************
+ *  site_presets.rs - 站点预设
+ *
+ *  @brief  按站点集中管理 yt-dlp 参数：提取器参数（--extractor-args）、
+ *          伪装目标和 Cookie 来源；获取信息和下载时按链接域名自动套用
+ *  @note   预设保存在设置中，首次使用时为 YouTube、Bilibili 的默认预设；
+ *          下载选项中明确指定的伪装目标和 Cookie 浏览器优先于预设
+ *****************************************************************************/
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieStrategy {
+    #[default]
+    Default,                        // 沿用默认（从 Chrome 读取 Cookie）
+    Disabled,                       // 不使用浏览器 Cookie（--no-cookies-from-browser）
+    Browser(String),                // 从指定浏览器读取（如 "firefox"）
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+pub struct SitePreset {
+    pub domains: Vec<String>,       // 匹配的域名（含子域名），如 "youtube.com"
+    pub extractor_args: Vec<String>, // --extractor-args 的取值，如 "youtube:player_client=default,mweb"
+    pub impersonate: Option<String>, // 伪装目标（不可用时自动移除）
+    pub cookies: CookieStrategy,
+}
+
+/***************************************************************************
+ * 默认预设
+ *
+ * - youtube：同时请求 mweb 客户端，减少需要 PO Token 的格式缺失
+ * - bilibili：伪装为 Chrome，避免接口返回 412
+ ***************************************************************************/
+
+pub fn default_site_presets() -> BTreeMap<String, SitePreset> {
+    BTreeMap::from([
+        (
+            "youtube".to_string(),
+            SitePreset {
+                domains: vec![
+                    "youtube.com".to_string(),
+                    "youtu.be".to_string(),
+                    "youtube-nocookie.com".to_string(),
+                ],
+                extractor_args: vec!["youtube:player_client=default,mweb".to_string()],
+                impersonate: None,
+                cookies: CookieStrategy::Default,
+            },
+        ),
+        (
+            "bilibili".to_string(),
+            SitePreset {
+                domains: vec!["bilibili.com".to_string(), "b23.tv".to_string()],
+                extractor_args: Vec::new(),
+                impersonate: Some("chrome".to_string()),
+                cookies: CookieStrategy::Default,
+            },
+        ),
+    ])
+}
+
+/// 校验预设（站点名和域名不能为空）
+pub fn validate_site_presets(presets: &BTreeMap<String, SitePreset>) -> Result<(), String> {
+    for (site, preset) in presets {
+        if site.trim().is_empty() {
+            return Err("站点名称不能为空".to_string());
+        }
+        if preset.domains.iter().all(|d| d.trim().is_empty()) {
+            return Err(format!("站点预设 {} 没有指定域名", site));
+        }
+    }
+    Ok(())
+}
+
+/***************************************************************************
+ * 按链接域名查找预设
+ *
+ * @return Option<(&String, &SitePreset)> - 站点名和预设（没有匹配时为 None）
+ ***************************************************************************/
+
+pub fn find_site_preset<'a>(
+    presets: &'a BTreeMap<String, SitePreset>,
+    url: &str,
+) -> Option<(&'a String, &'a SitePreset)> {
+    let host = Url::parse(url).ok()?.host_str()?.to_lowercase();
+    presets.iter().find(|(_, preset)| {
+        preset.domains.iter().any(|domain| {
+            let domain = domain.trim().to_lowercase();
+            !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+        })
+    })
+}
+
+impl SitePreset {
+    /***********************************************************************
+     * 生成附加到 yt-dlp 命令的参数
+     *
+     * 放在其他参数之后，覆盖默认的伪装目标和 Cookie 浏览器
+     *
+     * @param keep_impersonate - 下载选项已指定伪装目标时不覆盖
+     * @param keep_cookies - 下载选项已指定 Cookie 浏览器时不覆盖
+     ***********************************************************************/
+    pub fn args(&self, keep_impersonate: bool, keep_cookies: bool) -> Vec<String> {
+        let mut args = Vec::new();
+        for value in self.extractor_args.iter().filter(|v| !v.trim().is_empty()) {
+            args.push("--extractor-args".to_string());
+            args.push(value.clone());
+        }
+        if let Some(target) = self.impersonate.as_ref().filter(|_| !keep_impersonate) {
+            args.push("--impersonate".to_string());
+            args.push(target.clone());
+        }
+        if !keep_cookies {
+            match &self.cookies {
+                CookieStrategy::Default => {}
+                CookieStrategy::Disabled => args.push("--no-cookies-from-browser".to_string()),
+                CookieStrategy::Browser(browser) => {
+                    args.push("--cookies-from-browser".to_string());
+                    args.push(browser.clone());
+                }
+            }
+        }
+        args
+    }
+}
@@ -0,0 +1,132 @@
+/****************************************************************************
+ *  staging.rs - 下载暂存目录
+ *
+ *  @brief  开启 stage_downloads 时，yt-dlp 的全部输出（含 .part/.ytdl）写入应用管理的
+ *          暂存目录，下载成功后再整体移动到目标目录
+ *  @note   暂存目录按下载任务ID固定（<临时目录>/staging/<id>），
+ *          同一任务重试时 yt-dlp 可以找到之前的 .part 文件继续下载
+ *****************************************************************************/
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::files::move_file;
+use crate::settings::Settings;
+
+/// 暂存目录在临时目录下的子目录名
+const STAGING_DIR: &str = "staging";
+
+/// 下载任务的暂存目录路径（不创建，预览命令使用）
+pub fn staging_path(settings: &Settings, download_id: &str) -> Result<PathBuf, String> {
+    Ok(settings.temp_root()?.join(STAGING_DIR).join(download_id))
+}
+
+/***************************************************************************
+ * 获取（并创建）下载任务的暂存目录
+ ***************************************************************************/
+
+pub fn staging_dir(settings: &Settings, download_id: &str) -> Result<PathBuf, String> {
+    let dir = staging_path(settings, download_id)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("无法创建暂存目录: {}", e))?;
+    Ok(dir)
+}
+
+/***************************************************************************
+ * 把暂存目录中的全部文件移动到目标目录（保留子目录结构）
+ *
+ * 全部移动成功后删除暂存目录；失败时保留暂存目录，已移动的文件不回滚
+ *
+ * @param staging - 暂存目录
+ * @param destination - 目标目录
+ * @return Vec<(PathBuf, PathBuf)> - 每个文件的 (暂存路径, 最终路径)
+ ***************************************************************************/
+
+pub fn move_staged_files(staging: &Path, destination: &Path) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let mut files = Vec::new();
+    collect_files(staging, &mut files).map_err(|e| format!("读取暂存目录失败: {}", e))?;
+
+    let mut moved = Vec::with_capacity(files.len());
+    for source in files {
+        let relative = source.strip_prefix(staging).unwrap_or(&source);
+        let target = unique_destination(&destination.join(relative));
+        move_file(&source, &target)
+            .map_err(|e| format!("{} -> {} ({})", source.display(), target.display(), e))?;
+        debug!("已移动: {} -> {}", source.display(), target.display());
+        moved.push((source, target));
+    }
+
+    if let Err(e) = fs::remove_dir_all(staging) {
+        warn!("删除暂存目录失败: {} ({})", staging.display(), e);
+    }
+
+    Ok(moved)
+}
+
+/***************************************************************************
+ * 查找文件所在的下载任务暂存目录
+ *
+ * @param path - 暂存目录中的文件（可以在子目录中）
+ * @return Option<PathBuf> - 文件不在该任务的暂存目录中时为 None
+ ***************************************************************************/
+
+pub fn staging_root(path: &Path, download_id: &str) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| {
+            dir.file_name().is_some_and(|name| name == download_id)
+                && dir.parent().and_then(Path::file_name).is_some_and(|name| name == STAGING_DIR)
+        })
+        .map(Path::to_path_buf)
+}
+
+/// 暂存目录中已没有文件时删除它（连同空的子目录）
+pub fn remove_staging_if_empty(staging: &Path) {
+    let mut files = Vec::new();
+    if collect_files(staging, &mut files).is_err() || !files.is_empty() {
+        return;
+    }
+    match fs::remove_dir_all(staging) {
+        Ok(()) => debug!("已删除暂存目录: {}", staging.display()),
+        Err(e) => warn!("删除暂存目录失败: {} ({})", staging.display(), e),
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 目标已存在时在文件名后追加序号（"name (1).ext"），不覆盖已有文件
+fn unique_destination(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+
+    (1..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, ext)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/***************************************************************************
+ * 把暂存路径映射为移动后的最终路径
+ ***************************************************************************/
+
+pub fn resolve_moved_path(moved: &[(PathBuf, PathBuf)], staged: &str) -> String {
+    moved
+        .iter()
+        .find(|(source, _)| source == Path::new(staged))
+        .map(|(_, target)| target.to_string_lossy().into_owned())
+        .unwrap_or_else(|| staged.to_string())
+}
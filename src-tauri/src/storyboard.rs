@@ -0,0 +1,117 @@
+/****************************************************************************
+ *  storyboard.rs - 故事板（预览帧拼图）
+ *
+ *  @brief  识别 YouTube 的故事板格式（sb0、sb1 ...），并从下载得到的 .mhtml
+ *          文件中取出拼图图片
+ *  @note   yt-dlp 把故事板的所有分片保存为一个 MHTML 文件（multipart/related），
+ *          每个分片是一张 base64 编码的 JPEG 拼图
+ *****************************************************************************/
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// 故事板格式的扩展名
+pub const STORYBOARD_EXT: &str = "mhtml";
+
+/***************************************************************************
+ * 判断格式是否为故事板
+ *
+ * YouTube 的故事板格式ID为 sb + 数字，其他站点的故事板同样以 mhtml 保存
+ ***************************************************************************/
+
+pub fn is_storyboard_format(format_id: &str, ext: &str) -> bool {
+    let numbered = format_id
+        .strip_prefix("sb")
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    numbered || ext == STORYBOARD_EXT
+}
+
+/***************************************************************************
+ * 从 MHTML 文件中取出全部图片
+ *
+ * 图片保存在 MHTML 文件旁，命名为 "<文件名>_001.jpg"、"<文件名>_002.jpg" ...
+ *
+ * @param mhtml - yt-dlp 下载的故事板文件
+ * @return Vec<PathBuf> - 按分片顺序写出的图片
+ ***************************************************************************/
+
+pub fn extract_mhtml_images(mhtml: &Path) -> io::Result<Vec<PathBuf>> {
+    let content = fs::read_to_string(mhtml)?;
+    let boundary = find_boundary(&content)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "MHTML 文件缺少 boundary"))?;
+
+    let stem = mhtml
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "storyboard".to_string());
+    let delimiter = format!("--{}", boundary);
+
+    let mut images = Vec::new();
+    for part in content.split(delimiter.as_str()).skip(1) {
+        let Some((headers, body)) = split_part(part) else {
+            continue;
+        };
+        let Some(ext) = image_extension(headers) else {
+            continue;
+        };
+        if !header_value(headers, "Content-Transfer-Encoding")
+            .is_some_and(|v| v.eq_ignore_ascii_case("base64"))
+        {
+            debug!("跳过非 base64 编码的分片");
+            continue;
+        }
+
+        let encoded: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+        let bytes = match STANDARD.decode(encoded) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("故事板分片解码失败: {}", e);
+                continue;
+            }
+        };
+
+        let path = mhtml.with_file_name(format!("{}_{:03}.{}", stem, images.len() + 1, ext));
+        fs::write(&path, bytes)?;
+        images.push(path);
+    }
+
+    Ok(images)
+}
+
+/// 顶层 Content-Type 中的 boundary 参数
+fn find_boundary(content: &str) -> Option<String> {
+    let index = content.find("boundary=")?;
+    let rest = &content[index + "boundary=".len()..];
+    let boundary = match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next()?,
+        None => rest.split(|c: char| c == ';' || c.is_whitespace()).next()?,
+    };
+    (!boundary.is_empty()).then(|| boundary.to_string())
+}
+
+/// 把分片拆成头部和正文（以第一个空行分隔）
+fn split_part(part: &str) -> Option<(&str, &str)> {
+    part.split_once("\r\n\r\n").or_else(|| part.split_once("\n\n"))
+}
+
+fn header_value<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// 图片分片对应的扩展名（非图片分片返回 None）
+fn image_extension(headers: &str) -> Option<&'static str> {
+    let content_type = header_value(headers, "Content-Type")?.to_ascii_lowercase();
+    match content_type.split(';').next()?.trim() {
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
@@ -0,0 +1,312 @@
+/****************************************************************************
+ *  subscriptions.rs - 频道订阅
+ *
+ *  @brief  定期检查订阅频道的最新上传，发现新视频时通知前端，可选自动加入下载队列
+ *  @note   订阅列表与 history.rs 相同，使用 JSON 文件 + 托管状态；
+ *          后台任务逐个检查到期的订阅，两次检查之间间隔 CHECK_STAGGER，
+ *          同一时刻最多只有一个 yt-dlp 进程用于订阅检查
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info, warn};
+use ts_rs::TS;
+
+use crate::channel::ChannelVideo;
+use crate::commands::list_channel_videos;
+use crate::downloads::{unix_millis, DownloadManager};
+use crate::events;
+use crate::history::HistoryStore;
+use crate::impersonation::ImpersonationState;
+use crate::options::{duration_in_range, DownloadOptions};
+use crate::queue::DownloadQueue;
+use crate::settings::SettingsState;
+
+/// 订阅列表文件名
+pub const SUBSCRIPTIONS_FILE: &str = "subscriptions.json";
+
+/// 每次检查获取的最新视频数
+const FETCH_LIMIT: usize = 30;
+
+/// 每个订阅最多记住的视频ID数（超出时丢弃最早的）
+const MAX_SEEN_IDS: usize = 1000;
+
+/// 应用启动后首次检查前的等待时间
+const STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// 后台任务查看是否有到期订阅的间隔
+const CHECK_TICK: Duration = Duration::from_secs(5 * 60);
+
+/// 两个订阅检查之间的间隔（避免短时间内大量请求同一站点）
+const CHECK_STAGGER: Duration = Duration::from_secs(20);
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Subscription {
+    pub id: String,
+    pub channel_url: String,
+    pub options: DownloadOptions,   // 自动下载时使用的下载选项
+    pub auto_download: bool,        // 发现新视频时自动加入下载队列
+    #[ts(type = "number")]
+    pub created_at: u64,            // 添加时间（Unix 毫秒）
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub last_checked: Option<u64>,  // 上次检查时间（从未检查时为 None）
+    #[serde(default)]
+    pub last_error: Option<String>, // 上次检查失败的原因
+    #[serde(default)]
+    pub seen_ids: Vec<String>,      // 已见过的视频ID（首次检查时记录现有视频，不视为新视频）
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct NewVideosFound {
+    pub subscription_id: String,
+    pub channel_url: String,
+    pub videos: Vec<ChannelVideo>,
+    pub download_ids: Vec<String>,  // 自动加入队列的下载任务ID（未开启自动下载时为空）
+    pub filtered: usize,            // 时长不在订阅选项范围内、没有自动下载的视频数
+}
+
+/***************************************************************************
+ * 订阅列表托管状态
+ ***************************************************************************/
+
+pub struct SubscriptionStore {
+    path: Option<PathBuf>,
+    entries: Mutex<Vec<Subscription>>,
+}
+
+impl SubscriptionStore {
+    /***********************************************************************
+     * 从订阅文件加载，文件不存在或损坏时从空列表开始
+     ***********************************************************************/
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path
+            .as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match fs::read_to_string(p) {
+                Ok(content) => serde_json::from_str(&content)
+                    .map_err(|e| warn!("订阅列表解析失败，从空列表开始: {}", e))
+                    .ok(),
+                Err(e) => {
+                    warn!("读取订阅列表失败，从空列表开始: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// 获取全部订阅（按添加顺序）
+    pub fn list(&self) -> Vec<Subscription> {
+        self.entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+
+    /***********************************************************************
+     * 添加订阅并保存
+     *
+     * @return Subscription - 新建的订阅（同一频道已订阅时报错）
+     ***********************************************************************/
+    pub fn add(
+        &self,
+        channel_url: String,
+        options: DownloadOptions,
+        auto_download: bool,
+    ) -> Result<Subscription, String> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let mut entries = self.entries.lock().map_err(|_| "订阅列表不可用".to_string())?;
+        if entries.iter().any(|s| s.channel_url == channel_url) {
+            return Err(format!("已订阅该频道: {}", channel_url));
+        }
+
+        let subscription = Subscription {
+            id: format!("sub-{}-{}", unix_millis(), COUNTER.fetch_add(1, Ordering::Relaxed)),
+            channel_url,
+            options,
+            auto_download,
+            created_at: unix_millis(),
+            last_checked: None,
+            last_error: None,
+            seen_ids: Vec::new(),
+        };
+        entries.push(subscription.clone());
+
+        self.save(&entries)?;
+        Ok(subscription)
+    }
+
+    /***********************************************************************
+     * 删除订阅并保存
+     *
+     * @return bool - 订阅是否存在
+     ***********************************************************************/
+    pub fn remove(&self, id: &str) -> bool {
+        let Ok(mut entries) = self.entries.lock() else {
+            return false;
+        };
+        let before = entries.len();
+        entries.retain(|s| s.id != id);
+        if entries.len() == before {
+            return false;
+        }
+
+        if let Err(e) = self.save(&entries) {
+            warn!("保存订阅列表失败: {}", e);
+        }
+        true
+    }
+
+    /***********************************************************************
+     * 原位更新一条订阅并保存（检查期间订阅已被删除时忽略）
+     ***********************************************************************/
+    fn update(&self, subscription: Subscription) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        let Some(existing) = entries.iter_mut().find(|s| s.id == subscription.id) else {
+            return;
+        };
+        *existing = subscription;
+
+        if let Err(e) = self.save(&entries) {
+            warn!("保存订阅列表失败: {}", e);
+        }
+    }
+
+    fn save(&self, entries: &[Subscription]) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建数据目录: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("序列化订阅列表失败: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("写入订阅列表失败: {}", e))
+    }
+}
+
+/***************************************************************************
+ * 启动订阅检查任务
+ *
+ * 每 CHECK_TICK 查看一次，检查间隔（设置中的小时数）已到的订阅依次检查；
+ * 间隔设为 0 时不自动检查。单个订阅失败只记录在该订阅上，不影响其余订阅
+ ***************************************************************************/
+
+pub fn spawn_subscription_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_DELAY).await;
+        loop {
+            let hours = app.state::<SettingsState>().get().subscription_check_hours;
+            if hours > 0 {
+                let interval_ms = u64::from(hours) * 60 * 60 * 1000;
+                let now = unix_millis();
+                let due: Vec<Subscription> = app
+                    .state::<SubscriptionStore>()
+                    .list()
+                    .into_iter()
+                    .filter(|s| s.last_checked.is_none_or(|t| now.saturating_sub(t) >= interval_ms))
+                    .collect();
+
+                for (index, subscription) in due.into_iter().enumerate() {
+                    if index > 0 {
+                        tokio::time::sleep(CHECK_STAGGER).await;
+                    }
+                    check_subscription(&app, subscription).await;
+                }
+            }
+
+            tokio::time::sleep(CHECK_TICK).await;
+        }
+    });
+}
+
+/***************************************************************************
+ * 检查单个订阅
+ *
+ * 首次检查只记录现有视频；之后未见过、且不在下载历史中的视频视为新视频，
+ * 发送 new-videos-found 事件，开启自动下载时加入下载队列
+ ***************************************************************************/
+
+async fn check_subscription(app: &AppHandle, mut subscription: Subscription) {
+    debug!(subscription = %subscription.id, "检查订阅: {}", subscription.channel_url);
+    let first_check = subscription.last_checked.is_none();
+
+    let impersonation = app.state::<ImpersonationState>();
+    let result = list_channel_videos(&impersonation, &subscription.channel_url, FETCH_LIMIT, None).await;
+    subscription.last_checked = Some(unix_millis());
+
+    let videos = match result {
+        Ok(videos) => videos,
+        Err(e) => {
+            warn!(subscription = %subscription.id, "检查订阅失败: {}", e);
+            subscription.last_error = Some(e);
+            app.state::<SubscriptionStore>().update(subscription);
+            return;
+        }
+    };
+    subscription.last_error = None;
+
+    // 频道列表中已下载过的视频（手动下载或来自其他订阅）不重复提示
+    let history = app.state::<HistoryStore>().list();
+    let new_videos: Vec<ChannelVideo> = videos
+        .into_iter()
+        .filter(|video| !subscription.seen_ids.contains(&video.id))
+        .filter(|video| !history.iter().any(|e| e.url == video.url || e.url.contains(&video.id)))
+        .collect();
+
+    subscription
+        .seen_ids
+        .extend(new_videos.iter().map(|video| video.id.clone()));
+    let overflow = subscription.seen_ids.len().saturating_sub(MAX_SEEN_IDS);
+    subscription.seen_ids.drain(..overflow);
+
+    if first_check || new_videos.is_empty() {
+        app.state::<SubscriptionStore>().update(subscription);
+        return;
+    }
+
+    info!(subscription = %subscription.id, "发现 {} 个新视频: {}", new_videos.len(), subscription.channel_url);
+    let in_range = |video: &&ChannelVideo| duration_in_range(&subscription.options, video.duration);
+    let filtered = new_videos.iter().filter(|video| !in_range(video)).count();
+    let download_ids = if subscription.auto_download {
+        let queue = app.state::<DownloadQueue>();
+        let manager = app.state::<DownloadManager>();
+        new_videos
+            .iter()
+            .filter(in_range)
+            .map(|video| queue.enqueue(&manager, video.url.clone(), subscription.options.clone()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let found = NewVideosFound {
+        subscription_id: subscription.id.clone(),
+        channel_url: subscription.channel_url.clone(),
+        videos: new_videos,
+        download_ids,
+        filtered,
+    };
+    if let Err(e) = app.emit(events::NEW_VIDEOS_FOUND, &found) {
+        warn!(subscription = %subscription.id, "发送新视频事件失败: {}", e);
+    }
+
+    app.state::<SubscriptionStore>().update(subscription);
+}
@@ -0,0 +1,93 @@
+/****************************************************************************
+ *  thumbnails.rs - 缩略图
+ *
+ *  @brief  解析视频信息中的全部缩略图，并按需要的宽度挑选一张
+ *  @note   yt-dlp 的 "thumbnail" 字段是它按自身偏好选出的一张，可能分辨率较低；
+ *          "thumbnails" 列表按偏好从低到高排列，部分条目没有尺寸信息
+ *****************************************************************************/
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ts_rs::TS;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct Thumbnail {
+    pub url: String,
+    pub id: Option<String>,         // yt-dlp 给出的缩略图ID（如 "maxresdefault"）
+    #[ts(type = "number | null")]
+    pub width: Option<i64>,         // 宽度（像素，未知时为 None）
+    #[ts(type = "number | null")]
+    pub height: Option<i64>,        // 高度（像素，未知时为 None）
+}
+
+impl Thumbnail {
+    fn pixels(&self) -> i64 {
+        self.width.unwrap_or(0) * self.height.unwrap_or(0)
+    }
+}
+
+/***************************************************************************
+ * 解析缩略图列表
+ *
+ * 没有 "thumbnails" 时使用 "thumbnail" 字段（尺寸未知）
+ *
+ * @param json - yt-dlp 输出的视频信息
+ * @return Vec<Thumbnail> - 保持 yt-dlp 的顺序（偏好从低到高）
+ ***************************************************************************/
+
+pub fn parse_thumbnails(json: &Value) -> Vec<Thumbnail> {
+    let mut thumbnails: Vec<Thumbnail> = json["thumbnails"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .filter_map(|thumbnail| {
+                    let url = thumbnail["url"].as_str().filter(|u| !u.is_empty())?;
+                    Some(Thumbnail {
+                        url: url.to_string(),
+                        id: thumbnail["id"].as_str().map(|s| s.to_string()),
+                        width: thumbnail["width"].as_i64(),
+                        height: thumbnail["height"].as_i64(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if thumbnails.is_empty() {
+        if let Some(url) = json["thumbnail"].as_str().filter(|u| !u.is_empty()) {
+            thumbnails.push(Thumbnail {
+                url: url.to_string(),
+                id: None,
+                width: None,
+                height: None,
+            });
+        }
+    }
+    thumbnails
+}
+
+/***************************************************************************
+ * 挑选缩略图
+ *
+ * 宽度不小于 min_width 的缩略图中取最小的一张（够用即可，不取过大的原图）；
+ * 都不够宽时取分辨率最大的一张；都没有尺寸信息时取 yt-dlp 最偏好的一张
+ *
+ * @param thumbnails - parse_thumbnails 的结果
+ * @param min_width - 需要的最小宽度（像素）
+ * @return Option<&Thumbnail> - 没有缩略图时为 None
+ ***************************************************************************/
+
+pub fn best_thumbnail(thumbnails: &[Thumbnail], min_width: i64) -> Option<&Thumbnail> {
+    // 同样大小时取列表中靠后的（yt-dlp 更偏好的）
+    let wide_enough = thumbnails
+        .iter()
+        .rev()
+        .filter(|t| t.width.is_some_and(|width| width >= min_width))
+        .min_by_key(|t| t.pixels());
+    let largest = || thumbnails.iter().filter(|t| t.width.is_some()).max_by_key(|t| t.pixels());
+    wide_enough.or_else(largest).or_else(|| thumbnails.last())
+}
@@ -0,0 +1,219 @@
+/****************************************************************************
+ *  urls.rs - 视频链接规范化
+ *
+ *  @brief  校验用户输入的链接，并展开短链接/跳转链接得到规范地址
+ *  @note   常见的直连站点不做网络请求，其余链接通过 HEAD 请求跟随跳转
+ *****************************************************************************/
+
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// 展开跳转链接的超时时间
+const REDIRECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 最多跟随的跳转次数（超过视为跳转循环）
+const MAX_REDIRECTS: usize = 10;
+
+/// yt-dlp 可直接识别的站点，无需展开跳转
+const DIRECT_DOMAINS: &[&str] = &[
+    "youtube.com",
+    "youtu.be",
+    "youtube-nocookie.com",
+    "bilibili.com",
+    "twitter.com",
+    "x.com",
+    "vimeo.com",
+    "twitch.tv",
+];
+
+/***************************************************************************
+ * 规范化视频链接
+ *
+ * 1. 去除首尾空白并校验为 http/https 链接
+ * 2. 非直连站点的链接展开跳转，得到最终地址
+ *
+ * @param raw - 用户输入的链接
+ * @return Result<String, String> - 规范化后的链接
+ ***************************************************************************/
+
+pub async fn normalize_url(raw: &str) -> Result<String, String> {
+    let url = parse_http_url(raw)?;
+
+    if is_direct_domain(&url) {
+        return Ok(url.to_string());
+    }
+
+    Ok(resolve_redirects(&url).await.to_string())
+}
+
+/***************************************************************************
+ * 仅校验链接格式，不展开跳转（批量导入时使用，展开推迟到下载开始时）
+ ***************************************************************************/
+
+pub fn validate_url(raw: &str) -> Result<String, String> {
+    parse_http_url(raw).map(|url| url.to_string())
+}
+
+/***************************************************************************
+ * 生成用于判断重复的视频标识
+ *
+ * YouTube 的各种链接形式（youtu.be/ID、watch?v=ID、shorts/ID、embed/ID、live/ID）
+ * 统一为 "youtube:ID"；其他站点使用去掉片段和末尾斜杠、主机名小写的链接
+ *
+ * @param raw - 链接（无效链接返回 None）
+ ***************************************************************************/
+
+pub fn video_key(raw: &str) -> Option<String> {
+    let mut url = parse_http_url(raw).ok()?;
+    if let Some(id) = youtube_video_id(&url) {
+        return Some(format!("youtube:{}", id));
+    }
+
+    url.set_fragment(None);
+    let host = url.host_str()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let path = url.path().trim_end_matches('/');
+    Some(match url.query() {
+        Some(query) => format!("{}{}?{}", host, path, query),
+        None => format!("{}{}", host, path),
+    })
+}
+
+/***************************************************************************
+ * 判断链接是否会作为播放列表下载
+ *
+ * YouTube 的 /playlist 链接，以及带 list 参数的观看链接（yt-dlp 默认下载整个列表）
+ ***************************************************************************/
+
+pub fn is_playlist_url(raw: &str) -> bool {
+    let Ok(url) = parse_http_url(raw) else {
+        return false;
+    };
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let youtube = host == "youtu.be" || host == "youtube.com" || host.ends_with(".youtube.com");
+    youtube && (url.path().trim_end_matches('/') == "/playlist" || url.query_pairs().any(|(key, _)| key == "list"))
+}
+
+/***************************************************************************
+ * 判断链接是否为带 list 参数的单个视频链接（如 watch?v=ID&list=...）
+ *
+ * 这类链接同时指向一个视频和它所在的播放列表，yt-dlp 默认按播放列表处理；
+ * 应用默认只处理其中的视频（--no-playlist），用户明确要求时才按播放列表处理
+ ***************************************************************************/
+
+pub fn is_watch_with_list(raw: &str) -> bool {
+    let Ok(url) = parse_http_url(raw) else {
+        return false;
+    };
+    youtube_video_id(&url).is_some() && url.query_pairs().any(|(key, _)| key == "list")
+}
+
+/***************************************************************************
+ * 判断链接是否确定只指向单个视频（不需要先探测）
+ *
+ * 目前只识别 YouTube 的视频链接（不带 list 参数）；其他站点无法仅凭链接判断
+ ***************************************************************************/
+
+pub fn is_single_video_url(raw: &str) -> bool {
+    let Ok(url) = parse_http_url(raw) else {
+        return false;
+    };
+    youtube_video_id(&url).is_some() && !url.query_pairs().any(|(key, _)| key == "list")
+}
+
+/// 从 YouTube 链接中取出视频ID
+fn youtube_video_id(url: &Url) -> Option<String> {
+    let host = url.host_str()?.to_lowercase();
+    let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+
+    let id = if host == "youtu.be" {
+        segments.next()?.to_string()
+    } else if host == "youtube.com"
+        || host.ends_with(".youtube.com")
+        || host == "youtube-nocookie.com"
+        || host.ends_with(".youtube-nocookie.com")
+    {
+        match segments.next()? {
+            "watch" => url
+                .query_pairs()
+                .find(|(key, _)| key == "v")
+                .map(|(_, value)| value.into_owned())?,
+            "shorts" | "embed" | "live" | "v" => segments.next()?.to_string(),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+
+    (!id.is_empty()).then_some(id)
+}
+
+/***************************************************************************
+ * 解析并校验 http/https 链接
+ ***************************************************************************/
+
+fn parse_http_url(raw: &str) -> Result<Url, String> {
+    let trimmed = raw.trim();
+    let url = Url::parse(trimmed).map_err(|e| format!("无效的链接: {} ({})", trimmed, e))?;
+
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(format!("不支持的链接协议: {}", scheme)),
+    }
+}
+
+/// 判断链接是否属于直连站点（含子域名，如 m.youtube.com）
+fn is_direct_domain(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.to_lowercase();
+
+    DIRECT_DOMAINS
+        .iter()
+        .any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+}
+
+/***************************************************************************
+ * 跟随跳转获取最终地址
+ *
+ * 超时、跳转循环或网络错误时返回原链接，交给 yt-dlp 自行处理
+ ***************************************************************************/
+
+async fn resolve_redirects(url: &Url) -> Url {
+    let client = match reqwest::Client::builder()
+        .redirect(Policy::limited(MAX_REDIRECTS))
+        .timeout(REDIRECT_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("创建 HTTP 客户端失败，跳过链接展开: {}", e);
+            return url.clone();
+        }
+    };
+
+    match client.head(url.clone()).send().await {
+        Ok(response) => {
+            let resolved = response.url().clone();
+            if &resolved != url {
+                debug!("链接展开: {} -> {}", url, resolved);
+            }
+            resolved
+        }
+        Err(e) if e.is_redirect() => {
+            warn!("链接跳转次数过多（可能存在循环），使用原链接: {}", url);
+            url.clone()
+        }
+        Err(e) if e.is_timeout() => {
+            warn!("展开链接超时，使用原链接: {}", url);
+            url.clone()
+        }
+        Err(e) => {
+            warn!("展开链接失败，使用原链接: {} ({})", url, e);
+            url.clone()
+        }
+    }
+}
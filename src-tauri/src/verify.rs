@@ -0,0 +1,162 @@
+/****************************************************************************
+ *  verify.rs - 下载结果校验
+ *
+ *  @brief  下载进程正常退出后检查输出文件，发现截断、空文件或损坏的容器
+ *  @note   磁盘写满或 ffmpeg 静默失败时 yt-dlp 仍可能返回 0，
+ *          校验失败的下载在历史记录中标记为 suspect，而不是直接当作成功
+ *****************************************************************************/
+
+use serde::Serialize;
+use std::path::Path;
+use tracing::debug;
+use ts_rs::TS;
+
+use crate::ffmpeg::{find_ffprobe, probe_media, MediaProbe};
+
+/// 实际大小与预期大小的允许偏差（合并后的容器开销、估算大小的误差）
+const SIZE_TOLERANCE: f64 = 0.10;
+
+/// 时长比较的相对容差
+const DURATION_TOLERANCE: f64 = 0.02;
+
+/// 时长比较的最小绝对容差（秒），短视频按此值比较
+const MIN_DURATION_TOLERANCE: f64 = 2.0;
+
+/***************************************************************************
+ * 数据结构定义
+ ***************************************************************************/
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+pub struct Verification {
+    pub verified: bool,
+    #[ts(type = "number | null")]
+    pub actual_bytes: Option<u64>,  // 输出文件实际大小
+    #[ts(type = "number | null")]
+    pub expected_bytes: Option<u64>, // 根据进度累计的预期大小
+    pub issues: Vec<String>,        // 发现的问题（verified 为 false 时非空）
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+pub enum MediaVerdict {
+    Valid,                          // 容器可解析，时长与预期一致
+    Truncated,                      // 可解析，但时长明显短于预期
+    Corrupt,                        // ffprobe 无法解析或没有音视频流
+    Unknown,                        // 未找到 ffprobe，无法判断
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct MediaVerification {
+    pub verdict: MediaVerdict,
+    pub probe: Option<MediaProbe>,  // ffprobe 结果（无法解析时为 None）
+    pub expected_duration: Option<f64>,
+    pub message: Option<String>,    // 判定原因
+}
+
+/***************************************************************************
+ * 校验输出文件
+ *
+ * 1. 文件存在且非空
+ * 2. 大小与预期字节数的偏差在 SIZE_TOLERANCE 以内（预期未知时跳过）
+ * 3. 能找到 ffprobe 时，确认容器可解析、包含音视频流且时长有效
+ *
+ * @param output_path - 最终输出文件（无法确定时为 None）
+ * @param expected_bytes - 预期大小
+ ***************************************************************************/
+
+pub async fn verify_output(output_path: Option<&str>, expected_bytes: Option<u64>) -> Verification {
+    let mut verification = Verification {
+        expected_bytes,
+        ..Default::default()
+    };
+
+    let Some(path) = output_path.map(Path::new) else {
+        verification.issues.push("无法确定输出文件".to_string());
+        return verification;
+    };
+
+    let actual = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata.len(),
+        Err(e) => {
+            verification.issues.push(format!("输出文件不可访问: {}", e));
+            return verification;
+        }
+    };
+    verification.actual_bytes = Some(actual);
+
+    if actual == 0 {
+        verification.issues.push("输出文件为空".to_string());
+    } else if let Some(expected) = expected_bytes.filter(|&e| e > 0) {
+        let deviation = (actual as f64 - expected as f64).abs() / expected as f64;
+        if deviation > SIZE_TOLERANCE {
+            verification.issues.push(format!(
+                "文件大小与预期不符: 实际 {} 字节，预期 {} 字节",
+                actual, expected
+            ));
+        }
+    }
+
+    if actual > 0 {
+        match find_ffprobe() {
+            Some(ffprobe) => match probe_media(&ffprobe, path).await {
+                Ok(probe) => {
+                    if probe.video_streams + probe.audio_streams == 0 {
+                        verification.issues.push("文件中没有音视频流".to_string());
+                    }
+                    if !probe.duration.is_some_and(|d| d > 0.0) {
+                        verification.issues.push("无法读取有效的时长".to_string());
+                    }
+                }
+                Err(e) => verification.issues.push(e),
+            },
+            None => debug!("未找到 ffprobe，跳过容器校验"),
+        }
+    }
+
+    verification.verified = verification.issues.is_empty();
+    verification
+}
+
+/***************************************************************************
+ * 用 ffprobe 检查媒体文件是否完整
+ *
+ * 时长短于预期超过 max(2%, 2 秒) 时判定为截断；
+ * 未提供预期时长时只检查容器能否解析
+ *
+ * @param path - 媒体文件
+ * @param expected_duration - 预期时长（秒，如 VideoInfo.duration）
+ ***************************************************************************/
+
+pub async fn verify_media(path: &Path, expected_duration: Option<f64>) -> MediaVerification {
+    let expected_duration = expected_duration.filter(|d| *d > 0.0);
+    let verdict = |verdict, probe, message: Option<String>| MediaVerification {
+        verdict,
+        probe,
+        expected_duration,
+        message,
+    };
+
+    let Some(ffprobe) = find_ffprobe() else {
+        return verdict(MediaVerdict::Unknown, None, Some("未找到 ffprobe".to_string()));
+    };
+    let probe = match probe_media(&ffprobe, path).await {
+        Ok(probe) => probe,
+        Err(e) => return verdict(MediaVerdict::Corrupt, None, Some(e)),
+    };
+
+    if probe.video_streams + probe.audio_streams == 0 {
+        return verdict(MediaVerdict::Corrupt, Some(probe), Some("文件中没有音视频流".to_string()));
+    }
+    let Some(duration) = probe.duration.filter(|d| *d > 0.0) else {
+        return verdict(MediaVerdict::Corrupt, Some(probe), Some("无法读取有效的时长".to_string()));
+    };
+
+    if let Some(expected) = expected_duration {
+        let tolerance = (expected * DURATION_TOLERANCE).max(MIN_DURATION_TOLERANCE);
+        if expected - duration > tolerance {
+            let message = format!("时长 {:.1} 秒，短于预期的 {:.1} 秒", duration, expected);
+            return verdict(MediaVerdict::Truncated, Some(probe), Some(message));
+        }
+    }
+
+    verdict(MediaVerdict::Valid, Some(probe), None)
+}
@@ -0,0 +1,79 @@
+/****************************************************************************
+ *  warnings.rs - yt-dlp 警告
+ *
+ *  @brief  识别 yt-dlp 标准错误中的 "WARNING:" 行并归类
+ *  @note   下载和获取信息时每条警告发送一次 ytdlp-warning 事件；
+ *          下载的警告同时写入日志（带 download_id）并计入历史记录。
+ *          自定义参数覆盖生成参数的提示（ArgOverride）由应用自己发出，也使用该事件
+ *****************************************************************************/
+
+use serde::Serialize;
+use ts_rs::TS;
+
+/// 警告行前缀
+const WARNING_PREFIX: &str = "WARNING:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    FfmpegMissing,                  // 未找到 ffmpeg，无法合并或转换
+    FormatFallback,                 // 请求的格式不可用，已回退到其他格式
+    Cookies,                        // Cookie 读取或使用出现问题
+    ArgOverride,                    // 自定义参数覆盖了应用生成的同名参数
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+pub struct YtdlpWarning {
+    pub download_id: Option<String>, // 所属下载任务（获取信息时为 None）
+    pub kind: WarningKind,
+    pub message: String,            // 警告原文（不含 "WARNING:" 前缀）
+}
+
+/***************************************************************************
+ * 解析警告行
+ *
+ * @param line - yt-dlp 标准错误的一行
+ * @return Option<(WarningKind, String)> - 警告类型和原文，不是警告行时为 None
+ ***************************************************************************/
+
+pub fn parse_warning_line(line: &str) -> Option<(WarningKind, String)> {
+    let message = line.trim_start().strip_prefix(WARNING_PREFIX)?.trim();
+    Some((classify_warning(message), message.to_string()))
+}
+
+/***************************************************************************
+ * 解析重试提示
+ *
+ * 格式示例:
+ * [download] Got error: HTTP Error 429: Too Many Requests. Retrying (3/10)...
+ * [download] Got error: timed out. Retrying fragment 5 (2/10)...
+ *
+ * @param message - 警告原文（parse_warning_line 的返回值）
+ * @return Option<(u32, u32, String)> - (第几次重试, 最多重试次数, 原因)
+ ***************************************************************************/
+
+pub fn parse_retry_warning(message: &str) -> Option<(u32, u32, String)> {
+    let index = message.find("Retrying")?;
+    let counts = &message[index..];
+    let counts = &counts[counts.find('(')? + 1..counts.find(')')?];
+    let (attempt, retries) = counts.split_once('/')?;
+
+    let reason = message[..index].trim().trim_end_matches('.');
+    let reason = reason.strip_prefix("[download]").unwrap_or(reason).trim();
+    let reason = reason.strip_prefix("Got error:").unwrap_or(reason).trim();
+    Some((attempt.parse().ok()?, retries.parse().ok()?, reason.to_string()))
+}
+
+fn classify_warning(message: &str) -> WarningKind {
+    let lower = message.to_lowercase();
+    if lower.contains("ffmpeg") || lower.contains("ffprobe") {
+        WarningKind::FfmpegMissing
+    } else if lower.contains("cookie") {
+        WarningKind::Cookies
+    } else if lower.contains("requested format") || lower.contains("falling back") || lower.contains("formats may be missing") {
+        WarningKind::FormatFallback
+    } else {
+        WarningKind::Other
+    }
+}
@@ -0,0 +1,1209 @@
+/****************************************************************************
+ *  ytdlp.rs - yt-dlp 调用
+ *
+ *  @brief  查找 yt-dlp、获取版本、获取视频信息 JSON、执行下载并解析进度输出
+ *  @note   这里不依赖 AppHandle：下载过程中的进度、输出路径等通过回调交给
+ *          调用方，由命令层负责更新任务状态和发送事件。
+ *          MediaBackend 抽象了"获取信息/下载"两个操作，队列、重试等逻辑
+ *          可以换用不启动真实 yt-dlp 的实现
+ *****************************************************************************/
+
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::io::BufReader;
+use tokio::process::{ChildStderr, ChildStdout};
+use tracing::{debug, info, warn, Instrument, Span};
+
+use crate::json_lines::parse_json_lines;
+use crate::managed_child::ManagedChild;
+use crate::output_lines::{lossy_lines, LossyLines};
+use crate::playlist_items::PlaylistItems;
+use crate::process::{is_module_interpreter, module_probe_command, set_module_interpreter, ytdlp_command};
+use crate::progress::{
+    is_throttled_line, parse_comment_line, parse_eta, parse_speed, parse_total_size, postprocessor_stage, ByteTally, EwmaSmoother,
+    MergeJob, OutputTracker, ProgressInfo, ThroughputEstimator, PROGRESS_SCHEMA_VERSION,
+};
+use crate::warnings::{parse_retry_warning, parse_warning_line, WarningKind};
+
+/// 获取 yt-dlp 版本的超时时间
+const YTDLP_VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 未设置 PATHEXT 时 Windows 的默认值
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// 用户目录下的常见安装位置（相对于主目录）
+#[cfg(not(target_os = "windows"))]
+const USER_INSTALL_PATHS: &[&str] = &[
+    ".local/bin/yt-dlp",                        // pip --user、pipx 的命令目录
+    ".local/pipx/venvs/yt-dlp/bin/yt-dlp",      // pipx 1.3+ 的虚拟环境
+    ".pipx/venvs/yt-dlp/bin/yt-dlp",            // 旧版 pipx 的虚拟环境
+];
+
+/// 用户目录下的常见安装位置（相对于主目录）
+#[cfg(target_os = "windows")]
+const USER_INSTALL_PATHS: &[&str] = &[
+    "scoop\\shims\\yt-dlp.exe",                 // scoop
+    ".local\\bin\\yt-dlp.exe",                  // pipx 的命令目录
+    "pipx\\venvs\\yt-dlp\\Scripts\\yt-dlp.exe", // pipx 的虚拟环境
+];
+
+/// Tauri sidecar 文件名后缀中的目标三元组
+const TARGET_TRIPLE: &str = if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+    "aarch64-apple-darwin"
+} else if cfg!(target_os = "macos") {
+    "x86_64-apple-darwin"
+} else if cfg!(all(target_os = "windows", target_arch = "aarch64")) {
+    "aarch64-pc-windows-msvc"
+} else if cfg!(target_os = "windows") {
+    "x86_64-pc-windows-msvc"
+} else if cfg!(target_arch = "aarch64") {
+    "aarch64-unknown-linux-gnu"
+} else {
+    "x86_64-unknown-linux-gnu"
+};
+
+/// 搜索时同步检查候选 --version 是否结束的间隔
+const VERSION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 逐行获取条目时检查取消的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/***************************************************************************
+ * 公共函数 - 获取 yt-dlp 可执行文件路径
+ *
+ * 设置中固定了路径时优先使用；否则使用搜索到的第一个。
+ * 搜索要遍历 PATH 并检查多个候选文件，结果缓存后每次只检查文件是否还在，
+ * 文件消失或修改固定路径时重新搜索。搜索时逐个运行 --version，跳过损坏的
+ * 候选（如指向已卸载 Python 环境的 shim）。没有可运行的可执行文件、但 PATH 中
+ * 的 Python 能导入 yt_dlp 时返回该解释器，由 ytdlp_command 展开为
+ * "python -m yt_dlp"；两者都没有时仍返回第一个候选，由实际运行时的错误信息
+ * 给出修复建议。
+ * 检查候选要同步等待子进程（每个最多 YTDLP_VERSION_TIMEOUT），
+ * 在阻塞线程中进行，不占用异步运行时的工作线程
+ ***************************************************************************/
+
+pub async fn get_ytdlp_path() -> Result<PathBuf, String> {
+    tauri::async_runtime::spawn_blocking(find_ytdlp_path)
+        .await
+        .unwrap_or_else(|e| Err(format!("查找 yt-dlp 任务失败: {}", e)))
+}
+
+/// get_ytdlp_path 的实现（可能阻塞数秒）
+fn find_ytdlp_path() -> Result<PathBuf, String> {
+    let pinned = PINNED_YTDLP_PATH.read().ok().and_then(|p| p.clone());
+    if let Some(path) = pinned {
+        if path.is_file() {
+            return Ok(path);
+        }
+        warn!("固定的 yt-dlp 路径不存在，改为自动查找: {:?}", path);
+    }
+
+    let cached = RESOLVED_YTDLP_PATH.read().ok().and_then(|p| p.clone());
+    if let Some(path) = cached {
+        if path.exists() {
+            return Ok(path);
+        }
+        debug!("缓存的 yt-dlp 路径已不存在，重新查找: {:?}", path);
+    }
+
+    let candidates = ytdlp_candidates();
+    let mut module_interpreter = None;
+    let path = match candidates.iter().find(|path| ytdlp_runs(path)) {
+        Some(path) => path.clone(),
+        None => match find_module_interpreter() {
+            Some(python) => {
+                info!("未找到可运行的 yt-dlp 可执行文件，以 Python 模块方式运行: {:?} -m yt_dlp", python);
+                module_interpreter = Some(python.clone());
+                python
+            }
+            None => {
+                let path = candidates
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| "未找到 yt-dlp 可执行文件。请确保 yt-dlp 已安装并在 PATH 中。".to_string())?;
+                warn!("找到的 yt-dlp 都无法运行，使用第一个: {:?}", path);
+                path
+            }
+        },
+    };
+    debug!("找到 yt-dlp: {:?}", path);
+    set_module_interpreter(module_interpreter);
+    if let Ok(mut resolved) = RESOLVED_YTDLP_PATH.write() {
+        *resolved = Some(path.clone());
+    }
+    Ok(path)
+}
+
+/// 设置中固定的 yt-dlp 路径（启动时和修改设置时更新）
+static PINNED_YTDLP_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 自动查找到的 yt-dlp 路径（首次查找后缓存）
+static RESOLVED_YTDLP_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 应用的资源目录（启动时由 Tauri 的 resource_dir 登记）
+static RESOURCE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 是否优先使用应用内置的 yt-dlp（启动时和修改设置时更新）
+static PREFER_BUNDLED: AtomicBool = AtomicBool::new(true);
+
+/// 登记应用的资源目录
+pub fn set_resource_dir(dir: Option<PathBuf>) {
+    if let Ok(mut resource_dir) = RESOURCE_DIR.write() {
+        *resource_dir = dir;
+    }
+}
+
+/// 设置是否优先使用内置 yt-dlp，改变时清空查找缓存
+pub fn prefer_bundled_ytdlp(prefer: bool) {
+    if PREFER_BUNDLED.swap(prefer, Ordering::Relaxed) != prefer {
+        if let Ok(mut resolved) = RESOLVED_YTDLP_PATH.write() {
+            *resolved = None;
+        }
+        set_module_interpreter(None);
+    }
+}
+
+fn prefers_bundled() -> bool {
+    PREFER_BUNDLED.load(Ordering::Relaxed)
+}
+
+/// 固定 yt-dlp 路径，None 表示恢复自动查找；同时清空查找缓存（含模块方式）
+pub fn pin_ytdlp_path(path: Option<&str>) {
+    if let Ok(mut pinned) = PINNED_YTDLP_PATH.write() {
+        *pinned = path.filter(|p| !p.is_empty()).map(PathBuf::from);
+    }
+    if let Ok(mut resolved) = RESOLVED_YTDLP_PATH.write() {
+        *resolved = None;
+    }
+    set_module_interpreter(None);
+}
+
+/// PATH 中第一个能导入 yt_dlp 模块的 Python 解释器
+fn find_module_interpreter() -> Option<PathBuf> {
+    let names: &[&str] = if cfg!(target_os = "windows") {
+        &["python.exe", "python3.exe"]
+    } else {
+        &["python3", "python"]
+    };
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .filter(|python| python.is_file())
+        .find(|python| runs_successfully(&mut module_probe_command(python), python))
+}
+
+/// 同步运行 --version 检查候选能否运行（只在重新搜索时调用）
+fn ytdlp_runs(path: &Path) -> bool {
+    let mut command = ytdlp_command(path);
+    command.arg("--version");
+    runs_successfully(command.as_std_mut(), path)
+}
+
+/// 同步运行命令并等待结束（超时视为失败），用于搜索时检查候选
+fn runs_successfully(command: &mut std::process::Command, path: &Path) -> bool {
+    let spawned = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let Ok(mut child) = spawned else {
+        debug!("yt-dlp 候选无法启动: {:?}", path);
+        return false;
+    };
+
+    let deadline = Instant::now() + YTDLP_VERSION_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                if !status.success() {
+                    debug!("yt-dlp 候选运行失败 ({}): {:?}", status, path);
+                }
+                return status.success();
+            }
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(VERSION_POLL_INTERVAL),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                debug!("yt-dlp 候选检查超时: {:?}", path);
+                return false;
+            }
+        }
+    }
+}
+
+/// get_ytdlp_path 返回的路径来自哪里（诊断时显示）
+pub fn ytdlp_path_source(path: &Path) -> &'static str {
+    let pinned = PINNED_YTDLP_PATH.read().ok().and_then(|p| p.clone());
+    if pinned.as_deref() == Some(path) {
+        "设置中固定的路径"
+    } else if is_module_interpreter(path) {
+        "自动查找，以 Python 模块运行（-m yt_dlp）"
+    } else if is_bundled(path) {
+        "应用内置"
+    } else {
+        "系统安装（自动查找，已缓存）"
+    }
+}
+
+/***************************************************************************
+ * 按查找顺序列出所有找到的 yt-dlp（已去重）
+ *
+ * 顺序: PATH → 常见安装路径 → 应用内置（sidecar）；设置为优先使用内置版本
+ * （默认）时内置版本排在最前。
+ * 常见安装路径含用户目录下的 pip --user / pipx、scoop、winget 和 MacPorts；
+ * Windows 上按 PATHEXT 同时查找 yt-dlp.cmd / yt-dlp.bat 等 shim
+ ***************************************************************************/
+
+pub fn ytdlp_candidates() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+    let mut add = |path: PathBuf| {
+        let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if !candidates
+            .iter()
+            .any(|c| c.canonicalize().unwrap_or_else(|_| c.clone()) == key)
+        {
+            candidates.push(path);
+        }
+    };
+
+    let ytdlp_names = if cfg!(target_os = "windows") {
+        vec!["yt-dlp.exe", "yt-dlp_x86.exe", "yt-dlp.exe_x86.exe"]
+    } else {
+        vec!["yt-dlp", "yt-dlp_linux", "yt-dlp_macos"]
+    };
+
+    let bundled = bundled_ytdlp_paths();
+    if prefers_bundled() {
+        bundled.iter().cloned().for_each(&mut add);
+    }
+
+    // 1. 尝试从 PATH 环境变量查找（Windows 上另按 PATHEXT 查找 shim）
+    let mut path_names: Vec<String> = ytdlp_names.iter().map(|name| name.to_string()).collect();
+    if cfg!(target_os = "windows") {
+        let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| DEFAULT_PATHEXT.to_string());
+        for ext in pathext.split(';').map(str::trim).filter(|ext| !ext.is_empty()) {
+            let name = format!("yt-dlp{}", ext.to_lowercase());
+            if !path_names.contains(&name) {
+                path_names.push(name);
+            }
+        }
+    }
+    if let Ok(path_var) = std::env::var("PATH") {
+        for dir in std::env::split_paths(&path_var) {
+            for name in &path_names {
+                let path = dir.join(name);
+                if path.exists() && path.is_file() {
+                    add(path);
+                }
+            }
+        }
+    }
+
+    // 2. 尝试 common 安装路径
+    #[cfg(target_os = "macos")]
+    {
+        let homebrew_paths = vec![
+            "/opt/homebrew/bin/yt-dlp",
+            "/usr/local/bin/yt-dlp",
+            "/opt/local/bin/yt-dlp",
+        ];
+        for path in homebrew_paths {
+            let path = PathBuf::from(path);
+            if path.exists() {
+                add(path);
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let linux_paths = vec![
+            "/usr/bin/yt-dlp",
+            "/usr/local/bin/yt-dlp",
+            "/snap/bin/yt-dlp",
+        ];
+        for path in linux_paths {
+            let path = PathBuf::from(path);
+            if path.exists() {
+                add(path);
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let windows_paths = vec![
+            "C:\\ProgramData\\chocolatey\\bin\\yt-dlp.exe",
+            "C:\\Program Files\\yt-dlp\\yt-dlp.exe",
+            "C:\\Program Files (x86)\\yt-dlp\\yt-dlp.exe",
+        ];
+        for path in windows_paths {
+            let path = PathBuf::from(path);
+            if path.exists() {
+                add(path);
+            }
+        }
+        // winget 的命令链接
+        if let Some(local_app_data) = std::env::var_os("LOCALAPPDATA") {
+            let path = PathBuf::from(local_app_data).join("Microsoft\\WinGet\\Links\\yt-dlp.exe");
+            if path.exists() {
+                add(path);
+            }
+        }
+    }
+
+    // 用户目录下的安装（pip --user、pipx、scoop）
+    if let Some(home) = home_dir() {
+        for relative in USER_INSTALL_PATHS {
+            let path = home.join(relative);
+            if path.is_file() {
+                add(path);
+            }
+        }
+    }
+
+    // 3. 应用内置（已排在最前时去重后不变）
+    bundled.into_iter().for_each(&mut add);
+
+    candidates
+}
+
+/***************************************************************************
+ * 应用内置的 yt-dlp
+ *
+ * Tauri 打包时把 externalBin 中的 sidecar 去掉目标三元组后缀、放在主程序
+ * 同目录；开发模式和手动放置时可能保留后缀。另外查找资源目录（macOS 上为
+ * .app 包内的 Contents/Resources，由 set_resource_dir 登记）。
+ * Unix 上缺少可执行权限时补上（解压或复制时可能丢失）
+ ***************************************************************************/
+
+fn bundled_ytdlp_paths() -> Vec<PathBuf> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf));
+    let resource_dir = RESOURCE_DIR.read().ok().and_then(|dir| dir.clone());
+    bundled_ytdlp_paths_in(exe_dir.into_iter().chain(resource_dir))
+}
+
+/// 在给定目录中依次查找不带后缀和带目标三元组后缀的 yt-dlp
+fn bundled_ytdlp_paths_in(dirs: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let ext = std::env::consts::EXE_SUFFIX;
+    let names = [format!("yt-dlp{}", ext), format!("yt-dlp-{}{}", TARGET_TRIPLE, ext)];
+
+    let mut paths = Vec::new();
+    for dir in dirs {
+        for name in &names {
+            let path = dir.join(name);
+            if path.is_file() && !paths.contains(&path) {
+                ensure_executable(&path);
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+/// 补上可执行权限（失败时只记录，由 --version 检查决定能否使用）
+#[cfg(unix)]
+fn ensure_executable(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return;
+    };
+    let mut permissions = metadata.permissions();
+    if permissions.mode() & 0o111 != 0 {
+        return;
+    }
+    permissions.set_mode(permissions.mode() | 0o755);
+    if let Err(e) = std::fs::set_permissions(path, permissions) {
+        warn!("无法为内置 yt-dlp 添加可执行权限: {:?} ({})", path, e);
+    }
+}
+
+#[cfg(not(unix))]
+fn ensure_executable(_path: &Path) {}
+
+/// path 是否为应用内置的 yt-dlp
+fn is_bundled(path: &Path) -> bool {
+    bundled_ytdlp_paths().iter().any(|bundled| bundled == path)
+}
+
+/// 用户主目录（Unix 为 HOME，Windows 为 USERPROFILE）
+fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(target_os = "windows") { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var).filter(|home| !home.is_empty()).map(PathBuf::from)
+}
+
+/// 运行 yt-dlp --version（超时或失败时返回 None）
+pub async fn ytdlp_version(path: &Path) -> Option<String> {
+    let output = ytdlp_command(path).arg("--version").kill_on_drop(true).output();
+    let output = tokio::time::timeout(YTDLP_VERSION_TIMEOUT, output).await.ok()?.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+/***************************************************************************
+ * 下载过程中交给调用方的事件
+ ***************************************************************************/
+
+pub enum DownloadEvent {
+    Started,                        // 进程已启动
+    Destination(String),            // 新的输出文件（yt-dlp 的 Destination 行）
+    Progress {
+        progress: ProgressInfo,     // 进度帧，已填写 computed_eta_seconds 等估算字段
+        bytes_per_sec: Option<f64>,
+        at: Instant,                // 收到该行的时间
+    },
+    Throttled,                      // yt-dlp 报告下载被限速
+    FetchingComments {              // 获取评论的输出行（开启 write_comments 时，在下载开始前）
+        fetched: Option<u32>,
+        estimated_total: Option<u32>,
+    },
+    PostProcessing {                // 后处理步骤的输出行（合并、转码等）
+        stage: String,              // 步骤名（如 "Merger"）
+        merge: Option<MergeJob>,    // 开始合并时附带，用于估算合并进度
+    },
+    Warning {                       // 标准错误中的 "WARNING:" 行
+        kind: WarningKind,
+        message: String,
+    },
+    Retry {                         // yt-dlp 即将重试（随后按 --retry-sleep 等待）
+        attempt: u32,               // 第几次重试（从 1 开始）
+        retries: u32,               // 最多重试次数
+        reason: String,
+    },
+}
+
+/// 进程结束后的结果（退出码非零也在这里返回，由调用方决定如何处理）
+pub struct DownloadOutcome {
+    pub status: ExitStatus,
+    pub total_bytes: Option<u64>,   // 累计下载的字节数（取自最后的进度帧）
+    pub outputs: OutputTracker,     // 输出中出现的文件
+    pub warnings: u32,              // yt-dlp 输出的警告数
+    pub items: PlaylistItems,       // 播放列表各条目的开始和出错（单个视频时为空）
+    pub errors: Vec<String>,        // 标准错误中的出错行（不含警告，用于区分出错的阶段）
+}
+
+#[derive(Debug)]
+pub enum YtdlpError {
+    Spawn(String),                  // 无法启动进程或捕获输出
+    StartupTimeout(Duration),       // 启动超时内没有出现 [download] 行
+    Wait(String),                   // 等待进程结束失败
+    Failed { stderr: String },      // 进程返回非零退出码
+    Parse(String),                  // 输出无法解析
+    Cancelled,                      // 调用方取消，进程已结束
+}
+
+impl fmt::Display for YtdlpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YtdlpError::Spawn(e) | YtdlpError::Wait(e) | YtdlpError::Parse(e) => write!(f, "{}", e),
+            YtdlpError::StartupTimeout(limit) => {
+                write!(f, "下载启动失败：{} 秒内没有开始下载", limit.as_secs())
+            }
+            YtdlpError::Failed { stderr } => write!(f, "yt-dlp 执行失败: {}", stderr.trim()),
+            YtdlpError::Cancelled => write!(f, "已取消"),
+        }
+    }
+}
+
+/***************************************************************************
+ * 媒体后端：获取视频信息和执行下载
+ ***************************************************************************/
+
+pub trait MediaBackend {
+    /// 后端版本（无法运行时为 None）
+    fn version(&self) -> impl Future<Output = Option<String>> + Send;
+
+    /***********************************************************************
+     * 获取视频信息 JSON（播放列表时为第一个视频条目）
+     *
+     * @param args - 放在 URL 之前的参数（--dump-json 等由调用方决定）
+     ***********************************************************************/
+    fn fetch_info(&self, args: &[&str], url: &str) -> impl Future<Output = Result<Value, YtdlpError>> + Send;
+
+    /***********************************************************************
+     * 逐行获取 JSON 条目（如 --flat-playlist --dump-json 的播放列表条目）
+     *
+     * @param args - 放在 URL 之前的参数
+     * @param cancelled - 置位后结束进程并返回 Cancelled
+     * @param on_entry - 每解析出一个条目调用一次
+     * @return 全部条目（不能解析为 JSON 的行忽略）
+     ***********************************************************************/
+    fn fetch_entries<F>(
+        &self,
+        args: &[String],
+        url: &str,
+        cancelled: Arc<AtomicBool>,
+        on_entry: F,
+    ) -> impl Future<Output = Result<Vec<Value>, YtdlpError>> + Send
+    where
+        F: FnMut(&Value) + Send;
+
+    /***********************************************************************
+     * 执行下载，直到进程结束
+     *
+     * @param args - 完整的命令行参数（含 URL）
+     * @param startup_timeout - 超过该时间仍没有下载或等待输出则结束进程（见 is_startup_activity）
+     * @param on_event - 进度等事件回调（在读取输出的任务中调用）
+     ***********************************************************************/
+    fn download<F>(
+        &self,
+        args: &[String],
+        startup_timeout: Option<Duration>,
+        on_event: F,
+    ) -> impl Future<Output = Result<DownloadOutcome, YtdlpError>> + Send
+    where
+        F: FnMut(DownloadEvent) + Send + 'static;
+}
+
+/***************************************************************************
+ * 以 yt-dlp 子进程实现的媒体后端
+ ***************************************************************************/
+
+pub struct YtDlp {
+    path: PathBuf,
+}
+
+/// 逐行读取输出的子进程
+struct StreamingChild {
+    child: ManagedChild,
+    stdout: LossyLines<BufReader<ChildStdout>>,
+    stderr: LossyLines<BufReader<ChildStderr>>,
+}
+
+impl YtDlp {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 启动 yt-dlp 并按行读取输出（结束时连同 ffmpeg 等后代进程一起结束）
+    fn spawn_streaming<'a>(&self, args: impl IntoIterator<Item = &'a str>) -> Result<StreamingChild, YtdlpError> {
+        let mut child = ManagedChild::spawn(
+            ytdlp_command(&self.path)
+                .args(args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )
+        .map_err(|e| YtdlpError::Spawn(format!("无法启动 yt-dlp: {}", e)))?;
+
+        let stdout = child.stdout.take().ok_or_else(|| YtdlpError::Spawn("无法捕获标准输出".to_string()))?;
+        let stderr = child.stderr.take().ok_or_else(|| YtdlpError::Spawn("无法捕获标准错误".to_string()))?;
+        Ok(StreamingChild {
+            child,
+            stdout: lossy_lines(BufReader::new(stdout)),
+            stderr: lossy_lines(BufReader::new(stderr)),
+        })
+    }
+}
+
+impl MediaBackend for YtDlp {
+    async fn version(&self) -> Option<String> {
+        ytdlp_version(&self.path).await
+    }
+
+    async fn fetch_info(&self, args: &[&str], url: &str) -> Result<Value, YtdlpError> {
+        let output = ytdlp_command(&self.path)
+            .args(args)
+            .arg(url)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| YtdlpError::Spawn(format!("无法执行 yt-dlp: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            return Err(YtdlpError::Failed { stderr });
+        }
+
+        first_video_entry(&String::from_utf8_lossy(&output.stdout)).map_err(YtdlpError::Parse)
+    }
+
+    async fn fetch_entries<F>(
+        &self,
+        args: &[String],
+        url: &str,
+        cancelled: Arc<AtomicBool>,
+        mut on_entry: F,
+    ) -> Result<Vec<Value>, YtdlpError>
+    where
+        F: FnMut(&Value) + Send,
+    {
+        let StreamingChild {
+            mut child,
+            mut stdout,
+            mut stderr,
+        } = self.spawn_streaming(args.iter().map(String::as_str).chain([url]))?;
+
+        // stderr 单独读取，避免管道写满阻塞 yt-dlp
+        let stderr_task = tokio::spawn(async move {
+            let mut output = String::new();
+            while let Ok(Some(line)) = stderr.next_line().await {
+                output.push_str(&line);
+                output.push('\n');
+            }
+            output
+        });
+
+        // 读取一行最多等待 CANCEL_POLL_INTERVAL，期间检查是否已取消
+        // （next_line 在超时被丢弃时不会丢失已读取的数据）
+        let mut entries = Vec::new();
+        loop {
+            if cancelled.load(Ordering::Relaxed) {
+                let _ = child.kill().await;
+                return Err(YtdlpError::Cancelled);
+            }
+            let line = match tokio::time::timeout(CANCEL_POLL_INTERVAL, stdout.next_line()).await {
+                Ok(Ok(Some(line))) => line,
+                Ok(_) => break,
+                Err(_) => continue,
+            };
+            let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+                debug!("忽略非 JSON 行: {}", line);
+                continue;
+            };
+            on_entry(&entry);
+            entries.push(entry);
+        }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| YtdlpError::Wait(format!("等待 yt-dlp 失败: {}", e)))?;
+        if !status.success() && entries.is_empty() {
+            let stderr = stderr_task.await.unwrap_or_default();
+            return Err(YtdlpError::Failed { stderr });
+        }
+        Ok(entries)
+    }
+
+    async fn download<F>(
+        &self,
+        args: &[String],
+        startup_timeout: Option<Duration>,
+        mut on_event: F,
+    ) -> Result<DownloadOutcome, YtdlpError>
+    where
+        F: FnMut(DownloadEvent) + Send + 'static,
+    {
+        let StreamingChild {
+            mut child,
+            stdout: mut lines,
+            stderr: mut stderr_lines,
+        } = self.spawn_streaming(args.iter().map(String::as_str))?;
+        on_event(DownloadEvent::Started);
+
+        // 标准输出和标准错误的读取任务都会产生事件
+        let on_event = Arc::new(Mutex::new(on_event));
+        let stderr_events = on_event.clone();
+
+        // 出现第一行下载或等待输出后置位，此后不再受启动超时限制
+        let started = Arc::new(AtomicBool::new(false));
+        let started_flag = started.clone();
+
+        // 条目开始来自标准输出，出错来自标准错误
+        let items = Arc::new(Mutex::new(PlaylistItems::default()));
+        let stdout_items = items.clone();
+        let stderr_items = items.clone();
+
+        // 读取任务沿用调用方的 span（日志中带 download_id）
+        let span = Span::current();
+
+        // 异步读取标准输出（yt-dlp 进度信息），结束时返回累计下载的字节数和输出文件
+        let stdout_task = tokio::spawn(
+            async move {
+                let on_event = |event: DownloadEvent| {
+                    if let Ok(mut on_event) = on_event.lock() {
+                        on_event(event);
+                    }
+                };
+                let mut line_count = 0;
+                let mut estimator = ThroughputEstimator::new();
+                let mut smoother = EwmaSmoother::new();
+                let mut tally = ByteTally::new();
+                let mut outputs = OutputTracker::new();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    line_count += 1;
+                    debug!("[yt-dlp-{}] {}", line_count, line);
+                    if is_startup_activity(&line) {
+                        started_flag.store(true, Ordering::Relaxed);
+                    }
+                    if let Ok(mut items) = stdout_items.lock() {
+                        items.record_output(&line);
+                    }
+                    let known_destinations = outputs.destinations().len();
+                    outputs.record(&line);
+                    if let Some(path) = outputs.destinations().get(known_destinations) {
+                        on_event(DownloadEvent::Destination(path.clone()));
+                    }
+
+                    // 解析进度信息
+                    if let Some(mut progress) = parse_progress_line(&line) {
+                        // 基于吞吐量重新估算 ETA，与 yt-dlp 原始 ETA 一并发送
+                        let now = Instant::now();
+                        if let (Some(downloaded), Some(total)) = (progress.downloaded_bytes, progress.total_bytes) {
+                            tally.record(downloaded);
+                            progress.computed_eta_seconds = estimator.record(now, downloaded, total);
+                            progress.eta_seconds_smoothed = smoother.record(now, downloaded, Some(total));
+                        }
+                        progress.eta_seconds_raw = parse_eta(&progress.eta);
+
+                        let bytes_per_sec = parse_speed(&progress.speed);
+                        on_event(DownloadEvent::Progress {
+                            progress,
+                            bytes_per_sec,
+                            at: now,
+                        });
+                    } else if is_throttled_line(&line) {
+                        on_event(DownloadEvent::Throttled);
+                    } else if let Some(stage) = postprocessor_stage(&line) {
+                        // 合并开始时记下输入流，用于估算合并进度
+                        let merge = line
+                            .contains("Merging formats into")
+                            .then(|| outputs.final_path())
+                            .flatten()
+                            .map(|target| MergeJob::new(&target, &outputs.stream_files()));
+                        on_event(DownloadEvent::PostProcessing {
+                            stage: stage.to_string(),
+                            merge,
+                        });
+                    } else if let Some((fetched, estimated_total)) = parse_comment_line(&line) {
+                        on_event(DownloadEvent::FetchingComments {
+                            fetched,
+                            estimated_total,
+                        });
+                    } else if line.contains("[download]") || line.contains('%') {
+                        // 这行包含进度相关信息但解析失败
+                        debug!("进度行解析失败: {}", line);
+                    }
+                }
+                debug!("标准输出读取结束，共处理 {} 行", line_count);
+                (tally.total(), outputs)
+            }
+            .instrument(span.clone()),
+        );
+
+        // 异步读取标准错误，警告行交给调用方，结束时返回警告数和出错行
+        let stderr_task = tokio::spawn(
+            async move {
+                let mut warnings = 0;
+                let mut errors = Vec::new();
+                while let Ok(Some(line)) = stderr_lines.next_line().await {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let Some((kind, message)) = parse_warning_line(&line) else {
+                        warn!("[yt-dlp-err] {}", line);
+                        if let Ok(mut items) = stderr_items.lock() {
+                            items.record_error(&line);
+                        }
+                        errors.push(line);
+                        continue;
+                    };
+                    warn!("[yt-dlp-warning] {:?}: {}", kind, message);
+                    warnings += 1;
+                    let retry = parse_retry_warning(&message);
+                    if let Ok(mut on_event) = stderr_events.lock() {
+                        on_event(DownloadEvent::Warning { kind, message });
+                        if let Some((attempt, retries, reason)) = retry {
+                            on_event(DownloadEvent::Retry {
+                                attempt,
+                                retries,
+                                reason,
+                            });
+                        }
+                    }
+                }
+                (warnings, errors)
+            }
+            .instrument(span),
+        );
+
+        // 等待进程结束（启动阶段受 startup_timeout 限制）
+        let waited = match startup_timeout {
+            Some(limit) => match tokio::time::timeout(limit, child.wait()).await {
+                Ok(waited) => waited,
+                Err(_) if !started.load(Ordering::Relaxed) => {
+                    let _ = child.kill().await;
+                    return Err(YtdlpError::StartupTimeout(limit));
+                }
+                Err(_) => child.wait().await,
+            },
+            None => child.wait().await,
+        };
+        let status = waited.map_err(|e| YtdlpError::Wait(format!("等待下载进程失败: {}", e)))?;
+
+        // 最终字节数取自最后的进度帧（yt-dlp 不一定输出汇总行）
+        let (total_bytes, outputs) = stdout_task.await.unwrap_or_default();
+        let (warnings, errors) = stderr_task.await.unwrap_or_default();
+        let items = items.lock().map(|mut items| std::mem::take(&mut *items)).unwrap_or_default();
+        Ok(DownloadOutcome {
+            status,
+            total_bytes,
+            outputs,
+            warnings,
+            items,
+            errors,
+        })
+    }
+}
+
+/***************************************************************************
+ * 判断一行输出是否说明下载已经开始（此后不再受启动超时限制）
+ *
+ * 除 [download] 行外，--wait-for-video 等待首映或直播开始时的 [wait] 行、
+ * 获取评论的进度行（FetchingComments，评论多时持续数分钟）也算：
+ * 这些阶段可能远超启动超时，但 yt-dlp 并没有卡住
+ ***************************************************************************/
+
+fn is_startup_activity(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("[download]") || line.starts_with("[wait]") || parse_comment_line(line).is_some()
+}
+
+/***************************************************************************
+ * 解析 yt-dlp 进度输出
+ *
+ * 格式示例:
+ * [download]  42.0% of 125.89MiB at  5.82MiB/s ETA 00:12
+ *
+ * @param line - yt-dlp 输出的一行文本
+ * @return Option<ProgressInfo> - 解析后的进度信息（如果行包含进度，
+ *         download_id 和估算字段由调用方填写）
+ ***************************************************************************/
+
+pub fn parse_progress_line(line: &str) -> Option<ProgressInfo> {
+    // 增强匹配条件，支持更多格式
+    if !line.contains("[download]") && !line.contains("%") {
+        return None;
+    }
+
+    debug!("解析进度行: {}", line);
+
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    // 查找百分比（包含%的字段）
+    let mut percent: Option<f64> = None;
+    for part in &parts {
+        if part.contains('%') {
+            if let Some(p) = part.trim_end_matches('%').parse::<f64>().ok() {
+                percent = Some(p);
+                break;
+            }
+        }
+    }
+
+    let percent = percent?;
+
+    // 查找速度 - 支持多种格式
+    let mut speed = "".to_string();
+    for (i, part) in parts.iter().enumerate() {
+        if *part == "at" && i + 1 < parts.len() {
+            speed = parts[i + 1].to_string();
+            // 检查下一个词是否包含/s，如果是则加上
+            if i + 2 < parts.len() {
+                let next_part = parts[i + 2];
+                if next_part.contains("/s") {
+                    speed.push_str(" ");
+                    speed.push_str(next_part);
+                }
+            }
+            break;
+        }
+        // 也支持直接包含速度单位的词
+        if part.contains("MiB/s") || part.contains("KiB/s") || part.contains("MB/s") || part.contains("KB/s") {
+            speed = part.to_string();
+            break;
+        }
+    }
+
+    // 查找 ETA - 支持多种格式
+    let mut eta = "".to_string();
+    for (i, part) in parts.iter().enumerate() {
+        if *part == "ETA" && i + 1 < parts.len() {
+            eta = parts[i + 1].to_string();
+            break;
+        }
+        // 也支持直接包含时间格式的词
+        if part.chars().filter(|c| *c == ':').count() == 2 {
+            eta = part.to_string();
+            break;
+        }
+    }
+
+    // 总大小（"~" 前缀表示估算值），已下载字节按百分比推算
+    let total = parse_total_size(line);
+    let total_bytes = total.map(|(bytes, _)| bytes);
+    let downloaded_bytes = total_bytes.map(|bytes| (bytes as f64 * percent / 100.0).round() as u64);
+
+    let progress = ProgressInfo {
+        schema_version: PROGRESS_SCHEMA_VERSION,
+        percent,
+        speed,
+        eta,
+        downloaded_bytes,
+        total_bytes,
+        total_bytes_estimated: total.is_some_and(|(_, estimated)| estimated),
+        ..ProgressInfo::default()
+    };
+
+    debug!("解析的进度: {:?}", progress);
+    Some(progress)
+}
+
+/***************************************************************************
+ * 从 --dump-json 的输出中取第一个视频条目
+ ***************************************************************************/
+
+pub fn first_video_entry(stdout: &str) -> Result<Value, String> {
+    if stdout.trim().is_empty() {
+        return Err("无法获取视频信息: 无响应数据".to_string());
+    }
+
+    // 播放列表时跳过列表信息，取第一个视频条目
+    let parsed = parse_json_lines(stdout);
+    if parsed.failed_lines > 0 {
+        warn!("yt-dlp 输出中有 {} 行无法解析为 JSON", parsed.failed_lines);
+    }
+    match parsed.first_entry() {
+        Some(entry) => Ok(entry.clone()),
+        None if parsed.values.is_empty() => Err(format!(
+            "无法解析视频信息: {} 行输出均不是有效的 JSON",
+            parsed.failed_lines
+        )),
+        None => Err(format!(
+            "无法解析视频信息: 只返回了播放列表信息，没有视频条目（另有 {} 行无法解析）",
+            parsed.failed_lines
+        )),
+    }
+}
+
+/***************************************************************************
+ * 测试用媒体后端：按预设的输出行产生事件，不启动进程
+ ***************************************************************************/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// 测试用的空目录（以测试名区分，重复运行时先清空）
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("youtudown-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn download_and_wait_lines_count_as_started() {
+        assert!(is_startup_activity("[download]  42.0% of 125.89MiB at  5.82MiB/s ETA 00:12"));
+        assert!(is_startup_activity("[download] Destination: video.mp4"));
+        assert!(is_startup_activity("[wait] Waiting for 00:10:00 - Press Ctrl+C to try now"));
+        assert!(is_startup_activity("[wait] Remaining time until next attempt: 00:09:59"));
+        assert!(!is_startup_activity("[youtube] dQw4w9WgXcQ: Downloading webpage"));
+    }
+
+    #[test]
+    fn comment_fetching_counts_as_started() {
+        assert!(is_startup_activity("[youtube] Downloading comment section API JSON"));
+        assert!(is_startup_activity("[youtube] Downloading ~1234 comments"));
+        assert!(is_startup_activity("[youtube]     Downloading comment API JSON page 3 (40/~1234)"));
+        assert!(is_startup_activity("[youtube]        Downloading comment replies API JSON page 1 (45/~1234)"));
+        assert!(!is_startup_activity("[info] dQw4w9WgXcQ: Downloading 1 format(s): 137+140"));
+    }
+
+    #[test]
+    fn finds_sidecar_without_suffix_in_packaged_layout() {
+        // 打包后的应用：sidecar 去掉目标三元组后缀，与主程序放在同一目录
+        let exe_dir = test_dir("bundled-packaged");
+        let ytdlp = exe_dir.join(format!("yt-dlp{}", std::env::consts::EXE_SUFFIX));
+        fs::write(&ytdlp, b"").unwrap();
+        fs::write(exe_dir.join("youtudown"), b"").unwrap();
+
+        assert_eq!(bundled_ytdlp_paths_in([exe_dir.clone()]), vec![ytdlp.clone()]);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&ytdlp).unwrap().permissions().mode();
+            assert_ne!(mode & 0o111, 0, "应补上可执行权限");
+        }
+
+        fs::remove_dir_all(&exe_dir).unwrap();
+    }
+
+    #[test]
+    fn finds_suffixed_sidecar_and_resource_dir() {
+        // 开发模式保留后缀；资源目录中的文件排在主程序同目录之后
+        let exe_dir = test_dir("bundled-dev");
+        let resource_dir = test_dir("bundled-resources");
+        let ext = std::env::consts::EXE_SUFFIX;
+        let suffixed = exe_dir.join(format!("yt-dlp-{}{}", TARGET_TRIPLE, ext));
+        let resource = resource_dir.join(format!("yt-dlp{}", ext));
+        fs::write(&suffixed, b"").unwrap();
+        fs::write(&resource, b"").unwrap();
+        fs::write(exe_dir.join("yt-dlp-other-triple"), b"").unwrap();
+
+        let paths = bundled_ytdlp_paths_in([exe_dir.clone(), resource_dir.clone()]);
+        assert_eq!(paths, [suffixed, resource]);
+
+        fs::remove_dir_all(&exe_dir).unwrap();
+        fs::remove_dir_all(&resource_dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    pub struct MockBackend {
+        pub infos: Mutex<VecDeque<Result<Value, YtdlpError>>>, // fetch_info 依次返回的结果
+        pub entries: Vec<Value>,        // fetch_entries 逐个返回的条目
+        pub stdout: Vec<String>,        // download 的标准输出行
+        pub stderr: Vec<String>,        // download 的标准错误行
+        pub exit_code: i32,             // download 的退出码
+        pub delay: Duration,            // download 输出完毕后、进程结束前的等待时间
+        pub calls: Mutex<Vec<Vec<String>>>, // 每次调用的参数（含 URL）
+        pub max_running: AtomicUsize,   // 同时进行的 download 的最大数量
+        pub running: AtomicUsize,       // 正在进行的 download 数量
+    }
+
+    impl MockBackend {
+        fn record_call(&self, args: Vec<String>) {
+            if let Ok(mut calls) = self.calls.lock() {
+                calls.push(args);
+            }
+        }
+
+        pub fn calls(&self) -> Vec<Vec<String>> {
+            self.calls.lock().map(|calls| calls.clone()).unwrap_or_default()
+        }
+    }
+
+    fn exit_status(code: i32) -> ExitStatus {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            ExitStatus::from_raw(code << 8)
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::ExitStatusExt;
+            ExitStatus::from_raw(code as u32)
+        }
+    }
+
+    impl MediaBackend for MockBackend {
+        async fn version(&self) -> Option<String> {
+            Some("mock".to_string())
+        }
+
+        async fn fetch_info(&self, args: &[&str], url: &str) -> Result<Value, YtdlpError> {
+            self.record_call(args.iter().chain([&url]).map(|arg| arg.to_string()).collect());
+            let next = self.infos.lock().ok().and_then(|mut infos| infos.pop_front());
+            next.unwrap_or_else(|| Err(YtdlpError::Parse("没有预设的视频信息".to_string())))
+        }
+
+        async fn fetch_entries<F>(
+            &self,
+            args: &[String],
+            url: &str,
+            cancelled: Arc<AtomicBool>,
+            mut on_entry: F,
+        ) -> Result<Vec<Value>, YtdlpError>
+        where
+            F: FnMut(&Value) + Send,
+        {
+            self.record_call(args.iter().cloned().chain([url.to_string()]).collect());
+            for entry in &self.entries {
+                if cancelled.load(Ordering::Relaxed) {
+                    return Err(YtdlpError::Cancelled);
+                }
+                tokio::task::yield_now().await;
+                on_entry(entry);
+            }
+            Ok(self.entries.clone())
+        }
+
+        async fn download<F>(
+            &self,
+            args: &[String],
+            _startup_timeout: Option<Duration>,
+            mut on_event: F,
+        ) -> Result<DownloadOutcome, YtdlpError>
+        where
+            F: FnMut(DownloadEvent) + Send + 'static,
+        {
+            self.record_call(args.to_vec());
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            on_event(DownloadEvent::Started);
+
+            let mut outputs = OutputTracker::new();
+            let mut items = PlaylistItems::default();
+            for line in &self.stdout {
+                items.record_output(line);
+                let known_destinations = outputs.destinations().len();
+                outputs.record(line);
+                if let Some(path) = outputs.destinations().get(known_destinations) {
+                    on_event(DownloadEvent::Destination(path.clone()));
+                }
+                if let Some(progress) = parse_progress_line(line) {
+                    let bytes_per_sec = parse_speed(&progress.speed);
+                    on_event(DownloadEvent::Progress {
+                        progress,
+                        bytes_per_sec,
+                        at: Instant::now(),
+                    });
+                }
+            }
+
+            let mut warnings = 0;
+            let mut errors = Vec::new();
+            for line in &self.stderr {
+                let Some((kind, message)) = parse_warning_line(line) else {
+                    items.record_error(line);
+                    errors.push(line.clone());
+                    continue;
+                };
+                warnings += 1;
+                let retry = parse_retry_warning(&message);
+                on_event(DownloadEvent::Warning { kind, message });
+                if let Some((attempt, retries, reason)) = retry {
+                    on_event(DownloadEvent::Retry {
+                        attempt,
+                        retries,
+                        reason,
+                    });
+                }
+            }
+
+            tokio::time::sleep(self.delay).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(DownloadOutcome {
+                status: exit_status(self.exit_code),
+                total_bytes: None,
+                outputs,
+                warnings,
+                items,
+                errors,
+            })
+        }
+    }
+}
@@ -3,35 +3,9 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { open } from '@tauri-apps/plugin-dialog';
 import './App.css';
-
-/**
- * 核心数据结构定义
- */
-interface VideoInfo {
-  id: string;
-  title: string;
-  duration: number;
-  thumbnail: string;
-  formats: VideoFormat[];
-  available_resolutions: ResolutionOption[];
-}
-
-interface VideoFormat {
-  format_id: string;
-  height?: number;
-  width?: number;
-  ext: string;
-  filesize?: number;
-  vcodec?: string;
-  acodec?: string;
-}
-
-interface ResolutionOption {
-  height: number;
-  label: string;
-  format_id: string;
-}
-
+// 核心数据结构和事件名由后端生成（见 src-tauri/src/bindings.rs）
+import type { DownloadOptions, DownloadRetry, PostProcessingProgress, ProgressInfo, VideoInfo } from './bindings';
+import { DOWNLOAD_COMPLETE, DOWNLOAD_POSTPROCESSING, DOWNLOAD_PROGRESS, DOWNLOAD_RETRY } from './bindings/events';
 
 interface AdvancedConfig {
   impersonate: string;
@@ -55,6 +29,8 @@ function App(): JSX.Element {
   const [outputPath, setOutputPath] = useState<string>('');
   const [isDownloading, setIsDownloading] = useState<boolean>(false);
   const [downloadProgress, setDownloadProgress] = useState<number>(0);
+  const [postProcessing, setPostProcessing] = useState<string>('');
+  const [retryStatus, setRetryStatus] = useState<string>('');
   const [downloadSpeed, setDownloadSpeed] = useState<string>('');
   const [downloadEta, setDownloadEta] = useState<string>('');
   const [errorMsg, setErrorMsg] = useState<string>('');
@@ -142,14 +118,16 @@ function App(): JSX.Element {
   useEffect(() => {
     let unlistenProgress: (() => void) | undefined;
     let unlistenComplete: (() => void) | undefined;
+    let unlistenPostProcessing: (() => void) | undefined;
+    let unlistenRetry: (() => void) | undefined;
 
     const setupListeners = async () => {
       // 监听下载进度事件
-      unlistenProgress = await listen('download-progress', (event) => {
-        const progress = event.payload as any;
-        if (progress.percent !== undefined) {
-          setDownloadProgress(Math.round(progress.percent));
-        }
+      unlistenProgress = await listen<ProgressInfo>(DOWNLOAD_PROGRESS, (event) => {
+        const progress = event.payload;
+        setPostProcessing('');
+        setRetryStatus('');
+        setDownloadProgress(Math.round(progress.percent));
         if (progress.speed) {
           setDownloadSpeed(progress.speed);
         }
@@ -158,8 +136,28 @@ function App(): JSX.Element {
         }
       });
 
+      // 监听后处理进度（合并时进度条按合并进度重新走一遍）
+      unlistenPostProcessing = await listen<PostProcessingProgress>(DOWNLOAD_POSTPROCESSING, (event) => {
+        const { stage, elapsed_seconds, percent } = event.payload;
+        if (percent !== null) {
+          setDownloadProgress(Math.round(percent));
+        }
+        setDownloadSpeed('');
+        setDownloadEta('');
+        setPostProcessing(`${stage === 'Merger' ? '合并中' : `后处理中 (${stage})`} ${Math.floor(elapsed_seconds)}s`);
+      });
+
+      // 监听重试（如 429 后按退避时间等待）
+      unlistenRetry = await listen<DownloadRetry>(DOWNLOAD_RETRY, (event) => {
+        const { attempt, retries, wait_seconds } = event.payload;
+        const wait = wait_seconds ? `等待 ${Math.round(wait_seconds)}s 后` : '';
+        setRetryStatus(`${wait}第 ${attempt}/${retries} 次重试`);
+      });
+
       // 监听下载完成事件
-      unlistenComplete = await listen('download-complete', () => {
+      unlistenComplete = await listen(DOWNLOAD_COMPLETE, () => {
+        setPostProcessing('');
+        setRetryStatus('');
         setDownloadProgress(100);
         setDownloadSpeed('');
         setDownloadEta('');
@@ -178,6 +176,12 @@ function App(): JSX.Element {
       if (unlistenComplete) {
         unlistenComplete();
       }
+      if (unlistenPostProcessing) {
+        unlistenPostProcessing();
+      }
+      if (unlistenRetry) {
+        unlistenRetry();
+      }
     };
   }, []);
 
@@ -242,79 +246,63 @@ function App(): JSX.Element {
 
   
   /**
-   * 构建 yt-dlp 命令参数
+   * 构建下载选项（yt-dlp 参数由后端根据选项和设置生成）
    */
-  const buildCommandArgs = useCallback((): string[] => {
-    const args: string[] = [];
-
-    // 基本参数
-    args.push('--no-warnings');
-    args.push('--progress');
-
-    // 高级反检测参数
-    args.push('--impersonate', advancedConfig.impersonate);
-    args.push('--user-agent', advancedConfig.userAgent);
-    args.push('--cookies-from-browser', advancedConfig.cookiesFromBrowser);
-    args.push('--sleep-interval', advancedConfig.sleepInterval.toString());
-    args.push('--retries', advancedConfig.retries.toString());
+  // 后端对未提供的字段使用默认值，这里只填写需要的字段
+  const buildDownloadOptions = useCallback((): Partial<DownloadOptions> => {
+    const options: Partial<DownloadOptions> = {
+      impersonate: advancedConfig.impersonate,
+      user_agent: advancedConfig.userAgent,
+      cookies_from_browser: advancedConfig.cookiesFromBrowser,
+      sleep_interval: advancedConfig.sleepInterval,
+      retries: advancedConfig.retries,
+      output_dir: outputPath || undefined,
+    };
 
     // 质量选择 - 优先使用动态分辨率选择
     if (quality === 'best') {
       // 自动选择最佳质量
-      args.push('-f', 'bestvideo+bestaudio/best');
       console.log('使用自动最佳质量');
     } else if (videoInfo && videoInfo.available_resolutions.length > 0) {
       // 使用动态分辨率选择
       const selectedResolution = videoInfo.available_resolutions.find(r => r.format_id === quality);
       if (selectedResolution) {
-        // 直接使用格式ID，让yt-dlp自动处理音频
-        args.push('-f', quality);
+        // 直接使用格式ID，不可用时按该分辨率逐级回退
+        options.format_id = quality;
+        options.max_height = selectedResolution.height;
         console.log(`使用选定分辨率: ${selectedResolution.label} (${selectedResolution.height}p) - 格式ID: ${quality}`);
       } else {
         // 如果找不到对应的格式，使用第一个可用分辨率
         const firstResolution = videoInfo.available_resolutions[0];
-        args.push('-f', firstResolution.format_id);
+        options.format_id = firstResolution.format_id;
+        options.max_height = firstResolution.height;
         console.log(`未找到选定格式，使用第一个可用分辨率: ${firstResolution.label} (${firstResolution.height}p)`);
       }
     } else {
       // 兼容旧的选择方式（以防万一）
       const qualityHeight = parseInt(quality.replace(/[^\d]/g, ''));
       if (qualityHeight > 0) {
-        args.push('-f', `bestvideo[height<=${qualityHeight}]+bestaudio/best`);
+        options.max_height = qualityHeight;
         console.log(`使用兼容模式，最大高度: ${qualityHeight}p`);
       } else {
-        args.push('-f', 'bestvideo+bestaudio/best');
         console.log('使用默认最佳质量（兼容模式）');
       }
     }
 
     // 时间段下载（核心功能）
     if (videoInfo && (startTime > 0 || (endTime && endTime < videoInfo.duration))) {
-      const start = formatTime(startTime);
-      const end = endTime ? formatTime(endTime) : formatTime(videoInfo.duration);
-      args.push('--download-sections', `*${start}-${end}`);
-      console.log(`下载时间段: ${start} - ${end}`);
+      options.start_time = startTime;
+      options.end_time = endTime && endTime < videoInfo.duration ? endTime : undefined;
+      console.log(`下载时间段: ${formatTime(startTime)} - ${formatTime(endTime ?? videoInfo.duration)}`);
     }
 
     // 字幕下载
     if (downloadSubtitles && subtitleLangs) {
-      args.push('--write-subs');
-      args.push('--sub-langs', subtitleLangs);
-      args.push('--sub-format', 'srt');
-    }
-
-    // 输出路径
-    if (outputPath) {
-      args.push('-o', `${outputPath}/%(title)s.%(ext)s`);
-    } else {
-      args.push('-o', '%(title)s.%(ext)s');
+      options.subtitle_langs = subtitleLangs;
     }
 
-    // URL
-    args.push(url);
-
-    return args;
-  }, [quality, videoInfo, startTime, endTime, downloadSubtitles, subtitleLangs, outputPath, url, formatTime, advancedConfig]);
+    return options;
+  }, [quality, videoInfo, startTime, endTime, downloadSubtitles, subtitleLangs, outputPath, formatTime, advancedConfig]);
 
   /**
    * 开始下载
@@ -335,12 +323,12 @@ function App(): JSX.Element {
     setDownloadProgress(0);
 
     try {
-      const args = buildCommandArgs();
-      console.log('yt-dlp 参数:', args);
+      const options = buildDownloadOptions();
+      console.log('下载选项:', options);
 
       await invoke('download_video', {
         url,
-        args,
+        options,
       });
 
       // 注意：下载完成消息现在通过事件处理
@@ -348,7 +336,7 @@ function App(): JSX.Element {
       setErrorMsg(`下载失败: ${error}`);
       setIsDownloading(false);
     }
-  }, [url, outputPath, buildCommandArgs]);
+  }, [url, outputPath, buildDownloadOptions]);
 
   return (
     <div className="container">
@@ -486,8 +474,8 @@ function App(): JSX.Element {
               >
                 <option value="best">自动（选择最佳）</option>
                 {videoInfo.available_resolutions.map((resolution) => (
-                  <option key={resolution.height} value={resolution.format_id}>
-                    {resolution.label} ({resolution.height}p)
+                  <option key={resolution.format_id} value={resolution.format_id}>
+                    {resolution.label} ({resolution.height}p){resolution.above_max_resolution ? ' · 超过分辨率上限' : ''}
                   </option>
                 ))}
               </select>
@@ -695,6 +683,8 @@ function App(): JSX.Element {
                 </div>
                 <div className="progress-info">
                   <span>{downloadProgress}%</span>
+                  {postProcessing && <span>{postProcessing}</span>}
+                  {retryStatus && <span>{retryStatus}</span>}
                   {downloadSpeed && <span>速度: {downloadSpeed}</span>}
                   {downloadEta && <span>剩余时间: {downloadEta}</span>}
                 </div>
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorKind } from "./ErrorKind";
+
+export type AppError = { kind: ErrorKind, message: string, suggestion: string | null, detail: string, region: string | null, };
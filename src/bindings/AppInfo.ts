@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AppInfo = { version: string, git_commit: string | null, built_at: number | null, os: string, arch: string, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UpdateStatus } from "./UpdateStatus";
+
+export type AppUpdate = { status: UpdateStatus, current_version: string, latest_version: string | null, release_url: string | null, detail: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 分组（播放列表）内全部任务结束时发送的事件
+ */
+export type BatchCompleted = { playlist_id: string, manifest_path: string | null, downloaded: number, skipped: number, };
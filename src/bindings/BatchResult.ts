@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BatchEnqueued } from "./BatchEnqueued";
+import type { BatchLineError } from "./BatchLineError";
+
+export type BatchResult = { enqueued: Array<BatchEnqueued>, errors: Array<BatchLineError>, };
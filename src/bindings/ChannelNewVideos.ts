@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlaylistEnqueued } from "./PlaylistEnqueued";
+
+/**
+ * download_channel_new 的返回值
+ */
+export type ChannelNewVideos = { since: number | null, new_count: number, enqueued: PlaylistEnqueued, };
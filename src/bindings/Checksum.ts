@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChecksumAlgorithm } from "./ChecksumAlgorithm";
+
+export type Checksum = { algorithm: ChecksumAlgorithm, hex: string, computed_at: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载命令预览（与实际下载使用相同的参数）
+ */
+export type CommandPreview = { program: string, args: Array<string>, extra_args_start: number, extra_args_len: number, overridden: Array<string>, redacted: Array<string>, };
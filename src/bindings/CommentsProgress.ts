@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 获取评论的进度（download-comments 事件内容），评论在下载开始前获取，可能持续数分钟
+ */
+export type CommentsProgress = { download_id: string, fetched: number | null, estimated_total: number | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CookieSource } from "./CookieSource";
+
+export type CookieCheck = { source: CookieSource, readable: boolean, cookie_count: number | null, authenticated: boolean, detail: string | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileOperationResult } from "./FileOperationResult";
+
+export type DeleteHistoryResult = { entry_removed: boolean, files: Array<FileOperationResult>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * validate_download_dir 的返回值
+ */
+export type DestinationCheck = { path: string, available: boolean, error: string | null, };
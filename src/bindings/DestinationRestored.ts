@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载目录恢复可用、等待的任务重新排队时发送的事件
+ */
+export type DestinationRestored = { download_ids: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载目录不存在或不可写、任务暂停等待时发送的事件
+ */
+export type DestinationUnavailable = { path: string, error: string, download_ids: Array<string>, };
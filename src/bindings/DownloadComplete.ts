@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DownloadedFormat } from "./DownloadedFormat";
+import type { MediaProbe } from "./MediaProbe";
+import type { PartialSuccess } from "./PartialSuccess";
+import type { Verification } from "./Verification";
+
+export type DownloadComplete = { download_id: string, output_dir: string | null, output_path: string | null, kept_files: Array<string>, downloaded_format: DownloadedFormat | null, audio_language_fallback: boolean, thumbnail_path: string | null, stream_files: Array<string>, verified: boolean, verification: Verification, archive_tracks: MediaProbe | null, warnings: number, partial: PartialSuccess | null, comment_count: number | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 队列中的下载失败时发送的事件（直接调用 download_video 时错误由命令返回）
+ */
+export type DownloadFailed = { download_id: string, error: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FormatGoal } from "./FormatGoal";
+import type { ManifestFormat } from "./ManifestFormat";
+import type { RetrySleep } from "./RetrySleep";
+
+export type DownloadOptions = { format_id: string | null, max_height: number | null, start_time: number | null, end_time: number | null, subtitle_langs: string | null, output_dir: string | null, output_template: string | null, impersonate: string | null, cookies_from_browser: string | null, sleep_interval: number | null, retries: number | null, retry_sleep: RetrySleep | null, min_duration: number | null, max_duration: number | null, user_agent: string | null, write_comments: boolean, max_comments: number | null, audio_language: string | null, write_thumbnail: boolean, embed_thumbnail: boolean, convert_thumbnails: string | null, separate_streams: boolean, format: string | null, format_sort: string | null, extract_audio: string | null, playlist_concurrency: number | null, video_format_id: string | null, audio_format_id: string | null, merge_output_format: string | null, manifest_format: ManifestFormat | null, archive_file: string | null, format_goal: FormatGoal, extra_args: Array<string>, archive_best: boolean, treat_as_playlist: boolean, wait_for_video: number | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载出错，yt-dlp 等待后重试
+ */
+export type DownloadRetry = { download_id: string, attempt: number, retries: number, wait_seconds: number | null, reason: string, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DownloadStatus } from "./DownloadStatus";
+import type { ProgressInfo } from "./ProgressInfo";
+
+export type DownloadState = { download_id: string, status: DownloadStatus, progress: ProgressInfo | null, error: string | null, updated_at: number, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DownloadStatus = "Scheduled" | "Queued" | "WaitingForNetwork" | "WaitingForDestination" | "Running" | "FetchingComments" | "Paused" | "PostProcessing" | "Completed" | "PartialSuccess" | "Failed" | "Cancelled";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载速度持续低于限速阈值，或 yt-dlp 报告被限速
+ */
+export type DownloadThrottled = { download_id: string, bytes_per_sec: number | null, threshold: number | null, restarting: boolean, };
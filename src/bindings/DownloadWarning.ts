@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载完成但输出文件可疑（截断、空文件、容器损坏）
+ */
+export type DownloadWarning = { download_id: string, issues: Array<string>, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type DuplicateStatus = { "status": "already_queued", download_id: string, } | { "status": "already_downloaded", path: string, } | { "status": "previously_downloaded_file_missing" } | { "status": "new" };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ErrorKind = "bot_detection" | "geo_blocked" | "rate_limited" | "login_required" | "impersonation_unavailable" | "extractor_failed" | "unknown";
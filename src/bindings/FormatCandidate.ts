@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 同一分辨率选项下可选的一个格式
+ */
+export type FormatCandidate = { format_id: string, vcodec: string | null, ext: string, filesize: number | null, requires_merge: boolean, recommended: boolean, };
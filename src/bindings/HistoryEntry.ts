@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Checksum } from "./Checksum";
+import type { DownloadStatus } from "./DownloadStatus";
+import type { PartialSuccess } from "./PartialSuccess";
+import type { PendingMerge } from "./PendingMerge";
+
+export type HistoryEntry = { id: string, url: string, status: DownloadStatus, total_bytes: number | null, output_path: string | null, sidecar_files: Array<string>, error: string | null, finished_at: number, verified: boolean | null, suspect: boolean, verification_issues: Array<string>, checksum: Checksum | null, warnings: number, output_dir: string | null, partial: PartialSuccess | null, pending_merge: PendingMerge | null, };
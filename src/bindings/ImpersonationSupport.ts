@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImpersonateTarget } from "./ImpersonateTarget";
+import type { YtdlpInstall } from "./YtdlpInstall";
+
+export type ImpersonationSupport = { supported: boolean, targets: Array<ImpersonateTarget>, install: YtdlpInstall, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StreamInfo } from "./StreamInfo";
+
+export type MediaProbe = { duration: number | null, video_streams: number, audio_streams: number, streams: Array<StreamInfo>, };
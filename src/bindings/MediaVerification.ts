@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MediaProbe } from "./MediaProbe";
+import type { MediaVerdict } from "./MediaVerdict";
+
+export type MediaVerification = { verdict: MediaVerdict, probe: MediaProbe | null, expected_duration: number | null, message: string | null, };
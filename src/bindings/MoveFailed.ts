@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 暂存模式下，下载成功但移动到下载目录失败（文件仍保留在暂存目录中）
+ */
+export type MoveFailed = { download_id: string, staging_dir: string, error: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 网络恢复、暂停的任务重新开始时发送的事件
+ */
+export type NetworkRestored = { download_ids: Array<string>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChannelVideo } from "./ChannelVideo";
+
+export type NewVideosFound = { subscription_id: string, channel_url: string, videos: Array<ChannelVideo>, download_ids: Array<string>, filtered: number, };
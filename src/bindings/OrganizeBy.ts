@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载目录下的子目录组织方式
+ */
+export type OrganizeBy = "None" | "Site" | "Uploader" | "Playlist";
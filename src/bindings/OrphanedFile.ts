@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OrphanKind } from "./OrphanKind";
+
+export type OrphanedFile = { path: string, kind: OrphanKind, size: number, modified: number | null, };
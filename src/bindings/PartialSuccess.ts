@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ItemFailure } from "./ItemFailure";
+
+/**
+ * 部分条目下载失败，其余已完成
+ */
+export type PartialSuccess = { completed: number, failed: Array<ItemFailure>, };
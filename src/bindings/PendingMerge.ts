@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 下载完成但合并失败、等待重新合并的文件
+ */
+export type PendingMerge = { streams: Array<string>, target: string, };
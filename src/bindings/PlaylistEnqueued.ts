@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChannelVideo } from "./ChannelVideo";
+
+export type PlaylistEnqueued = { playlist_id: string, entries: Array<ChannelVideo>, download_ids: Array<string>, concurrency: number, skipped: Array<ChannelVideo>, filtered: Array<ChannelVideo>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChannelVideo } from "./ChannelVideo";
+
+/**
+ * 获取播放列表信息时每解析出一个条目发送的事件
+ */
+export type PlaylistEntryParsed = { fetch_id: string, index: number, entry: ChannelVideo, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChannelVideo } from "./ChannelVideo";
+
+/**
+ * get_playlist_info 的返回值
+ */
+export type PlaylistInfo = { fetch_id: string, title: string | null, entries: Array<ChannelVideo>, cancelled: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 带 list 参数的视频链接所指向的播放列表（供界面询问用户按视频还是播放列表处理）
+ */
+export type PlaylistSummary = { title: string | null, count: number | null, };
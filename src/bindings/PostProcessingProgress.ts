@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 后处理阶段的进度（download-postprocessing 事件内容），该阶段没有 [download] 进度行
+ */
+export type PostProcessingProgress = { download_id: string, stage: string, elapsed_seconds: number, percent: number | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueueItemState } from "./QueueItemState";
+
+/**
+ * set_queue_priority / download_next 的返回值
+ */
+export type PriorityChange = { download_id: string, state: QueueItemState, priority: number, applied: boolean, detail: string | null, };
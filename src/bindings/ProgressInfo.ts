@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ProgressInfo = { schema_version: number, download_id: string, percent: number, speed: string, eta: string, downloaded_bytes: number | null, total_bytes: number | null, total_bytes_estimated: boolean, computed_eta_seconds: number | null, eta_seconds_raw: number | null, eta_seconds_smoothed: number | null, };
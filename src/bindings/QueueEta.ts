@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * get_queue_eta 的返回值：全部未结束任务的剩余时间
+ */
+export type QueueEta = { eta_seconds: number | null, formatted: string | null, remaining: number, estimated_sizes: number, remaining_bytes: number | null, throughput: number | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QueueItemState } from "./QueueItemState";
+
+export type QueueItem = { download_id: string, url: string, state: QueueItemState, start_at: number | null, output_dir: string | null, priority: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PriorityMode } from "./PriorityMode";
+import type { QueueItem } from "./QueueItem";
+
+/**
+ * get_queue 的返回值：队列中的任务及当前的子进程优先级
+ */
+export type QueueOverview = { items: Array<QueueItem>, priority_mode: PriorityMode, paused: boolean, };
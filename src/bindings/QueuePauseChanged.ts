@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 队列暂停或继续时发送的事件（queue-paused / queue-resumed）
+ */
+export type QueuePauseChanged = { paused: boolean, pending: number, running: number, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type QueueProgress = { percent: number, total: number, completed: number, active: number, pending: number, speed: number, eta_seconds: number | null, };
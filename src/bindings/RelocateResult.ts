@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileOperationResult } from "./FileOperationResult";
+import type { HistoryEntry } from "./HistoryEntry";
+
+export type RelocateResult = { entry: HistoryEntry, files: Array<FileOperationResult>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FormatCandidate } from "./FormatCandidate";
+
+export type ResolutionOption = { height: number, label: string, format_id: string, requires_merge: boolean, is_progressive: boolean, fps: number | null, is_hdr: boolean, above_max_resolution: boolean, candidates: Array<FormatCandidate>, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type RetrySleep = { "kind": "fixed", seconds: number, } | { "kind": "linear", start: number, end: number | null, step: number | null, } | { "kind": "exponential", start: number, end: number | null, base: number | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SearchResult = { id: string, title: string, url: string, duration: number | null, uploader: string | null, thumbnail: string | null, view_count: number | null, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChecksumAlgorithm } from "./ChecksumAlgorithm";
+import type { Locale } from "./Locale";
+import type { OrganizeBy } from "./OrganizeBy";
+import type { Preset } from "./Preset";
+import type { PriorityMode } from "./PriorityMode";
+import type { SitePreset } from "./SitePreset";
+
+export type Settings = { temp_dir: string | null, cache_dir: string | null, keep_fragments: boolean, organize_by: OrganizeBy, max_concurrent_downloads: number, restrict_filenames: boolean, windows_safe_filenames: boolean, max_filename_length: number | null, stage_downloads: boolean, download_dir: string | null, auto_checksum: ChecksumAlgorithm | null, subscription_check_hours: number, presets: Array<Preset>, prefer_progressive: boolean, ytdlp_path: string | null, prefer_bundled_ytdlp: boolean, ytdlp_config: string | null, ignore_ytdlp_config: boolean, network_check: boolean, throttle_threshold: number | null, restart_throttled: boolean, proxy: string | null, inherit_proxy_env: boolean, site_presets: { [key in string]?: SitePreset }, background_priority: PriorityMode, background_ffmpeg_threads: number | null, startup_timeout: number | null, locale: Locale | null, max_resolution: bigint | null, hide_above_max_resolution: boolean, extra_args: Array<string>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CookieStrategy } from "./CookieStrategy";
+
+export type SitePreset = { domains: Array<string>, extractor_args: Array<string>, impersonate: string | null, cookies: CookieStrategy, };
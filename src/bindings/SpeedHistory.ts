@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpeedSample } from "./SpeedSample";
+
+export type SpeedHistory = { samples: Array<SpeedSample>, min: number, avg: number, max: number, };
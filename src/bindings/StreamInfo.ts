@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type StreamInfo = { codec_type: string, codec_name: string | null, width: number | null, height: number | null, };
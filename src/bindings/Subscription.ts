@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DownloadOptions } from "./DownloadOptions";
+
+export type Subscription = { id: string, channel_url: string, options: DownloadOptions, auto_download: boolean, created_at: number, last_checked: number | null, last_error: string | null, seen_ids: Array<string>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 尚未开始的首映或直播
+ */
+export type UpcomingRelease = { release_at: number | null, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UrlSupport = { supported: boolean, extractor: string | null, generic: boolean, timed_out: boolean, error: string | null, };
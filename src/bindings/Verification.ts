@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Verification = { verified: boolean, actual_bytes: number | null, expected_bytes: number | null, issues: Array<string>, };
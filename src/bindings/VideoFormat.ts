@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FormatKind } from "./FormatKind";
+
+export type VideoFormat = { format_id: string, kind: FormatKind | null, height: number | null, width: number | null, ext: string, filesize: number | null, vcodec: string | null, acodec: string | null, language: string | null, has_drm: boolean, format_note: string | null, dynamic_range: string | null, fps: number | null, };
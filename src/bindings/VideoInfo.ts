@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlaylistSummary } from "./PlaylistSummary";
+import type { ResolutionOption } from "./ResolutionOption";
+import type { Thumbnail } from "./Thumbnail";
+import type { UpcomingRelease } from "./UpcomingRelease";
+import type { VideoFormat } from "./VideoFormat";
+
+export type VideoInfo = { id: string, title: string, duration: number, thumbnail: string, thumbnails: Array<Thumbnail>, description: string | null, formats: Array<VideoFormat>, available_resolutions: Array<ResolutionOption>, audio_languages: Array<string>, extractor: string | null, extractor_key: string | null, webpage_url: string | null, drm_only: boolean, cookies_refreshed: boolean, playlist: PlaylistSummary | null, upcoming: UpcomingRelease | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InstallMethod } from "./InstallMethod";
+
+export type YtdlpInstall = { method: InstallMethod, ytdlp_path: string, python: string | null, fix_commands: Array<string>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WarningKind } from "./WarningKind";
+
+export type YtdlpWarning = { download_id: string | null, kind: WarningKind, message: string, };
@@ -0,0 +1,72 @@
+// 由 src-tauri/src/bindings.rs 生成，请勿手动修改
+import type { BatchCompleted } from "./BatchCompleted";
+import type { ChecksumProgress } from "./ChecksumProgress";
+import type { CommentsProgress } from "./CommentsProgress";
+import type { DestinationRestored } from "./DestinationRestored";
+import type { DestinationUnavailable } from "./DestinationUnavailable";
+import type { DiagnosticsReport } from "./DiagnosticsReport";
+import type { DownloadComplete } from "./DownloadComplete";
+import type { DownloadFailed } from "./DownloadFailed";
+import type { DownloadRetry } from "./DownloadRetry";
+import type { DownloadThrottled } from "./DownloadThrottled";
+import type { DownloadWarning } from "./DownloadWarning";
+import type { InfoExtractionProgress } from "./InfoExtractionProgress";
+import type { MoveFailed } from "./MoveFailed";
+import type { NetworkRestored } from "./NetworkRestored";
+import type { NewVideosFound } from "./NewVideosFound";
+import type { PlaylistEntryParsed } from "./PlaylistEntryParsed";
+import type { PostProcessingProgress } from "./PostProcessingProgress";
+import type { ProgressInfo } from "./ProgressInfo";
+import type { QueuePauseChanged } from "./QueuePauseChanged";
+import type { QueueProgress } from "./QueueProgress";
+import type { YtdlpWarning } from "./YtdlpWarning";
+
+export const INFO_EXTRACTION_PROGRESS = "info-extraction-progress" as const;
+export const PLAYLIST_ENTRY_PARSED = "playlist-entry-parsed" as const;
+export const DOWNLOAD_PROGRESS = "download-progress" as const;
+export const DOWNLOAD_POSTPROCESSING = "download-postprocessing" as const;
+export const DOWNLOAD_COMMENTS = "download-comments" as const;
+export const DOWNLOAD_THROTTLED = "download-throttled" as const;
+export const DOWNLOAD_RETRY = "download-retry" as const;
+export const DOWNLOAD_MOVE_FAILED = "download-move-failed" as const;
+export const DOWNLOAD_WARNING = "download-warning" as const;
+export const YTDLP_WARNING = "ytdlp-warning" as const;
+export const DOWNLOAD_COMPLETE = "download-complete" as const;
+export const DOWNLOAD_FAILED = "download-failed" as const;
+export const CHECKSUM_PROGRESS = "checksum-progress" as const;
+export const QUEUE_PROGRESS = "queue-progress" as const;
+export const BATCH_COMPLETED = "batch-completed" as const;
+export const NETWORK_RESTORED = "network-restored" as const;
+export const DESTINATION_UNAVAILABLE = "destination-unavailable" as const;
+export const DESTINATION_RESTORED = "destination-restored" as const;
+export const NEW_VIDEOS_FOUND = "new-videos-found" as const;
+export const STARTUP_HEALTH = "startup-health" as const;
+export const QUEUE_PAUSED = "queue-paused" as const;
+export const QUEUE_RESUMED = "queue-resumed" as const;
+
+export type EventPayloads = {
+  [INFO_EXTRACTION_PROGRESS]: InfoExtractionProgress;
+  [PLAYLIST_ENTRY_PARSED]: PlaylistEntryParsed;
+  [DOWNLOAD_PROGRESS]: ProgressInfo;
+  [DOWNLOAD_POSTPROCESSING]: PostProcessingProgress;
+  [DOWNLOAD_COMMENTS]: CommentsProgress;
+  [DOWNLOAD_THROTTLED]: DownloadThrottled;
+  [DOWNLOAD_RETRY]: DownloadRetry;
+  [DOWNLOAD_MOVE_FAILED]: MoveFailed;
+  [DOWNLOAD_WARNING]: DownloadWarning;
+  [YTDLP_WARNING]: YtdlpWarning;
+  [DOWNLOAD_COMPLETE]: DownloadComplete;
+  [DOWNLOAD_FAILED]: DownloadFailed;
+  [CHECKSUM_PROGRESS]: ChecksumProgress;
+  [QUEUE_PROGRESS]: QueueProgress;
+  [BATCH_COMPLETED]: BatchCompleted;
+  [NETWORK_RESTORED]: NetworkRestored;
+  [DESTINATION_UNAVAILABLE]: DestinationUnavailable;
+  [DESTINATION_RESTORED]: DestinationRestored;
+  [NEW_VIDEOS_FOUND]: NewVideosFound;
+  [STARTUP_HEALTH]: DiagnosticsReport;
+  [QUEUE_PAUSED]: QueuePauseChanged;
+  [QUEUE_RESUMED]: QueuePauseChanged;
+};
+
+export type EventName = keyof EventPayloads;
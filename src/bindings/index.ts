@@ -0,0 +1,101 @@
+// 由 src-tauri/src/bindings.rs 生成，请勿手动修改
+export * from "./AppError";
+export * from "./AppInfo";
+export * from "./AppUpdate";
+export * from "./BatchCompleted";
+export * from "./BatchEnqueued";
+export * from "./BatchLineError";
+export * from "./BatchResult";
+export * from "./ChannelNewVideos";
+export * from "./ChannelVideo";
+export * from "./CheckStatus";
+export * from "./Checksum";
+export * from "./ChecksumAlgorithm";
+export * from "./ChecksumProgress";
+export * from "./CleanupFailure";
+export * from "./CleanupReport";
+export * from "./CommandPreview";
+export * from "./CommentsProgress";
+export * from "./CookieCheck";
+export * from "./CookieSource";
+export * from "./CookieStrategy";
+export * from "./DeleteHistoryResult";
+export * from "./DestinationCheck";
+export * from "./DestinationRestored";
+export * from "./DestinationUnavailable";
+export * from "./DiagnosticCheck";
+export * from "./DiagnosticsReport";
+export * from "./DiskSpace";
+export * from "./DownloadComplete";
+export * from "./DownloadFailed";
+export * from "./DownloadOptions";
+export * from "./DownloadRetry";
+export * from "./DownloadState";
+export * from "./DownloadStatus";
+export * from "./DownloadThrottled";
+export * from "./DownloadWarning";
+export * from "./DownloadedFormat";
+export * from "./DuplicateStatus";
+export * from "./ErrorKind";
+export * from "./FileOperationResult";
+export * from "./FormatCandidate";
+export * from "./FormatGoal";
+export * from "./FormatKind";
+export * from "./HistoryEntry";
+export * from "./ImpersonateTarget";
+export * from "./ImpersonationDiagnosis";
+export * from "./ImpersonationSupport";
+export * from "./InfoExtractionProgress";
+export * from "./InstallMethod";
+export * from "./ItemFailure";
+export * from "./Locale";
+export * from "./ManifestFormat";
+export * from "./MediaProbe";
+export * from "./MediaVerdict";
+export * from "./MediaVerification";
+export * from "./MoveFailed";
+export * from "./NetworkRestored";
+export * from "./NewVideosFound";
+export * from "./OrganizeBy";
+export * from "./OrphanKind";
+export * from "./OrphanedFile";
+export * from "./PartialSuccess";
+export * from "./PendingMerge";
+export * from "./PlaylistEnqueued";
+export * from "./PlaylistEntryParsed";
+export * from "./PlaylistInfo";
+export * from "./PlaylistSummary";
+export * from "./PostProcessingProgress";
+export * from "./Preset";
+export * from "./PriorityChange";
+export * from "./PriorityMode";
+export * from "./ProgressInfo";
+export * from "./QueueEta";
+export * from "./QueueItem";
+export * from "./QueueItemState";
+export * from "./QueueOverview";
+export * from "./QueuePauseChanged";
+export * from "./QueueProgress";
+export * from "./RelocateResult";
+export * from "./ResolutionOption";
+export * from "./RetrySleep";
+export * from "./SearchResult";
+export * from "./Settings";
+export * from "./SitePreset";
+export * from "./SpeedHistory";
+export * from "./SpeedSample";
+export * from "./StoryboardResult";
+export * from "./StreamInfo";
+export * from "./Subscription";
+export * from "./Thumbnail";
+export * from "./UpcomingRelease";
+export * from "./UpdateStatus";
+export * from "./UrlSupport";
+export * from "./Verification";
+export * from "./VideoFormat";
+export * from "./VideoInfo";
+export * from "./WarningKind";
+export * from "./YtdlpCandidate";
+export * from "./YtdlpInstall";
+export * from "./YtdlpWarning";
+export * from "./events";